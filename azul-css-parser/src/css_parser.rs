@@ -15,6 +15,9 @@ use azul_css::{
     BoxShadowPreDisplayItem, BorderStyle, LayoutPadding, StyleBorderSide, BorderRadius, PixelSize,
 
     SizeMetric, BoxShadowClipMode, ExtendMode, FontId,
+    LayoutBoxSizing, LayoutAspectRatio, StyleObjectFit, StyleObjectPosition, ObjectPositionValue,
+    LayoutAlignSelf, LayoutDisplay, StyleHyphens, StyleDirection, StyleUserSelect, StyleSelectionBackgroundColor,
+    StyleWhiteSpace, StyleTabSize, StyleTextOverflow, StyleLineClamp, StyleTextDecoration,
 };
 
 /// A parser that can accept a list of items and mappings
@@ -89,6 +92,11 @@ pub fn from_kv<'a>(key: &'a str, value: &'a str) -> Result<CssProperty, CssParsi
         "min-height"        => Ok(parse_layout_min_height(value)?.into()),
         "max-width"         => Ok(parse_layout_max_width(value)?.into()),
         "max-height"        => Ok(parse_layout_max_height(value)?.into()),
+        "box-sizing"        => Ok(parse_layout_box_sizing(value)?.into()),
+        "aspect-ratio"      => Ok(parse_layout_aspect_ratio(value)?.into()),
+
+        "object-fit"        => Ok(parse_style_object_fit(value)?.into()),
+        "object-position"   => Ok(parse_style_object_position(value)?.into()),
 
         "position"          => Ok(parse_layout_position(value)?.into()),
         "top"               => Ok(parse_layout_top(value)?.into()),
@@ -96,6 +104,16 @@ pub fn from_kv<'a>(key: &'a str, value: &'a str) -> Result<CssProperty, CssParsi
         "left"              => Ok(parse_layout_left(value)?.into()),
         "bottom"            => Ok(parse_layout_bottom(value)?.into()),
         "text-align"        => Ok(parse_layout_text_align(value)?.into()),
+        "hyphens"           => Ok(parse_style_hyphens(value)?.into()),
+        "direction"         => Ok(parse_style_direction(value)?.into()),
+        "user-select"       => Ok(parse_style_user_select(value)?.into()),
+        "selection-background-color" => Ok(parse_style_selection_background_color(value)?.into()),
+        "white-space"       => Ok(parse_style_white_space(value)?.into()),
+        "tab-size"          => Ok(parse_style_tab_size(value)?.into()),
+        "text-overflow"     => Ok(parse_style_text_overflow(value)?.into()),
+        "-webkit-line-clamp" |
+        "line-clamp"        => Ok(parse_style_line_clamp(value)?.into()),
+        "text-decoration"   => Ok(parse_style_text_decoration(value)?.into()),
 
         "padding"           => Ok(parse_layout_padding(value)?.into()),
         "padding-top"       => Ok(layout_padding_parser::parse_top(value)?.into()),
@@ -119,10 +137,13 @@ pub fn from_kv<'a>(key: &'a str, value: &'a str) -> Result<CssProperty, CssParsi
 
         "align-cross-axis" |
         "align-items"       => Ok(parse_layout_align_items(value)?.into()),
+        "align-self"        => Ok(parse_layout_align_self(value)?.into()),
 
         "align-cross-axis-multiline" |
         "align-content"     => Ok(parse_layout_align_content(value)?.into()),
 
+        "display"           => Ok(parse_layout_display(value)?.into()),
+
         "overflow"          => {
             let overflow_both_directions = parse_layout_text_overflow(value)?;
             Ok(LayoutOverflow {
@@ -169,6 +190,9 @@ pub enum CssParsingError<'a> {
     MarginParseError(LayoutMarginParseError<'a>),
     FlexShrinkParseError(FlexShrinkParseError<'a>),
     FlexGrowParseError(FlexGrowParseError<'a>),
+    AspectRatioParseError(AspectRatioParseError<'a>),
+    ObjectPositionParseError(ObjectPositionParseError<'a>),
+    TabSizeParseError(TabSizeParseError<'a>),
     /// Key is not supported, i.e. `#div { aldfjasdflk: 400px }` results in an
     /// `UnsupportedCssKey("aldfjasdflk", "400px")` error
     UnsupportedCssKey(&'a str, &'a str),
@@ -189,6 +213,9 @@ impl_display!{ CssParsingError<'a>, {
     MarginParseError(e) => format!("{}", e),
     FlexShrinkParseError(e) => format!("{}", e),
     FlexGrowParseError(e) => format!("{}", e),
+    AspectRatioParseError(e) => format!("{}", e),
+    ObjectPositionParseError(e) => format!("{}", e),
+    TabSizeParseError(e) => format!("{}", e),
     UnsupportedCssKey(key, value) => format!("Unsupported Css-key: \"{}\" - value: \"{}\"", key, value),
 }}
 
@@ -205,6 +232,9 @@ impl_from!(LayoutPaddingParseError<'a>, CssParsingError::PaddingParseError);
 impl_from!(LayoutMarginParseError<'a>, CssParsingError::MarginParseError);
 impl_from!(FlexShrinkParseError<'a>, CssParsingError::FlexShrinkParseError);
 impl_from!(FlexGrowParseError<'a>, CssParsingError::FlexGrowParseError);
+impl_from!(AspectRatioParseError<'a>, CssParsingError::AspectRatioParseError);
+impl_from!(ObjectPositionParseError<'a>, CssParsingError::ObjectPositionParseError);
+impl_from!(TabSizeParseError<'a>, CssParsingError::TabSizeParseError);
 
 impl<'a> From<(&'a str, &'a str)> for CssParsingError<'a> {
     fn from((a, b): (&'a str, &'a str)) -> Self {
@@ -540,6 +570,12 @@ fn parse_style_text_color<'a>(input: &'a str)
     parse_css_color(input).and_then(|ok| Ok(StyleTextColor(ok)))
 }
 
+fn parse_style_selection_background_color<'a>(input: &'a str)
+-> Result<StyleSelectionBackgroundColor, CssColorParseError<'a>>
+{
+    parse_css_color(input).and_then(|ok| Ok(StyleSelectionBackgroundColor(ok)))
+}
+
 /// Parse a built-in background color
 ///
 /// "blue" -> "00FF00" -> ColorF { r: 0, g: 255, b: 0 })
@@ -1665,6 +1701,28 @@ pub(crate) struct RectStyle {
     pub(crate) line_height: Option<StyleLineHeight>,
     /// `letter-spacing` property (modifies the width and height)
     pub(crate) letter_spacing: Option<StyleLetterSpacing>,
+    /// `hyphens` property
+    pub(crate) hyphens: Option<StyleHyphens>,
+    /// `direction` property
+    pub(crate) direction: Option<StyleDirection>,
+    /// `user-select` property
+    pub(crate) user_select: Option<StyleUserSelect>,
+    /// `selection-background-color` property
+    pub(crate) selection_background_color: Option<StyleSelectionBackgroundColor>,
+    /// `white-space` property
+    pub(crate) white_space: Option<StyleWhiteSpace>,
+    /// `tab-size` property
+    pub(crate) tab_size: Option<StyleTabSize>,
+    /// `text-overflow` property
+    pub(crate) text_overflow: Option<StyleTextOverflow>,
+    /// `line-clamp` property
+    pub(crate) line_clamp: Option<StyleLineClamp>,
+    /// `text-decoration` property
+    pub(crate) text_decoration: Option<StyleTextDecoration>,
+    /// `object-fit` property (only affects images)
+    pub(crate) object_fit: Option<StyleObjectFit>,
+    /// `object-position` property (only affects images)
+    pub(crate) object_position: Option<StyleObjectPosition>,
 }
 
 typed_pixel_value_parser!(parse_style_letter_spacing, StyleLetterSpacing);
@@ -1679,6 +1737,8 @@ pub struct RectLayout {
     pub min_height: Option<LayoutMinHeight>,
     pub max_width: Option<LayoutMaxWidth>,
     pub max_height: Option<LayoutMaxHeight>,
+    pub box_sizing: Option<LayoutBoxSizing>,
+    pub aspect_ratio: Option<LayoutAspectRatio>,
 
     pub position: Option<LayoutPosition>,
     pub top: Option<LayoutTop>,
@@ -1695,7 +1755,9 @@ pub struct RectLayout {
     pub flex_shrink: Option<LayoutFlexShrink>,
     pub justify_content: Option<LayoutJustifyContent>,
     pub align_items: Option<LayoutAlignItems>,
+    pub align_self: Option<LayoutAlignSelf>,
     pub align_content: Option<LayoutAlignContent>,
+    pub display: Option<LayoutDisplay>,
 }
 
 typed_pixel_value_parser!(parse_layout_width, LayoutWidth);
@@ -1743,6 +1805,122 @@ fn parse_layout_flex_shrink<'a>(input: &'a str) -> Result<LayoutFlexShrink, Flex
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum AspectRatioParseError<'a> {
+    ParseFloat(ParseFloatError, &'a str),
+    ZeroOrNegative(&'a str),
+}
+
+impl_display!{AspectRatioParseError<'a>, {
+    ParseFloat(e, orig_str) => format!("aspect-ratio: Could not parse floating-point value: \"{}\" - Error: \"{}\"", orig_str, e),
+    ZeroOrNegative(orig_str) => format!("aspect-ratio: Ratio has to be greater than zero: \"{}\"", orig_str),
+}}
+
+// parses an "aspect-ratio" declaration, such as "16 / 9", "16/9" or "1.5"
+fn parse_layout_aspect_ratio<'a>(input: &'a str) -> Result<LayoutAspectRatio, AspectRatioParseError<'a>> {
+    let mut components = input.splitn(2, '/');
+    let width = components.next().unwrap_or(input);
+    let width = parse_float_value(width).map_err(|e| AspectRatioParseError::ParseFloat(e, input))?;
+    let height = match components.next() {
+        Some(height) => parse_float_value(height).map_err(|e| AspectRatioParseError::ParseFloat(e, input))?,
+        None => FloatValue::new(1.0),
+    };
+
+    if width.get() <= 0.0 || height.get() <= 0.0 {
+        return Err(AspectRatioParseError::ZeroOrNegative(input));
+    }
+
+    Ok(LayoutAspectRatio { width, height })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectPositionParseError<'a> {
+    TooManyComponents(&'a str),
+    InvalidComponent(&'a str),
+}
+
+impl_display!{ObjectPositionParseError<'a>, {
+    TooManyComponents(e) => format!("object-position: Too many components: \"{}\"", e),
+    InvalidComponent(e) => format!("object-position: Invalid component: \"{}\"", e),
+}}
+
+// parses an "object-position" declaration, such as "center", "left top", "25% 75%" or "10px 0px"
+fn parse_style_object_position<'a>(input: &'a str) -> Result<StyleObjectPosition, ObjectPositionParseError<'a>> {
+
+    use self::ObjectPositionValue::*;
+
+    fn percent(value: f32) -> ObjectPositionValue {
+        Percentage(PercentageValue::new(value))
+    }
+
+    fn parse_component<'a>(input: &'a str) -> Result<ObjectPositionValue, ObjectPositionParseError<'a>> {
+        if input.ends_with('%') {
+            input[..input.len() - 1].trim().parse::<f32>()
+                .map(|p| percent(p))
+                .map_err(|_| ObjectPositionParseError::InvalidComponent(input))
+        } else {
+            parse_pixel_value(input)
+                .map(|px| Exact(px))
+                .map_err(|_| ObjectPositionParseError::InvalidComponent(input))
+        }
+    }
+
+    let mut components = input.split_whitespace();
+    let first = components.next().ok_or(ObjectPositionParseError::InvalidComponent(input))?;
+    let second = components.next();
+
+    if components.next().is_some() {
+        return Err(ObjectPositionParseError::TooManyComponents(input));
+    }
+
+    let is_horizontal_keyword = |input: &str| input == "left" || input == "right";
+    let is_vertical_keyword = |input: &str| input == "top" || input == "bottom";
+
+    // Only two keywords can be given in any order, i.e. "top left" == "left top" - as soon as
+    // a length or percentage is used, the first value is horizontal and the second one vertical,
+    // so "top 10px" or "left right" are invalid
+    let (horizontal, vertical) = match (first, second) {
+        ("center", None) => (percent(50.0), percent(50.0)),
+        ("left", None) => (percent(0.0), percent(50.0)),
+        ("right", None) => (percent(100.0), percent(50.0)),
+        ("top", None) => (percent(50.0), percent(0.0)),
+        ("bottom", None) => (percent(50.0), percent(100.0)),
+        (other, None) => (parse_component(other)?, percent(50.0)),
+        (v, Some(h)) if is_vertical_keyword(v) || is_horizontal_keyword(h) => {
+            if !(is_horizontal_keyword(h) || h == "center") {
+                return Err(ObjectPositionParseError::InvalidComponent(h));
+            }
+            if !(is_vertical_keyword(v) || v == "center") {
+                return Err(ObjectPositionParseError::InvalidComponent(v));
+            }
+            (parse_horizontal(h)?, parse_vertical(v)?)
+        },
+        (h, Some(v)) => (parse_horizontal(h)?, parse_vertical(v)?),
+    };
+
+    fn parse_horizontal<'a>(input: &'a str) -> Result<ObjectPositionValue, ObjectPositionParseError<'a>> {
+        match input {
+            "left" => Ok(percent(0.0)),
+            "center" => Ok(percent(50.0)),
+            "right" => Ok(percent(100.0)),
+            "top" | "bottom" => Err(ObjectPositionParseError::InvalidComponent(input)),
+            other => parse_component(other),
+        }
+    }
+
+    fn parse_vertical<'a>(input: &'a str) -> Result<ObjectPositionValue, ObjectPositionParseError<'a>> {
+        match input {
+            "top" => Ok(percent(0.0)),
+            "center" => Ok(percent(50.0)),
+            "bottom" => Ok(percent(100.0)),
+            "left" | "right" => Err(ObjectPositionParseError::InvalidComponent(input)),
+            other => parse_component(other),
+        }
+    }
+
+    Ok(StyleObjectPosition { horizontal, vertical })
+}
+
 fn parse_style_line_height(input: &str)
 -> Result<StyleLineHeight, PercentageParseError>
 {
@@ -1851,7 +2029,16 @@ multi_type_parser!(parse_layout_align_items, LayoutAlignItems,
                     ["flex-start", Start],
                     ["flex-end", End],
                     ["stretch", Stretch],
-                    ["center", Center]);
+                    ["center", Center],
+                    ["baseline", Baseline]);
+
+multi_type_parser!(parse_layout_align_self, LayoutAlignSelf,
+                    ["auto", Auto],
+                    ["flex-start", Start],
+                    ["flex-end", End],
+                    ["stretch", Stretch],
+                    ["center", Center],
+                    ["baseline", Baseline]);
 
 multi_type_parser!(parse_layout_align_content, LayoutAlignContent,
                     ["flex-start", Start],
@@ -1876,10 +2063,104 @@ multi_type_parser!(parse_layout_text_overflow, TextOverflowBehaviourInner,
                     ["visible", Visible],
                     ["hidden", Hidden]);
 
+multi_type_parser!(parse_layout_display, LayoutDisplay,
+                    ["flex", Flex],
+                    ["inline", Inline]);
+
+multi_type_parser!(parse_layout_box_sizing, LayoutBoxSizing,
+                    ["content-box", ContentBox],
+                    ["border-box", BorderBox]);
+
+multi_type_parser!(parse_style_object_fit, StyleObjectFit,
+                    ["fill", Fill],
+                    ["contain", Contain],
+                    ["cover", Cover],
+                    ["none", None]);
+
 multi_type_parser!(parse_layout_text_align, StyleTextAlignmentHorz,
                     ["center", Center],
                     ["left", Left],
-                    ["right", Right]);
+                    ["right", Right],
+                    ["justify", Justify],
+                    ["start", Start],
+                    ["end", End]);
+
+multi_type_parser!(parse_style_direction, StyleDirection,
+                    ["ltr", Ltr],
+                    ["rtl", Rtl]);
+
+multi_type_parser!(parse_style_user_select, StyleUserSelect,
+                    ["text", Text],
+                    ["none", None]);
+
+multi_type_parser!(parse_style_hyphens, StyleHyphens,
+                    ["none", None],
+                    ["manual", Manual],
+                    ["auto", Auto]);
+
+multi_type_parser!(parse_style_white_space, StyleWhiteSpace,
+                    ["normal", Normal],
+                    ["nowrap", Nowrap],
+                    ["pre", Pre],
+                    ["pre-wrap", PreWrap],
+                    ["pre-line", PreLine]);
+
+multi_type_parser!(parse_style_text_overflow, StyleTextOverflow,
+                    ["clip", Clip],
+                    ["ellipsis", Ellipsis]);
+
+multi_type_parser!(parse_style_text_decoration, StyleTextDecoration,
+                    ["none", None],
+                    ["underline", Underline],
+                    ["overline", Overline],
+                    ["line-through", LineThrough]);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TabSizeParseError<'a> {
+    PixelParseError(PixelParseError<'a>),
+    Negative(&'a str),
+}
+
+impl_display!{TabSizeParseError<'a>, {
+    PixelParseError(e) => format!("tab-size: {}", e),
+    Negative(orig_str) => format!("tab-size: Value can't be negative: \"{}\"", orig_str),
+}}
+
+impl_from!(PixelParseError<'a>, TabSizeParseError::PixelParseError);
+
+/// Parses a `tab-size`: either a number of spaces (`"8"`) or a length (`"20px"`), neither can be negative
+fn parse_style_tab_size<'a>(input: &'a str)
+-> Result<StyleTabSize, TabSizeParseError<'a>>
+{
+    let tab_size = match parse_float_value(input) {
+        Ok(spaces) => StyleTabSize::Spaces(spaces),
+        Err(_) => StyleTabSize::Length(parse_pixel_value(input)?),
+    };
+
+    let value = match tab_size {
+        StyleTabSize::Spaces(spaces) => spaces.get(),
+        StyleTabSize::Length(length) => length.number.get(),
+    };
+
+    if value < 0.0 {
+        return Err(TabSizeParseError::Negative(input));
+    }
+
+    Ok(tab_size)
+}
+
+/// Parses a `line-clamp`: either `"none"` or the (positive) maximum number of lines
+fn parse_style_line_clamp<'a>(input: &'a str)
+-> Result<StyleLineClamp, InvalidValueErr<'a>>
+{
+    match input {
+        "none" => Ok(StyleLineClamp::None),
+        _ => match input.parse::<usize>() {
+            Ok(lines) if lines > 0 => Ok(StyleLineClamp::Lines(lines)),
+            _ => Err(InvalidValueErr(input)),
+        },
+    }
+}
 
 #[cfg(test)]
 mod css_tests {
@@ -2527,4 +2808,128 @@ mod css_tests {
             left: Some(PixelValue::px(100.0)),
         }));
     }
+
+    #[test]
+    fn test_parse_box_sizing() {
+        assert_eq!(parse_layout_box_sizing("border-box"), Ok(LayoutBoxSizing::BorderBox));
+        assert_eq!(parse_layout_box_sizing("content-box"), Ok(LayoutBoxSizing::ContentBox));
+        assert!(parse_layout_box_sizing("padding-box").is_err());
+    }
+
+    #[test]
+    fn test_parse_aspect_ratio() {
+        assert_eq!(parse_layout_aspect_ratio("16 / 9"), Ok(LayoutAspectRatio::new(16.0, 9.0)));
+        assert_eq!(parse_layout_aspect_ratio("4/3"), Ok(LayoutAspectRatio::new(4.0, 3.0)));
+        assert_eq!(parse_layout_aspect_ratio("1.5"), Ok(LayoutAspectRatio::new(1.5, 1.0)));
+        assert_eq!(parse_layout_aspect_ratio("16 / 0"), Err(AspectRatioParseError::ZeroOrNegative("16 / 0")));
+    }
+
+    #[test]
+    fn test_parse_object_fit() {
+        assert_eq!(parse_style_object_fit("cover"), Ok(StyleObjectFit::Cover));
+        assert_eq!(parse_style_object_fit("none"), Ok(StyleObjectFit::None));
+    }
+
+    #[test]
+    fn test_parse_object_position() {
+        use self::ObjectPositionValue::*;
+        let percent = |p| Percentage(PercentageValue::new(p));
+
+        assert_eq!(parse_style_object_position("center"), Ok(StyleObjectPosition::default()));
+        assert_eq!(parse_style_object_position("top left"), Ok(StyleObjectPosition {
+            horizontal: percent(0.0),
+            vertical: percent(0.0),
+        }));
+        assert_eq!(parse_style_object_position("right bottom"), Ok(StyleObjectPosition {
+            horizontal: percent(100.0),
+            vertical: percent(100.0),
+        }));
+        assert_eq!(parse_style_object_position("25% 10px"), Ok(StyleObjectPosition {
+            horizontal: percent(25.0),
+            vertical: Exact(PixelValue::px(10.0)),
+        }));
+        assert_eq!(parse_style_object_position("left 10px"), Ok(StyleObjectPosition {
+            horizontal: percent(0.0),
+            vertical: Exact(PixelValue::px(10.0)),
+        }));
+        assert_eq!(parse_style_object_position("center left"), Ok(StyleObjectPosition {
+            horizontal: percent(0.0),
+            vertical: percent(50.0),
+        }));
+        assert_eq!(parse_style_object_position("left top right"), Err(ObjectPositionParseError::TooManyComponents("left top right")));
+        assert_eq!(parse_style_object_position("top 10px"), Err(ObjectPositionParseError::InvalidComponent("10px")));
+        assert_eq!(parse_style_object_position("10px left"), Err(ObjectPositionParseError::InvalidComponent("10px")));
+        assert_eq!(parse_style_object_position("left right"), Err(ObjectPositionParseError::InvalidComponent("left")));
+        assert_eq!(parse_style_object_position("top bottom"), Err(ObjectPositionParseError::InvalidComponent("bottom")));
+        assert_eq!(parse_style_object_position("10px 20px 30px"), Err(ObjectPositionParseError::TooManyComponents("10px 20px 30px")));
+    }
+
+    #[test]
+    fn test_parse_align_baseline() {
+        assert_eq!(parse_layout_align_items("baseline"), Ok(LayoutAlignItems::Baseline));
+        assert_eq!(parse_layout_align_self("baseline"), Ok(LayoutAlignSelf::Baseline));
+        assert_eq!(parse_layout_align_self("auto"), Ok(LayoutAlignSelf::Auto));
+        assert!(parse_layout_align_self("space-between").is_err());
+    }
+
+    #[test]
+    fn test_parse_display() {
+        assert_eq!(parse_layout_display("inline"), Ok(LayoutDisplay::Inline));
+        assert_eq!(parse_layout_display("flex"), Ok(LayoutDisplay::Flex));
+        assert!(parse_layout_display("grid").is_err());
+    }
+
+    #[test]
+    fn test_parse_justify_and_hyphens() {
+        assert_eq!(parse_layout_text_align("justify"), Ok(StyleTextAlignmentHorz::Justify));
+        assert_eq!(parse_style_hyphens("auto"), Ok(StyleHyphens::Auto));
+        assert_eq!(parse_style_hyphens("none"), Ok(StyleHyphens::None));
+        assert!(parse_style_hyphens("always").is_err());
+    }
+
+    #[test]
+    fn test_parse_style_direction() {
+        assert_eq!(parse_style_direction("rtl"), Ok(StyleDirection::Rtl));
+        assert_eq!(parse_layout_text_align("start"), Ok(StyleTextAlignmentHorz::Start));
+        assert_eq!(StyleTextAlignmentHorz::Start.resolve(StyleDirection::Rtl), StyleTextAlignmentHorz::Right);
+        assert_eq!(StyleTextAlignmentHorz::End.resolve(StyleDirection::Rtl), StyleTextAlignmentHorz::Left);
+        assert_eq!(StyleTextAlignmentHorz::Center.resolve(StyleDirection::Rtl), StyleTextAlignmentHorz::Center);
+    }
+
+    #[test]
+    fn test_parse_style_user_select() {
+        assert_eq!(parse_style_user_select("text"), Ok(StyleUserSelect::Text));
+        assert_eq!(parse_style_user_select("none"), Ok(StyleUserSelect::None));
+        assert!(parse_style_user_select("all").is_err());
+    }
+
+    #[test]
+    fn test_parse_style_selection_background_color() {
+        assert_eq!(from_kv("selection-background-color", "#3399ff"),
+                   Ok(CssProperty::SelectionBackgroundColor(StyleSelectionBackgroundColor(ColorU { r: 51, g: 153, b: 255, a: 255 }))));
+        assert!(from_kv("selection-background-color", "text").is_err());
+    }
+
+    #[test]
+    fn test_parse_white_space_and_text_overflow() {
+        assert_eq!(parse_style_white_space("pre-wrap"), Ok(StyleWhiteSpace::PreWrap));
+        assert_eq!(parse_style_white_space("nowrap"), Ok(StyleWhiteSpace::Nowrap));
+        assert!(parse_style_white_space("break-spaces").is_err());
+        assert_eq!(parse_style_text_overflow("ellipsis"), Ok(StyleTextOverflow::Ellipsis));
+        assert_eq!(parse_style_tab_size("8"), Ok(StyleTabSize::Spaces(FloatValue::new(8.0))));
+        assert_eq!(parse_style_tab_size("20px"), Ok(StyleTabSize::Length(PixelValue::px(20.0))));
+        assert_eq!(parse_style_tab_size("0"), Ok(StyleTabSize::Spaces(FloatValue::new(0.0))));
+        assert_eq!(parse_style_tab_size("-4"), Err(TabSizeParseError::Negative("-4")));
+        assert_eq!(parse_style_tab_size("-20px"), Err(TabSizeParseError::Negative("-20px")));
+        assert_eq!(from_kv("-webkit-line-clamp", "3"), Ok(CssProperty::LineClamp(StyleLineClamp::Lines(3))));
+        assert_eq!(parse_style_line_clamp("none"), Ok(StyleLineClamp::None));
+        assert!(parse_style_line_clamp("0").is_err());
+    }
+
+    #[test]
+    fn test_parse_style_text_decoration() {
+        assert_eq!(parse_style_text_decoration("underline"), Ok(StyleTextDecoration::Underline));
+        assert_eq!(parse_style_text_decoration("line-through"), Ok(StyleTextDecoration::LineThrough));
+        assert!(parse_style_text_decoration("blink").is_err());
+    }
 }
@@ -179,9 +179,20 @@ pub enum CssProperty {
     FontFamily(StyleFontFamily),
     TextAlign(StyleTextAlignmentHorz),
     LetterSpacing(StyleLetterSpacing),
+    Hyphens(StyleHyphens),
+    Direction(StyleDirection),
+    UserSelect(StyleUserSelect),
+    SelectionBackgroundColor(StyleSelectionBackgroundColor),
+    WhiteSpace(StyleWhiteSpace),
+    TabSize(StyleTabSize),
+    TextOverflow(StyleTextOverflow),
+    LineClamp(StyleLineClamp),
+    TextDecoration(StyleTextDecoration),
     BoxShadow(StyleBoxShadow),
     LineHeight(StyleLineHeight),
     Cursor(StyleCursor),
+    ObjectFit(StyleObjectFit),
+    ObjectPosition(StyleObjectPosition),
 
     Width(LayoutWidth),
     Height(LayoutHeight),
@@ -189,6 +200,8 @@ pub enum CssProperty {
     MinHeight(LayoutMinHeight),
     MaxWidth(LayoutMaxWidth),
     MaxHeight(LayoutMaxHeight),
+    BoxSizing(LayoutBoxSizing),
+    AspectRatio(LayoutAspectRatio),
     Position(LayoutPosition),
     Top(LayoutTop),
     Right(LayoutRight),
@@ -202,7 +215,9 @@ pub enum CssProperty {
     FlexShrink(LayoutFlexShrink),
     JustifyContent(LayoutJustifyContent),
     AlignItems(LayoutAlignItems),
+    AlignSelf(LayoutAlignSelf),
     AlignContent(LayoutAlignContent),
+    Display(LayoutDisplay),
     Overflow(LayoutOverflow),
 }
 
@@ -215,7 +230,13 @@ impl CssProperty {
             | FontFamily(_)
             | FontSize(_)
             | LineHeight(_)
-            | TextAlign(_) => true,
+            | TextAlign(_)
+            | Hyphens(_)
+            | Direction(_)
+            | UserSelect(_)
+            | SelectionBackgroundColor(_)
+            | WhiteSpace(_)
+            | TabSize(_) => true,
             _ => false,
         }
     }
@@ -230,9 +251,20 @@ impl_from!(StyleFontFamily, CssProperty::FontFamily);
 impl_from!(StyleTextAlignmentHorz, CssProperty::TextAlign);
 impl_from!(StyleLineHeight, CssProperty::LineHeight);
 impl_from!(StyleLetterSpacing, CssProperty::LetterSpacing);
+impl_from!(StyleHyphens, CssProperty::Hyphens);
+impl_from!(StyleDirection, CssProperty::Direction);
+impl_from!(StyleUserSelect, CssProperty::UserSelect);
+impl_from!(StyleSelectionBackgroundColor, CssProperty::SelectionBackgroundColor);
+impl_from!(StyleWhiteSpace, CssProperty::WhiteSpace);
+impl_from!(StyleTabSize, CssProperty::TabSize);
+impl_from!(StyleTextOverflow, CssProperty::TextOverflow);
+impl_from!(StyleLineClamp, CssProperty::LineClamp);
+impl_from!(StyleTextDecoration, CssProperty::TextDecoration);
 impl_from!(StyleBackgroundColor, CssProperty::BackgroundColor);
 impl_from!(StyleTextColor, CssProperty::TextColor);
 impl_from!(StyleCursor, CssProperty::Cursor);
+impl_from!(StyleObjectFit, CssProperty::ObjectFit);
+impl_from!(StyleObjectPosition, CssProperty::ObjectPosition);
 
 impl_from!(LayoutOverflow, CssProperty::Overflow);
 impl_from!(LayoutWidth, CssProperty::Width);
@@ -241,6 +273,8 @@ impl_from!(LayoutMinWidth, CssProperty::MinWidth);
 impl_from!(LayoutMinHeight, CssProperty::MinHeight);
 impl_from!(LayoutMaxWidth, CssProperty::MaxWidth);
 impl_from!(LayoutMaxHeight, CssProperty::MaxHeight);
+impl_from!(LayoutBoxSizing, CssProperty::BoxSizing);
+impl_from!(LayoutAspectRatio, CssProperty::AspectRatio);
 
 impl_from!(LayoutPosition, CssProperty::Position);
 impl_from!(LayoutTop, CssProperty::Top);
@@ -257,7 +291,9 @@ impl_from!(LayoutFlexGrow, CssProperty::FlexGrow);
 impl_from!(LayoutFlexShrink, CssProperty::FlexShrink);
 impl_from!(LayoutJustifyContent, CssProperty::JustifyContent);
 impl_from!(LayoutAlignItems, CssProperty::AlignItems);
+impl_from!(LayoutAlignSelf, CssProperty::AlignSelf);
 impl_from!(LayoutAlignContent, CssProperty::AlignContent);
+impl_from!(LayoutDisplay, CssProperty::Display);
 
 /// Multiplier for floating point accuracy. Elements such as px or %
 /// are only accurate until a certain number of decimal points, therefore
@@ -380,6 +416,17 @@ impl Default for StyleBackgroundColor {
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct StyleTextColor(pub ColorU);
 
+/// Represents a `selection-background-color` attribute, the color that
+/// selected text (see `user-select`) is highlighted with
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StyleSelectionBackgroundColor(pub ColorU);
+
+impl Default for StyleSelectionBackgroundColor {
+    fn default() -> Self {
+        StyleSelectionBackgroundColor(ColorU { r: 179, g: 215, b: 255, a: 255 })
+    }
+}
+
 /// Represents a `padding` attribute
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct LayoutPadding {
@@ -843,6 +890,47 @@ pub struct LayoutMinHeight(pub PixelValue);
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub struct LayoutMaxHeight(pub PixelValue);
 
+/// Represents a `box-sizing` attribute - default: `ContentBox`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LayoutBoxSizing {
+    /// `width` and `height` only describe the content, the padding is added on top
+    ContentBox,
+    /// `width` and `height` describe the content and the padding
+    BorderBox,
+}
+
+impl Default for LayoutBoxSizing {
+    fn default() -> Self {
+        LayoutBoxSizing::ContentBox
+    }
+}
+
+/// Represents an `aspect-ratio` attribute, i.e. `16 / 9`
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct LayoutAspectRatio {
+    pub width: FloatValue,
+    pub height: FloatValue,
+}
+
+impl LayoutAspectRatio {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width: width.into(),
+            height: height.into(),
+        }
+    }
+
+    /// Returns `width / height`, or `None` if the height is zero
+    pub fn get(&self) -> Option<f32> {
+        let height = self.height.get();
+        if height == 0.0 {
+            None
+        } else {
+            Some(self.width.get() / height)
+        }
+    }
+}
+
 /// Represents a `top` attribute
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub struct LayoutTop(pub PixelValue);
@@ -970,6 +1058,8 @@ pub enum LayoutAlignItems {
     Start,
     /// Items are positioned at the end of the container
     End,
+    /// Items are positioned so that the baselines of their first line of text line up
+    Baseline,
 }
 
 impl Default for LayoutAlignItems {
@@ -978,6 +1068,62 @@ impl Default for LayoutAlignItems {
     }
 }
 
+/// Represents a `align-self` attribute, overrides the `align-items` of the parent
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LayoutAlignSelf {
+    /// Default value. The item is aligned according to the `align-items` of the parent
+    Auto,
+    /// The item is stretched to fit the container
+    Stretch,
+    /// The item is positioned at the center of the container
+    Center,
+    /// The item is positioned at the beginning of the container
+    Start,
+    /// The item is positioned at the end of the container
+    End,
+    /// The item is positioned so that its baseline lines up with the baseline of its siblings
+    Baseline,
+}
+
+impl Default for LayoutAlignSelf {
+    fn default() -> Self {
+        LayoutAlignSelf::Auto
+    }
+}
+
+impl LayoutAlignSelf {
+    /// Returns the alignment of the item, given the `align-items` of the parent
+    pub fn resolve(&self, parent_align_items: LayoutAlignItems) -> LayoutAlignItems {
+        use self::LayoutAlignSelf::*;
+        match self {
+            Auto => parent_align_items,
+            Stretch => LayoutAlignItems::Stretch,
+            Center => LayoutAlignItems::Center,
+            Start => LayoutAlignItems::Start,
+            End => LayoutAlignItems::End,
+            Baseline => LayoutAlignItems::Baseline,
+        }
+    }
+}
+
+/// Represents a `display` attribute - default: `Flex`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LayoutDisplay {
+    /// The item is laid out as a flex item
+    Flex,
+    /// The item is laid out as a run of text on the lines of its parent.
+    ///
+    /// Only has an effect on text nodes (`Label`) and only if all children of the
+    /// parent are `display: inline` - otherwise the item is laid out as a flex item.
+    Inline,
+}
+
+impl Default for LayoutDisplay {
+    fn default() -> Self {
+        LayoutDisplay::Flex
+    }
+}
+
 /// Represents a `align-content` attribute
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum LayoutAlignContent {
@@ -1060,12 +1206,267 @@ impl Default for TextOverflowBehaviourInner {
     }
 }
 
-/// Horizontal text alignment enum (left, center, right) - default: `Center`
+/// Represents an `object-fit` attribute - default: `Fill`
+///
+/// Determines how the content of an image is sized relative to the
+/// rectangle of the image node.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleObjectFit {
+    /// Stretches the image to the size of the rectangle, ignoring the aspect ratio
+    Fill,
+    /// Scales the image so that it fits into the rectangle, preserving the aspect ratio
+    Contain,
+    /// Scales the image so that it covers the rectangle, preserving the aspect ratio
+    Cover,
+    /// Renders the image at its original size
+    None,
+}
+
+impl Default for StyleObjectFit {
+    fn default() -> Self {
+        StyleObjectFit::Fill
+    }
+}
+
+impl StyleObjectFit {
+    /// Given the original size of the image and the size of the rectangle
+    /// it is rendered into, returns the size the image should be rendered at
+    pub fn get_image_size(&self, image_size: LayoutSize, rect_size: LayoutSize) -> LayoutSize {
+        use self::StyleObjectFit::*;
+
+        if image_size.width <= 0.0 || image_size.height <= 0.0 {
+            return rect_size;
+        }
+
+        let scale_x = rect_size.width / image_size.width;
+        let scale_y = rect_size.height / image_size.height;
+
+        let scale = match self {
+            Fill => return rect_size,
+            None => return image_size,
+            Contain => scale_x.min(scale_y),
+            Cover => scale_x.max(scale_y),
+        };
+
+        LayoutSize::new(image_size.width * scale, image_size.height * scale)
+    }
+}
+
+/// One axis of an `object-position` attribute, i.e. `50%` or `10px`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ObjectPositionValue {
+    /// Percentage of the free space between the image and the rectangle
+    Percentage(PercentageValue),
+    /// Offset from the top / left edge of the rectangle
+    Exact(PixelValue),
+}
+
+impl ObjectPositionValue {
+    /// Returns the offset of the image, given the space that is left over
+    /// between the rectangle and the image (may be negative if the image is larger)
+    pub fn get_offset(&self, free_space: f32) -> f32 {
+        use self::ObjectPositionValue::*;
+        match self {
+            Percentage(p) => free_space * p.get() / 100.0,
+            Exact(px) => px.to_pixels(),
+        }
+    }
+}
+
+/// Represents an `object-position` attribute - default: `50% 50%`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct StyleObjectPosition {
+    pub horizontal: ObjectPositionValue,
+    pub vertical: ObjectPositionValue,
+}
+
+impl Default for StyleObjectPosition {
+    fn default() -> Self {
+        StyleObjectPosition {
+            horizontal: ObjectPositionValue::Percentage(PercentageValue::new(50.0)),
+            vertical: ObjectPositionValue::Percentage(PercentageValue::new(50.0)),
+        }
+    }
+}
+
+/// Horizontal text alignment enum (left, center, right, justify, start, end) - default: `Center`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum StyleTextAlignmentHorz {
     Left,
     Center,
     Right,
+    /// Stretches the spaces between the words so that every line (except for the
+    /// last line of a paragraph) fills the entire width of the rectangle
+    Justify,
+    /// `Left` for left-to-right text, `Right` for right-to-left text (see `StyleDirection`)
+    Start,
+    /// `Right` for left-to-right text, `Left` for right-to-left text (see `StyleDirection`)
+    End,
+}
+
+impl StyleTextAlignmentHorz {
+    /// Resolves `Start` and `End` to `Left` or `Right`, depending on the text direction
+    pub fn resolve(&self, direction: StyleDirection) -> Self {
+        use self::StyleTextAlignmentHorz::*;
+        match (*self, direction) {
+            (Start, StyleDirection::Ltr) | (End, StyleDirection::Rtl) => Left,
+            (Start, StyleDirection::Rtl) | (End, StyleDirection::Ltr) => Right,
+            (other, _) => other,
+        }
+    }
+}
+
+/// Represents a `direction` attribute - default: `Ltr`
+///
+/// Sets the base direction of the paragraphs for the bidirectional text layout
+/// and determines what `text-align: start / end` means.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleDirection {
+    Ltr,
+    Rtl,
+}
+
+impl Default for StyleDirection {
+    fn default() -> Self {
+        StyleDirection::Ltr
+    }
+}
+
+/// Represents a `user-select` attribute - default: `Text`
+///
+/// Determines whether the text of a `Label` or `Text` node can be selected with the mouse
+/// (and copied with Ctrl + C). Set it to `none` for text that acts like a button.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleUserSelect {
+    Text,
+    None,
+}
+
+impl Default for StyleUserSelect {
+    fn default() -> Self {
+        StyleUserSelect::Text
+    }
+}
+
+/// Represents a `white-space` attribute - default: `PreLine`
+///
+/// Determines how the white space in the text is collapsed and whether the lines are wrapped.
+/// Unlike in browsers, the default is `pre-line`, so that the line breaks in the text are kept.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleWhiteSpace {
+    /// Spaces, tabs and line breaks are collapsed into a single space, lines are wrapped
+    Normal,
+    /// Same as `Normal`, but the lines are never wrapped
+    Nowrap,
+    /// Spaces, tabs and line breaks are preserved, lines are never wrapped
+    Pre,
+    /// Same as `Pre`, but the lines are wrapped
+    PreWrap,
+    /// Line breaks are preserved, spaces and tabs are collapsed, lines are wrapped
+    PreLine,
+}
+
+impl Default for StyleWhiteSpace {
+    fn default() -> Self {
+        StyleWhiteSpace::PreLine
+    }
+}
+
+impl StyleWhiteSpace {
+    /// Whether the lines are wrapped at the edge of the rectangle
+    pub fn wraps_lines(&self) -> bool {
+        use self::StyleWhiteSpace::*;
+        match self {
+            Normal | PreWrap | PreLine => true,
+            Nowrap | Pre => false,
+        }
+    }
+
+    /// Whether line breaks in the text start a new line (instead of being collapsed into a space)
+    pub fn preserves_line_breaks(&self) -> bool {
+        use self::StyleWhiteSpace::*;
+        match self {
+            Pre | PreWrap | PreLine => true,
+            Normal | Nowrap => false,
+        }
+    }
+
+    /// Whether consecutive spaces and tabs are preserved (instead of being collapsed into one space)
+    pub fn preserves_spaces(&self) -> bool {
+        use self::StyleWhiteSpace::*;
+        match self {
+            Pre | PreWrap => true,
+            Normal | Nowrap | PreLine => false,
+        }
+    }
+}
+
+/// Represents a `tab-size` attribute - default: 4 spaces
+///
+/// Only has an effect if tabs are preserved, see `StyleWhiteSpace::preserves_spaces`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleTabSize {
+    /// Width of a tab as a multiple of the width of the space character
+    Spaces(FloatValue),
+    /// Fixed width of a tab
+    Length(PixelValue),
+}
+
+impl Default for StyleTabSize {
+    fn default() -> Self {
+        StyleTabSize::Spaces(FloatValue::new(4.0))
+    }
+}
+
+/// Represents a `text-overflow` attribute - default: `Clip`
+///
+/// Determines what happens to lines that are too wide for the rectangle
+/// (because they can't be wrapped, see `StyleWhiteSpace::wraps_lines`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleTextOverflow {
+    /// The text is cut off at the edge of the rectangle
+    Clip,
+    /// The text is cut off and an ellipsis (`"…"`) is displayed at the end of the line
+    Ellipsis,
+}
+
+impl Default for StyleTextOverflow {
+    fn default() -> Self {
+        StyleTextOverflow::Clip
+    }
+}
+
+/// Represents a `line-clamp` (or `-webkit-line-clamp`) attribute - default: `None`
+///
+/// Limits the text to a maximum number of lines, the last line ends with an ellipsis
+/// if the text had to be cut off.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleLineClamp {
+    None,
+    Lines(usize),
+}
+
+impl Default for StyleLineClamp {
+    fn default() -> Self {
+        StyleLineClamp::None
+    }
+}
+
+/// Represents a `text-decoration` attribute - default: `None`
+///
+/// Draws a line (in the text color) under, over or through each line of the text
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleTextDecoration {
+    None,
+    Underline,
+    Overline,
+    LineThrough,
+}
+
+impl Default for StyleTextDecoration {
+    fn default() -> Self {
+        StyleTextDecoration::None
+    }
 }
 
 impl Default for StyleTextAlignmentHorz {
@@ -1074,6 +1475,26 @@ impl Default for StyleTextAlignmentHorz {
     }
 }
 
+/// Represents a `hyphens` attribute - default: `Manual`
+///
+/// Determines if words can be broken with a hyphen at the end of a line.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StyleHyphens {
+    /// Words are never hyphenated, soft hyphens (`U+00AD`) are ignored
+    None,
+    /// Words are only hyphenated at soft hyphens (`U+00AD`)
+    Manual,
+    /// Words are hyphenated at soft hyphens and wherever the hyphenation
+    /// patterns of the application allow it
+    Auto,
+}
+
+impl Default for StyleHyphens {
+    fn default() -> Self {
+        StyleHyphens::Manual
+    }
+}
+
 /// Vertical text alignment enum (top, center, bottom) - default: `Center`
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum StyleTextAlignmentVert {
@@ -1116,6 +1537,28 @@ pub struct RectStyle {
     pub line_height: Option<StyleLineHeight>,
     /// `letter-spacing` property (modifies the width and height)
     pub letter_spacing: Option<StyleLetterSpacing>,
+    /// `hyphens` property
+    pub hyphens: Option<StyleHyphens>,
+    /// `direction` property
+    pub direction: Option<StyleDirection>,
+    /// `user-select` property
+    pub user_select: Option<StyleUserSelect>,
+    /// `selection-background-color` property
+    pub selection_background_color: Option<StyleSelectionBackgroundColor>,
+    /// `white-space` property
+    pub white_space: Option<StyleWhiteSpace>,
+    /// `tab-size` property
+    pub tab_size: Option<StyleTabSize>,
+    /// `text-overflow` property
+    pub text_overflow: Option<StyleTextOverflow>,
+    /// `line-clamp` property
+    pub line_clamp: Option<StyleLineClamp>,
+    /// `text-decoration` property
+    pub text_decoration: Option<StyleTextDecoration>,
+    /// `object-fit` property (only affects images)
+    pub object_fit: Option<StyleObjectFit>,
+    /// `object-position` property (only affects images)
+    pub object_position: Option<StyleObjectPosition>,
 }
 
 impl_pixel_value!(StyleLetterSpacing);
@@ -1130,6 +1573,8 @@ pub struct RectLayout {
     pub min_height: Option<LayoutMinHeight>,
     pub max_width: Option<LayoutMaxWidth>,
     pub max_height: Option<LayoutMaxHeight>,
+    pub box_sizing: Option<LayoutBoxSizing>,
+    pub aspect_ratio: Option<LayoutAspectRatio>,
 
     pub position: Option<LayoutPosition>,
     pub top: Option<LayoutTop>,
@@ -1146,7 +1591,9 @@ pub struct RectLayout {
     pub flex_shrink: Option<LayoutFlexShrink>,
     pub justify_content: Option<LayoutJustifyContent>,
     pub align_items: Option<LayoutAlignItems>,
+    pub align_self: Option<LayoutAlignSelf>,
     pub align_content: Option<LayoutAlignContent>,
+    pub display: Option<LayoutDisplay>,
 }
 
 impl_pixel_value!(LayoutWidth);
@@ -5,25 +5,76 @@ use azul_css::Css;
 
 extern crate azul_css_parser;
 
+// The shared widget styles come first, so that the platform-specific rules of
+// the native stylesheet override them.
+
 /// CSS mimicking the OS-native look - Windows: `styles/native_windows.css`
 #[cfg(target_os="windows")]
 pub const NATIVE_CSS: &str = concat!(
+    include_str!("styles/shared/text_area.css"),
+    include_str!("styles/shared/checkbox.css"),
+    include_str!("styles/shared/radio_group.css"),
+    include_str!("styles/shared/switch.css"),
+    include_str!("styles/shared/slider.css"),
+    include_str!("styles/shared/dropdown.css"),
+    include_str!("styles/shared/tree_view.css"),
+    include_str!("styles/shared/list_view.css"),
+    include_str!("styles/shared/tabs.css"),
+    include_str!("styles/shared/stack.css"),
+    include_str!("styles/shared/menu.css"),
+    include_str!("styles/shared/splitter.css"),
+    include_str!("styles/shared/tooltip.css"),
+    include_str!("styles/shared/modal.css"),
+    include_str!("styles/shared/toast.css"),
     include_str!("styles/native_windows.css"),
     include_str!("styles/shared/table.css"),
+    include_str!("styles/shared/rich_text.css"),
 );
 
 /// CSS mimicking the OS-native look - Linux: `styles/native_linux.css`
 #[cfg(target_os="linux")]
 pub const NATIVE_CSS: &str = concat!(
+    include_str!("styles/shared/text_area.css"),
+    include_str!("styles/shared/checkbox.css"),
+    include_str!("styles/shared/radio_group.css"),
+    include_str!("styles/shared/switch.css"),
+    include_str!("styles/shared/slider.css"),
+    include_str!("styles/shared/dropdown.css"),
+    include_str!("styles/shared/tree_view.css"),
+    include_str!("styles/shared/list_view.css"),
+    include_str!("styles/shared/tabs.css"),
+    include_str!("styles/shared/stack.css"),
+    include_str!("styles/shared/menu.css"),
+    include_str!("styles/shared/splitter.css"),
+    include_str!("styles/shared/tooltip.css"),
+    include_str!("styles/shared/modal.css"),
+    include_str!("styles/shared/toast.css"),
     include_str!("styles/native_linux.css"),
     include_str!("styles/shared/table.css"),
+    include_str!("styles/shared/rich_text.css"),
 );
 
 /// CSS mimicking the OS-native look - Mac: `styles/native_macos.css`
 #[cfg(target_os="macos")]
 pub const NATIVE_CSS: &str = concat!(
+    include_str!("styles/shared/text_area.css"),
+    include_str!("styles/shared/checkbox.css"),
+    include_str!("styles/shared/radio_group.css"),
+    include_str!("styles/shared/switch.css"),
+    include_str!("styles/shared/slider.css"),
+    include_str!("styles/shared/dropdown.css"),
+    include_str!("styles/shared/tree_view.css"),
+    include_str!("styles/shared/list_view.css"),
+    include_str!("styles/shared/tabs.css"),
+    include_str!("styles/shared/stack.css"),
+    include_str!("styles/shared/menu.css"),
+    include_str!("styles/shared/splitter.css"),
+    include_str!("styles/shared/tooltip.css"),
+    include_str!("styles/shared/modal.css"),
+    include_str!("styles/shared/toast.css"),
     include_str!("styles/native_macos.css"),
     include_str!("styles/shared/table.css"),
+    include_str!("styles/shared/rich_text.css"),
 );
 
 /// Returns the native style for the OS
@@ -2,7 +2,9 @@ use std::{
     mem,
     fmt,
     io::Read,
+    rc::Rc,
     sync::{Arc, Mutex, PoisonError},
+    collections::BTreeMap,
 };
 use glium::{
     SwapBuffersError,
@@ -31,6 +33,8 @@ use {
     window::{Window, WindowId, FakeWindow, ScrollStates},
     window_state::WindowSize,
     text_cache::TextId,
+    text_layout::TextLayoutOptions,
+    hyphenation::Hyphenator,
     dom::{ScrollTagId, UpdateScreen},
     app_resources::AppResources,
     app_state::AppState,
@@ -184,6 +188,10 @@ impl<T: Layout> App<T> {
             state: window.state.clone(),
             default_callbacks: DefaultCallbackSystem::new(),
             read_only_window: window.display.clone(),
+            laid_out_texts: Rc::default(),
+            iframe_rects: BTreeMap::new(),
+            next_iframe_rects: BTreeMap::new(),
+            daemons: Vec::new(),
         });
 
         self.windows.push(window);
@@ -283,6 +291,24 @@ impl<T: Layout> App<T> {
                             &ui_state_cache,
                             &mut self.app_state
                         );
+                        // Select text with the mouse, copy it with Ctrl + C
+                        let selection_changed = window.text_selection.handle_event(
+                            event,
+                            hit_test_results.as_ref(),
+                            &ui_state_cache[idx],
+                            window.state.focused_element,
+                            &mut self.app_state.resources);
+                        if selection_changed {
+                            frame_event_info.should_redraw_window = true;
+                        }
+                    }
+                }
+
+                // Show the tooltip of the hovered node after a delay, hide it when the mouse leaves
+                // the node or the window, or when a mouse button or key is pressed
+                for event in &events {
+                    if window.tooltip.handle_event(event, hit_test_results.as_ref(), &ui_state_cache[idx]) {
+                        frame_event_info.should_redraw_window = true;
                     }
                 }
 
@@ -321,7 +347,9 @@ impl<T: Layout> App<T> {
 
                     // Call the Layout::layout() fn, get the DOM
                     let window_id = WindowId { id: idx };
-                    ui_state_cache[idx] = UiState::from_app_state(&mut self.app_state, window_id);
+                    let ui_state = UiState::from_app_state(&mut self.app_state, window_id, &window.tooltip);
+                    window.update_node_ids(&ui_state_cache[idx], &ui_state);
+                    ui_state_cache[idx] = ui_state;
 
                     // Style the DOM
                     ui_description_cache[idx] = UiDescription::from_dom(
@@ -341,6 +369,12 @@ impl<T: Layout> App<T> {
                         &mut self.app_state.resources);
 
                     awakened_task[idx] = false;
+
+                    // A tooltip that was shown is measured first, then it is drawn at its position
+                    if window.tooltip.measure(&ui_state_cache[idx], &window.internal.last_laid_out_rects, &window.text_selection.laid_out_texts) {
+                        window.events_loop.create_proxy().wakeup().unwrap_or(());
+                        awakened_task[idx] = true;
+                    }
                 }
             }
 
@@ -371,6 +405,22 @@ impl<T: Layout> App<T> {
                 }
             }
 
+            // Redraw the windows whose text caret has to blink
+            for (window_idx, window) in self.windows.iter().enumerate() {
+                if window.text_selection.caret_needs_redraw() {
+                    window.events_loop.create_proxy().wakeup().unwrap_or(());
+                    awakened_task[window_idx] = true;
+                }
+            }
+
+            // Redraw the windows whose tooltip has to be shown
+            for (window_idx, window) in self.windows.iter_mut().enumerate() {
+                if window.tooltip.run_timer(&mut self.app_state.resources) == UpdateScreen::Redraw {
+                    window.events_loop.create_proxy().wakeup().unwrap_or(());
+                    awakened_task[window_idx] = true;
+                }
+            }
+
             // Close windows if necessary
             closed_windows.into_iter().for_each(|closed_window_id| {
                 ui_state_cache.remove(closed_window_id);
@@ -402,9 +452,9 @@ impl<T: Layout> App<T> {
     fn initialize_ui_state(windows: &[Window<T>], app_state: &mut AppState<T>)
     -> Vec<UiState<T>>
     {
-        windows.iter().enumerate().map(|(idx, _window)| {
+        windows.iter().enumerate().map(|(idx, window)| {
             let window_id = WindowId { id: idx };
-            UiState::from_app_state(app_state, window_id)
+            UiState::from_app_state(app_state, window_id, &window.tooltip)
         }).collect()
     }
 
@@ -504,6 +554,12 @@ impl<T: Layout> App<T> {
         self.app_state.add_text_cached(text, font_id, font_size, letter_spacing)
     }
 
+    pub fn add_text_cached_with_options<S: Into<String>>(&mut self, text: S, font_id: &FontId, font_size: PixelValue, options: &TextLayoutOptions)
+    -> Result<TextId, FontError>
+    {
+        self.app_state.add_text_cached_with_options(text, font_id, font_size, options)
+    }
+
     pub fn delete_text(&mut self, id: TextId) {
         self.app_state.delete_text(id);
     }
@@ -512,6 +568,18 @@ impl<T: Layout> App<T> {
         self.app_state.clear_all_texts();
     }
 
+    /// Sets the hyphenation patterns for text with `hyphens: auto`,
+    /// see `AppResources::set_hyphenator`
+    pub fn set_hyphenator(&mut self, hyphenator: Option<Hyphenator>) {
+        self.app_state.set_hyphenator(hyphenator);
+    }
+
+    /// Sets the fonts for characters that the fonts of the `font-family` can't display,
+    /// see `AppResources::set_fallback_fonts`
+    pub fn set_fallback_fonts(&mut self, fallback_fonts: Vec<FontId>) {
+        self.app_state.set_fallback_fonts(fallback_fonts);
+    }
+
     /// Get the contents of the system clipboard as a string
     pub fn get_clipboard_string(&mut self)
     -> Result<String, ClipboardError>
@@ -643,39 +711,62 @@ fn call_callbacks<T: Layout>(
     use window::WindowEvent;
     use dom::UpdateScreen;
     use window_state::{KeyboardState, MouseState};
+    use dom::Callback;
+    use id_tree::NodeId;
 
-    let hit_test_results = match hit_test_results {
-        None => return,
-        Some(s) => s,
-    };
+    // Even if the mouse isn't over any node, the focused node receives the keyboard events
+    let no_hit_test_results = HitTestResult { items: Vec::new() };
+    let hit_test_results = hit_test_results.unwrap_or(&no_hit_test_results);
 
     let mut should_update_screen = UpdateScreen::DontRedraw;
 
-    let callbacks_filter_list = window.state.determine_callbacks(&hit_test_results, event, &ui_state_cache[window_id.id]);
+    let callbacks_filter_list = window.state.determine_callbacks(hit_test_results, event, &ui_state_cache[window_id.id], window.internal.pipeline_id, &window.internal.last_laid_out_rects);
 
     // TODO: this should be refactored - currently very stateful and error-prone!
     app_state.windows[window_id.id].set_keyboard_state(&window.state.keyboard_state);
     app_state.windows[window_id.id].set_mouse_state(&window.state.mouse_state);
 
+    // User-defined callbacks that the default callbacks want to run, see `AppStateNoData::defer_callback`
+    let mut deferred_callbacks = BTreeMap::<NodeId, Vec<Callback<T>>>::new();
+
+    // Nodes whose children don't receive the event anymore, see `AppStateNoData::stop_propagation`.
+    // Parents come before their children in `callbacks_filter_list`, so they are called first.
+    let mut stopped_nodes = Vec::<NodeId>::new();
+    let is_propagation_stopped = |stopped_nodes: &[NodeId], node_id: NodeId| {
+        !stopped_nodes.is_empty() && node_id.ancestors(&ui_state_cache[window_id.id].dom.arena.borrow().node_layout)
+            .skip(1)
+            .any(|parent| stopped_nodes.contains(&parent))
+    };
+
     // Run all default callbacks - **before** the user-defined callbacks are run!
     {
         let mut lock = app_state.data.lock().unwrap();
         for (node_id, callback_results) in callbacks_filter_list.iter() {
+            if is_propagation_stopped(&stopped_nodes, *node_id) {
+                continue;
+            }
             let hit_item = &callback_results.hit_test_item;
+            let mut propagation_stopped = false;
+            let node_deferred_callbacks = deferred_callbacks.entry(*node_id).or_insert_with(Vec::new);
             for default_callback_id in callback_results.default_callbacks.values() {
 
                 let window_event = WindowEvent {
                     window: window_id.id,
                     hit_dom_node: *node_id,
                     ui_state: &ui_state_cache[window_id.id],
-                    hit_test_result: &hit_test_results,
+                    hit_test_result: hit_test_results,
                     cursor_relative_to_item: (hit_item.point_relative_to_item.x, hit_item.point_relative_to_item.y),
                     cursor_in_viewport: (hit_item.point_in_viewport.x, hit_item.point_in_viewport.y),
+                    laid_out_texts: &window.text_selection.laid_out_texts,
+                    laid_out_rects: &window.internal.last_laid_out_rects,
+                    pointer_capture: window.state.pointer_capture,
                 };
 
                 let app_state_no_data = AppStateNoData {
                     windows: &app_state.windows,
                     resources: &mut app_state.resources,
+                    deferred_callbacks: &mut *node_deferred_callbacks,
+                    propagation_stopped: &mut propagation_stopped,
                 };
 
                 // safe unwrap, we have added the callback previously
@@ -683,20 +774,35 @@ fn call_callbacks<T: Layout>(
                     should_update_screen = UpdateScreen::Redraw;
                 }
             }
+            if propagation_stopped {
+                stopped_nodes.push(*node_id);
+            }
         }
     } // release mutex
 
+    // The text of a key that was consumed (ex. by a menu) isn't inserted into a text input
+    if !stopped_nodes.is_empty() {
+        window.state.text_input_stopped = true;
+    }
+
     for (node_id, callback_results) in callbacks_filter_list.iter() {
+        if is_propagation_stopped(&stopped_nodes, *node_id) {
+            continue;
+        }
         let hit_item = &callback_results.hit_test_item;
-        for callback in callback_results.normal_callbacks.values() {
+        let node_deferred_callbacks = deferred_callbacks.remove(node_id).unwrap_or_default();
+        for callback in node_deferred_callbacks.iter().chain(callback_results.normal_callbacks.values()) {
 
             let window_event = WindowEvent {
                 window: window_id.id,
                 hit_dom_node: *node_id,
                 ui_state: &ui_state_cache[window_id.id],
-                hit_test_result: &hit_test_results,
+                hit_test_result: hit_test_results,
                 cursor_relative_to_item: (hit_item.point_relative_to_item.x, hit_item.point_relative_to_item.y),
                 cursor_in_viewport: (hit_item.point_in_viewport.x, hit_item.point_in_viewport.y),
+                laid_out_texts: &window.text_selection.laid_out_texts,
+                laid_out_rects: &window.internal.last_laid_out_rects,
+                pointer_capture: window.state.pointer_capture,
             };
 
             if (callback.0)(app_state, window_event) == UpdateScreen::Redraw {
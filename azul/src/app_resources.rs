@@ -9,16 +9,17 @@ use webrender::api::{FontKey, FontInstanceKey};
 use image::{self, ImageError};
 #[cfg(feature = "image_loading")]
 use images::ImageType;
-use FastHashMap;
+use {FastHashMap, FastHashSet};
 use app_units::Au;
 use clipboard2::{Clipboard, ClipboardError, SystemClipboard};
 use rusttype::Font;
-use azul_css::{StyleFontSize, FontId, StyleLetterSpacing};
+use azul_css::{StyleFontSize, FontId, StyleLetterSpacing, StyleFontFamily};
 use {
-    text_layout::{split_text_into_words, TextSizePx},
-    text_cache::{TextId, TextCache},
+    text_layout::{split_text_into_words, get_missing_chars, TextSizePx, TextFont, TextLayoutOptions},
+    text_cache::{TextId, TextCache, WordsCacheKey},
     font::{FontState, FontError},
     images::{ImageId, ImageState},
+    hyphenation::Hyphenator,
 };
 
 /// Stores the resources for the application, souch as fonts, images and cached
@@ -44,6 +45,12 @@ pub struct AppResources {
     // the font instance key (if there is any). If there is no font instance key,
     // we first need to create one.
     pub(crate) fonts: FastHashMap<FontKey, FastHashMap<Au, FontInstanceKey>>,
+    /// System fonts that are used for characters that none of the fonts of the
+    /// `font-family` can display, in order of precedence (see `set_fallback_fonts`)
+    pub(crate) fallback_fonts: Vec<FontId>,
+    /// Builtin fonts that weren't found on the system, so that we don't have to search
+    /// for them again on every frame
+    missing_system_fonts: RefCell<FastHashSet<String>>,
     /// Stores long texts across frames
     pub(crate) text_cache: TextCache,
     /// Keyboard clipboard storage and retrieval functionality
@@ -56,6 +63,8 @@ impl Default for AppResources {
             style_ids_to_image_ids: FastHashMap::default(),
             fonts: FastHashMap::default(),
             font_data: RefCell::new(FastHashMap::default()),
+            fallback_fonts: get_default_fallback_fonts(),
+            missing_system_fonts: RefCell::new(FastHashSet::default()),
             images: FastHashMap::default(),
             text_cache: TextCache::default(),
             clipboard: SystemClipboard::new().unwrap(),
@@ -155,6 +164,8 @@ impl AppResources {
                 data.read_to_end(&mut font_data).map_err(|e| FontError::IoError(e))?;
                 let (parsed_font, fd) = font::rusttype_load_font(font_data.clone(), None)?;
                 v.insert((Rc::new(parsed_font), Rc::new(fd), Rc::new(RefCell::new(FontState::ReadyForUpload(font_data)))));
+                // The font might display characters that the fonts of a text were missing
+                self.text_cache.font_fallback_chains.clear();
                 Ok(Some(()))
             },
         }
@@ -176,7 +187,16 @@ impl AppResources {
         match id {
             FontId::BuiltinFont(b) => {
                 if self.font_data.borrow().get(id).is_none() {
-                    let (font, font_bytes, font_state) = Self::get_builtin_font(b.clone())?;
+                    if self.missing_system_fonts.borrow().contains(b) {
+                        return None;
+                    }
+                    let (font, font_bytes, font_state) = match Self::get_builtin_font(b.clone()) {
+                        Some(s) => s,
+                        None => {
+                            self.missing_system_fonts.borrow_mut().insert(b.clone());
+                            return None;
+                        }
+                    };
                     self.font_data.borrow_mut().insert(id.clone(), (Rc::new(font), Rc::new(font_bytes), Rc::new(RefCell::new(font_state))));
                 }
                 self.font_data.borrow().get(id).and_then(|(font, bytes, state)| Some((font.clone(), bytes.clone(), state.clone())))
@@ -195,6 +215,57 @@ impl AppResources {
         self.get_font_internal(id).and_then(|(font, bytes, _)| Some((font, bytes)))
     }
 
+    /// Returns the fonts that the text should be rendered with: the fonts of the `font_family`
+    /// that are available, followed by the fallback fonts (see `set_fallback_fonts`) that can
+    /// display characters which none of the previous fonts can display. Fallback fonts are only
+    /// loaded if necessary - they are uploaded to the GPU together with the other new fonts.
+    pub(crate) fn get_font_fallback_chain(&self, font_family: &StyleFontFamily, text: &str) -> Vec<FontId> {
+
+        let mut font_ids = Vec::new();
+        let mut fonts = Vec::new();
+
+        for font_id in &font_family.fonts {
+            if let Some((font, _)) = self.get_font(font_id) {
+                font_ids.push(font_id.clone());
+                fonts.push(font);
+            }
+        }
+
+        let mut missing_chars = get_missing_chars(text, &fonts.iter().map(|f| &**f).collect::<Vec<_>>());
+
+        for font_id in &self.fallback_fonts {
+            if missing_chars.is_empty() {
+                break;
+            }
+            if font_ids.contains(font_id) {
+                continue;
+            }
+            let font = match self.get_font(font_id) {
+                Some((font, _)) => font,
+                None => continue,
+            };
+            let missing_chars_before = missing_chars.len();
+            missing_chars = get_missing_chars(&missing_chars.iter().collect::<String>(), &[&*font]);
+            if missing_chars.len() < missing_chars_before {
+                font_ids.push(font_id.clone());
+            }
+        }
+
+        font_ids
+    }
+
+    /// Same as `get_font_fallback_chain`, but the chain of the text is only calculated once
+    /// for each `font_family` and stored in the `TextCache`. Returns `None` if the `TextId` is invalid.
+    pub(crate) fn get_cached_font_fallback_chain(&mut self, text_id: &TextId, font_family: &StyleFontFamily) -> Option<Vec<FontId>> {
+        let key = (*text_id, font_family.clone());
+        if let Some(font_ids) = self.text_cache.font_fallback_chains.get(&key) {
+            return Some(font_ids.clone());
+        }
+        let font_ids = self.get_font_fallback_chain(font_family, self.text_cache.string_cache.get(text_id)?);
+        self.text_cache.font_fallback_chains.insert(key, font_ids.clone());
+        Some(font_ids)
+    }
+
     /// Note the pub(crate) here: We don't want to expose the FontState in the public API
     pub(crate) fn get_font_state(&self, id: &FontId) -> Option<Rc<RefCell<FontState>>> {
         self.get_font_internal(id).and_then(|(_, _, state)| Some(state))
@@ -224,6 +295,7 @@ impl AppResources {
 
         let mut borrow_mut = self.font_data.borrow_mut();
         *borrow_mut.get_mut(&id).unwrap().2.borrow_mut() = FontState::AboutToBeDeleted(to_delete_font_key);
+        self.text_cache.font_fallback_chains.clear();
         Some(())
     }
 
@@ -238,7 +310,9 @@ impl AppResources {
     /// Calculates the widths for the words (layouts the string), then stores
     /// them in a text cache, together with the actual string
     ///
-    /// This leads to a faster layout cycle, but has an upfront performance cost
+    /// This leads to a faster layout cycle, but has an upfront performance cost.
+    /// If the font isn't loaded, the text is only stored as a string,
+    /// see `add_text_cached_with_options` to get the error instead.
     pub fn add_text_cached<S: Into<String>>(&mut self, text: S, font_id: &FontId, font_size: StyleFontSize, letter_spacing: Option<StyleLetterSpacing>)
     -> TextId
     {
@@ -248,21 +322,63 @@ impl AppResources {
         id
     }
 
+    /// Same as `add_text_cached`, but the words are split with the `letter_spacing`, `direction`
+    /// and `white_space` of the `options` (the other options don't affect the words). These
+    /// should be the same as the style of the node that the text is displayed in, otherwise the
+    /// text has to be split into words again during the layout.
+    ///
+    /// If the font isn't loaded, the text is not added and `FontError::NotFound` is returned.
+    pub fn add_text_cached_with_options<S: Into<String>>(&mut self, text: S, font_id: &FontId, font_size: StyleFontSize, options: &TextLayoutOptions)
+    -> Result<TextId, FontError>
+    {
+        let id = self.add_text_uncached(text);
+        match self.cache_text_with_options(id, font_id.clone(), font_size, options) {
+            Ok(()) => Ok(id),
+            Err(e) => {
+                self.delete_text(id);
+                Err(e)
+            }
+        }
+    }
+
     /// Promotes an uncached text (i.e. a text that was added via `add_text_uncached`)
     /// to a cached text by calculating the font metrics for the uncached text.
     /// This will not delete the original text!
+    ///
+    /// Does nothing if the text or the font doesn't exist, see `cache_text_with_options`
+    /// to get the error instead.
     pub fn cache_text(&mut self, id: TextId, font: FontId, size: StyleFontSize, letter_spacing: Option<StyleLetterSpacing>) {
-        // We need to assume that the actual string contents have already been stored in self.text_cache
-        // Otherwise, how would the TextId be valid?
-        let text = self.text_cache.string_cache.get(&id).expect("Invalid text Id");
+        let options = TextLayoutOptions { letter_spacing, .. Default::default() };
+        let _ = self.cache_text_with_options(id, font, size, &options);
+    }
+
+    /// Same as `cache_text`, but the words are split with the `letter_spacing`, `direction`
+    /// and `white_space` of the `options`, see `add_text_cached_with_options`.
+    ///
+    /// Returns `FontError::InvalidTextId` if the text doesn't exist and `FontError::NotFound`
+    /// if the font isn't loaded - in both cases, nothing is cached.
+    pub fn cache_text_with_options(&mut self, id: TextId, font: FontId, size: StyleFontSize, options: &TextLayoutOptions)
+    -> Result<(), FontError>
+    {
+        let font_ids = self.get_cached_font_fallback_chain(&id, &StyleFontFamily { fonts: vec![font.clone()] }).ok_or(FontError::InvalidTextId(id))?;
+        if font_ids.first() != Some(&font) {
+            return Err(FontError::NotFound(font));
+        }
+        let TextLayoutOptions { letter_spacing, direction, white_space, .. } = *options;
+        let text = &self.text_cache.string_cache[&id];
         let font_size_no_line_height = TextSizePx(size.0.to_pixels());
-        let rusttype_font = self.get_font(&font).expect("Invalid font ID");
-        let words = split_text_into_words(text.as_ref(), &rusttype_font.0, font_size_no_line_height, letter_spacing);
+        let fonts = font_ids.iter().filter_map(|id| self.get_font(id)).collect::<Vec<_>>();
+        let mut words = split_text_into_words(text.as_ref(), &fonts.iter().map(|(font, data)| TextFont::new(&**font, &**data)).collect::<Vec<_>>(), font_size_no_line_height, letter_spacing, direction, white_space, self.text_cache.hyphenator.as_ref());
+        words.fonts = font_ids.clone();
+
+        let cache_key = WordsCacheKey { fonts: font_ids, letter_spacing, direction, white_space };
 
         self.text_cache.layouted_strings_cache
             .entry(id).or_insert_with(|| FastHashMap::default())
-            .entry(font).or_insert_with(|| FastHashMap::default())
+            .entry(cache_key).or_insert_with(|| FastHashMap::default())
             .insert(size, words);
+
+        Ok(())
     }
 
     /// Removes a string from the string cache, but not the layouted text cache
@@ -285,6 +401,27 @@ impl AppResources {
         self.text_cache.clear_all_texts();
     }
 
+    /// Sets the hyphenation patterns that are used for text with `hyphens: auto`
+    /// (or disables automatic hyphenation if `hyphenator` is `None`).
+    ///
+    /// Since the hyphenation points are calculated when the text is split into words,
+    /// this drops the layout of all cached texts - they are re-layouted on the next frame.
+    pub fn set_hyphenator(&mut self, hyphenator: Option<Hyphenator>) {
+        self.text_cache.hyphenator = hyphenator;
+        self.text_cache.layouted_strings_cache.clear();
+    }
+
+    /// Sets the fonts that are used for characters that none of the fonts of the `font-family`
+    /// can display (ex. emoji or CJK characters), in order of precedence. The default fallback
+    /// fonts depend on the operating system, builtin fonts that aren't installed are skipped.
+    ///
+    /// Drops the layout of all cached texts, since the glyphs depend on the fonts.
+    pub fn set_fallback_fonts(&mut self, fallback_fonts: Vec<FontId>) {
+        self.fallback_fonts = fallback_fonts;
+        self.text_cache.layouted_strings_cache.clear();
+        self.text_cache.font_fallback_chains.clear();
+    }
+
     /// Returns the contents of the system clipboard
     pub fn get_clipboard_string(&self)
     -> Result<String, ClipboardError>
@@ -299,3 +436,27 @@ impl AppResources {
         self.clipboard.set_string_contents(contents)
     }
 }
+
+/// Fonts that are commonly installed and cover symbols, emoji, math and CJK characters
+fn get_default_fallback_fonts() -> Vec<FontId> {
+
+    #[cfg(target_os = "windows")]
+    const FALLBACK_FONTS: &[&str] = &[
+        "Segoe UI", "Segoe UI Symbol", "Segoe UI Emoji", "Cambria Math",
+        "Microsoft YaHei", "Yu Gothic", "Malgun Gothic", "Nirmala UI",
+    ];
+
+    #[cfg(target_os = "macos")]
+    const FALLBACK_FONTS: &[&str] = &[
+        "Helvetica Neue", "Apple Symbols", "Apple Color Emoji", "STIXGeneral",
+        "PingFang SC", "Hiragino Sans", "Apple SD Gothic Neo", "Kohinoor Devanagari",
+    ];
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    const FALLBACK_FONTS: &[&str] = &[
+        "DejaVu Sans", "Noto Sans", "Noto Sans Symbols", "Noto Sans Symbols2", "Noto Color Emoji",
+        "Noto Sans Math", "Noto Sans CJK SC", "Noto Sans Devanagari",
+    ];
+
+    FALLBACK_FONTS.iter().map(|family| FontId::BuiltinFont(family.to_string())).collect()
+}
@@ -13,14 +13,16 @@ use azul_css::{FontId, StyleFontSize, PixelValue, StyleLetterSpacing};
 use {
     FastHashMap,
     text_cache::TextId,
+    text_layout::TextLayoutOptions,
     window::FakeWindow,
     task::Task,
-    dom::UpdateScreen,
+    dom::{UpdateScreen, Callback},
     traits::Layout,
     app_resources::AppResources,
     font::FontError,
     error::ClipboardError,
     daemon::{Daemon, DaemonId, TerminateDaemon},
+    hyphenation::Hyphenator,
 };
 
 /// Wrapper for your application data, stores the data, windows and resources, as
@@ -78,6 +80,28 @@ pub struct AppStateNoData<'a, T: 'a + Layout> {
     pub windows: &'a Vec<FakeWindow<T>>,
     /// See [`AppState.resources`](./struct.AppState.html#structfield.resources)
     pub resources : &'a mut AppResources,
+    /// Callbacks that are run after the default callbacks, see `defer_callback`
+    pub(crate) deferred_callbacks: &'a mut Vec<Callback<T>>,
+    /// Whether the event shouldn't be sent to the children of the node, see `stop_propagation`
+    pub(crate) propagation_stopped: &'a mut bool,
+}
+
+impl<'a, T: 'a + Layout> AppStateNoData<'a, T> {
+
+    /// Runs the (user-defined) callback after all default callbacks have run, with the same
+    /// `WindowEvent` as the current default callback. Default callbacks don't have access
+    /// to the application data, so this is how a widget can call a callback of the application,
+    /// ex. when a menu item is activated.
+    pub fn defer_callback(&mut self, callback: Callback<T>) {
+        self.deferred_callbacks.push(callback);
+    }
+
+    /// Stops sending the event to the children of the current node, ex. when a menu consumes
+    /// a key, a focused text input inside of the menu doesn't receive the key anymore. If the
+    /// event is a key press, the text of the key isn't sent to any node either.
+    pub fn stop_propagation(&mut self) {
+        *self.propagation_stopped = true;
+    }
 }
 
 impl<T: Layout> AppState<T> {
@@ -236,6 +260,12 @@ impl<T: Layout> AppState<T> {
     pub(crate) fn run_all_daemons(&mut self)
     -> UpdateScreen
     {
+        // Start the daemons that were added in the layout() functions, see `FakeWindow::add_daemon`
+        let new_daemons = self.windows.iter_mut().flat_map(|window| window.daemons.drain(..)).collect::<Vec<_>>();
+        for daemon in new_daemons {
+            self.add_daemon(daemon);
+        }
+
         let mut should_update_screen = UpdateScreen::DontRedraw;
         let mut lock = self.data.lock().unwrap();
         let mut daemons_to_terminate = Vec::new();
@@ -304,6 +334,14 @@ impl<T: Layout> AppState<T> {
         self.resources.add_text_cached(text, font_id, font_size, letter_spacing)
     }
 
+    /// See `AppResources::add_text_cached_with_options`
+    pub fn add_text_cached_with_options<S: Into<String>>(&mut self, text: S, font_id: &FontId, font_size: PixelValue, options: &TextLayoutOptions)
+    -> Result<TextId, FontError>
+    {
+        let font_size = StyleFontSize(font_size);
+        self.resources.add_text_cached_with_options(text, font_id, font_size, options)
+    }
+
     pub fn delete_text(&mut self, id: TextId) {
         self.resources.delete_text(id);
     }
@@ -312,6 +350,16 @@ impl<T: Layout> AppState<T> {
         self.resources.clear_all_texts();
     }
 
+    /// See `AppResources::set_hyphenator`
+    pub fn set_hyphenator(&mut self, hyphenator: Option<Hyphenator>) {
+        self.resources.set_hyphenator(hyphenator);
+    }
+
+    /// See `AppResources::set_fallback_fonts`
+    pub fn set_fallback_fonts(&mut self, fallback_fonts: Vec<FontId>) {
+        self.resources.set_fallback_fonts(fallback_fonts);
+    }
+
     /// Get the contents of the system clipboard as a string
     pub fn get_clipboard_string(&mut self)
     -> Result<String, ClipboardError>
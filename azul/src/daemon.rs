@@ -42,6 +42,7 @@ pub struct Daemon<T> {
     last_run: Instant,
     run_every: Option<Duration>,
     max_timeout: Option<Duration>,
+    delay: Option<Duration>,
     callback: DaemonCallback<T>,
     pub(crate) id: DaemonId,
 }
@@ -95,6 +96,7 @@ impl<T> Daemon<T> {
             last_run: Instant::now(),
             run_every: None,
             max_timeout: None,
+            delay: None,
             callback,
             id,
         }
@@ -109,6 +111,15 @@ impl<T> Daemon<T> {
         }
     }
 
+    /// Delays the first run of the daemon by the given `Duration` (counted from the
+    /// creation of the Daemon), ex. for showing a tooltip after the mouse has rested on a node
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self {
+            delay: Some(delay),
+            .. self
+        }
+    }
+
     /// Converts the daemon into a timer, running the function only if the given
     /// `Duration` has elapsed since the last run
     pub fn run_every(self, every: Duration) -> Self {
@@ -133,6 +144,12 @@ impl<T> Daemon<T> {
             }
         }
 
+        if let Some(delay) = self.delay {
+            if Instant::now() - self.created < delay {
+                return (UpdateScreen::DontRedraw, TerminateDaemon::Continue);
+            }
+        }
+
         if let Some(run_every) = self.run_every {
             if Instant::now() - self.last_run < run_every {
                 return (UpdateScreen::DontRedraw, TerminateDaemon::Continue);
@@ -156,6 +173,7 @@ impl<T> fmt::Debug for Daemon<T> {
             run_every: {:?}, \
             last_run: {:?}, \
             max_timeout: {:?}, \
+            delay: {:?}, \
             callback: {:?}, \
             id: {:?}, \
         }}",
@@ -163,6 +181,7 @@ impl<T> fmt::Debug for Daemon<T> {
         self.run_every,
         self.last_run,
         self.max_timeout,
+        self.delay,
         self.callback,
         self.id)
     }
@@ -175,6 +194,7 @@ impl<T> Clone for Daemon<T> {
             run_every: self.run_every,
             last_run: self.last_run,
             max_timeout: self.max_timeout,
+            delay: self.delay,
             callback: self.callback,
             id: self.id,
         }
@@ -187,6 +207,7 @@ impl<T> Hash for Daemon<T> {
         self.run_every.hash(state);
         self.last_run.hash(state);
         self.max_timeout.hash(state);
+        self.delay.hash(state);
         self.callback.hash(state);
         self.id.hash(state);
     }
@@ -198,6 +219,7 @@ impl<T> PartialEq for Daemon<T> {
         self.run_every == rhs.run_every &&
         self.last_run == rhs.last_run &&
         self.max_timeout == rhs.max_timeout &&
+        self.delay == rhs.delay &&
         self.callback == rhs.callback &&
         self.id == rhs.id
     }
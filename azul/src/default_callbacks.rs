@@ -10,12 +10,14 @@ mod stack_checked_pointer {
 
     use std::{
         fmt,
+        mem,
+        cell::RefCell,
         hash::{Hash, Hasher},
         marker::PhantomData,
     };
     use {
         traits::Layout,
-        dom::{UpdateScreen, Dom, Texture},
+        dom::{UpdateScreen, Dom, NodeType, Texture},
         default_callbacks::DefaultCallbackType,
         app_state::AppStateNoData,
         window::{WindowEvent, WindowInfo, HidpiAdjustedBounds},
@@ -25,6 +27,11 @@ mod stack_checked_pointer {
     /// value **inside** of `T`, i.e. contained within `&T as usize` and
     /// `&T as usize + mem::size_of::<T>()`. `StackCheckedPointer<T>`
     /// has the same lifetime as `T`.
+    ///
+    /// The value can also be inside of a page (ex. of a `Stack` or `Tabs`) that is stored
+    /// in a `Vec` inside of `T`, if the pointer is created while the page is rendered,
+    /// see `with_page_scope`. Since the pages can move or be removed, the page is looked
+    /// up by its key every time the pointer is used.
     pub struct StackCheckedPointer<T: Layout> {
         /// Type-erased pointer to a value on the stack in the `app_data.data`
         /// model. When invoking default methods, we have to store a pointer to
@@ -32,22 +39,122 @@ mod stack_checked_pointer {
         /// erase the type doesn't help anything - we trust the user of this
         /// pointer to know the exact type of this pointer.
         internal: *const (),
+        /// Where the value is if it is inside of a page on the heap, `internal`
+        /// is only the address of the value when the pointer was created
+        page: Option<PageLocation>,
         /// Marker so that one stack checked pointer can't be shared across
         /// two data models that are both `T: Layout`.
         marker: PhantomData<T>,
     }
 
+    /// Finds the page with the key in the type-erased pages, returns the address of the page
+    pub(crate) type FindPageFn = unsafe fn(*const (), usize) -> Option<*const u8>;
+
+    /// Location of a value inside of a page that is stored on the heap
+    ///
+    /// `pages` points to a field of `T` (checked in `StackCheckedPointer::new`), so it stays valid
+    /// for as long as `T` does, even though the pages themselves can be moved or dropped.
+    /// `find_page` is the function that was given to `with_page_scope` together with `pages`.
+    #[derive(Copy, Clone)]
+    struct PageLocation {
+        /// Type-erased pointer to the pages, which are inside of `T`
+        pages: *const (),
+        /// Unique key of the page in the pages
+        key: usize,
+        /// Distance of the value from the start of the page in bytes
+        offset: usize,
+        find_page: FindPageFn,
+    }
+
+    /// A page that is currently rendered, see `with_page_scope`
+    #[derive(Copy, Clone)]
+    struct PageScope {
+        pages: *const (),
+        pages_size: usize,
+        key: usize,
+        page: *const u8,
+        page_size: usize,
+        find_page: FindPageFn,
+    }
+
+    // The pages that are currently rendered, innermost last. A scope is only on the stack while
+    // its `render` function runs, so its pointers are never used after the page was borrowed.
+    thread_local! {
+        static PAGE_SCOPES: RefCell<Vec<PageScope>> = RefCell::new(Vec::new());
+    }
+
+    /// Renders a page that is stored on the heap, i.e. in the `pages` (a `Vec` inside of `T`).
+    /// While `render` runs, `StackCheckedPointer::new` accepts pointers to the values inside
+    /// of the `page`, so that the widgets of the page can be bound to the state of the page.
+    /// `find_page` has to find the page with the (unique) `key` in the `pages`.
+    ///
+    /// Only the innermost page scope is used, so a page that is stored inside of
+    /// another page on the heap (ex. a `Stack` inside of a tab) can't have bound widgets.
+    ///
+    /// `find_page` is called with the type-erased `pages` whenever a pointer into the page is
+    /// used, so it has to cast them back to a `C` and may only return the address of a `P`
+    /// (with the same `key`) that is inside of them. Then the offset of a value inside of the
+    /// `page` points to a value of the same type inside of the page that `find_page` returns.
+    pub(crate) fn with_page_scope<C, P, R, F: FnOnce() -> R>(pages: &C, key: usize, page: &P, find_page: FindPageFn, render: F) -> R {
+
+        /// Removes the scope again, even if `render` panics
+        struct PageScopeGuard;
+
+        impl Drop for PageScopeGuard {
+            fn drop(&mut self) {
+                PAGE_SCOPES.with(|scopes| scopes.borrow_mut().pop());
+            }
+        }
+
+        let scope = PageScope {
+            pages: pages as *const C as *const (),
+            pages_size: mem::size_of::<C>(),
+            key,
+            page: page as *const P as *const u8,
+            page_size: mem::size_of::<P>(),
+            find_page,
+        };
+
+        PAGE_SCOPES.with(|scopes| scopes.borrow_mut().push(scope));
+        let _guard = PageScopeGuard;
+        render()
+    }
+
     impl<T: Layout> StackCheckedPointer<T> {
 
-        /// Validates that the pointer to U is contained in T.
+        /// Validates that the pointer to U is contained in T (or in the page that is
+        /// currently rendered, if the pages are contained in T, see `with_page_scope`).
         ///
         /// This means that the lifetime of U is the same lifetime as T -
         /// the returned `StackCheckedPointer` is valid for as long as `stack`
         /// is valid.
         pub fn new<U: Sized>(stack: &T, pointer: &U) -> Option<Self> {
+
+            let internal = pointer as *const _ as *const ();
+
             if is_subtype_of(stack, pointer) {
+                return Some(Self { internal, page: None, marker: PhantomData });
+            }
+
+            // Only the addresses of the scope are compared here, nothing is dereferenced
+            // until the pointer is used (see `get_pointer`)
+            let scope = PAGE_SCOPES.with(|scopes| scopes.borrow().last().cloned())?;
+            let stack_address = stack as *const T as usize;
+            let page_address = scope.page as usize;
+            let pointer_address = internal as usize;
+
+            let pages_in_stack = is_inside(stack_address, mem::size_of::<T>(), scope.pages as usize, scope.pages_size);
+            let pointer_in_page = is_inside(page_address, scope.page_size, pointer_address, mem::size_of::<U>());
+
+            if pages_in_stack && pointer_in_page {
                 Some(Self {
-                    internal: pointer as *const _ as *const (),
+                    internal,
+                    page: Some(PageLocation {
+                        pages: scope.pages,
+                        key: scope.key,
+                        offset: pointer_address - page_address,
+                        find_page: scope.find_page,
+                    }),
                     marker: PhantomData,
                 })
             } else {
@@ -55,6 +162,28 @@ mod stack_checked_pointer {
             }
         }
 
+        /// Returns the address of the value, which identifies the value across frames
+        pub(crate) fn get_address(&self) -> usize {
+            self.internal as usize
+        }
+
+        /// Returns the current address of the value, `None` if the page of the value was removed
+        ///
+        /// **UNSAFE**: `T` has to be alive and not be borrowed mutably.
+        pub(crate) unsafe fn get_pointer(&self) -> Option<*const ()> {
+            match self.page {
+                Some(location) => {
+                    // SAFETY: `location.pages` is inside of `T`, which is alive and not borrowed
+                    // mutably, and `find_page` casts it back to the type of the pages that it was
+                    // created with (see `with_page_scope`). The page that it returns has the same
+                    // type as the page that the value was in, so the value is at the same offset.
+                    let page = (location.find_page)(location.pages, location.key)?;
+                    Some(page.add(location.offset) as *const ())
+                },
+                None => Some(self.internal),
+            }
+        }
+
         /// **UNSAFE**: Invoke the pointer with a function pointer that can
         /// modify the pointer. It isn't checked that the type that the
         /// `StackCheckedPointer` was created with is the same as this `U`,
@@ -72,7 +201,15 @@ mod stack_checked_pointer {
         -> UpdateScreen
         {
             // VERY UNSAFE, TRIPLE-CHECK FOR UNDEFINED BEHAVIOUR
-            callback(&mut *(self.internal as *mut U), app_state_no_data, window_event)
+            //
+            // SAFETY: The default callbacks are only run by `DefaultCallbackSystem::run_callback`,
+            // which takes the `&mut T` of the locked app data, so nothing else can access `T` (or
+            // the pages inside of it) while the callback has the `&mut U`. The pointer is looked up
+            // again for every call, so it doesn't dangle if a page was moved after it was created.
+            match self.get_pointer() {
+                Some(pointer) => callback(&mut *(pointer as *mut U), app_state_no_data, window_event),
+                None => UpdateScreen::DontRedraw,
+            }
         }
 
         pub unsafe fn invoke_mut_iframe<U: Sized>(
@@ -82,7 +219,10 @@ mod stack_checked_pointer {
             dimensions: HidpiAdjustedBounds)
         -> Dom<T>
         {
-            callback(&mut *(self.internal as *mut U), window_info, dimensions)
+            match self.get_pointer() {
+                Some(pointer) => callback(&mut *(pointer as *mut U), window_info, dimensions),
+                None => Dom::new(NodeType::Div),
+            }
         }
 
         pub unsafe fn invoke_mut_texture<U: Sized>(
@@ -92,7 +232,8 @@ mod stack_checked_pointer {
             dimensions: HidpiAdjustedBounds)
         -> Option<Texture>
         {
-            callback(&mut *(self.internal as *mut U), window_info, dimensions)
+            let pointer = self.get_pointer()?;
+            callback(&mut *(pointer as *mut U), window_info, dimensions)
         }
     }
 
@@ -106,7 +247,7 @@ mod stack_checked_pointer {
 
     impl<T: Layout> Clone for StackCheckedPointer<T> {
         fn clone(&self) -> Self {
-            StackCheckedPointer { internal: self.internal, marker: self.marker.clone() }
+            StackCheckedPointer { internal: self.internal, page: self.page, marker: self.marker.clone() }
         }
     }
 
@@ -166,6 +307,11 @@ mod stack_checked_pointer {
         }
     }
 
+    /// Whether the `size` bytes at the `address` are inside of the `container_size` bytes at the `container_address`
+    fn is_inside(container_address: usize, container_size: usize, address: usize, size: usize) -> bool {
+        address >= container_address && address + size <= container_address + container_size
+    }
+
     #[test]
     fn test_reflection_subtyping() {
 
@@ -180,6 +326,7 @@ mod stack_checked_pointer {
 
 
 pub use self::stack_checked_pointer::StackCheckedPointer;
+pub(crate) use self::stack_checked_pointer::with_page_scope;
 use std::{
     collections::BTreeMap,
     fmt,
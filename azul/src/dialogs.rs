@@ -1,4 +1,7 @@
 //! Dialogs (open folder, open file), message boxes and native color pickers
+//!
+//! These dialogs are native and block the thread until they are closed. For dialogs that
+//! are drawn inside of the window and can hold custom content, see `widgets::modal`.
 
 pub use tinyfiledialogs::{MessageBoxIcon, DefaultColorValue};
 
@@ -3,6 +3,7 @@
 
 use std::{
     fmt,
+    rc::Rc,
     sync::{Arc, Mutex},
     collections::BTreeMap,
 };
@@ -26,7 +27,7 @@ use azul_css::{
     StyleTextColor, StyleBackground, StyleBoxShadow, StyleBackgroundColor,
     StyleBorder, BoxShadowPreDisplayItem, LayoutPadding, SizeMetric,
     BoxShadowClipMode, FontId, StyleTextAlignmentVert, RectStyle, RectLayout,
-    ColorU as StyleColorU
+    StyleObjectFit, StyleObjectPosition, StyleUserSelect, StyleTextDecoration, ColorU as StyleColorU
 };
 use {
     FastHashMap,
@@ -40,9 +41,10 @@ use {
         IFrameCallback, NodeData, GlTextureCallback, ScrollTagId, DomHash, new_scroll_tag_id,
         NodeType::{self, Div, Text, Image, GlTexture, IFrame, Label}
     },
-    text_layout::{TextOverflowPass2, ScrollbarInfo, Words, FontMetrics},
+    text_layout::{TextOverflowPass2, ScrollbarInfo, Words, FontMetrics, GlyphInstance, InlineTextRun},
     images::ImageId,
     text_cache::TextInfo,
+    text_selection::{TextSelection, TextCaret, LaidOutText},
     compositor::new_opengl_texture_id,
     window::{Window, WindowInfo, FakeWindow, ScrollStates, HidpiAdjustedBounds},
 };
@@ -110,6 +112,10 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
             let tag = ui_state.node_ids_to_tag_ids.get(&node_id).and_then(|tag| Some(*tag));
             let mut rect = DisplayRectangle::new(tag, style);
             populate_style_properties(&mut rect, node_id, &ui_description.dynamic_style_overrides);
+            // Text that can't be selected doesn't need to be hit-tested
+            if rect.style.user_select == Some(StyleUserSelect::None) && tag.map(|tag| ui_state.text_only_tags.contains(&tag)).unwrap_or(false) {
+                rect.tag = None;
+            }
             rect
         });
 
@@ -270,7 +276,13 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
         let LogicalSize { width, height } = window.state.size.dimensions;
         let mut builder = DisplayListBuilder::with_capacity(window.internal.pipeline_id, TypedSize2D::new(width as f32, height as f32), self.rectangles.len());
 
-        let rects_in_rendering_order = determine_rendering_order(node_hierarchy, &self.rectangles, &laid_out_rectangles);
+        let rects_in_rendering_order = determine_rendering_order(node_hierarchy, node_data, &self.rectangles, &laid_out_rectangles);
+
+        let mut laid_out_texts = BTreeMap::new();
+
+        // The caret of a text editor is only drawn while the editor is focused, and it blinks
+        let focused_caret = get_focused_caret(node_hierarchy, node_data, window.state.focused_element);
+        let caret_is_visible = window.text_selection.update_caret(focused_caret);
 
         push_rectangles_into_displaylist(
             &laid_out_rectangles,
@@ -286,6 +298,8 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
                 display_rectangle_arena: &self.rectangles,
                 app_style: &window.style,
                 word_cache: &word_cache,
+                text_selection: window.text_selection.selection,
+                visible_caret: focused_caret.filter(|_| caret_is_visible).map(|(node_id, _)| node_id),
             },
             &mut DisplayListParametersMut {
                 app_data: &mut app_data_access,
@@ -293,10 +307,19 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
                 fake_window,
                 builder: &mut builder,
                 resource_updates: &mut resource_updates,
+                laid_out_texts: Some(&mut laid_out_texts),
                 pipeline_id: window.internal.pipeline_id,
             },
         );
 
+        // The layout of the text is kept until the next frame, for selecting text and for
+        // `FakeWindow::hit_test_text` / `FakeWindow::caret_rect`
+        let laid_out_texts = Rc::new(laid_out_texts);
+        fake_window.laid_out_texts = laid_out_texts.clone();
+        fake_window.iframe_rects = ::std::mem::replace(&mut fake_window.next_iframe_rects, BTreeMap::new());
+        window.text_selection.laid_out_texts = laid_out_texts;
+        window.internal.last_laid_out_rects = laid_out_rectangles;
+
         &window.internal.api.update_resources(resource_updates);
 
         (builder, scrollable_nodes)
@@ -343,6 +366,9 @@ impl<'a, T: Layout + 'a> DisplayList<'a, T> {
 /// ```
 /// Then the groups are simply rendered in-order: if there are multiple position:absolute
 /// groups, this has the side effect of later groups drawing on top of earlier groups.
+///
+/// Overlays (see `NodeData::is_overlay`) are grouped the same way, but their groups are
+/// drawn after all other groups, so that they are on top of all absolute items, too.
 #[derive(Debug, Clone, PartialEq)]
 struct ContentGroup {
     /// The parent of the current node group, i.e. either the root node (0)
@@ -352,6 +378,9 @@ struct ContentGroup {
     root_depth: usize,
     /// Node ids in order of drawing
     node_ids: Vec<RenderableNodeId>,
+    /// Whether the group is (part of) an overlay, see `NodeData::is_overlay`. Overlay groups
+    /// are drawn after all other groups and aren't clipped by the parents of the overlay.
+    is_overlay: bool,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -371,25 +400,32 @@ struct ContentGroupOrder {
 }
 
 
-fn determine_rendering_order<'a>(
+fn determine_rendering_order<'a, T: Layout>(
     node_hierarchy: &NodeHierarchy,
+    node_data: &NodeDataContainer<NodeData<T>>,
     rectangles: &NodeDataContainer<DisplayRectangle<'a>>,
     layouted_rects: &NodeDataContainer<LayoutRect>,
 ) -> ContentGroupOrder
 {
     let mut content_groups = Vec::new();
-    determine_rendering_order_inner(node_hierarchy, rectangles, layouted_rects, 0, NodeId::new(0), &mut content_groups);
+    let mut overlay_groups = Vec::new();
+    determine_rendering_order_inner(node_hierarchy, node_data, rectangles, layouted_rects, 0, NodeId::new(0), false, &mut content_groups, &mut overlay_groups);
+    // Overlays are drawn on top of all other content
+    content_groups.extend(overlay_groups);
     ContentGroupOrder { groups: content_groups }
 }
 
-fn determine_rendering_order_inner<'a>(
+fn determine_rendering_order_inner<'a, T: Layout>(
     node_hierarchy: &NodeHierarchy,
+    node_data: &NodeDataContainer<NodeData<T>>,
     rectangles: &NodeDataContainer<DisplayRectangle<'a>>,
     layouted_rects: &NodeDataContainer<LayoutRect>,
     // recursive parameters
     root_depth: usize,
     root_id: NodeId,
+    is_overlay: bool,
     content_groups: &mut Vec<ContentGroup>,
+    overlay_groups: &mut Vec<ContentGroup>,
 )
 {
     use id_tree::NodeEdge;
@@ -402,9 +438,11 @@ fn determine_rendering_order_inner<'a>(
         },
         root_depth,
         node_ids: Vec::new(),
+        is_overlay,
     };
 
     let mut absolute_node_ids = Vec::new();
+    let mut overlay_node_ids = Vec::new();
     let mut depth = root_depth + 1;
 
     // Same as the traverse function, but allows us to skip items, returns the next element
@@ -439,7 +477,12 @@ fn determine_rendering_order_inner<'a>(
                 NodeEdge::Start(start_tag) => {
                     let rect_node = &rectangles[start_tag];
                     let position = rect_node.layout.position.unwrap_or_default();
-                    if position == LayoutPosition::Absolute {
+                    if node_data[start_tag].is_overlay {
+                        // Same as for absolute nodes, but the overlay is drawn after all other groups
+                        overlay_node_ids.push((depth, start_tag));
+                        current_node_edge = NodeEdge::End(start_tag);
+                        should_continue_loop = false;
+                    } else if position == LayoutPosition::Absolute {
                         // For now, ignore the node and put it aside for later
                         absolute_node_ids.push((depth, start_tag));
                         // Skip this sub-tree and go straight to the next sibling
@@ -470,17 +513,32 @@ fn determine_rendering_order_inner<'a>(
         }
     }
 
-    content_groups.push(root_group);
+    if is_overlay {
+        overlay_groups.push(root_group);
+    } else {
+        content_groups.push(root_group);
+    }
 
     // Note: Currently reversed order, so that earlier absolute items are drawn
     // on top of later absolute items
     for (absolute_depth, absolute_node_id) in absolute_node_ids.into_iter().rev() {
-        determine_rendering_order_inner(node_hierarchy, rectangles, layouted_rects, absolute_depth, absolute_node_id, content_groups);
+        determine_rendering_order_inner(node_hierarchy, node_data, rectangles, layouted_rects, absolute_depth, absolute_node_id, is_overlay, content_groups, overlay_groups);
+    }
+
+    for (overlay_depth, overlay_node_id) in overlay_node_ids {
+        determine_rendering_order_inner(node_hierarchy, node_data, rectangles, layouted_rects, overlay_depth, overlay_node_id, true, content_groups, overlay_groups);
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct WordCache(BTreeMap<NodeId, (Words, FontMetrics)>);
+pub struct WordCache {
+    /// Words and font metrics of all text nodes
+    words: BTreeMap<NodeId, (Words, FontMetrics)>,
+    /// Glyphs of the `display: inline` labels, already positioned by their parent
+    inline_glyphs: BTreeMap<NodeId, Vec<GlyphInstance>>,
+    /// The part of each `display: inline` label on each line, see `InlineRunLayout::line_rects`
+    inline_rects: BTreeMap<NodeId, Vec<LayoutRect>>,
+}
 
 fn do_the_layout<'a,'b, T: Layout>(
     node_hierarchy: &NodeHierarchy,
@@ -493,24 +551,32 @@ fn do_the_layout<'a,'b, T: Layout>(
     rect_offset: LogicalPosition)
 -> (NodeDataContainer<LayoutRect>, Vec<(usize, NodeId)>, WordCache)
 {
-    use text_layout::{split_text_into_words, get_words_cached};
+    use text_layout::{split_text_into_words, get_words_cached, layout_inline_runs, InlineLayout, TextFont};
     use ui_solver::{solve_flex_layout_height, solve_flex_layout_width, get_x_positions, get_y_positions};
 
     let word_cache: BTreeMap<NodeId, (Words, FontMetrics)> = node_hierarchy
     .linear_iter()
     .filter_map(|id| {
-        let (font, font_metrics, font_id, font_size) = match node_data[id].node_type {
+        let (fonts, font_metrics, font_ids, font_size) = match node_data[id].node_type {
             NodeType::Label(_) | NodeType::Text(_) => {
                 use text_layout::TextLayoutOptions;
 
                 let rect = &display_rects[id];
                 let style = &rect.style;
-                let font_id = style.font_family.as_ref()?.fonts.get(0)?.clone();
+
+                // The characters that none of the fonts of the font-family can display
+                // are displayed with the fallback fonts
+                let font_ids = match &node_data[id].node_type {
+                    NodeType::Label(string_to_render) => app_resources.get_font_fallback_chain(style.font_family.as_ref()?, string_to_render),
+                    NodeType::Text(text_id) => app_resources.get_cached_font_fallback_chain(text_id, style.font_family.as_ref()?)?,
+                    _ => return None,
+                };
+                let font_id = font_ids.get(0)?.clone();
                 let font_size = style.font_size.unwrap_or(*DEFAULT_FONT_SIZE);
                 let font_size_app_units = Au((font_size.0.to_pixels() as i32) * AU_PER_PX as i32);
                 let font_instance_key = push_font(&font_id, font_size_app_units, resource_updates, app_resources, render_api)?;
                 let overflow_behaviour = style.overflow.unwrap_or(LayoutOverflow::default());
-                let font = app_resources.get_font(&font_id)?;
+                let fonts = font_ids.iter().filter_map(|f| app_resources.get_font(f)).collect::<Vec<_>>();
                 let (horz_alignment, vert_alignment) = determine_text_alignment(rect);
 
                 let text_layout_options = TextLayoutOptions {
@@ -518,56 +584,302 @@ fn do_the_layout<'a,'b, T: Layout>(
                     vert_alignment,
                     line_height: style.line_height,
                     letter_spacing: style.letter_spacing,
+                    hyphens: style.hyphens,
+                    direction: style.direction,
+                    white_space: style.white_space,
+                    tab_size: style.tab_size,
+                    text_overflow: style.text_overflow,
+                    line_clamp: style.line_clamp,
                 };
-                let font_metrics = FontMetrics::new(&font.0, &font_size, &text_layout_options);
+                let font_metrics = FontMetrics::new(&fonts[0].0, &font_size, &text_layout_options);
 
-                (font.0, font_metrics, font_id, font_size)
+                (fonts, font_metrics, font_ids, font_size)
             },
             _ => return None,
         };
 
+        let fonts = fonts.iter().map(|(font, data)| TextFont::new(&**font, &**data)).collect::<Vec<_>>();
+
         match &node_data[id].node_type {
             NodeType::Label(ref string_to_render) => {
-                Some((id, (split_text_into_words(&string_to_render, &font, font_metrics.font_size_no_line_height, font_metrics.letter_spacing), font_metrics)))
+                let mut words = split_text_into_words(&string_to_render, &fonts, font_metrics.font_size_no_line_height, font_metrics.letter_spacing, font_metrics.layout_options.direction, font_metrics.layout_options.white_space, app_resources.text_cache.hyphenator.as_ref());
+                words.fonts = font_ids;
+                Some((id, (words, font_metrics)))
             },
             NodeType::Text(text_id) => {
                 // Cloning the words here due to lifetime problems
                 Some((id, (get_words_cached(&text_id,
-                    &font,
-                    &font_id,
+                    &font_ids,
+                    &fonts,
                     &font_size,
                     font_metrics.font_size_no_line_height,
                     font_metrics.letter_spacing,
+                    font_metrics.layout_options.direction,
+                    font_metrics.layout_options.white_space,
                     &mut app_resources.text_cache).clone(), font_metrics)))
             },
             _ => None,
         }
     }).collect();
 
-    let preferred_widths = node_data.transform(|node, _| node.node_type.get_preferred_width(&app_resources.images));
+    let preferred_widths = node_data.transform(|node, id| {
+        // `aspect-ratio` with a fixed height takes precedence over the width of the content
+        get_preferred_width_from_aspect_ratio(&display_rects[id].layout)
+        .or_else(|| node.node_type.get_preferred_width(&app_resources.images))
+    });
     let solved_widths = solve_flex_layout_width(node_hierarchy, &display_rects, preferred_widths, rect_size.width as f32);
+
+    // Lay out the text of the `display: inline` labels on the lines of their parent
+    let inline_layouts = get_inline_formatting_contexts(node_hierarchy, node_data, display_rects)
+    .into_iter()
+    .map(|parent_id| {
+        let runs = get_inline_text_runs(parent_id, node_hierarchy, node_data, &word_cache);
+        let parent_style = &display_rects[parent_id].style;
+        let horz_alignment = parent_style.text_align.unwrap_or(StyleTextAlignmentHorz::Start)
+            .resolve(parent_style.direction.unwrap_or_default());
+        let inline_layout = layout_inline_runs(
+            &runs.iter().map(|(_, run)| *run).collect::<Vec<_>>(),
+            Some(get_content_width(&display_rects[parent_id].layout, solved_widths.solved_widths[parent_id].total())),
+            horz_alignment);
+        (parent_id, (runs.into_iter().map(|(child_id, _)| child_id).collect::<Vec<_>>(), inline_layout))
+    })
+    .collect::<BTreeMap<NodeId, (Vec<NodeId>, InlineLayout)>>();
+
+    let is_inline_child = |id: NodeId| {
+        node_hierarchy[id].parent.and_then(|parent_id| Some(inline_layouts.contains_key(&parent_id))).unwrap_or(false)
+    };
+
     let preferred_heights = node_data.transform(|node, id| {
         use text_layout::TextSizePx;
-        node.node_type.get_preferred_height_based_on_width(
-            TextSizePx(solved_widths.solved_widths[id].total()),
+
+        // The text of inline labels is laid out by the parent, so they don't take up any space themselves
+        if is_inline_child(id) {
+            return None;
+        }
+
+        let layout = &display_rects[id].layout;
+        let content_width = get_content_width(layout, solved_widths.solved_widths[id].total());
+
+        // `aspect-ratio` takes precedence over the aspect ratio of the content
+        get_preferred_height_from_aspect_ratio(layout, content_width)
+        .or_else(|| inline_layouts.get(&id).and_then(|(_, inline_layout)| Some(inline_layout.height.0)))
+        .or_else(|| node.node_type.get_preferred_height_based_on_width(
+            TextSizePx(content_width),
             &app_resources.images,
             word_cache.get(&id).and_then(|e| Some(&e.0)),
             word_cache.get(&id).and_then(|e| Some(e.1)),
-        ).and_then(|text_size| Some(text_size.0))
+        ).and_then(|text_size| Some(text_size.0)))
     });
     let solved_heights = solve_flex_layout_height(node_hierarchy, &solved_widths, preferred_heights, rect_size.height as f32);
 
+    // Distance from the top of each node to the baseline of its first line of text, for `align-items: baseline`
+    let baselines = node_data.transform(|_, id| {
+        if is_inline_child(id) {
+            return None;
+        }
+
+        let padding_top = get_padding_px(display_rects[id].layout.padding.unwrap_or_default().top);
+
+        if let Some((_, inline_layout)) = inline_layouts.get(&id) {
+            return inline_layout.first_baseline.and_then(|baseline| Some(padding_top + baseline.0));
+        }
+
+        let (words, font_metrics) = word_cache.get(&id)?;
+        Some(get_text_baseline(
+            &display_rects[id],
+            words,
+            font_metrics,
+            solved_widths.solved_widths[id].total(),
+            solved_heights.solved_heights[id].total()))
+    });
+
     let x_positions = get_x_positions(&solved_widths, node_hierarchy, rect_offset);
-    let y_positions = get_y_positions(&solved_heights, &solved_widths, node_hierarchy, rect_offset);
+    let y_positions = get_y_positions(&solved_heights, &solved_widths, node_hierarchy, &baselines, rect_offset);
 
-    let layouted_arena = node_data.transform(|node, node_id| {
+    let mut layouted_arena = node_data.transform(|node, node_id| {
         LayoutRect::new(
             LayoutPoint::new(x_positions[node_id].0, y_positions[node_id].0),
             LayoutSize::new(solved_widths.solved_widths[node_id].total(), solved_heights.solved_heights[node_id].total())
         )
     });
 
-    (layouted_arena, solved_widths.non_leaf_nodes_sorted_by_depth, WordCache(word_cache))
+    // Move the inline labels (and their glyphs) from the content box of the parent into world space
+    let mut inline_glyphs = BTreeMap::new();
+    let mut inline_rects = BTreeMap::new();
+
+    for (parent_id, (child_ids, inline_layout)) in inline_layouts.iter() {
+        let parent_rect = layouted_arena[*parent_id];
+        let padding = display_rects[*parent_id].layout.padding.unwrap_or_default();
+        let origin_x = parent_rect.origin.x + get_padding_px(padding.left);
+        let origin_y = parent_rect.origin.y + get_padding_px(padding.top);
+
+        for (child_id, run_layout) in child_ids.iter().zip(inline_layout.runs.iter()) {
+            let glyphs = run_layout.glyphs.iter().map(|glyph| GlyphInstance {
+                index: glyph.index,
+                point: LayoutPoint::new(glyph.point.x + origin_x, glyph.point.y + origin_y),
+            }).collect();
+            layouted_arena[*child_id] = LayoutRect::new(
+                LayoutPoint::new(origin_x + run_layout.bounds.origin.x, origin_y + run_layout.bounds.origin.y),
+                run_layout.bounds.size,
+            );
+            inline_glyphs.insert(*child_id, glyphs);
+            inline_rects.insert(*child_id, run_layout.line_rects.iter().map(|line_rect| {
+                LayoutRect::new(LayoutPoint::new(origin_x + line_rect.origin.x, origin_y + line_rect.origin.y), line_rect.size)
+            }).collect());
+        }
+    }
+
+    (layouted_arena, solved_widths.non_leaf_nodes_sorted_by_depth, WordCache { words: word_cache, inline_glyphs, inline_rects })
+}
+
+/// Returns the nodes whose children are laid out in an inline formatting context,
+/// i.e. nodes where all children are `display: inline` labels
+fn get_inline_formatting_contexts<'a, T: Layout>(
+    node_hierarchy: &NodeHierarchy,
+    node_data: &NodeDataContainer<NodeData<T>>,
+    display_rects: &NodeDataContainer<DisplayRectangle<'a>>)
+-> Vec<NodeId>
+{
+    use azul_css::LayoutDisplay;
+
+    let is_inline_label = |node_id: NodeId| match node_data[node_id].node_type {
+        Label(_) => display_rects[node_id].layout.display == Some(LayoutDisplay::Inline),
+        _ => false,
+    };
+
+    node_hierarchy.linear_iter().filter(|parent_id| {
+        parent_id.children(node_hierarchy).next().is_some() &&
+        parent_id.children(node_hierarchy).all(|child_id| is_inline_label(child_id))
+    }).collect()
+}
+
+/// Returns the runs of text of the children of an inline formatting context, together with
+/// the ID of the label that the run belongs to. Labels without a font are skipped.
+fn get_inline_text_runs<'a, T: Layout>(
+    parent_id: NodeId,
+    node_hierarchy: &NodeHierarchy,
+    node_data: &NodeDataContainer<NodeData<T>>,
+    word_cache: &'a BTreeMap<NodeId, (Words, FontMetrics)>)
+-> Vec<(NodeId, InlineTextRun<'a>)>
+{
+    let mut runs = Vec::new();
+    let mut previous_run_ends_with_space = false;
+
+    for child_id in parent_id.children(node_hierarchy) {
+        let text = match &node_data[child_id].node_type {
+            Label(text) => text,
+            _ => continue,
+        };
+
+        let (words, font_metrics) = match word_cache.get(&child_id) {
+            Some(s) => s,
+            None => continue,
+        };
+
+        // The words don't store any white space, so check the original text
+        runs.push((child_id, InlineTextRun {
+            words,
+            font_metrics: *font_metrics,
+            leading_space: previous_run_ends_with_space || text.starts_with(char::is_whitespace),
+        }));
+
+        previous_run_ends_with_space = text.ends_with(char::is_whitespace);
+    }
+
+    runs
+}
+
+/// Returns the distance from the top of a text node to the baseline of its first line,
+/// using the same vertical alignment as `text_layout::get_glyphs`
+fn get_text_baseline<'a>(
+    rect: &DisplayRectangle<'a>,
+    words: &Words,
+    font_metrics: &FontMetrics,
+    width: f32,
+    height: f32)
+-> f32
+{
+    use text_layout::TextSizePx;
+
+    let padding_top = get_padding_px(rect.layout.padding.unwrap_or_default().top);
+
+    let content_width = get_content_width(&rect.layout, width);
+    let content_height = (height - get_vertical_padding(&rect.layout)).max(0.0);
+
+    let overflow = rect.style.overflow.unwrap_or_default();
+    let text_height = words.get_vertical_height(&overflow, font_metrics, TextSizePx(content_width)).vertical_height.0;
+    let remaining_space = content_height - text_height;
+
+    // Text that overflows the rectangle isn't aligned vertically
+    let (_, vert_alignment) = determine_text_alignment(rect);
+    let alignment_offset = if remaining_space < 0.0 {
+        0.0
+    } else {
+        match vert_alignment {
+            StyleTextAlignmentVert::Top => 0.0,
+            StyleTextAlignmentVert::Center => remaining_space * 0.5,
+            StyleTextAlignmentVert::Bottom => remaining_space,
+        }
+    };
+
+    padding_top + alignment_offset + font_metrics.get_baseline_offset().0
+}
+
+/// Returns one side of the padding in pixels, `0.0` if the side isn't set
+fn get_padding_px(side: Option<PixelValue>) -> f32 {
+    side.and_then(|px| Some(px.to_pixels())).unwrap_or(0.0)
+}
+
+/// Returns `padding-left + padding-right` in pixels
+fn get_horizontal_padding(layout: &RectLayout) -> f32 {
+    let padding = layout.padding.unwrap_or_default();
+    get_padding_px(padding.left) + get_padding_px(padding.right)
+}
+
+/// Returns `padding-top + padding-bottom` in pixels
+fn get_vertical_padding(layout: &RectLayout) -> f32 {
+    let padding = layout.padding.unwrap_or_default();
+    get_padding_px(padding.top) + get_padding_px(padding.bottom)
+}
+
+/// Returns the width of the content box, i.e. the solved width without the padding
+fn get_content_width(layout: &RectLayout, solved_width: f32) -> f32 {
+    (solved_width - get_horizontal_padding(layout)).max(0.0)
+}
+
+/// If the node has an `aspect-ratio` and a fixed `height`, but no `width`, returns
+/// the width of the content box that satisfies the aspect ratio
+fn get_preferred_width_from_aspect_ratio(layout: &RectLayout) -> Option<f32> {
+    use azul_css::LayoutBoxSizing;
+
+    if layout.width.is_some() {
+        return None;
+    }
+
+    let aspect_ratio = layout.aspect_ratio?.get()?;
+    let height = layout.height?.0.to_pixels();
+
+    match layout.box_sizing.unwrap_or_default() {
+        LayoutBoxSizing::ContentBox => Some(height * aspect_ratio),
+        LayoutBoxSizing::BorderBox => Some((height * aspect_ratio - get_horizontal_padding(layout)).max(0.0)),
+    }
+}
+
+/// If the node has an `aspect-ratio`, returns the height of the content box
+/// that satisfies the aspect ratio, given the width of the content box
+fn get_preferred_height_from_aspect_ratio(layout: &RectLayout, content_width: f32) -> Option<f32> {
+    use azul_css::LayoutBoxSizing;
+
+    let aspect_ratio = layout.aspect_ratio?.get()?;
+
+    match layout.box_sizing.unwrap_or_default() {
+        LayoutBoxSizing::ContentBox => Some(content_width / aspect_ratio),
+        LayoutBoxSizing::BorderBox => {
+            // The aspect ratio applies to the outer size, including the padding
+            let outer_width = content_width + get_horizontal_padding(layout);
+            Some((outer_width / aspect_ratio - get_vertical_padding(layout)).max(0.0))
+        },
+    }
 }
 
 #[derive(Default, Debug, Clone)]
@@ -693,6 +1005,14 @@ fn push_rectangles_into_displaylist<'a, 'b, 'c, 'd, 'e, 'f, T: Layout>(
     let mut clip_stack = Vec::new();
 
     for content_group in content_grouped_rectangles.groups {
+
+        // Overlays aren't clipped by the `overflow: hidden` of their parents
+        if content_group.is_overlay {
+            while clip_stack.pop().is_some() {
+                referenced_mutable_content.builder.pop_clip_id();
+            }
+        }
+
         // Push the root of the node
         fn push_rect<'a,'b,'c,'d,'e,'f, T: Layout>(
             item: RenderableNodeId,
@@ -860,7 +1180,7 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
     let DisplayListParametersRef {
         render_api, app_style,
         display_rectangle_arena, word_cache, pipeline_id,
-        node_hierarchy, node_data,
+        node_hierarchy, node_data, text_selection, visible_caret,
     } = referenced_content;
 
     let DisplayListRectParams {
@@ -894,20 +1214,37 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
     }
 
 
+    // Inline labels that are broken across lines get one background (and hit-test) rect per line,
+    // so that they don't cover the text of the other labels. The second part of the hit-test tag
+    // is the index of the line, so that the text selection knows which rect was hit
+    let background_infos: Vec<LayoutPrimitiveInfo> = match word_cache.inline_rects.get(&rect_idx) {
+        Some(line_rects) => line_rects.iter().enumerate().map(|(line_index, line_rect)| LayoutPrimitiveInfo {
+            rect: *line_rect,
+            clip_rect: *line_rect,
+            tag: info.tag.map(|(tag, _)| (tag, line_index as u16)),
+            .. info
+        }).collect(),
+        None => vec![info],
+    };
+
     // If the rect is hit-testing relevant, we need to push a rect anyway. Otherwise the hit-testing gets confused
     if let Some(bg_col) = &rect.style.background_color {
         // The background color won't be seen anyway, so don't push a
         // background color if we do have a background already
         if rect.style.background.is_none() {
-            push_rect(&info,
-                      referenced_mutable_content.builder,
-                      bg_col);
+            for background_info in &background_infos {
+                push_rect(background_info,
+                          referenced_mutable_content.builder,
+                          bg_col);
+            }
         }
     } else if info.tag.is_some() {
         const TRANSPARENT_BG: StyleBackgroundColor = StyleBackgroundColor(StyleColorU { r: 0, g: 0, b: 0, a: 0 });
-        push_rect(&info,
-                  referenced_mutable_content.builder,
-                  &TRANSPARENT_BG);
+        for background_info in &background_infos {
+            push_rect(background_info,
+                      referenced_mutable_content.builder,
+                      &TRANSPARENT_BG);
+        }
     }
 
     if let Some(bg) = &rect.style.background {
@@ -943,19 +1280,21 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
         text_info: &TextInfo,
         builder: &mut DisplayListBuilder,
         app_resources: &mut AppResources,
-        resource_updates: &mut Vec<ResourceUpdate>|
+        resource_updates: &mut Vec<ResourceUpdate>,
+        laid_out_texts: Option<&mut BTreeMap<NodeId, LaidOutText>>|
     {
-        let words = word_cache.0.get(&rect_idx)?;
+        let words = word_cache.words.get(&rect_idx)?;
+        let inline_glyphs = word_cache.inline_glyphs.get(&rect_idx);
 
-        // Adjust the bounds by the padding
-        let mut text_bounds = rect.layout.padding.as_ref().and_then(|padding| {
+        // Adjust the bounds by the padding - inline text is already positioned by the parent
+        let mut text_bounds = rect.layout.padding.as_ref().filter(|_| inline_glyphs.is_none()).and_then(|padding| {
             Some(subtract_padding(&bounds, padding))
         }).unwrap_or(bounds);
 
         text_bounds.size.width = text_bounds.size.width.max(0.0);
         text_bounds.size.height = text_bounds.size.height.max(0.0);
 
-        let text_clip_region_id = rect.layout.padding.and_then(|_|
+        let text_clip_region_id = rect.layout.padding.filter(|_| inline_glyphs.is_none()).and_then(|_|
             Some(builder.define_clip(text_bounds, vec![ComplexClipRegion {
                 rect: text_bounds,
                 radii: BorderRadius::zero(),
@@ -979,7 +1318,14 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
             horz_alignment,
             vert_alignment,
             &scrollbar_style,
-            &words.0);
+            &words.0,
+            inline_glyphs,
+            background_infos.iter().map(|background_info| background_info.rect.origin).collect(),
+            rect_idx,
+            *text_selection,
+            node_data[rect_idx].caret,
+            *visible_caret == Some(rect_idx),
+            laid_out_texts);
 
         if text_clip_region_id.is_some() {
             builder.pop_clip_id();
@@ -995,18 +1341,42 @@ fn displaylist_handle_rect<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
             &TextInfo::Uncached(text.clone()),
             referenced_mutable_content.builder,
             referenced_mutable_content.app_resources,
-            referenced_mutable_content.resource_updates),
+            referenced_mutable_content.resource_updates,
+            referenced_mutable_content.laid_out_texts.as_mut().map(|s| &mut **s)),
         Text(text_id) => push_text_wrapper(
             &TextInfo::Cached(*text_id),
             referenced_mutable_content.builder,
             referenced_mutable_content.app_resources,
-            referenced_mutable_content.resource_updates),
-        Image(image_id) => push_image(
-            &info,
-            referenced_mutable_content.builder,
-            referenced_mutable_content.app_resources,
-            image_id,
-            info.rect.size),
+            referenced_mutable_content.resource_updates,
+            referenced_mutable_content.laid_out_texts.as_mut().map(|s| &mut **s)),
+        Image(image_id) => {
+            // Images are positioned inside of the content box, respecting `object-fit` / `object-position`
+            let content_bounds = rect.layout.padding.as_ref().and_then(|padding| {
+                Some(subtract_padding(&bounds, padding))
+            }).unwrap_or(bounds);
+
+            let image_dimensions = referenced_mutable_content.app_resources.images.get(image_id).and_then(|i| Some(i.get_dimensions()))
+                .unwrap_or((content_bounds.size.width, content_bounds.size.height));
+
+            let image_rect = get_object_fit_rect(
+                &content_bounds,
+                image_dimensions,
+                rect.style.object_fit.unwrap_or_default(),
+                rect.style.object_position.unwrap_or_default());
+
+            let image_info = LayoutPrimitiveInfo {
+                rect: image_rect,
+                clip_rect: content_bounds,
+                .. info
+            };
+
+            push_image(
+                &image_info,
+                referenced_mutable_content.builder,
+                referenced_mutable_content.app_resources,
+                image_id,
+                image_rect.size)
+        },
         GlTexture(callback) => push_opengl_texture(callback, &info, rectangle, referenced_content, referenced_mutable_content),
         IFrame(callback) => push_iframe(callback, &info, scrollable_nodes, rectangle, referenced_content, referenced_mutable_content),
     };
@@ -1150,11 +1520,18 @@ fn push_iframe<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
         rect_size,
         rect_origin);
 
+    // Keep the layout of the IFrame until the next frame, see `FakeWindow::get_iframe_rects`
+    {
+        use css::webrender_translate::wr_translate_layout_rect;
+        let iframe_rects = laid_out_rectangles.internal.iter().map(|rect| wr_translate_layout_rect(*rect)).collect();
+        referenced_mutable_content.fake_window.next_iframe_rects.insert(iframe_pointer.get_address(), iframe_rects);
+    }
+
     let mut scrollable_nodes = get_nodes_that_need_scroll_clip(
         node_hierarchy, &display_list.rectangles, node_data, &laid_out_rectangles,
         &node_depths, referenced_content.pipeline_id);
 
-    let rects_in_rendering_order = determine_rendering_order(node_hierarchy, &display_list.rectangles, &laid_out_rectangles);
+    let rects_in_rendering_order = determine_rendering_order(node_hierarchy, node_data, &display_list.rectangles, &laid_out_rectangles);
 
     let referenced_content = DisplayListParametersRef {
         // Important: Need to update the ui description, otherwise this function would be endlessly recursive
@@ -1162,9 +1539,14 @@ fn push_iframe<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
         node_data,
         display_rectangle_arena: &display_list.rectangles,
         word_cache: &word_cache,
+        visible_caret: None,
         .. *referenced_content
     };
 
+    // The node IDs of the IFrame DOM overlap with the node IDs of the window DOM,
+    // so the text inside of IFrames can't be selected or hit-tested yet
+    let laid_out_texts = referenced_mutable_content.laid_out_texts.take();
+
     push_rectangles_into_displaylist(
         &laid_out_rectangles,
         rectangle.epoch,
@@ -1175,6 +1557,8 @@ fn push_iframe<'a,'b,'c,'d,'e,'f,'g, T: Layout>(
         referenced_mutable_content
     );
 
+    referenced_mutable_content.laid_out_texts = laid_out_texts;
+
     parent_scrollable_nodes.overflowing_nodes.extend(scrollable_nodes.overflowing_nodes.into_iter());
     parent_scrollable_nodes.tags_to_node_ids.extend(scrollable_nodes.tags_to_node_ids.into_iter());
 
@@ -1201,6 +1585,10 @@ struct DisplayListParametersRef<'a, 'b, 'c, 'd, 'e, T: 'a + Layout> {
     /// Reference to the word cache (left over from the layout,
     /// to re-use the text layout from there)
     pub word_cache: &'c WordCache,
+    /// The text selection of the window, drawn behind the selected glyphs
+    pub text_selection: Option<TextSelection>,
+    /// The node whose caret is drawn in this frame, see `get_focused_caret`
+    pub visible_caret: Option<NodeId>,
 }
 
 /// Same as `DisplayListParametersRef`, but for `&mut Something`
@@ -1218,6 +1606,9 @@ struct DisplayListParametersMut<'a, T: 'a + Layout> {
     pub app_resources: &'a mut AppResources,
     /// If new fonts or other stuff are created, we need to tell WebRender about this
     pub resource_updates: &'a mut Vec<ResourceUpdate>,
+    /// The laid out text of the `Label` and `Text` nodes, for selecting and hit-testing text
+    /// in the next frame. `None` while pushing the nodes of an IFrame.
+    pub laid_out_texts: Option<&'a mut BTreeMap<NodeId, LaidOutText>>,
     /// Window access, so that sub-items can register OpenGL textures
     pub fake_window: &'a mut FakeWindow<T>,
     pub pipeline_id: PipelineId,
@@ -1252,20 +1643,46 @@ fn push_text(
     horz_alignment: StyleTextAlignmentHorz,
     vert_alignment: StyleTextAlignmentVert,
     scrollbar_info: &ScrollbarInfo,
-    words: &Words)
+    words: &Words,
+    inline_glyphs: Option<&Vec<GlyphInstance>>,
+    hit_test_origins: Vec<LayoutPoint>,
+    node_id: NodeId,
+    text_selection: Option<TextSelection>,
+    caret: Option<TextCaret>,
+    draw_caret: bool,
+    laid_out_texts: Option<&mut BTreeMap<NodeId, LaidOutText>>)
 -> Option<OverflowInfo>
 {
-    use text_layout::{self, TextLayoutOptions};
+    use text_layout::{self, TextLayoutOptions, TextOverflow, TextSizePx};
     use css::webrender_translate::wr_translate_color_u;
 
+    let caret_color = StyleBackgroundColor(style.font_color.unwrap_or(DEFAULT_FONT_COLOR).0);
+
     if text.is_empty_text(&*app_resources) {
+        // The caret of an empty text editor is drawn at the start of the first line
+        if draw_caret {
+            let font_size = style.font_size.unwrap_or(*DEFAULT_FONT_SIZE).0.to_pixels();
+            let line_height = style.line_height.map(|lh| lh.0.get()).unwrap_or(1.0) * font_size;
+            let caret_x = match horz_alignment {
+                StyleTextAlignmentHorz::Center => bounds.origin.x + bounds.size.width / 2.0,
+                StyleTextAlignmentHorz::Right => bounds.max_x() - 1.0,
+                _ => bounds.origin.x,
+            };
+            let caret_info = LayoutPrimitiveInfo {
+                rect: LayoutRect::new(LayoutPoint::new(caret_x, bounds.origin.y), LayoutSize::new(1.0, line_height)),
+                tag: None,
+                .. *info
+            };
+            push_rect(&caret_info, builder, &caret_color);
+        }
         return None;
     }
 
-    let font_id = style.font_family.as_ref()?.fonts.get(0)?.clone();
+    // The first font of the font fallback chain, see `AppResources::get_font_fallback_chain`
+    let font_id = words.fonts.get(0)?.clone();
     let font_size = style.font_size.unwrap_or(*DEFAULT_FONT_SIZE);
     let font_size_app_units = Au((font_size.0.to_pixels() as i32) * AU_PER_PX as i32);
-    let font_instance_key = push_font(&font_id, font_size_app_units, resource_updates, app_resources, render_api)?;
+    push_font(&font_id, font_size_app_units, resource_updates, app_resources, render_api)?;
     let overflow_behaviour = style.overflow.unwrap_or_default();
 
     let text_layout_options = TextLayoutOptions {
@@ -1273,19 +1690,32 @@ fn push_text(
         vert_alignment,
         line_height: style.line_height,
         letter_spacing: style.letter_spacing,
+        hyphens: style.hyphens,
+        direction: style.direction,
+        white_space: style.white_space,
+        tab_size: style.tab_size,
+        text_overflow: style.text_overflow,
+        line_clamp: style.line_clamp,
     };
 
-    let (positioned_glyphs, text_overflow) = text_layout::get_glyphs(
-        words,
-        app_resources,
-        bounds,
-        &font_id,
-        &font_size,
-        &text_layout_options,
-        text,
-        &overflow_behaviour,
-        scrollbar_info
-    );
+    let (positioned_glyphs, index_map, text_overflow) = match inline_glyphs {
+        // Inline text has already been laid out by the parent
+        Some(glyphs) => (glyphs.clone(), words.get_inline_index_map(), TextOverflowPass2 {
+            horizontal: TextOverflow::InBounds(TextSizePx(0.0)),
+            vertical: TextOverflow::InBounds(TextSizePx(0.0)),
+        }),
+        None => text_layout::get_glyphs(
+            words,
+            app_resources,
+            bounds,
+            &font_id,
+            &font_size,
+            &text_layout_options,
+            text,
+            &overflow_behaviour,
+            scrollbar_info
+        ),
+    };
 
     // WARNING: Do not enable FontInstanceFlags::FONT_SMOOTHING or FontInstanceFlags::FORCE_AUTOHINT -
     // they seem to interfere with the text layout thereby messing with the actual text layout.
@@ -1299,11 +1729,142 @@ fn push_text(
         flags: flags,
     };
 
-    builder.push_text(&info, &positioned_glyphs, font_instance_key, font_color, Some(options));
+    let is_selectable = style.user_select.unwrap_or_default() == StyleUserSelect::Text;
+    let laid_out_texts = laid_out_texts.filter(|_| is_selectable || caret.is_some());
+    let text_decoration = style.text_decoration.unwrap_or_default();
+
+    // The advances of the glyphs are only needed for selecting the text and for the text-decoration
+    let (font_metrics, advances) = if laid_out_texts.is_some() || text_decoration != StyleTextDecoration::None {
+        let fonts = words.fonts.iter().filter_map(|id| app_resources.get_font(id).map(|f| f.0)).collect::<Vec<_>>();
+        let font_refs = fonts.iter().map(|f| &**f).collect::<Vec<_>>();
+        // None of the fonts are loaded, so none of the glyphs could be drawn
+        let font_metrics = FontMetrics::new(font_refs.first()?, &font_size, &text_layout_options);
+        let advances = text_layout::get_glyph_advances(&positioned_glyphs, &font_refs, font_metrics.font_size_no_line_height);
+        (Some(font_metrics), Some(advances))
+    } else {
+        (None, None)
+    };
+
+    let line_extents = advances.as_ref()
+        .filter(|_| text_decoration != StyleTextDecoration::None)
+        .map(|advances| text_layout::get_line_extents(&positioned_glyphs, advances));
+
+    // Remember where the glyphs of selectable text and of text editors are, so that the text can be
+    // selected and hit-tested with the mouse, and draw the current selection behind the glyphs
+    let mut caret_rect = None;
+    if let (Some(laid_out_texts), Some(font_metrics), Some(advances)) = (laid_out_texts, font_metrics.as_ref(), advances) {
+        let laid_out_text = LaidOutText::new(
+            text.get_text(&*app_resources),
+            positioned_glyphs.clone(),
+            advances,
+            index_map,
+            info.rect.origin,
+            hit_test_origins,
+            is_selectable,
+            font_metrics);
+
+        // The selection of a text editor (see `Dom::with_caret`) is drawn the same way as the text selection,
+        // the offsets of the editor refer to the text before it was normalized
+        let selected_range = caret.and_then(|c| c.selection)
+            .map(|(start, end)| (laid_out_text.from_original_offset(start), laid_out_text.from_original_offset(end)))
+            .or_else(|| text_selection.filter(|_| is_selectable).and_then(|s| s.get_range(node_id, &laid_out_text.text)));
+        if let Some((start, end)) = selected_range {
+            let selection_color = StyleBackgroundColor(style.selection_background_color.unwrap_or_default().0);
+            for selection_rect in laid_out_text.get_selection_rects(start, end) {
+                let selection_info = LayoutPrimitiveInfo {
+                    rect: selection_rect,
+                    tag: None,
+                    .. *info
+                };
+                push_rect(&selection_info, builder, &selection_color);
+            }
+        }
+
+        caret_rect = caret.filter(|_| draw_caret).map(|caret| {
+            let rect = laid_out_text.get_caret_rect(laid_out_text.from_original_offset(caret.offset), caret.affinity);
+            LayoutRect::new(rect.origin + laid_out_text.origin.to_vector(), rect.size)
+        });
+
+        laid_out_texts.insert(node_id, laid_out_text);
+    }
+
+    // One text item per font - the fallback fonts that were loaded for this text
+    // are uploaded in the next frame, until then their glyphs are skipped
+    for (font_index, glyphs) in text_layout::split_into_font_runs(&positioned_glyphs) {
+        if let Some(font_instance_key) = push_font(&words.fonts[font_index], font_size_app_units, resource_updates, app_resources, render_api) {
+            builder.push_text(&info, &glyphs, font_instance_key, font_color, Some(options));
+        }
+    }
+
+    if let (Some(line_extents), Some(font_metrics)) = (line_extents, font_metrics.as_ref()) {
+        let decoration_color = StyleBackgroundColor(style.font_color.unwrap_or(DEFAULT_FONT_COLOR).0);
+        for decoration_rect in get_text_decoration_rects(text_decoration, &line_extents, font_metrics) {
+            let decoration_info = LayoutPrimitiveInfo {
+                rect: decoration_rect,
+                tag: None,
+                .. *info
+            };
+            push_rect(&decoration_info, builder, &decoration_color);
+        }
+    }
+
+    if let Some(caret_rect) = caret_rect {
+        let caret_info = LayoutPrimitiveInfo {
+            rect: caret_rect,
+            tag: None,
+            .. *info
+        };
+        push_rect(&caret_info, builder, &caret_color);
+    }
 
     Some(OverflowInfo { text_overflow })
 }
 
+/// Returns the node and the caret offset of the focused text editor, i.e. the first `Label` / `Text`
+/// node with a visible caret (see `Dom::with_caret`) that is the focused node or a child of it
+fn get_focused_caret<T: Layout>(
+    node_hierarchy: &NodeHierarchy,
+    node_data: &NodeDataContainer<NodeData<T>>,
+    focused_node: Option<NodeId>)
+-> Option<(NodeId, usize)>
+{
+    let focused_node = focused_node?;
+    node_data.linear_iter().find_map(|node_id| {
+        let caret = node_data[node_id].caret.filter(|caret| caret.show_caret)?;
+        if node_id.ancestors(node_hierarchy).any(|ancestor| ancestor == focused_node) {
+            Some((node_id, caret.offset))
+        } else {
+            None
+        }
+    })
+}
+
+/// Returns the rectangles of the lines that are drawn under, over or through each line
+/// of the text, `line_extents` are the lines of the glyphs (see `text_layout::get_line_extents`)
+fn get_text_decoration_rects(
+    text_decoration: StyleTextDecoration,
+    line_extents: &[(f32, f32, f32)],
+    font_metrics: &FontMetrics)
+-> Vec<LayoutRect>
+{
+    // rusttype doesn't expose the underline metrics of the font, so these are approximated
+    let font_size = font_metrics.font_size_no_line_height.0;
+    let thickness = (font_size / 16.0).max(1.0);
+
+    let offset_from_baseline = match text_decoration {
+        StyleTextDecoration::None => return Vec::new(),
+        StyleTextDecoration::Underline => font_size * 0.1,
+        StyleTextDecoration::Overline => -font_metrics.ascent.0,
+        StyleTextDecoration::LineThrough => -font_size * 0.3,
+    };
+
+    line_extents.iter().map(|(baseline, x_start, x_end)| {
+        LayoutRect::new(
+            LayoutPoint::new(*x_start, baseline + offset_from_baseline - thickness / 2.0),
+            LayoutSize::new(x_end - x_start, thickness))
+    }).collect()
+}
+
 /// Adds a scrollbar to the left or bottom side of a rectangle.
 /// TODO: make styling configurable (like the width / style of the scrollbar)
 fn push_scrollbar(
@@ -1786,6 +2347,56 @@ fn push_image(
     None
 }
 
+/// Returns the rectangle that an image with the original size of `image_dimensions`
+/// has to be drawn into, so that it satisfies the `object-fit` and `object-position`
+/// properties. The resulting rectangle can be larger than the `bounds` (for
+/// `object-fit: cover` or `object-fit: none`), so it needs to be clipped to the bounds.
+fn get_object_fit_rect(
+    bounds: &LayoutRect,
+    image_dimensions: (f32, f32),
+    object_fit: StyleObjectFit,
+    object_position: StyleObjectPosition)
+-> LayoutRect
+{
+    use azul_css::LayoutSize as CssLayoutSize;
+
+    let image_size = object_fit.get_image_size(
+        CssLayoutSize::new(image_dimensions.0, image_dimensions.1),
+        CssLayoutSize::new(bounds.size.width, bounds.size.height));
+
+    let offset_x = object_position.horizontal.get_offset(bounds.size.width - image_size.width);
+    let offset_y = object_position.vertical.get_offset(bounds.size.height - image_size.height);
+
+    LayoutRect::new(
+        LayoutPoint::new(bounds.origin.x + offset_x, bounds.origin.y + offset_y),
+        LayoutSize::new(image_size.width, image_size.height),
+    )
+}
+
+#[test]
+fn test_object_fit_rect() {
+    use azul_css::{ObjectPositionValue, PercentageValue};
+
+    let bounds = LayoutRect::new(LayoutPoint::new(10.0, 10.0), LayoutSize::new(200.0, 100.0));
+
+    // 400x400 image in a 200x100 box
+    let contain = get_object_fit_rect(&bounds, (400.0, 400.0), StyleObjectFit::Contain, StyleObjectPosition::default());
+    assert_eq!(contain, LayoutRect::new(LayoutPoint::new(60.0, 10.0), LayoutSize::new(100.0, 100.0)));
+
+    let cover = get_object_fit_rect(&bounds, (400.0, 400.0), StyleObjectFit::Cover, StyleObjectPosition::default());
+    assert_eq!(cover, LayoutRect::new(LayoutPoint::new(10.0, -40.0), LayoutSize::new(200.0, 200.0)));
+
+    let fill = get_object_fit_rect(&bounds, (400.0, 400.0), StyleObjectFit::Fill, StyleObjectPosition::default());
+    assert_eq!(fill, bounds);
+
+    let top_left = StyleObjectPosition {
+        horizontal: ObjectPositionValue::Percentage(PercentageValue::new(0.0)),
+        vertical: ObjectPositionValue::Percentage(PercentageValue::new(0.0)),
+    };
+    let none = get_object_fit_rect(&bounds, (400.0, 400.0), StyleObjectFit::None, top_left);
+    assert_eq!(none, LayoutRect::new(LayoutPoint::new(10.0, 10.0), LayoutSize::new(400.0, 400.0)));
+}
+
 #[inline]
 fn push_border(
     info: &PrimitiveInfo<LayoutPixel>,
@@ -1881,8 +2492,8 @@ fn determine_text_alignment<'a>(rect: &DisplayRectangle<'a>)
         use azul_css::LayoutJustifyContent;
         // Horizontal text alignment
         match justify_content {
-            LayoutJustifyContent::Start => horz_alignment = StyleTextAlignmentHorz::Left,
-            LayoutJustifyContent::End => horz_alignment = StyleTextAlignmentHorz::Right,
+            LayoutJustifyContent::Start => horz_alignment = StyleTextAlignmentHorz::Start,
+            LayoutJustifyContent::End => horz_alignment = StyleTextAlignmentHorz::End,
             _ => horz_alignment = StyleTextAlignmentHorz::Center,
         }
     }
@@ -1892,6 +2503,9 @@ fn determine_text_alignment<'a>(rect: &DisplayRectangle<'a>)
         horz_alignment = text_align;
     }
 
+    // `start` and `end` depend on the direction of the text
+    let horz_alignment = horz_alignment.resolve(rect.style.direction.unwrap_or_default());
+
     (horz_alignment, vert_alignment)
 }
 
@@ -1901,10 +2515,10 @@ fn determine_text_alignment<'a>(rect: &DisplayRectangle<'a>)
 fn subtract_padding(bounds: &TypedRect<f32, LayoutPixel>, padding: &LayoutPadding)
 -> TypedRect<f32, LayoutPixel>
 {
-    let top     = padding.top.and_then(|top| Some(top.to_pixels())).unwrap_or(0.0);
-    let bottom  = padding.bottom.and_then(|bottom| Some(bottom.to_pixels())).unwrap_or(0.0);
-    let left    = padding.left.and_then(|left| Some(left.to_pixels())).unwrap_or(0.0);
-    let right   = padding.right.and_then(|right| Some(right.to_pixels())).unwrap_or(0.0);
+    let top     = get_padding_px(padding.top);
+    let bottom  = get_padding_px(padding.bottom);
+    let left    = get_padding_px(padding.left);
+    let right   = get_padding_px(padding.right);
 
     let mut new_bounds = *bounds;
 
@@ -1934,10 +2548,21 @@ fn populate_style_properties(
             FontSize(f)         => { rect.style.font_size = Some(*f);                       },
             FontFamily(f)       => { rect.style.font_family = Some(f.clone());              },
             LetterSpacing(l)    => { rect.style.letter_spacing = Some(*l);                  },
+            Hyphens(h)          => { rect.style.hyphens = Some(*h);                         },
+            Direction(d)        => { rect.style.direction = Some(*d);                       },
+            UserSelect(u)       => { rect.style.user_select = Some(*u);                     },
+            SelectionBackgroundColor(s) => { rect.style.selection_background_color = Some(*s); },
+            TextDecoration(t)   => { rect.style.text_decoration = Some(*t);                 },
+            WhiteSpace(w)       => { rect.style.white_space = Some(*w);                     },
+            TabSize(t)          => { rect.style.tab_size = Some(*t);                        },
+            TextOverflow(t)     => { rect.style.text_overflow = Some(*t);                   },
+            LineClamp(l)        => { rect.style.line_clamp = Some(*l);                      },
             Overflow(o)         => { LayoutOverflow::merge(&mut rect.style.overflow, &o);   },
             TextAlign(ta)       => { rect.style.text_align = Some(*ta);                     },
             BoxShadow(b)        => { StyleBoxShadow::merge(&mut rect.style.box_shadow, b);  },
             LineHeight(lh)      => { rect.style.line_height = Some(*lh);                    },
+            ObjectFit(o)        => { rect.style.object_fit = Some(*o);                      },
+            ObjectPosition(o)   => { rect.style.object_position = Some(*o);                 },
 
             Width(w)            => { rect.layout.width = Some(*w);                          },
             Height(h)           => { rect.layout.height = Some(*h);                         },
@@ -1945,6 +2570,8 @@ fn populate_style_properties(
             MinHeight(mh)       => { rect.layout.min_height = Some(*mh);                    },
             MaxWidth(mw)        => { rect.layout.max_width = Some(*mw);                     },
             MaxHeight(mh)       => { rect.layout.max_height = Some(*mh);                    },
+            BoxSizing(b)        => { rect.layout.box_sizing = Some(*b);                     },
+            AspectRatio(a)      => { rect.layout.aspect_ratio = Some(*a);                   },
 
             Position(p)         => { rect.layout.position = Some(*p);                       },
             Top(t)              => { rect.layout.top = Some(*t);                            },
@@ -1961,7 +2588,9 @@ fn populate_style_properties(
             FlexDirection(d)    => { rect.layout.direction = Some(*d);                      },
             JustifyContent(j)   => { rect.layout.justify_content = Some(*j);                },
             AlignItems(a)       => { rect.layout.align_items = Some(*a);                    },
+            AlignSelf(a)        => { rect.layout.align_self = Some(*a);                     },
             AlignContent(a)     => { rect.layout.align_content = Some(*a);                  },
+            Display(d)          => { rect.layout.display = Some(*d);                        },
             Cursor(_)           => { /* cursor neither affects layout nor styling */        },
         }
     }
@@ -4,7 +4,7 @@ use std::{
     cell::RefCell,
     hash::{Hash, Hasher},
     sync::atomic::{AtomicUsize, Ordering},
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     iter::FromIterator,
 };
 use glium::{Texture2d, framebuffer::SimpleFrameBuffer};
@@ -21,6 +21,8 @@ use {
     default_callbacks::{DefaultCallbackId, StackCheckedPointer},
     window::HidpiAdjustedBounds,
     text_layout::{Words, FontMetrics, TextSizePx},
+    text_selection::TextCaret,
+    tooltip::Tooltip,
 };
 
 static TAG_ID: AtomicUsize = AtomicUsize::new(1);
@@ -43,6 +45,27 @@ pub(crate) fn new_scroll_tag_id() -> ScrollTagId {
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
 pub struct DomHash(pub u64);
 
+/// Identifies a node across frames: the identity is derived from the identity of the parent,
+/// the type of the node and either its `key` or its position among its siblings, see `NodeData::key`
+#[derive(Debug, Default, Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub struct NodeIdentity(pub u64);
+
+impl NodeIdentity {
+    fn new<T: Layout>(parent: Option<NodeIdentity>, index_in_parent: usize, data: &NodeData<T>) -> Self {
+        use std::collections::hash_map::DefaultHasher;
+        use std::mem::discriminant;
+
+        let mut hasher = DefaultHasher::default();
+        parent.hash(&mut hasher);
+        match data.key {
+            Some(key) => (1_u8, key).hash(&mut hasher),
+            None => (0_u8, index_in_parent as u64).hash(&mut hasher),
+        }
+        discriminant(&data.node_type).hash(&mut hasher);
+        NodeIdentity(hasher.finish())
+    }
+}
+
 /// A callback function has to return if the screen should
 /// be updated after the function has run.PartialEq
 ///
@@ -193,7 +216,7 @@ impl<T: Layout> Copy for IFrameCallback<T> { }
 pub enum NodeType<T: Layout> {
     /// Regular div with no particular type of data attached
     Div,
-    /// A small label that can be (optionally) be selectable with the mouse
+    /// A small label, the text can be selected with the mouse unless it has `user-select: none`
     Label(String),
     /// Larger amount of text, that has to be cached
     Text(TextId),
@@ -305,7 +328,8 @@ impl<T: Layout> NodeType<T> {
         }
     }
 
-    /// Given a certain width, returns the
+    /// Given a certain width, returns the preferred height, i.e. the height of an image
+    /// scaled to the width (preserving its aspect ratio) or the height of a wrapped text
     pub(crate) fn get_preferred_height_based_on_width(
         &self,
         div_width: TextSizePx,
@@ -319,8 +343,12 @@ impl<T: Layout> NodeType<T> {
 
         match self {
             Image(i) => image_cache.get(i).and_then(|image_state| {
-                let (image_original_height, image_original_width) = image_state.get_dimensions();
-                Some(div_width * (image_original_width / image_original_height))
+                let (image_original_width, image_original_height) = image_state.get_dimensions();
+                if image_original_width == 0.0 {
+                    None
+                } else {
+                    Some(div_width * (image_original_height / image_original_width))
+                }
             }),
             Label(_) | Text(_) => {
                 let (words, font) = (words?, font_metrics?);
@@ -428,10 +456,37 @@ pub struct NodeData<T: Layout> {
     ///
     /// **TODO**: Currently doesn't do anything, since the drag & drop implementation is missing, API stub.
     pub draggable: bool,
+    /// Whether this node captures the pointer when the left mouse button is pressed on it, i.e.
+    /// it keeps receiving the mouse events until the button is released, even if the mouse leaves
+    /// the node (ex. for dragging the thumb of a slider). Default: `false`
+    pub captures_pointer: bool,
+    /// Whether this node (and its children) is drawn on top of all other content, ex. for the
+    /// popup of a dropdown. The overlay isn't clipped by its parents and the nodes below it don't
+    /// receive the mouse events. Overlays should be `position: absolute`. Default: `false`
+    pub is_overlay: bool,
+    /// Whether the keyboard focus is kept inside of this node, ex. for a modal dialog: focusing a node
+    /// outside of it focuses this node instead. If there are several focus traps, the last one in the
+    /// DOM is used. Default: `false`
+    pub traps_focus: bool,
     /// Whether this div can be focused, and if yes, in what default to `None` (not focusable).
     /// Note that without this, there can be no `On::FocusReceived` (equivalent to onfocus),
     /// `On::FocusLost` (equivalent to onblur), etc. events.
     pub tab_index: Option<TabIndex>,
+    /// The caret and the selection of a text editor, only used on `Label` and `Text` nodes.
+    /// Default: `None` (no caret).
+    pub caret: Option<TextCaret>,
+    /// Identifies the node among its siblings across frames. The focus, the pointer capture
+    /// and the text selection stay on the node with the same identity (see `NodeIdentity`)
+    /// when the DOM is rebuilt, or are removed if there is no such node anymore. Without a
+    /// key, a node is identified by its position in its parent, so that a node in a list
+    /// that changes should have a key, i.e. the ID of the item that the node displays.
+    /// Keys have to be unique among the siblings (checked in debug builds): siblings with the
+    /// same key and node type have the same identity, so that the focus ends up on the last of
+    /// them. Default: `None`
+    pub key: Option<u64>,
+    /// Shown when the mouse rests on the node (or one of its children, that has no tooltip
+    /// itself), similar to the `title` attribute in HTML, see `Tooltip`. Default: `None`
+    pub tooltip: Option<Tooltip<T>>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
@@ -478,7 +533,13 @@ impl<T: Layout> PartialEq for NodeData<T> {
         self.default_callback_ids == other.default_callback_ids &&
         self.dynamic_style_overrides == other.dynamic_style_overrides &&
         self.draggable == other.draggable &&
-        self.tab_index == other.tab_index
+        self.captures_pointer == other.captures_pointer &&
+        self.is_overlay == other.is_overlay &&
+        self.traps_focus == other.traps_focus &&
+        self.tab_index == other.tab_index &&
+        self.caret == other.caret &&
+        self.key == other.key &&
+        self.tooltip == other.tooltip
     }
 }
 
@@ -494,7 +555,13 @@ impl<T: Layout> Default for NodeData<T> {
             default_callback_ids: Vec::new(),
             dynamic_style_overrides: Vec::new(),
             draggable: false,
+            captures_pointer: false,
+            is_overlay: false,
+            traps_focus: false,
             tab_index: None,
+            caret: None,
+            key: None,
+            tooltip: None,
         }
     }
 }
@@ -518,7 +585,13 @@ impl<T: Layout> Hash for NodeData<T> {
             dynamic_style_override.hash(state);
         }
         self.draggable.hash(state);
+        self.captures_pointer.hash(state);
+        self.is_overlay.hash(state);
+        self.traps_focus.hash(state);
         self.tab_index.hash(state);
+        self.caret.hash(state);
+        self.key.hash(state);
+        self.tooltip.hash(state);
     }
 }
 
@@ -532,7 +605,13 @@ impl<T: Layout> Clone for NodeData<T> {
             default_callback_ids: self.default_callback_ids.clone(),
             dynamic_style_overrides: self.dynamic_style_overrides.clone(),
             draggable: self.draggable.clone(),
+            captures_pointer: self.captures_pointer.clone(),
+            is_overlay: self.is_overlay.clone(),
+            traps_focus: self.traps_focus.clone(),
             tab_index: self.tab_index.clone(),
+            caret: self.caret.clone(),
+            key: self.key.clone(),
+            tooltip: self.tooltip.clone(),
         }
     }
 }
@@ -569,7 +648,13 @@ impl<T: Layout> fmt::Debug for NodeData<T> {
                 \tdefault_callback_ids: {:?}, \
                 \tdynamic_style_overrides: {:?}, \
                 \tdraggable: {:?}, \
+                \tcaptures_pointer: {:?}, \
+                \tis_overlay: {:?}, \
+                \ttraps_focus: {:?}, \
                 \ttab_index: {:?}, \
+                \tcaret: {:?}, \
+                \tkey: {:?}, \
+                \ttooltip: {:?}, \
             }}",
         self.node_type,
         self.ids,
@@ -578,7 +663,13 @@ impl<T: Layout> fmt::Debug for NodeData<T> {
         self.default_callback_ids,
         self.dynamic_style_overrides,
         self.draggable,
-        self.tab_index)
+        self.captures_pointer,
+        self.is_overlay,
+        self.traps_focus,
+        self.tab_index,
+        self.caret,
+        self.key,
+        self.tooltip)
     }
 }
 
@@ -867,6 +958,55 @@ impl<T: Layout> Dom<T> {
         self
     }
 
+    /// Same as `set_tab_index`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_tab_index(mut self, tab_index: TabIndex) -> Self {
+        self.set_tab_index(tab_index);
+        self
+    }
+
+    /// Same as `set_pointer_capture`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_pointer_capture(mut self, captures_pointer: bool) -> Self {
+        self.set_pointer_capture(captures_pointer);
+        self
+    }
+
+    /// Same as `set_overlay`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_overlay(mut self, is_overlay: bool) -> Self {
+        self.set_overlay(is_overlay);
+        self
+    }
+
+    /// Same as `set_focus_trap`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_focus_trap(mut self, traps_focus: bool) -> Self {
+        self.set_focus_trap(traps_focus);
+        self
+    }
+
+    /// Same as `set_caret`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_caret(mut self, caret: TextCaret) -> Self {
+        self.set_caret(caret);
+        self
+    }
+
+    /// Same as `set_key`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_key<K: Hash>(mut self, key: K) -> Self {
+        self.set_key(key);
+        self
+    }
+
+    /// Same as `set_tooltip`, but easier to use for method chaining in a builder-style pattern
+    #[inline]
+    pub fn with_tooltip<U: Into<Tooltip<T>>>(mut self, tooltip: U) -> Self {
+        self.set_tooltip(tooltip);
+        self
+    }
+
     #[inline]
     pub fn add_id<S: Into<String>>(&mut self, id: S) {
         self.arena.borrow_mut().node_data[self.head].ids.push(id.into());
@@ -892,6 +1032,54 @@ impl<T: Layout> Dom<T> {
         self.arena.borrow_mut().node_data[self.head].dynamic_style_overrides.push((override_id.into(), property));
     }
 
+    /// Makes the node focusable, see `NodeData::tab_index`
+    #[inline]
+    pub fn set_tab_index(&mut self, tab_index: TabIndex) {
+        self.arena.borrow_mut().node_data[self.head].tab_index = Some(tab_index);
+    }
+
+    /// Routes all mouse events to this node while the left mouse button is held down on it,
+    /// see `NodeData::captures_pointer`
+    #[inline]
+    pub fn set_pointer_capture(&mut self, captures_pointer: bool) {
+        self.arena.borrow_mut().node_data[self.head].captures_pointer = captures_pointer;
+    }
+
+    /// Draws this node on top of all other content, see `NodeData::is_overlay`
+    #[inline]
+    pub fn set_overlay(&mut self, is_overlay: bool) {
+        self.arena.borrow_mut().node_data[self.head].is_overlay = is_overlay;
+    }
+
+    /// Keeps the keyboard focus inside of this node, see `NodeData::traps_focus`
+    #[inline]
+    pub fn set_focus_trap(&mut self, traps_focus: bool) {
+        self.arena.borrow_mut().node_data[self.head].traps_focus = traps_focus;
+    }
+
+    /// Draws a caret and the selected text on top of the text of this `Label` / `Text` node.
+    /// Only text with a caret or with `user-select: text` can be hit-tested, see `FakeWindow::hit_test_text`.
+    #[inline]
+    pub fn set_caret(&mut self, caret: TextCaret) {
+        self.arena.borrow_mut().node_data[self.head].caret = Some(caret);
+    }
+
+    /// Identifies the node among its siblings across frames, see `NodeData::key`
+    #[inline]
+    pub fn set_key<K: Hash>(&mut self, key: K) {
+        use std::collections::hash_map::DefaultHasher;
+        let mut hasher = DefaultHasher::default();
+        key.hash(&mut hasher);
+        self.arena.borrow_mut().node_data[self.head].key = Some(hasher.finish());
+    }
+
+    /// Shows a tooltip when the mouse rests on this node, see `NodeData::tooltip`.
+    /// Accepts a `Tooltip` or a text (`&str` / `String`).
+    #[inline]
+    pub fn set_tooltip<U: Into<Tooltip<T>>>(&mut self, tooltip: U) {
+        self.arena.borrow_mut().node_data[self.head].tooltip = Some(tooltip.into());
+    }
+
     /// Prints a debug formatted version of the DOM for easier debugging
     pub fn debug_dump(&self) {
         println!("{}", self.arena.borrow().print_tree(|t| format!("{}", t)));
@@ -920,13 +1108,27 @@ impl<T: Layout> Dom<T> {
         let mut tab_index_tags = BTreeMap::new();
         // All tags that have can be dragged & dropped (necessary for hit-testing)
         let mut draggable_tags = BTreeMap::new();
+        // All tags that capture the pointer while the mouse is pressed on them (necessary for hit-testing)
+        let mut pointer_capture_tags = BTreeMap::new();
+        // All tags of overlays, so that the nodes below an overlay can be excluded from hit-testing
+        let mut overlay_tags = BTreeMap::new();
+        // All tags of text nodes, for selecting text with the mouse (necessary for hit-testing)
+        let mut text_tags = BTreeMap::new();
+        // The tags of `text_tags` that were only created for selecting the text
+        let mut text_only_tags = BTreeSet::new();
+        // All tags of nodes with a tooltip, for showing the tooltip of the hovered node (necessary for hit-testing)
+        let mut tooltip_tags = BTreeMap::new();
 
         // Mapping from tags to nodes (necessary so that the hit-testing can resolve the NodeId from any given tag)
         let mut tag_ids_to_node_ids = BTreeMap::new();
         // Mapping from nodes to tags, reverse mapping (not used right now, may be useful in the future)
         let mut node_ids_to_tag_ids = BTreeMap::new();
+        // The last node that keeps the focus inside of it, see `NodeData::traps_focus`
+        let mut focus_trap = None;
         // Which nodes have extra dynamic CSS overrides?
         let mut dynamic_style_overrides = BTreeMap::new();
+        // Identities of the nodes, for finding the same nodes in the next frame
+        let node_identities;
 
         // Reset the tag
         TAG_ID.swap(1, Ordering::SeqCst);
@@ -960,22 +1162,59 @@ impl<T: Layout> Dom<T> {
                     node_tag_id = Some(tag_id);
                 }
 
+                if data.captures_pointer {
+                    let tag_id = node_tag_id.unwrap_or_else(|| new_tag_id());
+                    pointer_capture_tags.insert(tag_id, node_id);
+                    node_tag_id = Some(tag_id);
+                }
+
+                if data.is_overlay {
+                    let tag_id = node_tag_id.unwrap_or_else(|| new_tag_id());
+                    overlay_tags.insert(tag_id, node_id);
+                    node_tag_id = Some(tag_id);
+                }
+
                 if let Some(tab_index) = data.tab_index {
                     let tag_id = node_tag_id.unwrap_or_else(|| new_tag_id());
                     tab_index_tags.insert(tag_id, (node_id, tab_index));
                     node_tag_id = Some(tag_id);
                 }
 
+                if data.tooltip.is_some() {
+                    let tag_id = node_tag_id.unwrap_or_else(|| new_tag_id());
+                    tooltip_tags.insert(tag_id, node_id);
+                    node_tag_id = Some(tag_id);
+                }
+
+                // Whether the text is actually selectable depends on the `user-select` property,
+                // which isn't known until the DOM is styled - the display list removes the
+                // `text_only_tags` of text that can't be selected again
+                if let NodeType::Label(_) | NodeType::Text(_) = data.node_type {
+                    let tag_id = node_tag_id.unwrap_or_else(|| {
+                        let tag_id = new_tag_id();
+                        text_only_tags.insert(tag_id);
+                        tag_id
+                    });
+                    text_tags.insert(tag_id, node_id);
+                    node_tag_id = Some(tag_id);
+                }
+
                 if let Some(tag_id) = node_tag_id {
                     tag_ids_to_node_ids.insert(tag_id, node_id);
                     node_ids_to_tag_ids.insert(node_id, tag_id);
                 }
 
+                if data.traps_focus {
+                    focus_trap = Some(node_id);
+                }
+
                 // Collect all the styling overrides into one hash map
                 if !data.dynamic_style_overrides.is_empty() {
                     dynamic_style_overrides.insert(node_id, data.dynamic_style_overrides.iter().cloned().collect());
                 }
             }
+
+            node_identities = get_node_identities(arena);
         }
 
         UiState {
@@ -984,13 +1223,46 @@ impl<T: Layout> Dom<T> {
             tag_ids_to_default_callbacks,
             tab_index_tags,
             draggable_tags,
+            pointer_capture_tags,
+            overlay_tags,
+            text_tags,
+            text_only_tags,
+            tooltip_tags,
+            focus_trap,
             node_ids_to_tag_ids,
             tag_ids_to_node_ids,
             dynamic_style_overrides,
+            node_identities,
         }
     }
 }
 
+/// Calculates the `NodeIdentity` of every node, parents before their children
+fn get_node_identities<T: Layout>(arena: &Arena<NodeData<T>>) -> NodeDataContainer<NodeIdentity> {
+
+    let root = NodeId::new(0);
+    let mut node_identities = vec![NodeIdentity::default(); arena.len()];
+    node_identities[root.index()] = NodeIdentity::new(None, 0, &arena.node_data[root]);
+
+    let mut parents = vec![root];
+    while let Some(parent) = parents.pop() {
+        let parent_identity = node_identities[parent.index()];
+        debug_assert!({
+            let mut keys = parent.children(&arena.node_layout).filter_map(|child| arena.node_data[child].key).collect::<Vec<_>>();
+            let key_count = keys.len();
+            keys.sort();
+            keys.dedup();
+            keys.len() == key_count
+        }, "the children of a node have duplicate keys, see `NodeData::key`");
+        for (index_in_parent, child) in parent.children(&arena.node_layout).enumerate() {
+            node_identities[child.index()] = NodeIdentity::new(Some(parent_identity), index_in_parent, &arena.node_data[child]);
+            parents.push(child);
+        }
+    }
+
+    NodeDataContainer::new(node_identities)
+}
+
 /// OpenGL texture, use `ReadOnlyWindow::create_texture` to create a texture
 ///
 /// **WARNING**: Don't forget to call `ReadOnlyWindow::unbind_framebuffer()`
@@ -1173,3 +1445,52 @@ fn test_zero_size_dom() {
     null_dom.add_class("hello"); // should not panic
     null_dom.add_id("id-hello"); // should not panic
 }
+
+/// Test that keyed nodes keep their identity when their siblings change,
+/// while unkeyed nodes are identified by their position
+#[test]
+fn test_node_identity_across_frames() {
+
+    struct TestLayout { }
+
+    impl Layout for TestLayout {
+        fn layout(&self) -> Dom<Self> {
+            Dom::new(NodeType::Div)
+        }
+    }
+
+    fn page(key: &str) -> Dom<TestLayout> {
+        Dom::new(NodeType::Div).with_key(key).with_child(Dom::new(NodeType::Label(key.into())))
+    }
+
+    // root           NodeId(0)
+    //   |-> div      NodeId(1)
+    //   |-> page a   NodeId(2)
+    //   |   '-> a    NodeId(3)
+    //   '-> page b   NodeId(4)
+    //       '-> b    NodeId(5)
+    let previous_frame = Dom::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div))
+        .with_child(page("a"))
+        .with_child(page("b"))
+        .into_ui_state();
+
+    // root           NodeId(0)
+    //   |-> page b   NodeId(1)
+    //   |   '-> b    NodeId(2)
+    //   |-> div      NodeId(3)
+    //   '-> page c   NodeId(4)
+    //       '-> c    NodeId(5)
+    let frame = Dom::new(NodeType::Div)
+        .with_child(page("b"))
+        .with_child(Dom::new(NodeType::Div))
+        .with_child(page("c"))
+        .into_ui_state();
+
+    let get_node_id = frame.get_node_id_mapping(&previous_frame);
+    assert_eq!(get_node_id(NodeId::new(0)), Some(NodeId::new(0)));
+    assert_eq!(get_node_id(NodeId::new(1)), None);
+    assert_eq!(get_node_id(NodeId::new(3)), None);
+    assert_eq!(get_node_id(NodeId::new(4)), Some(NodeId::new(1)));
+    assert_eq!(get_node_id(NodeId::new(5)), Some(NodeId::new(2)));
+}
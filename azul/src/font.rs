@@ -1,6 +1,8 @@
 //! Module for loading and handling fonts
 use webrender::api::FontKey;
 use rusttype::{Error as RusttypeError, Font, FontCollection};
+use azul_css::FontId;
+use text_cache::TextId;
 
 #[derive(Debug, Clone)]
 pub(crate) enum FontState {
@@ -25,6 +27,10 @@ pub enum FontError {
     ParseError(RusttypeError),
     /// IO error
     IoError(::std::io::Error),
+    /// The font hasn't been added (see `AppState::add_font`) and isn't installed on the system
+    NotFound(FontId),
+    /// The text hasn't been added (see `AppState::add_text_uncached`) or was deleted
+    InvalidTextId(TextId),
 }
 
 impl_display!{ FontError, {
@@ -32,6 +38,8 @@ impl_display!{ FontError, {
     InvalidFormat => "Invalid format",
     ParseError(e) => format!("Rusttype failed to parse the font: {}", e),
     IoError(e) => format!("IO error: {}", e),
+    NotFound(id) => format!("Font not found: {:?}", id),
+    InvalidTextId(id) => format!("Invalid text ID: {:?}", id),
 }}
 
 impl From<RusttypeError> for FontError {
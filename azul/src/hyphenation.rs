@@ -0,0 +1,160 @@
+//! Dictionary-based hyphenation (Liang's algorithm, as used by TeX)
+//!
+//! Azul doesn't ship any hyphenation patterns, since they are language-specific
+//! and fairly large. Instead, you can load the TeX patterns for your language
+//! (for example `hyph-en-us.tex` from the `hyph-utf8` project) and register them via
+//! `AppResources::set_hyphenator`. Text with `hyphens: auto` will then be hyphenated
+//! using these patterns.
+
+use FastHashMap;
+
+/// A set of hyphenation patterns + exceptions for one language
+#[derive(Debug, Default, Clone)]
+pub struct Hyphenator {
+    /// Maps the letters of a pattern (ex. `".hy"`) to the values between
+    /// the letters, i.e. `pattern.len() + 1` values
+    patterns: FastHashMap<Vec<char>, Vec<u8>>,
+    /// Words that are hyphenated differently than the patterns would suggest,
+    /// maps the lowercase word to the character indices where it can be broken
+    exceptions: FastHashMap<String, Vec<usize>>,
+    /// Length of the longest pattern, so we don't have to look up longer substrings
+    max_pattern_len: usize,
+    /// Minimum number of characters that have to stay before the hyphen (default: 2)
+    pub left_min: usize,
+    /// Minimum number of characters that have to go to the next line (default: 3)
+    pub right_min: usize,
+}
+
+impl Hyphenator {
+
+    /// Creates a new hyphenator from the content of the TeX `\patterns{}` and the
+    /// `\hyphenation{}` blocks, i.e. whitespace-separated patterns such as `".hy3p"` or `"4b1"`
+    /// and whitespace-separated exceptions such as `"ta-ble"`. Both are case-insensitive.
+    pub fn new(patterns: &str, exceptions: &str) -> Self {
+
+        let mut hyphenator = Self {
+            left_min: 2,
+            right_min: 3,
+            .. Default::default()
+        };
+
+        for pattern in patterns.split_whitespace() {
+            let mut letters = Vec::new();
+            let mut values = Vec::new();
+            let mut current_value = 0;
+
+            for c in pattern.chars() {
+                match c.to_digit(10) {
+                    Some(d) => current_value = d as u8,
+                    None => {
+                        values.push(current_value);
+                        letters.push(to_lowercase(c));
+                        current_value = 0;
+                    }
+                }
+            }
+
+            values.push(current_value);
+            hyphenator.max_pattern_len = hyphenator.max_pattern_len.max(letters.len());
+            hyphenator.patterns.insert(letters, values);
+        }
+
+        for exception in exceptions.split_whitespace() {
+            let mut word = String::new();
+            let mut break_points = Vec::new();
+            let mut char_count = 0;
+
+            for c in exception.chars() {
+                if c == '-' {
+                    break_points.push(char_count);
+                } else {
+                    word.push(to_lowercase(c));
+                    char_count += 1;
+                }
+            }
+
+            hyphenator.exceptions.insert(word, break_points);
+        }
+
+        hyphenator
+    }
+
+    /// Returns the character indices in the word before which a hyphen may be inserted,
+    /// in ascending order. Leading and trailing punctuation is ignored, words that contain
+    /// non-alphabetic characters (ex. `"e-mail"`, `"mp3"`) are not hyphenated.
+    pub fn hyphenate(&self, word: &str) -> Vec<usize> {
+        let chars = word.chars().collect::<Vec<char>>();
+        self.hyphenate_chars(&chars)
+    }
+
+    pub(crate) fn hyphenate_chars(&self, word: &[char]) -> Vec<usize> {
+
+        // Strip the punctuation, i.e. only hyphenate the `hyphenation` of `"hyphenation",`
+        let start = match word.iter().position(|c| c.is_alphabetic()) {
+            Some(s) => s,
+            None => return Vec::new(),
+        };
+        let end = word.iter().rposition(|c| c.is_alphabetic()).unwrap() + 1;
+        let core = &word[start..end];
+
+        if core.iter().any(|c| !c.is_alphabetic()) || core.len() < self.left_min + self.right_min {
+            return Vec::new();
+        }
+
+        let lowercase = core.iter().map(|c| to_lowercase(*c)).collect::<String>();
+
+        if let Some(break_points) = self.exceptions.get(&lowercase) {
+            return break_points.iter().map(|b| start + b).collect();
+        }
+
+        // The word, surrounded by the word boundary markers, ex. ".hyphenation."
+        let mut marked_word = Vec::with_capacity(core.len() + 2);
+        marked_word.push('.');
+        marked_word.extend(lowercase.chars());
+        marked_word.push('.');
+
+        // values[i] is the value of the gap in front of marked_word[i]
+        let mut values = vec![0_u8; marked_word.len() + 1];
+
+        for pattern_start in 0..marked_word.len() {
+            let max_end = (pattern_start + self.max_pattern_len).min(marked_word.len());
+            for pattern_end in (pattern_start + 1)..=max_end {
+                if let Some(pattern_values) = self.patterns.get(&marked_word[pattern_start..pattern_end]) {
+                    for (offset, value) in pattern_values.iter().enumerate() {
+                        let gap = &mut values[pattern_start + offset];
+                        *gap = (*gap).max(*value);
+                    }
+                }
+            }
+        }
+
+        // Odd values are break opportunities. The gap in front of core[i] is values[i + 1],
+        // because of the leading "." marker
+        (self.left_min..=(core.len() - self.right_min))
+            .filter(|i| values[i + 1] % 2 == 1)
+            .map(|i| start + i)
+            .collect()
+    }
+}
+
+fn to_lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[test]
+fn test_hyphenate_with_patterns() {
+    // Patterns from the example in Liang's thesis, hyphenating "hyphenation" as "hy-phen-ation"
+    let hyphenator = Hyphenator::new("hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n", "");
+    assert_eq!(hyphenator.hyphenate("hyphenation"), vec![2, 6]);
+    // Punctuation and case are ignored
+    assert_eq!(hyphenator.hyphenate("\"Hyphenation,\""), vec![3, 7]);
+    // Too short to be hyphenated
+    assert_eq!(hyphenator.hyphenate("hyph"), Vec::<usize>::new());
+}
+
+#[test]
+fn test_hyphenate_with_exceptions() {
+    let hyphenator = Hyphenator::new("1na", "ta-ble present");
+    assert_eq!(hyphenator.hyphenate("Table"), vec![2]);
+    assert_eq!(hyphenator.hyphenate("present"), Vec::<usize>::new());
+}
@@ -230,6 +230,23 @@ impl<T> Arena<T> {
         NodeId::new(next_index)
     }
 
+    /// Appends the `child` as the last child of the `parent`. The `child` must not
+    /// have a parent yet, i.e. it was just created with `new_node`.
+    pub(crate) fn append_child(&mut self, parent: NodeId, child: NodeId) {
+        let last_child = self.node_layout[parent].last_child;
+        {
+            let child_node = &mut self.node_layout[child];
+            child_node.parent = Some(parent);
+            child_node.previous_sibling = last_child;
+            child_node.next_sibling = None;
+        }
+        match last_child {
+            Some(last_child) => self.node_layout[last_child].next_sibling = Some(child),
+            None => self.node_layout[parent].first_child = Some(child),
+        }
+        self.node_layout[parent].last_child = Some(child);
+    }
+
     // Returns how many nodes there are in the arena
     pub fn len(&self) -> usize {
         self.node_layout.len()
@@ -81,6 +81,10 @@ extern crate webrender;
 extern crate rusttype;
 extern crate app_units;
 extern crate unicode_normalization;
+extern crate unicode_segmentation;
+extern crate xi_unicode;
+extern crate unicode_bidi;
+extern crate rustybuzz;
 extern crate tinyfiledialogs;
 extern crate clipboard2;
 extern crate font_loader;
@@ -136,6 +140,8 @@ pub mod dom;
 pub mod error;
 /// Font handling
 pub mod font;
+/// Dictionary-based hyphenation for `hyphens: auto`
+pub mod hyphenation;
 /// Async IO / task system
 pub mod task;
 /// Module for caching long texts (including their layout / character positions) across multiple frames
@@ -172,6 +178,13 @@ mod compositor;
 mod logging;
 /// Flexbox-based UI solver
 mod ui_solver;
+/// Selecting text with the mouse and copying it to the clipboard
+mod text_selection;
+/// Tooltips that are shown when the mouse rests on a node
+mod tooltip;
+/// Mock data model for the unit tests
+#[cfg(test)]
+mod test_support;
 
 // Faster implementation of a HashMap (optional, disabled by default, turn on with --feature="faster-hashing")
 
@@ -193,7 +206,7 @@ pub mod prelude {
     pub use dom::{
         Dom, NodeType, NodeData, Callback, On,
         UpdateScreen, Texture, GlTextureCallback,
-        IFrameCallback,
+        IFrameCallback, TabIndex,
     };
     pub use traits::{Layout, Modify};
     pub use window::{MonitorIter, Window, WindowCreateOptions, WindowId,
@@ -212,6 +225,9 @@ pub mod prelude {
     pub use daemon::{TerminateDaemon, DaemonId, DaemonCallback, Daemon};
     pub use default_callbacks::StackCheckedPointer;
     pub use text_layout::TextLayoutOptions;
+    pub use text_selection::{CaretAffinity, TextCaret};
+    pub use tooltip::{Tooltip, DEFAULT_TOOLTIP_DELAY};
+    pub use hyphenation::Hyphenator;
 
     #[cfg(any(feature = "css_parser", feature = "native-style"))]
     pub use css;
@@ -0,0 +1,18 @@
+//! Shared fixtures of the unit tests
+
+use {
+    traits::Layout,
+    dom::{Dom, NodeType},
+};
+
+/// Data model of the tests, its DOM is an empty `div`. Tests that need values inside
+/// of the data model (ex. to bind widgets to them) can store them in the `data`.
+pub(crate) struct Mock<D = ()> {
+    pub(crate) data: D,
+}
+
+impl<D> Layout for Mock<D> {
+    fn layout(&self) -> Dom<Self> {
+        Dom::new(NodeType::Div)
+    }
+}
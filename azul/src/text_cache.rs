@@ -1,9 +1,10 @@
 use std::sync::atomic::{Ordering, AtomicUsize};
-use azul_css::{FontId, StyleFontSize};
+use azul_css::{FontId, StyleFontSize, StyleFontFamily, StyleLetterSpacing, StyleDirection, StyleWhiteSpace};
 use {
     FastHashMap,
     text_layout::Words,
     app_resources::AppResources,
+    hyphenation::Hyphenator,
 };
 
 static TEXT_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
@@ -21,6 +22,18 @@ pub struct TextId {
     inner: usize,
 }
 
+/// Everything that the words of a cached text depend on, except for the font size
+/// (the words of one font size can be scaled to another font size)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WordsCacheKey {
+    /// The font fallback chain that the text was split with, the first font is the font of the text
+    pub fonts: Vec<FontId>,
+    pub letter_spacing: Option<StyleLetterSpacing>,
+    /// The base direction of the text, `None` if it is detected from the text itself
+    pub direction: Option<StyleDirection>,
+    pub white_space: Option<StyleWhiteSpace>,
+}
+
 /// Cache for accessing large amounts of text
 #[derive(Debug, Default, Clone)]
 pub struct TextCache {
@@ -31,10 +44,17 @@ pub struct TextCache {
     pub string_cache: FastHashMap<TextId, String>,
     /// Caches the layout of the strings / words.
     ///
-    /// TextId -> FontId (to look up by font)
-    /// FontId -> StyleFontSize (to categorize by size within a font)
+    /// TextId -> WordsCacheKey (to look up by the fonts and the text options)
+    /// WordsCacheKey -> StyleFontSize (to categorize by size within a font)
     /// StyleFontSize -> layouted words (to cache the glyph widths on a per-font-size basis)
-    pub layouted_strings_cache: FastHashMap<TextId, FastHashMap<FontId, FastHashMap<StyleFontSize, Words>>>,
+    pub layouted_strings_cache: FastHashMap<TextId, FastHashMap<WordsCacheKey, FastHashMap<StyleFontSize, Words>>>,
+    /// Patterns for `hyphens: auto`. Since the hyphenation points are stored in the `Words`,
+    /// the layouted strings have to be recalculated when the hyphenator changes.
+    pub hyphenator: Option<Hyphenator>,
+    /// The fonts that each text is rendered with in a `font-family`, so that the characters
+    /// of the text don't have to be looked up in the fonts on every frame
+    /// (see `AppResources::get_cached_font_fallback_chain`)
+    pub(crate) font_fallback_chains: FastHashMap<(TextId, StyleFontFamily), Vec<FontId>>,
 }
 
 impl TextCache {
@@ -49,6 +69,7 @@ impl TextCache {
     /// Removes a string from the string cache, but not the layouted text cache
     pub fn delete_string(&mut self, id: TextId) {
         self.string_cache.remove(&id);
+        self.font_fallback_chains.retain(|(text_id, _), _| *text_id != id);
     }
 
     /// Removes a string from the layouted text cache, but not the string cache
@@ -65,6 +86,7 @@ impl TextCache {
     pub fn clear_all_texts(&mut self) {
         self.string_cache.clear();
         self.layouted_strings_cache.clear();
+        self.font_fallback_chains.clear();
     }
 }
 
@@ -102,4 +124,16 @@ impl TextInfo {
             Uncached(s) => s.is_empty(),
         }
     }
+
+    /// Returns the text of the node, an empty string if the `TextId` doesn't exist
+    pub(crate) fn get_text<'a>(&'a self, app_resources: &'a AppResources)
+    -> &'a str
+    {
+        use self::TextInfo::*;
+
+        match self {
+            Cached(text_id) => app_resources.text_cache.string_cache.get(text_id).map(|s| s.as_str()).unwrap_or(""),
+            Uncached(s) => s,
+        }
+    }
 }
@@ -5,12 +5,14 @@ use euclid::{TypedRect, TypedSize2D, TypedPoint2D};
 use rusttype::{Font, Scale, GlyphId};
 use azul_css::{
     StyleTextAlignmentHorz, StyleFontSize, StyleBackgroundColor, StyleLetterSpacing,
-    FontId, StyleTextAlignmentVert, StyleLineHeight, LayoutOverflow
+    FontId, StyleTextAlignmentVert, StyleLineHeight, LayoutOverflow, StyleHyphens,
+    StyleDirection, StyleWhiteSpace, StyleTabSize, StyleTextOverflow, StyleLineClamp,
 };
 use {
     app_resources::AppResources,
     text_cache::TextInfo,
-    text_cache::{TextId, TextCache},
+    text_cache::{TextId, TextCache, WordsCacheKey},
+    hyphenation::Hyphenator,
 };
 
 pub use webrender::api::GlyphInstance;
@@ -78,10 +80,31 @@ impl TextSizePx {
 pub struct Words {
     pub items: Vec<SemanticWordItem>,
     pub longest_word_width: f32,
+    /// The base direction that was used to resolve the bidi levels of the words
+    /// (`None` if it was detected from the text itself)
+    pub paragraph_direction: Option<StyleDirection>,
+    /// The font fallback chain that the glyphs were created with: the glyphs refer to these
+    /// fonts by their index (see `split_into_font_runs`). Empty if the fonts have no IDs.
+    pub fonts: Vec<FontId>,
+    /// How the white space of the text was collapsed when it was split into words
+    pub white_space: Option<StyleWhiteSpace>,
 }
 
 impl Words {
 
+    /// Whether the lines are broken with `break_lines_total_fit`: if the text options require it
+    /// (see `TextLayoutOptions::uses_total_fit_line_breaking`) or if the text contains soft hyphens,
+    /// since the greedy line breaking can't break the words at their hyphenation points
+    fn uses_total_fit_line_breaking(&self, layout_options: &TextLayoutOptions) -> bool {
+        layout_options.uses_total_fit_line_breaking() || (
+            layout_options.hyphens.unwrap_or_default() != StyleHyphens::None &&
+            self.items.iter().any(|item| match item {
+                SemanticWordItem::Word(word) => word.hyphenation_points.iter().any(|h| h.is_soft_hyphen),
+                _ => false,
+            })
+        )
+    }
+
     /// Given a width, returns the vertical height of the text (no vertical overflow checks)
     pub fn get_vertical_height(&self, overflow: &LayoutOverflow, font_metrics: &FontMetrics, width: TextSizePx)
     -> VerticalTextInfo
@@ -90,7 +113,7 @@ impl Words {
 
         let FontMetrics { space_width, tab_width, vertical_advance, .. } = *font_metrics;
 
-        if overflow.allows_horizontal_overflow() {
+        let vertical_info = if overflow.allows_horizontal_overflow() {
             // If we can overflow horizontally, we only need to sum up the `Return`
             // characters, since the actual length of the line doesn't matter
             let number_of_returns = self.items.iter().filter(|w| w.is_return()).count();
@@ -98,6 +121,41 @@ impl Words {
                 vertical_height: vertical_advance * number_of_returns as f32,
                 max_hor_len: None
             }
+        } else if !font_metrics.layout_options.white_space.unwrap_or_default().wraps_lines() {
+            // The lines are only broken at the `Return` characters, lines that are too long
+            // are cut off (see `truncate_lines`), so the text never overflows horizontally
+            let mut max_line_cursor: f32 = 0.0;
+            let mut cur_line_cursor = 0.0;
+            let mut number_of_lines = 1;
+
+            for w in &self.items {
+                match w {
+                    Word(w) => {
+                        cur_line_cursor += w.total_width;
+                        max_line_cursor = max_line_cursor.max(cur_line_cursor);
+                        if w.has_trailing_space {
+                            cur_line_cursor += space_width.0;
+                        }
+                    },
+                    Tab => cur_line_cursor += tab_width.0,
+                    Space => cur_line_cursor += space_width.0,
+                    Return => {
+                        cur_line_cursor = 0.0;
+                        number_of_lines += 1;
+                    }
+                }
+            }
+
+            VerticalTextInfo {
+                max_hor_len: Some(TextSizePx(max_line_cursor.min(width.0))),
+                vertical_height: vertical_advance * number_of_lines as f32,
+            }
+        } else if self.uses_total_fit_line_breaking(&font_metrics.layout_options) {
+            let lines = break_lines_total_fit(&get_line_break_items(self, font_metrics), width.0);
+            VerticalTextInfo {
+                max_hor_len: lines.last().map(|line| TextSizePx(line.width)),
+                vertical_height: vertical_advance * lines.len() as f32,
+            }
         } else {
             // TODO: should this be cached? The calculation is probably quick, but this
             // is essentially the same thing as we do in the actual text layout stage
@@ -114,10 +172,14 @@ impl Words {
                             cur_line_cursor = 0.0;
                             cur_line += 1;
                         }
-                        cur_line_cursor += w.total_width + space_width.0; // space width is in px
+                        cur_line_cursor += w.total_width;
+                        if w.has_trailing_space {
+                            cur_line_cursor += space_width.0; // space width is in px
+                        }
                     },
                     // TODO: also check for rect break after tabs? Kinda pointless, isn't it?
                     Tab => cur_line_cursor += tab_width.0, // tab width is in px
+                    Space => cur_line_cursor += space_width.0,
                     Return => {
                         max_line_cursor = max_line_cursor.max(cur_line_cursor);
                         cur_line_cursor = 0.0;
@@ -130,6 +192,26 @@ impl Words {
                 max_hor_len: Some(TextSizePx(cur_line_cursor)),
                 vertical_height: vertical_advance * cur_line as f32,
             }
+        };
+
+        // The lines after the `line-clamp` are cut off
+        match font_metrics.layout_options.line_clamp {
+            Some(StyleLineClamp::Lines(max_lines)) => VerticalTextInfo {
+                vertical_height: TextSizePx(vertical_info.vertical_height.0.min(vertical_advance.0 * max_lines as f32)),
+                .. vertical_info
+            },
+            _ => vertical_info,
+        }
+    }
+
+    /// Returns the `TextIndexMap` for the glyphs of all words, placed one after another -
+    /// which is how inline text is laid out (see `layout_inline_runs`)
+    pub(crate) fn get_inline_index_map(&self) -> TextIndexMap {
+        TextIndexMap {
+            glyph_to_text: self.items.iter().flat_map(|item| match item {
+                SemanticWordItem::Word(word) => word.clusters.iter().map(|c| Some(*c)).collect(),
+                _ => Vec::new(),
+            }).collect(),
         }
     }
 }
@@ -141,15 +223,42 @@ pub struct Word {
     pub glyphs: Vec<GlyphInstance>,
     /// The sum of the width of all the characters
     pub total_width: f32,
+    /// Positions where the word may be broken with a hyphen, in ascending order
+    pub hyphenation_points: Vec<HyphenationPoint>,
+    /// Whether the word is followed by a space. If not, the next word directly follows this
+    /// word (ex. `"known"` after `"well-"`), but the line can still be broken between them.
+    pub has_trailing_space: bool,
+    /// Lowest bidi embedding level (UAX #9) of the characters in the word, odd levels are
+    /// right-to-left. Used to reorder the words on each line after the line breaking.
+    pub bidi_level: u8,
+    /// For each glyph: the byte offset of the character it was created from (the first character
+    /// of a ligature), in the NFC-normalized text. The glyphs of right-to-left words are
+    /// already in visual order, so the offsets are descending.
+    pub clusters: Vec<usize>,
 }
 
-/// Either a white-space delimited word, tab or return character
+/// A position inside of a word where the word may be broken with a hyphen
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HyphenationPoint {
+    /// Index of the first glyph that is moved onto the next line
+    pub glyph_index: usize,
+    /// Width of the glyphs before the break (without the hyphen)
+    pub width: f32,
+    /// Whether the break comes from a soft hyphen (`U+00AD`) in the text
+    /// instead of the hyphenation patterns
+    pub is_soft_hyphen: bool,
+}
+
+/// Either a word (delimited by line break opportunities), tab, space or return character
 #[derive(Debug, Clone)]
 pub enum SemanticWordItem {
-    /// Encountered a word (delimited by spaces)
+    /// Encountered a word (delimited by line break opportunities)
     Word(Word),
     // `\t` or `x09`
     Tab,
+    /// A space that isn't collapsed (`white-space: pre / pre-wrap`), i.e. a space at the
+    /// start of a line or a space that follows the trailing space of a word
+    Space,
     /// `\r`, `\n` or `\r\n`, escaped: `\x0D`, `\x0A` or `\x0D\x0A`
     Return,
 }
@@ -209,12 +318,6 @@ impl TextOverflow {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-struct HarfbuzzAdjustment(pub f32);
-
-#[derive(Debug, Copy, Clone)]
-struct KnuthPlassAdjustment(pub f32);
-
 /// Holds info necessary for layouting / styling scrollbars
 #[derive(Debug, Clone)]
 pub(crate) struct ScrollbarInfo {
@@ -237,8 +340,10 @@ pub(crate) struct ScrollbarInfo {
 pub struct FontMetrics {
     /// Width of the space character
     pub space_width: TextSizePx,
-    /// Usually 4 * space_width
+    /// Width of a tab, 4 * space_width by default (see `StyleTabSize`)
     pub tab_width: TextSizePx,
+    /// Width of the hyphen that is inserted when a word is hyphenated
+    pub hyphen_width: TextSizePx,
     /// font_size * line_height
     pub vertical_advance: TextSizePx,
     /// Font size, including line height
@@ -246,6 +351,11 @@ pub struct FontMetrics {
     /// Same as `font_size_with_line_height` but without the
     /// `self.line height` incorporated. Used for horizontal layouting
     pub font_size_no_line_height: TextSizePx,
+    /// Distance from the baseline to the top of the glyphs (without the line height)
+    pub ascent: TextSizePx,
+    /// Distance from the baseline to the bottom of the glyphs (without the line height),
+    /// usually negative
+    pub descent: TextSizePx,
     /// Some fonts have a base height of 2048 or something weird like that
     pub height_for_1px: f32,
     /// Spacing of the letters, or 0.0 by default
@@ -272,6 +382,7 @@ pub struct FontMetrics {
 ///
 /// - `Vec<GlyphInstance>`: The layouted glyphs. If a scrollbar is necessary, they will be layouted so that
 ///   the scrollbar has space to the left or bottom (so it doesn't overlay the text)
+/// - `TextIndexMap`: Maps the layouted glyphs back to the text, used for selecting text
 /// - `TextOverflowPass2`: This is internally used for aligning text (horizontally / vertically), but
 ///   it is necessary for drawing the scrollbars later on, to determine the height of the bar. Contains
 ///   info about if the text has overflown the rectangle, and if yes, by how many pixels
@@ -285,13 +396,19 @@ pub(crate) fn get_glyphs(
     text: &TextInfo,
     overflow: &LayoutOverflow,
     scrollbar_info: &ScrollbarInfo)
--> (Vec<GlyphInstance>, TextOverflowPass2)
+-> (Vec<GlyphInstance>, TextIndexMap, TextOverflowPass2)
 {
     let TextLayoutOptions {
         horz_alignment,
         vert_alignment,
         line_height,
         letter_spacing,
+        hyphens,
+        direction,
+        white_space,
+        tab_size,
+        text_overflow,
+        line_clamp,
     } = *text_layout_options;
 
     let mut bounds = *bounds;
@@ -304,12 +421,14 @@ pub(crate) fn get_glyphs(
     let font_metrics = FontMetrics::new(&target_font.0, target_font_size, text_layout_options);
 
     // Prevent negative width / rect height or a too small rectangle -
-    // the rect must be at least wide enough for the longest word
-    bounds.size.width = bounds.size.width.max(words.longest_word_width);
+    // the rect must be at least wide enough for the longest word,
+    // unless the words are cut off with an ellipsis
+    let min_width = if text_overflow == Some(StyleTextOverflow::Ellipsis) { 0.0 } else { words.longest_word_width };
+    bounds.size.width = bounds.size.width.max(min_width);
     bounds.size.height = bounds.size.height.abs();
 
-    // (2) Calculate the additions / subtractions that have to be take into account
-    // let harfbuzz_adjustments = calculate_harfbuzz_adjustments(&text, &target_font.0);
+    // (2) The glyphs are already shaped (ligatures + kerning) and the hyphenation points
+    // are calculated when the text is split into words, see `split_text_into_words`
 
     // (3) Determine if the words will overflow the bounding rectangle
     let overflow_pass_1 = estimate_overflow_pass_1(&words, &bounds.size, &font_metrics, &overflow);
@@ -322,28 +441,34 @@ pub(crate) fn get_glyphs(
     let max_horizontal_text_width = if overflow.allows_horizontal_overflow() { None } else { Some(new_size.width) };
 
     // (5) Align text to the left, initial layout of glyphs
-    let (mut positioned_glyphs, line_break_offsets, _, _) =
+    //
+    // (6) - (8) For justified or hyphenated text, the lines are broken using the
+    // Knuth-Plass algorithm instead of greedily, which also stretches the spaces of
+    // justified lines, see `words_to_total_fit_glyphs`
+    //
+    // The words of bidirectional text are reordered per line, see `reorder_line`
+    let (mut positioned_glyphs, mut index_map, mut line_break_offsets, _, _) =
         words_to_left_aligned_glyphs(words, &target_font.0, max_horizontal_text_width, &font_metrics);
 
-    // (6) Add the harfbuzz adjustments to the positioned glyphs
-    // apply_harfbuzz_adjustments(&mut positioned_glyphs, harfbuzz_adjustments);
-
-    // (7) Calculate the Knuth-Plass adjustments for the (now layouted) glyphs
-    let knuth_plass_adjustments = calculate_knuth_plass_adjustments(&positioned_glyphs, &line_break_offsets);
-
-    // (8) Add the Knuth-Plass adjustments to the positioned glyphs
-    apply_knuth_plass_adjustments(&mut positioned_glyphs, knuth_plass_adjustments);
+    // (9) Cut off the lines after the `line-clamp` and the lines that are too long
+    // for `text-overflow: ellipsis`, the glyphs refer to the fonts of the `words`
+    let fonts = words.fonts.iter().filter_map(|id| app_resources.get_font(id).map(|f| f.0)).collect::<Vec<_>>();
+    let mut font_refs = fonts.iter().map(|f| &**f).collect::<Vec<_>>();
+    if font_refs.is_empty() {
+        font_refs.push(&*target_font.0);
+    }
+    truncate_lines(&mut positioned_glyphs, &mut index_map, &mut line_break_offsets, &font_refs, &font_metrics, max_horizontal_text_width);
 
-    // (9) Align text horizontally (early return if left-aligned)
-    align_text_horz(horz_alignment, &mut positioned_glyphs, &line_break_offsets);
+    // (10) Align text horizontally (early return if left- or justify-aligned)
+    align_text_horz(horz_alignment.resolve(direction.unwrap_or_default()), &mut positioned_glyphs, &line_break_offsets);
 
-    // (10) Align text vertically (early return if text overflows)
+    // (11) Align text vertically (early return if text overflows)
     align_text_vert(&font_metrics, vert_alignment, &mut positioned_glyphs, &line_break_offsets, &overflow_pass_2);
 
-    // (11) Add the self.origin to all the glyphs to bring them from glyph space into world space
+    // (12) Add the self.origin to all the glyphs to bring them from glyph space into world space
     add_origin(&mut positioned_glyphs, bounds.origin.x, bounds.origin.y);
 
-    (positioned_glyphs, overflow_pass_2)
+    (positioned_glyphs, index_map, overflow_pass_2)
 }
 
 impl FontMetrics {
@@ -357,15 +482,23 @@ impl FontMetrics {
         let space_glyph = font.glyph(' ').scaled(font_size_no_line_height.to_rusttype_scale());
         let height_for_1px = font.glyph(' ').standalone().get_data().unwrap().scale_for_1_pixel;
         let space_width = TextSizePx(space_glyph.h_metrics().advance_width);
-        let tab_width = space_width * 4.0; // TODO: make this configurable
+        let tab_width = match layout_options.tab_size.unwrap_or_default() {
+            StyleTabSize::Spaces(spaces) => space_width * spaces.get(),
+            StyleTabSize::Length(length) => TextSizePx(length.to_pixels()),
+        };
+        let hyphen_width = TextSizePx(font.glyph('-').scaled(font_size_no_line_height.to_rusttype_scale()).h_metrics().advance_width);
 
         let v_metrics_scaled = font.v_metrics(font_size_with_line_height.to_rusttype_scale());
         let v_advance_scaled = TextSizePx(v_metrics_scaled.ascent - v_metrics_scaled.descent + v_metrics_scaled.line_gap);
+        let v_metrics_no_line_height = font.v_metrics(font_size_no_line_height.to_rusttype_scale());
 
         FontMetrics {
             vertical_advance: v_advance_scaled,
+            ascent: TextSizePx(v_metrics_no_line_height.ascent),
+            descent: TextSizePx(v_metrics_no_line_height.descent),
             space_width,
             tab_width,
+            hyphen_width,
             height_for_1px,
             font_size_with_line_height,
             font_size_no_line_height,
@@ -378,34 +511,58 @@ impl FontMetrics {
     pub fn get_svg_font_scale_factor(&self) -> f32 {
         self.font_size_no_line_height.0 * self.height_for_1px
     }
+
+    /// Returns the distance from the top of a line to the baseline of that line,
+    /// i.e. the ascent of the font + half of the space added by the `line-height`
+    pub fn get_baseline_offset(&self) -> TextSizePx {
+        let half_leading = (self.vertical_advance.0 - (self.ascent.0 - self.descent.0)) / 2.0;
+        TextSizePx(half_leading + self.ascent.0)
+    }
 }
 
+/// Returns the cached words of the text or splits the text into words if the text hasn't been
+/// split with these fonts and options yet. `font_ids` and `fonts` are the font fallback chain
+/// (see `AppResources::get_font_fallback_chain`).
 pub(crate) fn get_words_cached<'a>(
     text_id: &TextId,
-    font: &Font<'a>,
-    font_id: &FontId,
+    font_ids: &[FontId],
+    fonts: &[TextFont<'a>],
     font_size: &StyleFontSize,
     font_size_no_line_height: TextSizePx,
     letter_spacing: Option<StyleLetterSpacing>,
+    direction: Option<StyleDirection>,
+    white_space: Option<StyleWhiteSpace>,
     text_cache: &'a mut TextCache)
 -> &'a Words
 {
     use std::collections::hash_map::Entry::*;
     use FastHashMap;
 
+    let cache_key = WordsCacheKey {
+        fonts: font_ids.to_vec(),
+        letter_spacing,
+        direction,
+        white_space,
+    };
     let mut should_words_be_scaled = false;
 
+    let split_words = |text: &str, hyphenator: Option<&Hyphenator>| {
+        let mut words = split_text_into_words(text, fonts, font_size_no_line_height, letter_spacing, direction, white_space, hyphenator);
+        words.fonts = font_ids.to_vec();
+        words
+    };
+
     match text_cache.layouted_strings_cache.entry(*text_id) {
         Occupied(mut font_hash_map) => {
 
-            let font_size_map = font_hash_map.get_mut().entry(font_id.clone()).or_insert_with(|| FastHashMap::default());
+            let font_size_map = font_hash_map.get_mut().entry(cache_key.clone()).or_insert_with(|| FastHashMap::default());
             let is_new_font = font_size_map.is_empty();
 
             match font_size_map.entry(*font_size) {
                 Occupied(existing_font_size_words) => { }
                 Vacant(v) => {
                     if is_new_font {
-                        v.insert(split_text_into_words(&text_cache.string_cache[text_id], font, font_size_no_line_height, letter_spacing));
+                        v.insert(split_words(&text_cache.string_cache[text_id], text_cache.hyphenator.as_ref()));
                     } else {
                         // If we can get the words from any other size, we can just scale them here
                         // ex. if an existing font size gets scaled.
@@ -414,26 +571,33 @@ pub(crate) fn get_words_cached<'a>(
                 }
             }
         },
-        Vacant(_) => { },
+        Vacant(v) => {
+            // The layout of the text was dropped (ex. because the hyphenator changed)
+            let words = split_words(&text_cache.string_cache[text_id], text_cache.hyphenator.as_ref());
+            let mut font_size_map = FastHashMap::default();
+            font_size_map.insert(*font_size, words);
+            let mut font_map = FastHashMap::default();
+            font_map.insert(cache_key.clone(), font_size_map);
+            v.insert(font_map);
+        },
     }
 
     // We have an entry in the font size -> words cache already, but it's not the right font size
     // instead of recalculating the words, we simply scale them up.
     if should_words_be_scaled {
         let words_cloned = {
-            let font_size_map = &text_cache.layouted_strings_cache[&text_id][&font_id];
+            let font_size_map = &text_cache.layouted_strings_cache[&text_id][&cache_key];
             let (old_font_size, next_words_for_font) = font_size_map.iter().next().unwrap();
             let mut words_cloned: Words = next_words_for_font.clone();
             let scale_factor = font_size.0.to_pixels() / old_font_size.0.to_pixels();
-
             scale_words(&mut words_cloned, scale_factor);
             words_cloned
         };
 
-        text_cache.layouted_strings_cache.get_mut(&text_id).unwrap().get_mut(&font_id).unwrap().insert(*font_size, words_cloned);
+        text_cache.layouted_strings_cache.get_mut(&text_id).unwrap().get_mut(&cache_key).unwrap().insert(*font_size, words_cloned);
     }
 
-    text_cache.layouted_strings_cache.get(&text_id).unwrap().get(&font_id).unwrap().get(&font_size).unwrap()
+    text_cache.layouted_strings_cache.get(&text_id).unwrap().get(&cache_key).unwrap().get(&font_size).unwrap()
 }
 
 fn scale_words(words: &mut Words, scale_factor: f32) {
@@ -448,7 +612,8 @@ fn scale_words(words: &mut Words, scale_factor: f32) {
     // large amount of text.
     for word in words.items.iter_mut() {
         if let SemanticWordItem::Word(ref mut w) = word {
-            w.glyphs.iter_mut().for_each(|g| g.point.x *= scale_factor);
+            w.glyphs.iter_mut().for_each(|g| { g.point.x *= scale_factor; g.point.y *= scale_factor; });
+            w.hyphenation_points.iter_mut().for_each(|h| h.width *= scale_factor);
             w.total_width *= scale_factor;
         }
     }
@@ -456,143 +621,509 @@ fn scale_words(words: &mut Words, scale_factor: f32) {
 
 /// This function is also used in the `text_cache` module for caching large strings.
 ///
+/// The text is split at the line break opportunities of the Unicode line breaking algorithm
+/// (UAX #14), so a `Word` is the text between two break opportunities without the white space,
+/// ex. `"well-known"` is split into `"well-"` and `"known"` and every CJK ideograph is its own word.
+/// Non-breaking spaces (`U+00A0`) are kept inside of the word, zero-width spaces (`U+200B`)
+/// only mark a break opportunity and are not rendered.
+///
+/// If a `hyphenator` is given, the hyphenation points of each word are calculated
+/// (only for words without soft hyphens), so that `hyphens: auto` can break the words.
+///
+/// `fonts` is the font fallback chain: each character is displayed with the first font
+/// that has a glyph for it, so it must contain at least one font (see `get_font_index`).
+///
+/// The bidi levels (UAX #9) are resolved for each paragraph, with the base level given by the
+/// `direction` (or detected from the first strong character of the paragraph if it is `None`).
+/// The glyphs of each word are shaped and placed in visual order (see `shape_word`), the words
+/// themselves are reordered per line after the line breaking (see `reorder_line`).
+///
+/// The `white_space` determines which tabs, spaces and line breaks are collapsed into the
+/// trailing space of the previous word - the others are kept as `Tab`, `Space` or `Return` items.
+/// If it isn't set, the text is split like with the default `white-space` (`pre-line`).
+///
 /// It is one of the most expensive functions, use with care.
-pub(crate) fn split_text_into_words<'a>(text: &str, font: &Font<'a>, font_size: TextSizePx, letter_spacing: Option<StyleLetterSpacing>)
+pub(crate) fn split_text_into_words<'a>(
+    text: &str,
+    fonts: &[TextFont<'a>],
+    font_size: TextSizePx,
+    letter_spacing: Option<StyleLetterSpacing>,
+    direction: Option<StyleDirection>,
+    white_space: Option<StyleWhiteSpace>,
+    hyphenator: Option<&Hyphenator>)
 -> Words
 {
     use unicode_normalization::UnicodeNormalization;
+    use unicode_bidi::{BidiInfo, Level};
+    use xi_unicode::LineBreakIterator;
+
+    const SOFT_HYPHEN: char = '\u{00AD}';
+    const ZERO_WIDTH_SPACE: char = '\u{200B}';
+    const WORD_JOINER: char = '\u{2060}';
+    const ZERO_WIDTH_NO_BREAK_SPACE: char = '\u{FEFF}';
 
     let letter_spacing_px = letter_spacing.and_then(|l| Some(l.0.to_pixels())).unwrap_or(0.0);
 
     let mut words = Vec::new();
-
-    let mut word_caret = 0.0;
-    let mut cur_word_length = 0.0;
     let mut chars_in_this_word = Vec::new();
-    let mut glyphs_in_this_word = Vec::new();
-    let mut last_glyph = None;
+    let mut char_offsets_in_this_word = Vec::new();
+    let mut soft_hyphens_in_this_word = Vec::new();
 
     // In case the rectangle is smaller than the longest word,
     // we need to expand the rectangle to be that size
     let mut longest_word_width = 0.0;
 
-    fn end_word(words: &mut Vec<SemanticWordItem>,
-                glyphs_in_this_word: &mut Vec<GlyphInstance>,
-                cur_word_length: &mut f32,
-                word_caret: &mut f32,
-                longest_word_width: &mut f32,
-                last_glyph: &mut Option<GlyphId>)
+    fn end_word<'a>(words: &mut Vec<SemanticWordItem>,
+                    chars_in_this_word: &mut Vec<char>,
+                    char_offsets_in_this_word: &mut Vec<usize>,
+                    soft_hyphens_in_this_word: &mut Vec<usize>,
+                    bidi_levels: &[Level],
+                    has_trailing_space: bool,
+                    longest_word_width: &mut f32,
+                    fonts: &[TextFont<'a>],
+                    font_size: TextSizePx,
+                    letter_spacing_px: f32,
+                    hyphenator: Option<&Hyphenator>)
     {
-        // End of word
-        words.push(SemanticWordItem::Word(Word {
-            glyphs: glyphs_in_this_word.drain(..).collect(),
-            total_width: *cur_word_length,
-        }));
+        if chars_in_this_word.is_empty() {
+            // Collapse consecutive spaces into the space after the previous word
+            if has_trailing_space {
+                if let Some(SemanticWordItem::Word(ref mut last_word)) = words.last_mut() {
+                    last_word.has_trailing_space = true;
+                }
+            }
+            return;
+        }
+
+        // The shaper brings the characters into visual order and mirrors the brackets in right-to-left runs
+        let char_levels = char_offsets_in_this_word.iter().map(|o| bidi_levels[*o].number()).collect::<Vec<u8>>();
+        let is_reordered = chars_in_this_word.len() > 1 && char_levels.iter().any(|level| level % 2 == 1);
 
-        if cur_word_length > longest_word_width {
-            *longest_word_width = *cur_word_length;
+        // End of word - soft hyphens take precedence over the hyphenation patterns.
+        // The line is broken in logical order, so reordered words are not hyphenated.
+        let break_points = if is_reordered {
+            Vec::new()
+        } else if !soft_hyphens_in_this_word.is_empty() {
+            soft_hyphens_in_this_word.iter().map(|c| (*c, true)).collect()
+        } else {
+            hyphenator.map(|h| h.hyphenate_chars(chars_in_this_word)).unwrap_or_default()
+                .into_iter().map(|c| (c, false)).collect::<Vec<_>>()
+        };
+
+        let mut word = shape_word(chars_in_this_word, &char_levels, &break_points, fonts, font_size, letter_spacing_px);
+        word.has_trailing_space = has_trailing_space;
+        word.bidi_level = char_levels.iter().cloned().min().unwrap_or(0);
+        word.clusters = word.clusters.iter().map(|c| char_offsets_in_this_word[*c]).collect();
+
+        if word.total_width > *longest_word_width {
+            *longest_word_width = word.total_width;
         }
 
+        words.push(SemanticWordItem::Word(word));
+
         // Reset everything
-        *last_glyph = None;
-        *word_caret = 0.0;
-        *cur_word_length = 0.0;
-    }
-
-    let v_metrics_font = font.v_metrics_unscaled();
-    // Warning: rusttype has a bit of a weird layout system - you have to
-    // subtract the descent from the ascent to get the proper vertical height
-    let v_metrics_height_unscaled = TextSizePx(v_metrics_font.ascent - v_metrics_font.descent).to_rusttype_scale();
-
-    for cur_char in text.nfc() {
-        match cur_char {
-            '\t' => {
-                // End of word + tab
-                if !chars_in_this_word.is_empty() {
-                    end_word(
-                        &mut words,
-                        &mut glyphs_in_this_word,
-                        &mut cur_word_length,
-                        &mut word_caret,
-                        &mut longest_word_width,
-                        &mut last_glyph);
-                }
-                words.push(SemanticWordItem::Tab);
-            },
-            '\n' => {
-                // End of word + newline
-                if !chars_in_this_word.is_empty() {
-                    end_word(
-                        &mut words,
-                        &mut glyphs_in_this_word,
-                        &mut cur_word_length,
-                        &mut word_caret,
-                        &mut longest_word_width,
-                        &mut last_glyph);
-                }
-                words.push(SemanticWordItem::Return);
-            },
-            ' ' => {
-                if !chars_in_this_word.is_empty() {
-                    end_word(
-                        &mut words,
-                        &mut glyphs_in_this_word,
-                        &mut cur_word_length,
-                        &mut word_caret,
-                        &mut longest_word_width,
-                        &mut last_glyph);
-                }
-            },
-            cur_char =>  {
-                // Regular character
-                let g = font.glyph(cur_char);
-                let id = g.id();
-
-                // calculate the real width
-                let glyph_metrics = g.standalone().get_data().unwrap();
-                let h_metrics = g.scaled(v_metrics_height_unscaled).h_metrics();
-                let kerning_adjust = last_glyph.and_then(|last| {
-                    Some(font.pair_kerning(font_size.to_rusttype_scale(), last, id))
-                }).unwrap_or(0.0);
-
-                let horiz_advance = {
-                        h_metrics.advance_width *
-                        glyph_metrics.scale_for_1_pixel *
-                        font_size.0 // px
+        chars_in_this_word.clear();
+        char_offsets_in_this_word.clear();
+        soft_hyphens_in_this_word.clear();
+    }
+
+    let text = text.nfc().collect::<String>();
+    let base_level = direction.map(|d| match d {
+        StyleDirection::Ltr => Level::ltr(),
+        StyleDirection::Rtl => Level::rtl(),
+    });
+    // One level per byte of the text
+    let bidi_levels = BidiInfo::new(&text, base_level).levels;
+    let mut segment_start = 0;
+
+    for (segment_end, _) in LineBreakIterator::new(&text) {
+
+        // Each segment consists of the word itself, followed by the (breaking) white space
+        let segment_offset = segment_start;
+        let mut segment = text[segment_start..segment_end].char_indices().peekable();
+        segment_start = segment_end;
+
+        while let Some((char_offset, cur_char)) = segment.next() {
+            match cur_char {
+                '\t' if white_space.unwrap_or_default().preserves_spaces() => {
+                    // End of word + tab
+                    end_word(&mut words, &mut chars_in_this_word, &mut char_offsets_in_this_word, &mut soft_hyphens_in_this_word, &bidi_levels, false, &mut longest_word_width, fonts, font_size, letter_spacing_px, hyphenator);
+                    words.push(SemanticWordItem::Tab);
+                },
+                '\n' | '\r' if white_space.unwrap_or_default().preserves_line_breaks() => {
+                    // End of word + newline, `\r\n` is only one newline
+                    end_word(&mut words, &mut chars_in_this_word, &mut char_offsets_in_this_word, &mut soft_hyphens_in_this_word, &bidi_levels, false, &mut longest_word_width, fonts, font_size, letter_spacing_px, hyphenator);
+                    if cur_char == '\r' && segment.peek().map(|(_, c)| *c) == Some('\n') {
+                        segment.next();
+                    }
+                    words.push(SemanticWordItem::Return);
+                },
+                ' ' if white_space.unwrap_or_default().preserves_spaces() && chars_in_this_word.is_empty() => {
+                    // The first space after a word is the trailing space of the word,
+                    // every other space is kept as it is
+                    words.push(SemanticWordItem::Space);
+                },
+                ' ' | '\t' | '\n' | '\r' => {
+                    // Collapsed into the trailing space of the previous word
+                    end_word(&mut words, &mut chars_in_this_word, &mut char_offsets_in_this_word, &mut soft_hyphens_in_this_word, &bidi_levels, true, &mut longest_word_width, fonts, font_size, letter_spacing_px, hyphenator);
+                },
+                SOFT_HYPHEN => {
+                    // Invisible, unless the word is broken at this position
+                    if !chars_in_this_word.is_empty() {
+                        soft_hyphens_in_this_word.push(chars_in_this_word.len());
                     }
-                    + letter_spacing_px
-                    - kerning_adjust;
+                },
+                ZERO_WIDTH_SPACE | WORD_JOINER | ZERO_WIDTH_NO_BREAK_SPACE => {
+                    // Only affect the break opportunities, never rendered
+                },
+                cur_char =>  {
+                    // Regular character, the glyphs are calculated at the end of the word
+                    chars_in_this_word.push(cur_char);
+                    char_offsets_in_this_word.push(segment_offset + char_offset);
+                }
+            }
+        }
 
-                let word_caret_saved = word_caret;
+        // Break opportunity without white space (ex. after a hyphen or between CJK ideographs)
+        end_word(&mut words, &mut chars_in_this_word, &mut char_offsets_in_this_word, &mut soft_hyphens_in_this_word, &bidi_levels, false, &mut longest_word_width, fonts, font_size, letter_spacing_px, hyphenator);
+    }
+
+    Words {
+        items: words,
+        longest_word_width: longest_word_width,
+        paragraph_direction: direction,
+        fonts: Vec::new(),
+        white_space,
+    }
+}
 
-                last_glyph = Some(id);
-                word_caret += horiz_advance;
-                cur_word_length += horiz_advance;
+/// Rule L2 of the Unicode bidi algorithm: from the highest level down to the lowest odd level,
+/// reverses every sequence of items that are at this level or higher. Returns the (logical)
+/// indices of the items in visual order, i.e. from left to right.
+fn reorder_by_bidi_levels(levels: &[u8]) -> Vec<usize> {
 
-                glyphs_in_this_word.push(GlyphInstance {
-                    index: id.0,
-                    point: TypedPoint2D::new(word_caret_saved, 0.0),
-                });
+    let mut visual_order = (0..levels.len()).collect::<Vec<usize>>();
 
-                chars_in_this_word.push(cur_char);
+    let highest_level = match levels.iter().max() {
+        Some(l) => *l,
+        None => return visual_order,
+    };
+    let lowest_odd_level = levels.iter().min().map(|l| *l | 1).unwrap_or(1);
+
+    for level in (lowest_odd_level..=highest_level).rev() {
+        let mut i = 0;
+        while i < visual_order.len() {
+            if levels[visual_order[i]] < level {
+                i += 1;
+                continue;
+            }
+            let run_start = i;
+            while i < visual_order.len() && levels[visual_order[i]] >= level {
+                i += 1;
             }
+            visual_order[run_start..i].reverse();
         }
     }
 
-    // Push last word
-    if !chars_in_this_word.is_empty() {
-        end_word(
-            &mut words,
-            &mut glyphs_in_this_word,
-            &mut cur_word_length,
-            &mut word_caret,
-            &mut longest_word_width,
-            &mut last_glyph);
+    visual_order
+}
+
+/// A font of the font fallback chain: the parsed `font` is used for the metrics, the
+/// original font file (`data`) for shaping the text with rustybuzz. Fonts without the
+/// font file are not shaped, their glyphs are only placed by the advance width and the
+/// pair kerning of the font.
+#[derive(Copy, Clone)]
+pub(crate) struct TextFont<'a> {
+    pub(crate) font: &'a Font<'a>,
+    pub(crate) data: Option<&'a [u8]>,
+}
+
+impl<'a> TextFont<'a> {
+    pub(crate) fn new(font: &'a Font<'a>, data: &'a [u8]) -> Self {
+        Self { font, data: Some(data) }
     }
 
-    Words {
-        items: words,
-        longest_word_width: longest_word_width,
+    /// Creates a font that is only used for its metrics, see `TextFont`
+    pub(crate) fn without_data(font: &'a Font<'a>) -> Self {
+        Self { font, data: None }
+    }
+}
+
+/// A glyph returned from the shaper, positioned relative to the previous glyph
+struct ShapedGlyph {
+    id: u32,
+    /// Index of the (first) character that the glyph was created from
+    cluster: usize,
+    x_advance: f32,
+    x_offset: f32,
+    y_offset: f32,
+}
+
+/// Shapes a single word with rustybuzz (substitutes the ligatures and contextual forms from
+/// the `GSUB` table, positions the glyphs with the kerning and mark positioning of the `GPOS`
+/// table) and places the glyphs of the word relative to the start of the word.
+///
+/// The word is split into runs with the same font (see `get_font_index`) and bidi level,
+/// each run is shaped separately and the runs are brought into visual order. `chars` and
+/// `levels` are in logical order, the returned `Word::clusters` are logical character indices.
+///
+/// `break_points` are the character indices where the word may be hyphenated
+/// (and whether the break comes from a soft hyphen), ligatures are never formed across them.
+///
+/// Since this is done when splitting the text into words, the result is cached
+/// together with the words in the `TextCache`.
+fn shape_word<'a>(chars: &[char], levels: &[u8], break_points: &[(usize, bool)], fonts: &[TextFont<'a>], font_size: TextSizePx, letter_spacing_px: f32)
+-> Word
+{
+    // (1) Split the word into runs of characters with the same font and the same direction
+    let char_runs = chars.iter().zip(levels.iter()).map(|(c, level)| (get_font_index(fonts, *c), *level)).collect::<Vec<_>>();
+    let mut runs = Vec::<(usize, usize)>::new();
+    let mut run_start = 0;
+
+    while run_start < chars.len() {
+        let run_end = char_runs[run_start..].iter().position(|r| *r != char_runs[run_start]).map(|p| run_start + p).unwrap_or(chars.len());
+        runs.push((run_start, run_end));
+        run_start = run_end;
+    }
+
+    // (2) Shape the runs and place them from left to right - letter-spacing disables ligatures
+    let run_levels = runs.iter().map(|(start, _)| char_runs[*start].1).collect::<Vec<u8>>();
+    let mut word_caret = 0.0;
+    let mut positioned_glyphs = Vec::with_capacity(chars.len());
+    let mut clusters = Vec::with_capacity(chars.len());
+
+    for run_index in reorder_by_bidi_levels(&run_levels) {
+        let (run_start, run_end) = runs[run_index];
+        let (font_index, level) = char_runs[run_start];
+
+        let run_break_points = break_points.iter()
+            .filter(|(b, _)| *b > run_start && *b < run_end)
+            .map(|(b, _)| *b - run_start)
+            .collect::<Vec<_>>();
+
+        let shaped_glyphs = shape_run(&chars[run_start..run_end], level % 2 == 1, &run_break_points, letter_spacing_px != 0.0, fonts[font_index], font_size);
+
+        for (glyph_index, glyph) in shaped_glyphs.iter().enumerate() {
+            positioned_glyphs.push(GlyphInstance {
+                index: ((font_index as u32) << FONT_INDEX_SHIFT) | glyph.id,
+                point: TypedPoint2D::new(word_caret + glyph.x_offset, -glyph.y_offset),
+            });
+            clusters.push(run_start + glyph.cluster);
+
+            // The letter spacing is added after each character, not after each glyph
+            let is_end_of_cluster = shaped_glyphs.get(glyph_index + 1).map(|next| next.cluster != glyph.cluster).unwrap_or(true);
+            word_caret += glyph.x_advance;
+            if is_end_of_cluster {
+                word_caret += letter_spacing_px;
+            }
+        }
+    }
+
+    // (3) Map the hyphenation points from characters to glyphs
+    let hyphenation_points = break_points.iter().filter_map(|(char_index, is_soft_hyphen)| {
+        let glyph_index = clusters.iter().position(|c| c == char_index).filter(|g| *g > 0)?;
+        Some(HyphenationPoint {
+            glyph_index,
+            width: positioned_glyphs[glyph_index].point.x,
+            is_soft_hyphen: *is_soft_hyphen,
+        })
+    }).collect();
+
+    Word {
+        glyphs: positioned_glyphs,
+        total_width: word_caret,
+        hyphenation_points,
+        has_trailing_space: false,
+        bidi_level: 0,
+        clusters,
+    }
+}
+
+/// Shapes a run of characters with the same font and direction, returns the glyphs in visual
+/// order (right-to-left runs are reversed by the shaper, which also mirrors the brackets).
+/// No ligatures are formed across the `break_points` (character indices into the run).
+fn shape_run<'a>(chars: &[char], is_rtl: bool, break_points: &[usize], disable_ligatures: bool, font: TextFont<'a>, font_size: TextSizePx)
+-> Vec<ShapedGlyph>
+{
+    use rustybuzz::{Face, UnicodeBuffer, Direction, Feature, Tag};
+
+    // The glyph positions are in font units, the font size of rusttype is the height of the
+    // font (ascent - descent), not the size of the em square
+    let v_metrics = font.font.v_metrics_unscaled();
+    let font_units_to_px = font_size.0 / (v_metrics.ascent - v_metrics.descent);
+
+    let face = match font.data.and_then(|data| Face::from_slice(data, 0)) {
+        Some(face) => face,
+        None => return place_glyphs_without_shaping(chars, is_rtl, font.font, font_units_to_px),
+    };
+
+    // The clusters of the shaped glyphs are byte offsets into the run
+    let text = chars.iter().collect::<String>();
+    let char_offsets = text.char_indices().map(|(offset, _)| offset).collect::<Vec<usize>>();
+
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(&text);
+    buffer.set_direction(if is_rtl { Direction::RightToLeft } else { Direction::LeftToRight });
+
+    const LIGATURE_FEATURES: [&[u8; 4]; 2] = [b"liga", b"clig"];
+
+    let features = if disable_ligatures {
+        LIGATURE_FEATURES.iter().map(|tag| Feature::new(Tag::from_bytes(tag), 0, ..)).collect::<Vec<_>>()
+    } else {
+        // A ligature can't start before the break point and end after it,
+        // so the character after the break point can't be part of a ligature.
+        // NOTE: rustybuzz 0.3 excludes the end of an inclusive range, so the end of the
+        // character is passed as the inclusive end (see the "of\u{AD}fice" test)
+        break_points.iter().flat_map(|b| {
+            let start = char_offsets[*b];
+            let end = char_offsets.get(*b + 1).cloned().unwrap_or(text.len());
+            LIGATURE_FEATURES.iter().map(move |tag| Feature::new(Tag::from_bytes(tag), 0, start..=end))
+        }).collect::<Vec<_>>()
+    };
+
+    let glyph_buffer = rustybuzz::shape(&face, &features, buffer);
+
+    glyph_buffer.glyph_infos().iter().zip(glyph_buffer.glyph_positions().iter()).map(|(info, position)| ShapedGlyph {
+        id: info.codepoint,
+        cluster: char_offsets.binary_search(&(info.cluster as usize)).unwrap_or_else(|i| i.saturating_sub(1)),
+        x_advance: position.x_advance as f32 * font_units_to_px,
+        x_offset: position.x_offset as f32 * font_units_to_px,
+        y_offset: position.y_offset as f32 * font_units_to_px,
+    }).collect()
+}
+
+/// Places one glyph per character by its advance width, with the pair kerning of the font,
+/// for fonts that can't be shaped (see `TextFont`). Like `shape_run`, right-to-left runs
+/// are reversed and their brackets are mirrored.
+fn place_glyphs_without_shaping<'a>(chars: &[char], is_rtl: bool, font: &Font<'a>, font_units_to_px: f32) -> Vec<ShapedGlyph> {
+
+    let v_metrics = font.v_metrics_unscaled();
+    // Scaled to the height of the font, the metrics are in font units
+    let unscaled = Scale::uniform(v_metrics.ascent - v_metrics.descent);
+
+    let mut clusters = (0..chars.len()).collect::<Vec<usize>>();
+    if is_rtl {
+        clusters.reverse();
+    }
+
+    let glyph_ids = clusters.iter().map(|cluster| {
+        let c = chars[*cluster];
+        get_glyph_id(font, if is_rtl { get_mirrored_char(c) } else { c })
+    }).collect::<Vec<GlyphId>>();
+
+    glyph_ids.iter().zip(clusters.iter()).enumerate().map(|(glyph_index, (id, cluster))| {
+        // pair_kerning returns negative values if the next glyph should move closer
+        let kerning = glyph_ids.get(glyph_index + 1).map(|next| font.pair_kerning(unscaled, *id, *next)).unwrap_or(0.0);
+        ShapedGlyph {
+            id: id.0,
+            cluster: *cluster,
+            x_advance: (font.glyph(*id).scaled(unscaled).h_metrics().advance_width + kerning) * font_units_to_px,
+            x_offset: 0.0,
+            y_offset: 0.0,
+        }
+    }).collect()
+}
+
+/// Rule L4 of the Unicode bidi algorithm: brackets in right-to-left text are displayed mirrored,
+/// so that `(` still opens a parenthesis when reading from right to left
+fn get_mirrored_char(c: char) -> char {
+    match c {
+        '(' => ')', ')' => '(',
+        '[' => ']', ']' => '[',
+        '{' => '}', '}' => '{',
+        '<' => '>', '>' => '<',
+        '\u{AB}' => '\u{BB}', '\u{BB}' => '\u{AB}',
+        '\u{2039}' => '\u{203A}', '\u{203A}' => '\u{2039}',
+        other => other,
+    }
+}
+
+/// The index of the font in the font fallback chain (`Words::fonts`) is stored in the upper
+/// bits of `GlyphInstance::index`, since the glyph IDs of TrueType fonts only use 16 bits
+const FONT_INDEX_SHIFT: u32 = 16;
+const GLYPH_ID_MASK: u32 = (1 << FONT_INDEX_SHIFT) - 1;
+
+/// Returns the index of the first font in the fallback chain that has a glyph for the
+/// character - or the first font (which then displays the `.notdef` glyph) if no font has one
+fn get_font_index<'a>(fonts: &[TextFont<'a>], c: char) -> usize {
+    fonts.iter().position(|font| has_glyph(font.font, c)).unwrap_or(0)
+}
+
+/// Glyph 0 is the `.notdef` glyph, i.e. the font can't display the character
+fn has_glyph<'a>(font: &Font<'a>, c: char) -> bool {
+    get_glyph_id(font, c).0 != 0
+}
+
+/// Returns the (visible) characters of the text that none of the fonts can display,
+/// used to determine which fallback fonts are needed for the text
+pub(crate) fn get_missing_chars<'a>(text: &str, fonts: &[&Font<'a>]) -> Vec<char> {
+    let mut missing_chars = text.chars().filter(|c| {
+        let is_invisible = match *c {
+            '\u{00AD}' | '\u{200B}'..='\u{200F}' | '\u{2060}'..='\u{206F}' | '\u{FEFF}' => true,
+            c => c.is_whitespace() || c.is_control(),
+        };
+        !is_invisible && !fonts.iter().any(|font| has_glyph(font, *c))
+    }).collect::<Vec<char>>();
+    missing_chars.sort();
+    missing_chars.dedup();
+    missing_chars
+}
+
+/// Splits the layouted glyphs into one run per font (the index of the font in `Words::fonts`)
+/// and strips the font index from the glyph indices, so that they can be pushed to WebRender
+pub(crate) fn split_into_font_runs(glyphs: &[GlyphInstance]) -> Vec<(usize, Vec<GlyphInstance>)> {
+    use std::collections::BTreeMap;
+
+    let mut runs = BTreeMap::<usize, Vec<GlyphInstance>>::new();
+
+    for glyph in glyphs {
+        runs.entry((glyph.index >> FONT_INDEX_SHIFT) as usize).or_insert_with(Vec::new).push(GlyphInstance {
+            index: glyph.index & GLYPH_ID_MASK,
+            point: glyph.point,
+        });
+    }
+
+    runs.into_iter().collect()
+}
+
+/// Returns the advance width of each layouted glyph, looked up in the font that the
+/// glyph was created with (the index of the font in `Words::fonts`)
+pub(crate) fn get_glyph_advances<'a>(glyphs: &[GlyphInstance], fonts: &[&Font<'a>], font_size: TextSizePx) -> Vec<f32> {
+    glyphs.iter().map(|glyph| {
+        fonts.get((glyph.index >> FONT_INDEX_SHIFT) as usize).map(|font| {
+            font.glyph(GlyphId(glyph.index & GLYPH_ID_MASK)).scaled(font_size.to_rusttype_scale()).h_metrics().advance_width
+        }).unwrap_or(0.0)
+    }).collect()
+}
+
+/// Returns the baseline and the horizontal extent (start, end) of each line of the glyphs,
+/// from top to bottom. `advances` are the advances of the glyphs (see `get_glyph_advances`).
+pub(crate) fn get_line_extents(glyphs: &[GlyphInstance], advances: &[f32]) -> Vec<(f32, f32, f32)> {
+
+    let mut lines = Vec::<(f32, f32, f32)>::new();
+
+    for (glyph, advance) in glyphs.iter().zip(advances.iter()) {
+        let (x_start, x_end) = (glyph.point.x, glyph.point.x + advance);
+        match lines.iter_mut().find(|(baseline, _, _)| (*baseline - glyph.point.y).abs() < 0.5) {
+            Some(line) => {
+                line.1 = line.1.min(x_start);
+                line.2 = line.2.max(x_end);
+            },
+            None => lines.push((glyph.point.y, x_start, x_end)),
+        }
+    }
+
+    lines.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal));
+    lines
+}
+
+/// Returns the glyph for a character - non-breaking spaces fall back to the
+/// regular space, since many fonts don't have a glyph for them
+fn get_glyph_id<'a>(font: &Font<'a>, c: char) -> GlyphId {
+    let id = font.glyph(c).id();
+    match c {
+        '\u{00A0}' | '\u{202F}' if id.0 == 0 => font.glyph(' ').id(),
+        _ => id,
     }
 }
 
@@ -648,6 +1179,7 @@ fn estimate_overflow_pass_1(
                 match w {
                     Word(w) => cur_line_cursor += w.total_width,
                     Tab => cur_line_cursor += tab_width.0,
+                    Space => cur_line_cursor += space_width.0,
                     Return => {
                         max_line_cursor = max_line_cursor.max(cur_line_cursor);
                         cur_line_cursor = 0.0;
@@ -718,47 +1250,34 @@ fn estimate_overflow_pass_2(
     })
 }
 
-fn calculate_harfbuzz_adjustments<'a>(text: &str, font: &Font<'a>)
--> Vec<HarfbuzzAdjustment>
-{
-    /*
-    use harfbuzz_rs::*;
-    use harfbuzz_rs::rusttype::SetRustTypeFuncs;
-
-    let path = "path/to/some/font_file.otf";
-    let index = 0; //< face index in the font file
-    let face = Face::from_file(path, index).unwrap();
-    let mut font = Font::new(face);
-
-    font.set_rusttype_funcs();
-
-    let output = UnicodeBuffer::new().add_str(text).shape(&font, &[]);
-    let positions = output.get_glyph_positions();
-    let infos = output.get_glyph_infos();
-
-    for (position, info) in positions.iter().zip(infos) {
-        println!("gid: {:?}, cluster: {:?}, x_advance: {:?}, x_offset: {:?}, y_offset: {:?}",
-            info.codepoint, info.cluster, position.x_advance, position.x_offset, position.y_offset);
-    }
-    */
-    Vec::new() // TODO
-}
-
 /// If `max_horizontal_width` is `None`, it means that the text is allowed to overflow
-/// the rectangle horizontally
+/// the rectangle horizontally. Lines that aren't wrapped (`white-space: nowrap / pre`)
+/// can be wider than the `max_horizontal_width`.
 fn words_to_left_aligned_glyphs<'a>(
     words: &Words,
     font: &Font<'a>,
     max_horizontal_width: Option<f32>,
     font_metrics: &FontMetrics)
--> (Vec<GlyphInstance>, Vec<(usize, f32)>, TextSizePx, TextSizePx)
+-> (Vec<GlyphInstance>, TextIndexMap, Vec<(usize, f32)>, TextSizePx, TextSizePx)
 {
+    let wraps_lines = font_metrics.layout_options.white_space.unwrap_or_default().wraps_lines();
+
+    if let Some(max_width) = max_horizontal_width {
+        if wraps_lines && words.uses_total_fit_line_breaking(&font_metrics.layout_options) {
+            return words_to_total_fit_glyphs(words, font, max_width, font_metrics);
+        }
+    }
+
     let words = &words.items;
 
     let FontMetrics { space_width, tab_width, vertical_advance, font_size_no_line_height, letter_spacing, .. } = *font_metrics;
+    let baseline_offset = font_metrics.get_baseline_offset();
 
     // left_aligned_glyphs stores the X and Y coordinates of the positioned glyphs
     let mut left_aligned_glyphs = Vec::<GlyphInstance>::new();
+    let mut index_map = TextIndexMap::default();
+    // The words on the current line, for reordering bidirectional text
+    let mut line_items = Vec::<BidiLineItem>::new();
 
     enum WordCaretMax {
         SomeMaxWidth(f32),
@@ -783,10 +1302,10 @@ fn words_to_left_aligned_glyphs<'a>(
         match word {
             Word(word) => {
                 let text_overflows_rect = match max_horizontal_width {
-                    Some(max) => word_caret + word.total_width > max,
+                    Some(max) if wraps_lines => word_caret + word.total_width > max,
                     // If we don't have a maximum horizontal width, the text can overflow the
                     // bounding rectangle in the horizontal direction
-                    None => false,
+                    _ => false,
                 };
 
                 if text_overflows_rect {
@@ -802,20 +1321,28 @@ fn words_to_left_aligned_glyphs<'a>(
                     if word_caret > max_word_caret {
                         max_word_caret = word_caret;
                     }
+                    reorder_line(&mut left_aligned_glyphs, &line_items, word_caret);
+                    line_items.clear();
                     word_caret = 0.0;
                     current_line_num += 1;
                 }
 
-                for glyph in &word.glyphs {
-                    use azul_css::PT_TO_PX;
+                line_items.push(BidiLineItem {
+                    glyph_start: left_aligned_glyphs.len(),
+                    x_start: word_caret,
+                    level: word.bidi_level,
+                });
+                index_map.glyph_to_text.extend(word.clusters.iter().map(|c| Some(*c)));
 
-                    // vertical_advance is in px
+                for glyph in &word.glyphs {
+                    // The glyph positions, the baseline offset and the vertical_advance are all in px
                     let mut new_glyph = *glyph;
                     let push_x = word_caret;
-                    let push_y = (current_line_num + 1) as f32 * vertical_advance.0;
+                    let push_y = current_line_num as f32 * vertical_advance.0;
 
                     new_glyph.point.x += push_x;
-                    new_glyph.point.y += push_y / PT_TO_PX; // note: new_glyph.point seems to be in pt, not px!
+                    // The first line sits on the baseline, so that `align-items: baseline` lines up with the glyphs
+                    new_glyph.point.y += baseline_offset.0 + push_y;
 
                     left_aligned_glyphs.push(new_glyph);
                 }
@@ -823,13 +1350,19 @@ fn words_to_left_aligned_glyphs<'a>(
                 // Add the word width to the current word_caret
                 // space_width is in px
                 // letter_spacing is in px
-                word_caret += word.total_width + space_width.0 + letter_spacing;
+                word_caret += word.total_width + letter_spacing;
+                if word.has_trailing_space {
+                    word_caret += space_width.0;
+                }
             },
             Tab => {
                 // tab_width is in px
                 // letter_spacing is in px
                 word_caret += tab_width.0 + letter_spacing;
             },
+            Space => {
+                word_caret += space_width.0;
+            },
             Return => {
                 // TODO: duplicated code
                 let space_until_horz_return = match max_horizontal_width {
@@ -842,12 +1375,16 @@ fn words_to_left_aligned_glyphs<'a>(
                 if word_caret > max_word_caret {
                     max_word_caret = word_caret;
                 }
+                reorder_line(&mut left_aligned_glyphs, &line_items, word_caret);
+                line_items.clear();
                 word_caret = 0.0;
                 current_line_num += 1;
             },
         }
     }
 
+    reorder_line(&mut left_aligned_glyphs, &line_items, word_caret);
+
     // push the infos about the last line
     if !left_aligned_glyphs.is_empty() {
         let space_until_horz_return = match max_horizontal_width {
@@ -865,30 +1402,552 @@ fn words_to_left_aligned_glyphs<'a>(
 
     let line_break_offsets = line_break_offsets.into_iter().map(|(line, space_r)| {
         let space_r = match space_r {
-            WordCaretMax::SomeMaxWidth(s) => s,
+            // Lines that are too long (because they aren't wrapped) are aligned at the start
+            WordCaretMax::SomeMaxWidth(s) => s.max(0.0),
             WordCaretMax::NoMaxWidth(word_caret) => max_word_caret - word_caret,
         };
         (line, space_r)
     }).collect();
 
-    (left_aligned_glyphs, line_break_offsets, min_enclosing_width, min_enclosing_height)
+    (left_aligned_glyphs, index_map, line_break_offsets, min_enclosing_width, min_enclosing_height)
 }
 
-fn apply_harfbuzz_adjustments(positioned_glyphs: &mut [GlyphInstance], harfbuzz_adjustments: Vec<HarfbuzzAdjustment>)
-{
-    // TODO
+/// A word (or the part of a hyphenated word) on a line, see `reorder_line`
+#[derive(Debug, Copy, Clone)]
+struct BidiLineItem {
+    /// Index of the first glyph of the word in the layouted glyphs
+    glyph_start: usize,
+    /// Position of the word on the line, before reordering
+    x_start: f32,
+    /// Bidi level of the word
+    level: u8,
+}
+
+/// Moves the words of a line from the logical into the visual order (see `reorder_by_bidi_levels`).
+/// The glyphs of each item span up to the next item (the last item up to the end of the `glyphs`),
+/// the space after each item (up to the next item or `line_end`) moves together with the item.
+fn reorder_line(glyphs: &mut [GlyphInstance], line_items: &[BidiLineItem], line_end: f32) {
+
+    let visual_order = reorder_by_bidi_levels(&line_items.iter().map(|i| i.level).collect::<Vec<u8>>());
+    if visual_order.iter().enumerate().all(|(visual, logical)| visual == *logical) {
+        return;
+    }
+
+    let item_ends = (0..line_items.len()).map(|i| match line_items.get(i + 1) {
+        Some(next) => (next.glyph_start, next.x_start),
+        None => (glyphs.len(), line_end),
+    }).collect::<Vec<_>>();
+
+    let mut x = line_items[0].x_start;
+
+    for logical in visual_order {
+        let item = line_items[logical];
+        let (glyph_end, x_end) = item_ends[logical];
+        for glyph in &mut glyphs[item.glyph_start..glyph_end] {
+            glyph.point.x += x - item.x_start;
+        }
+        x += x_end - item.x_start;
+    }
+}
+
+/// Penalty for breaking a line at a hyphenation point
+const HYPHEN_PENALTY: f32 = 50.0;
+/// Added to the badness of every line, so that fewer lines are preferred
+const LINE_PENALTY: f32 = 10.0;
+/// Extra demerits for two consecutive lines ending with a hyphen
+const DOUBLE_HYPHEN_DEMERITS: f64 = 3000.0;
+/// Demerits of a line that overflows the rectangle (ex. because a single word is too
+/// long for the line). Only used if there is no other way to break the line.
+const OVERFLOW_DEMERITS: f64 = 1.0e12;
+/// Badness of a line that can't be stretched (or shrunk) at all
+const MAX_BADNESS: f32 = 10000.0;
+
+/// An item of the paragraph, as used by the Knuth-Plass line breaking algorithm
+#[derive(Debug, Copy, Clone)]
+enum LineBreakItem {
+    /// Glyphs that can't be broken: the glyphs `glyphs.0..glyphs.1` of the
+    /// `Word` at `words.items[word]`, `x_offset` is the position of the first glyph in the word
+    Box { word: usize, glyphs: (usize, usize), x_offset: f32, width: f32 },
+    /// Space between words that can be stretched / shrunk. A line can be broken
+    /// at a glue if the glue directly follows a box.
+    Glue { width: f32, stretch: f32, shrink: f32 },
+    /// Infinitely stretchable glue at the end of a paragraph, so that
+    /// the last line of a paragraph is never justified
+    FillGlue,
+    /// Possible line break, `width` is the width of the hyphen if the line is broken here.
+    /// A `cost` of `None` forces a line break.
+    Penalty { width: f32, cost: Option<f32>, is_hyphen: bool },
+}
+
+/// A line, as determined by `break_lines_total_fit`
+#[derive(Debug, Copy, Clone)]
+struct TotalFitLine {
+    /// Index of the first item on the line
+    start: usize,
+    /// Index of the item at which the line is broken (exclusive)
+    end: usize,
+    /// Adjustment ratio: how much of the stretch (if positive) or the shrink (if negative)
+    /// of the glue is necessary to fill the line
+    ratio: f32,
+    /// Natural width of the line, including the hyphen
+    width: f32,
+    /// Whether the line ends with a hyphen
+    is_hyphenated: bool,
+}
+
+impl TextLayoutOptions {
+    /// Whether the lines are broken with the (slower) Knuth-Plass algorithm instead of
+    /// the default greedy algorithm, which is necessary for justified and hyphenated text
+    pub fn uses_total_fit_line_breaking(&self) -> bool {
+        self.horz_alignment == StyleTextAlignmentHorz::Justify ||
+        self.hyphens == Some(StyleHyphens::Auto)
+    }
 }
 
-fn calculate_knuth_plass_adjustments(positioned_glyphs: &[GlyphInstance], line_break_offsets: &[(usize, f32)])
--> Vec<KnuthPlassAdjustment>
+/// Converts the words into boxes (words or parts of hyphenated words),
+/// glue (spaces and tabs) and penalties (hyphenation points and returns)
+fn get_line_break_items(words: &Words, font_metrics: &FontMetrics) -> Vec<LineBreakItem> {
+
+    use self::SemanticWordItem::*;
+    use self::LineBreakItem::*;
+
+    let FontMetrics { space_width, tab_width, hyphen_width, letter_spacing, .. } = *font_metrics;
+    let letter_spacing = letter_spacing.and_then(|p| Some(p.0.to_pixels())).unwrap_or(0.0);
+    let hyphens = font_metrics.layout_options.hyphens.unwrap_or_default();
+
+    // Same stretchability as TeX: a space can grow by 1/2 and shrink by 1/3 of its width
+    let space = Glue {
+        width: space_width.0 + letter_spacing,
+        stretch: space_width.0 / 2.0,
+        shrink: space_width.0 / 3.0,
+    };
+
+    let end_paragraph = |items: &mut Vec<LineBreakItem>| {
+        items.push(FillGlue);
+        items.push(Penalty { width: 0.0, cost: None, is_hyphen: false });
+    };
+
+    let mut items = Vec::with_capacity(words.items.len() * 2);
+    // If the previous item was a word: whether the word was followed by a space
+    let mut previous_word_has_space = None;
+
+    for (word_idx, word) in words.items.iter().enumerate() {
+        match word {
+            Word(word) => {
+                match previous_word_has_space {
+                    Some(true) => items.push(space),
+                    // Line break opportunity between two words without a space
+                    Some(false) => items.push(Penalty { width: 0.0, cost: Some(0.0), is_hyphen: false }),
+                    None => { },
+                }
+
+                let mut glyph_start = 0;
+                let mut x_start = 0.0;
+
+                let hyphenation_points = word.hyphenation_points.iter().filter(|h| match hyphens {
+                    StyleHyphens::None => false,
+                    StyleHyphens::Manual => h.is_soft_hyphen,
+                    StyleHyphens::Auto => true,
+                });
+
+                for point in hyphenation_points {
+                    items.push(Box { word: word_idx, glyphs: (glyph_start, point.glyph_index), x_offset: x_start, width: point.width - x_start });
+                    items.push(Penalty { width: hyphen_width.0, cost: Some(HYPHEN_PENALTY), is_hyphen: true });
+                    glyph_start = point.glyph_index;
+                    x_start = point.width;
+                }
+
+                items.push(Box { word: word_idx, glyphs: (glyph_start, word.glyphs.len()), x_offset: x_start, width: word.total_width - x_start });
+                previous_word_has_space = Some(word.has_trailing_space);
+            },
+            Tab => {
+                if previous_word_has_space == Some(true) {
+                    items.push(space);
+                }
+                items.push(Glue { width: tab_width.0 + letter_spacing, stretch: 0.0, shrink: 0.0 });
+                previous_word_has_space = None;
+            },
+            Space => {
+                if previous_word_has_space == Some(true) {
+                    items.push(space);
+                }
+                items.push(space);
+                previous_word_has_space = None;
+            },
+            Return => {
+                end_paragraph(&mut items);
+                previous_word_has_space = None;
+            },
+        }
+    }
+
+    end_paragraph(&mut items);
+
+    items
+}
+
+/// Breaks the items into lines using the Knuth-Plass "total fit" algorithm: instead of filling
+/// each line as much as possible (greedy), it chooses the line breaks that minimize the
+/// "demerits" (how much the spaces have to be stretched / shrunk) of the whole paragraph.
+///
+/// The last item has to be a forced break (see `get_line_break_items`).
+fn break_lines_total_fit(items: &[LineBreakItem], max_width: f32) -> Vec<TotalFitLine> {
+
+    use self::LineBreakItem::*;
+
+    #[derive(Debug, Copy, Clone)]
+    struct Breakpoint {
+        /// `None` for the start of the text
+        item: Option<usize>,
+        previous: Option<usize>,
+        total_demerits: f64,
+        line: Option<TotalFitLine>,
+    }
+
+    // Prefix sums of the widths, stretch and shrink, so that the natural
+    // width of any line can be calculated in O(1): sums[i] = sum of items[0..i]
+    let mut sums = Vec::with_capacity(items.len() + 1);
+    let (mut width, mut stretch, mut shrink, mut fills) = (0.0, 0.0, 0.0, 0);
+    sums.push((width, stretch, shrink, fills));
+    for item in items {
+        match item {
+            Box { width: w, .. } => width += w,
+            Glue { width: w, stretch: st, shrink: sh } => { width += w; stretch += st; shrink += sh; },
+            FillGlue => fills += 1,
+            Penalty { .. } => { },
+        }
+        sums.push((width, stretch, shrink, fills));
+    }
+
+    let is_legal_break = |i: usize| match items[i] {
+        Glue { .. } => i > 0 && match items[i - 1] { Box { .. } => true, _ => false },
+        Penalty { .. } => true,
+        _ => false,
+    };
+
+    // Glue and penalties directly after a line break are discarded
+    let get_line_start = |after: Option<usize>, end: usize| {
+        let mut start = after.map(|a| a + 1).unwrap_or(0);
+        while start < end {
+            match items[start] {
+                Glue { .. } | Penalty { .. } => start += 1,
+                _ => break,
+            }
+        }
+        start
+    };
+
+    let mut breakpoints = vec![Breakpoint { item: None, previous: None, total_demerits: 0.0, line: None }];
+    // Breakpoints from which a line can still be started + whether a line
+    // from this breakpoint has already been tried
+    let mut active = vec![(0, false)];
+
+    for end in 0..items.len() {
+
+        if !is_legal_break(end) {
+            continue;
+        }
+
+        let (break_width, break_cost, break_is_hyphen) = match items[end] {
+            Penalty { width, cost, is_hyphen } => (width, cost, is_hyphen),
+            _ => (0.0, Some(0.0), false),
+        };
+
+        let mut best: Option<Breakpoint> = None;
+        let mut still_active = Vec::with_capacity(active.len() + 1);
+
+        for (breakpoint_idx, has_line) in active.drain(..) {
+
+            let previous = breakpoints[breakpoint_idx];
+            let start = get_line_start(previous.item, end);
+            let (w0, st0, sh0, f0) = sums[start];
+            let (w1, st1, sh1, f1) = sums[end];
+            let line_width = w1 - w0 + break_width;
+
+            let ratio = if line_width <= max_width && f1 > f0 {
+                0.0
+            } else if line_width < max_width {
+                if st1 - st0 > 0.0 { (max_width - line_width) / (st1 - st0) } else { ::std::f32::INFINITY }
+            } else if line_width > max_width {
+                if sh1 - sh0 > 0.0 { (max_width - line_width) / (sh1 - sh0) } else { ::std::f32::NEG_INFINITY }
+            } else {
+                0.0
+            };
+
+            let is_overflowing = ratio < -1.0;
+
+            // Lines from this breakpoint will only get longer, so the breakpoint can be
+            // deactivated - unless this is the only possible line (ex. a single, very long word)
+            if is_overflowing && has_line {
+                continue;
+            }
+
+            still_active.push((breakpoint_idx, true));
+
+            let demerits = if is_overflowing {
+                OVERFLOW_DEMERITS
+            } else {
+                let badness = if ratio.is_infinite() { MAX_BADNESS } else { (100.0 * ratio.abs().powi(3)).min(MAX_BADNESS) };
+                let base = ((LINE_PENALTY + badness) as f64).powi(2);
+                let cost_demerits = match break_cost {
+                    Some(c) if c >= 0.0 => (c as f64).powi(2),
+                    Some(c) => -(c as f64).powi(2),
+                    None => 0.0,
+                };
+                let previous_is_hyphen = previous.line.map(|l| l.is_hyphenated).unwrap_or(false);
+                base + cost_demerits + if previous_is_hyphen && break_is_hyphen { DOUBLE_HYPHEN_DEMERITS } else { 0.0 }
+            };
+
+            let total_demerits = previous.total_demerits + demerits;
+
+            if best.map(|b| total_demerits < b.total_demerits).unwrap_or(true) {
+                best = Some(Breakpoint {
+                    item: Some(end),
+                    previous: Some(breakpoint_idx),
+                    total_demerits,
+                    line: Some(TotalFitLine {
+                        start,
+                        end,
+                        ratio: if is_overflowing { -1.0 } else { ratio },
+                        width: line_width,
+                        is_hyphenated: break_is_hyphen,
+                    }),
+                });
+            }
+        }
+
+        // A forced break ends all lines, nothing can be carried over to the next paragraph
+        if break_cost.is_none() {
+            still_active.clear();
+        }
+
+        if let Some(best) = best {
+            breakpoints.push(best);
+            still_active.push((breakpoints.len() - 1, false));
+        }
+
+        active = still_active;
+    }
+
+    // Walk back from the last breakpoint to the start of the text
+    let mut lines = Vec::new();
+    let mut current = Some(breakpoints.len() - 1);
+
+    while let Some(breakpoint_idx) = current {
+        let breakpoint = breakpoints[breakpoint_idx];
+        lines.extend(breakpoint.line);
+        current = breakpoint.previous;
+    }
+
+    lines.reverse();
+    lines
+}
+
+/// Same as `words_to_left_aligned_glyphs`, but breaks the lines with `break_lines_total_fit`,
+/// hyphenates words and (for `text-align: justify`) stretches the spaces on each line
+fn words_to_total_fit_glyphs<'a>(
+    words: &Words,
+    font: &Font<'a>,
+    max_horizontal_width: f32,
+    font_metrics: &FontMetrics)
+-> (Vec<GlyphInstance>, TextIndexMap, Vec<(usize, f32)>, TextSizePx, TextSizePx)
 {
-    // TODO
-    Vec::new()
+    use self::LineBreakItem::*;
+
+    let FontMetrics { vertical_advance, font_size_no_line_height, .. } = *font_metrics;
+    let baseline_offset = font_metrics.get_baseline_offset();
+    let is_justified = font_metrics.layout_options.horz_alignment == StyleTextAlignmentHorz::Justify;
+    let hyphen_glyph = font.glyph('-').id().0;
+
+    let items = get_line_break_items(words, font_metrics);
+    let lines = break_lines_total_fit(&items, max_horizontal_width);
+
+    let mut glyphs = Vec::<GlyphInstance>::new();
+    let mut index_map = TextIndexMap::default();
+    let mut line_items = Vec::<BidiLineItem>::new();
+    let mut line_break_offsets = Vec::<(usize, f32)>::new();
+    let mut max_word_caret: f32 = 0.0;
+
+    for (line_num, line) in lines.iter().enumerate() {
+
+        let line_y = baseline_offset.0 + line_num as f32 * vertical_advance.0;
+        let mut word_caret = 0.0;
+
+        for item in &items[line.start..line.end] {
+            match *item {
+                Box { word, glyphs: (glyph_start, glyph_end), x_offset, width } => {
+                    if let SemanticWordItem::Word(ref word) = words.items[word] {
+                        line_items.push(BidiLineItem { glyph_start: glyphs.len(), x_start: word_caret, level: word.bidi_level });
+                        index_map.glyph_to_text.extend(word.clusters[glyph_start..glyph_end].iter().map(|c| Some(*c)));
+                        glyphs.extend(word.glyphs[glyph_start..glyph_end].iter().map(|glyph| {
+                            let mut glyph = *glyph;
+                            glyph.point.x += word_caret - x_offset;
+                            glyph.point.y += line_y;
+                            glyph
+                        }));
+                    }
+                    word_caret += width;
+                },
+                Glue { width, stretch, shrink } => {
+                    // Lines that are too long are always shrunk, but only justified lines are stretched
+                    let adjustment = if line.ratio < 0.0 {
+                        line.ratio * shrink
+                    } else if is_justified && line.ratio.is_finite() {
+                        line.ratio * stretch
+                    } else {
+                        0.0
+                    };
+                    word_caret += width + adjustment;
+                },
+                FillGlue | Penalty { .. } => { },
+            }
+        }
+
+        if line.is_hyphenated {
+            glyphs.push(GlyphInstance { index: hyphen_glyph, point: TypedPoint2D::new(word_caret, line_y) });
+            index_map.glyph_to_text.push(None);
+            word_caret += font_metrics.hyphen_width.0;
+        }
+
+        reorder_line(&mut glyphs, &line_items, word_caret);
+        line_items.clear();
+
+        if !glyphs.is_empty() {
+            line_break_offsets.push((glyphs.len() - 1, max_horizontal_width - word_caret));
+        }
+
+        max_word_caret = max_word_caret.max(word_caret);
+    }
+
+    let min_enclosing_width = TextSizePx(max_word_caret);
+    let min_enclosing_height = (vertical_advance * lines.len().saturating_sub(1) as f32) + font_size_no_line_height;
+
+    (glyphs, index_map, line_break_offsets, min_enclosing_width, min_enclosing_height)
 }
 
-fn apply_knuth_plass_adjustments(positioned_glyphs: &mut [GlyphInstance], knuth_plass_adjustments: Vec<KnuthPlassAdjustment>)
+/// Cuts off the lines after the `line-clamp` and (for `text-overflow: ellipsis`) the glyphs of
+/// the lines that are wider than the `max_horizontal_width`. If a line was cut off, it ends with
+/// an ellipsis, which is placed after the last glyph that fits into the line.
+///
+/// The glyphs have to be left-aligned, the `line_breaks` are updated so that the glyphs can be
+/// aligned afterwards. `fonts` are the fonts that the glyphs refer to (see `Words::fonts`).
+fn truncate_lines<'a>(
+    glyphs: &mut Vec<GlyphInstance>,
+    index_map: &mut TextIndexMap,
+    line_breaks: &mut Vec<(usize, f32)>,
+    fonts: &[&Font<'a>],
+    font_metrics: &FontMetrics,
+    max_horizontal_width: Option<f32>)
 {
-    // TODO
+    use std::mem;
+
+    let max_lines = match font_metrics.layout_options.line_clamp {
+        Some(StyleLineClamp::Lines(max_lines)) => max_lines,
+        _ => usize::max_value(),
+    };
+
+    // Lines can only be too long if there is a maximum width
+    let max_line_width = match font_metrics.layout_options.text_overflow {
+        Some(StyleTextOverflow::Ellipsis) => max_horizontal_width,
+        _ => None,
+    };
+
+    if max_lines == usize::max_value() && max_line_width.is_none() {
+        return;
+    }
+
+    let FontMetrics { vertical_advance, font_size_no_line_height, .. } = *font_metrics;
+    let baseline_offset = font_metrics.get_baseline_offset().0;
+    let advances = get_glyph_advances(glyphs, fonts, font_size_no_line_height);
+    let (ellipsis_glyphs, ellipsis_width) = get_ellipsis_glyphs(fonts[0], font_size_no_line_height);
+    // The ellipsis has to fit into the line, together with the glyphs before it
+    let max_x_before_ellipsis = max_horizontal_width.map(|max| max - ellipsis_width).unwrap_or(::std::f32::INFINITY);
+
+    let old_glyphs = mem::replace(glyphs, Vec::new());
+    let old_index_map = mem::replace(&mut index_map.glyph_to_text, Vec::new());
+    let old_line_breaks = mem::replace(line_breaks, Vec::new());
+
+    let mut line_start = 0;
+    let mut line_num = 0;
+
+    for (line_end, remaining_space) in old_line_breaks {
+
+        // Empty lines have the same line break as the line before them (see `align_text_horz`)
+        let line = line_start..(line_end + 1);
+        line_start = line.end;
+
+        // Empty lines at the start of the text don't have a line break,
+        // so the line number has to be calculated from the position of the glyphs
+        line_num = if line.start < line.end {
+            ((old_glyphs[line.start].point.y - baseline_offset) / vertical_advance.0).round() as usize
+        } else {
+            line_num + 1
+        };
+
+        if line_num >= max_lines {
+            break;
+        }
+
+        let line_width = line.clone().map(|i| old_glyphs[i].point.x + advances[i]).fold(0.0, f32::max);
+        let is_last_line = line_num + 1 == max_lines && line.end < old_glyphs.len();
+        let is_too_long = max_line_width.map(|max| line_width > max).unwrap_or(false);
+
+        if !is_last_line && !is_too_long {
+            glyphs.extend_from_slice(&old_glyphs[line.clone()]);
+            index_map.glyph_to_text.extend_from_slice(&old_index_map[line.clone()]);
+            if !glyphs.is_empty() {
+                line_breaks.push((glyphs.len() - 1, remaining_space));
+            }
+            continue;
+        }
+
+        // Keep the glyphs that fit into the line before the ellipsis
+        let mut ellipsis_x: f32 = 0.0;
+        for i in line.clone().filter(|i| old_glyphs[*i].point.x + advances[*i] <= max_x_before_ellipsis) {
+            glyphs.push(old_glyphs[i]);
+            index_map.glyph_to_text.push(old_index_map[i]);
+            ellipsis_x = ellipsis_x.max(old_glyphs[i].point.x + advances[i]);
+        }
+
+        let line_y = baseline_offset + line_num as f32 * vertical_advance.0;
+        glyphs.extend(ellipsis_glyphs.iter().map(|glyph| {
+            let mut glyph = *glyph;
+            glyph.point.x += ellipsis_x;
+            glyph.point.y += line_y;
+            glyph
+        }));
+        index_map.glyph_to_text.extend(ellipsis_glyphs.iter().map(|_| None));
+
+        let new_line_width = ellipsis_x + ellipsis_width;
+        let remaining_space = match max_horizontal_width {
+            Some(max) => max - new_line_width,
+            None => remaining_space + line_width - new_line_width,
+        };
+        line_breaks.push((glyphs.len() - 1, remaining_space.max(0.0)));
+
+        if is_last_line {
+            break;
+        }
+    }
+}
+
+/// Returns the glyphs of an ellipsis (`"…"`, or `"..."` if the font has no glyph for it),
+/// relative to the start of the ellipsis, and the width of the ellipsis
+fn get_ellipsis_glyphs<'a>(font: &Font<'a>, font_size: TextSizePx) -> (Vec<GlyphInstance>, f32) {
+    const ELLIPSIS: char = '\u{2026}';
+
+    let chars: &[char] = if has_glyph(font, ELLIPSIS) { &[ELLIPSIS] } else { &['.', '.', '.'] };
+    let mut width = 0.0;
+
+    let glyphs = chars.iter().map(|c| {
+        // The glyph is from the first font, so the font index is 0 (see `split_into_font_runs`)
+        let glyph = font.glyph(*c).scaled(font_size.to_rusttype_scale());
+        let instance = GlyphInstance { index: glyph.id().0, point: TypedPoint2D::new(width, 0.0) };
+        width += glyph.h_metrics().advance_width;
+        instance
+    }).collect();
+
+    (glyphs, width)
 }
 
 fn align_text_horz(
@@ -933,10 +1992,13 @@ fn align_text_horz(
     // i.e. the last line has to end with the last glyph
     assert!(glyphs.len() - 1 == line_breaks[line_breaks.len() - 1].0);
 
+    // `Start` / `End` should already be resolved (see `StyleTextAlignmentHorz::resolve`),
+    // otherwise they are treated as left-to-right
     let multiply_factor = match alignment {
-        Left => return,
+        // Justified lines already fill the entire line (see `words_to_total_fit_glyphs`)
+        Left | Start | Justify => return,
         Center => 0.5, // move the line by the half width
-        Right => 1.0, // move the line by the full width
+        Right | End => 1.0, // move the line by the full width
     };
 
     // If we have the characters "ABC\n\nDEF", this will result in:
@@ -1016,6 +2078,232 @@ fn add_origin(positioned_glyphs: &mut [GlyphInstance], x: f32, y: f32)
     }
 }
 
+/// A run of text inside of an inline formatting context, i.e. the words of one `display: inline` label
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct InlineTextRun<'a> {
+    pub(crate) words: &'a Words,
+    pub(crate) font_metrics: FontMetrics,
+    /// Whether there is white space between the end of the previous run and this run
+    pub(crate) leading_space: bool,
+}
+
+/// The layout of a single `InlineTextRun`
+#[derive(Debug, Clone)]
+pub(crate) struct InlineRunLayout {
+    /// Positioned glyphs, relative to the top left corner of the inline formatting context
+    pub(crate) glyphs: Vec<GlyphInstance>,
+    /// Bounding box of the run, relative to the top left corner of the inline formatting context.
+    /// If the run is broken across multiple lines, this is the union of all lines of the run.
+    pub(crate) bounds: TypedRect<f32, LayoutPixel>,
+    /// The part of the run on each line (from top to bottom), relative to the top left corner
+    /// of the inline formatting context. Backgrounds and hit-testing use these instead of the
+    /// `bounds`, which can overlap the other runs.
+    pub(crate) line_rects: Vec<TypedRect<f32, LayoutPixel>>,
+}
+
+/// Returned result from the `layout_inline_runs` function
+#[derive(Debug, Clone)]
+pub(crate) struct InlineLayout {
+    /// One layout per `InlineTextRun`, in the same order as the runs
+    pub(crate) runs: Vec<InlineRunLayout>,
+    /// Sum of the heights of all lines
+    pub(crate) height: TextSizePx,
+    /// Distance from the top to the baseline of the first line, `None` if there is no text
+    pub(crate) first_baseline: Option<TextSizePx>,
+}
+
+/// Lays out multiple runs of text (each with its own font metrics) on shared lines,
+/// breaking the lines at word boundaries if `max_horizontal_width` is exceeded.
+///
+/// Each line is as high as the tallest run on that line, the runs on one line
+/// share a common baseline.
+pub(crate) fn layout_inline_runs(
+    runs: &[InlineTextRun],
+    max_horizontal_width: Option<f32>,
+    horz_alignment: StyleTextAlignmentHorz)
+-> InlineLayout
+{
+    use self::SemanticWordItem::*;
+
+    /// A glyph on the current line, the Y position is still relative to the baseline
+    struct LineGlyph {
+        run: usize,
+        glyph: GlyphInstance,
+        /// Number of spaces before the glyph on the line, which are stretched for `text-align: justify`
+        gaps_before: usize,
+    }
+
+    /// Horizontal extent of a run on the current line
+    struct LineFragment {
+        run: usize,
+        x_start: f32,
+        x_end: f32,
+        /// Number of spaces on the line before the start / end of the fragment
+        gaps_before_start: usize,
+        gaps_before_end: usize,
+    }
+
+    #[derive(Default)]
+    struct Line {
+        glyphs: Vec<LineGlyph>,
+        fragments: Vec<LineFragment>,
+        /// Space above the baseline
+        above_baseline: f32,
+        /// Space below the baseline
+        below_baseline: f32,
+        width: f32,
+        /// Number of spaces between the words on the line
+        gaps: usize,
+    }
+
+    fn finish_line(
+        line: &mut Line,
+        runs: &[InlineTextRun],
+        layouted_runs: &mut [InlineRunLayout],
+        line_top: &mut f32,
+        first_baseline: &mut Option<TextSizePx>,
+        max_horizontal_width: Option<f32>,
+        horz_alignment: StyleTextAlignmentHorz,
+        is_last_line_of_paragraph: bool)
+    {
+        use azul_css::StyleTextAlignmentHorz::*;
+
+        let alignment_offset = match (max_horizontal_width, horz_alignment) {
+            (None, _) | (_, Left) | (_, Start) | (_, Justify) => 0.0,
+            (Some(max), Center) => (max - line.width) / 2.0,
+            (Some(max), Right) | (Some(max), End) => max - line.width,
+        };
+
+        // Justified lines are filled by stretching the spaces, except for the last line of a paragraph
+        let gap_stretch = match (max_horizontal_width, horz_alignment) {
+            (Some(max), Justify) if !is_last_line_of_paragraph && line.gaps > 0 => (max - line.width).max(0.0) / line.gaps as f32,
+            _ => 0.0,
+        };
+
+        let baseline = *line_top + line.above_baseline;
+        if first_baseline.is_none() {
+            *first_baseline = Some(TextSizePx(baseline));
+        }
+
+        for line_glyph in line.glyphs.drain(..) {
+            let mut glyph = line_glyph.glyph;
+            glyph.point.x += alignment_offset + line_glyph.gaps_before as f32 * gap_stretch;
+            glyph.point.y += baseline;
+            layouted_runs[line_glyph.run].glyphs.push(glyph);
+        }
+
+        for fragment in line.fragments.drain(..) {
+            let run_ascent = runs[fragment.run].font_metrics.get_baseline_offset().0;
+            let run_height = runs[fragment.run].font_metrics.vertical_advance.0;
+            let x_start = fragment.x_start + alignment_offset + fragment.gaps_before_start as f32 * gap_stretch;
+            let x_end = fragment.x_end + alignment_offset + fragment.gaps_before_end as f32 * gap_stretch;
+            let fragment_rect = TypedRect::new(
+                TypedPoint2D::new(x_start, baseline - run_ascent),
+                TypedSize2D::new(x_end - x_start, run_height),
+            );
+            let run_layout = &mut layouted_runs[fragment.run];
+            run_layout.bounds = if run_layout.line_rects.is_empty() {
+                fragment_rect
+            } else {
+                run_layout.bounds.union(&fragment_rect)
+            };
+            run_layout.line_rects.push(fragment_rect);
+        }
+
+        *line_top += line.above_baseline + line.below_baseline;
+        *line = Line::default();
+    }
+
+    let mut layouted_runs = runs.iter().map(|_| InlineRunLayout {
+        glyphs: Vec::new(),
+        bounds: TypedRect::zero(),
+        line_rects: Vec::new(),
+    }).collect::<Vec<_>>();
+
+    let mut line = Line::default();
+    let mut line_top = 0.0;
+    let mut first_baseline = None;
+
+    for (run_idx, run) in runs.iter().enumerate() {
+
+        let FontMetrics { space_width, tab_width, vertical_advance, .. } = run.font_metrics;
+        let above_baseline = run.font_metrics.get_baseline_offset().0;
+        let below_baseline = vertical_advance.0 - above_baseline;
+
+        // White space between two words is only added if the next word lands on the same line
+        let mut pending_space = if run.leading_space && run_idx != 0 { space_width.0 } else { 0.0 };
+
+        for item in &run.words.items {
+            match item {
+                Word(word) => {
+                    let text_overflows_line = match max_horizontal_width {
+                        Some(max) => !line.glyphs.is_empty() && line.width + pending_space + word.total_width > max,
+                        None => false,
+                    };
+
+                    if text_overflows_line {
+                        finish_line(&mut line, runs, &mut layouted_runs, &mut line_top, &mut first_baseline, max_horizontal_width, horz_alignment, false);
+                    } else if !line.glyphs.is_empty() && pending_space > 0.0 {
+                        line.width += pending_space;
+                        line.gaps += 1;
+                    }
+
+                    let x_start = line.width;
+                    let gaps_before = line.gaps;
+                    line.glyphs.extend(word.glyphs.iter().map(|glyph| {
+                        let mut glyph = *glyph;
+                        glyph.point.x += x_start;
+                        LineGlyph { run: run_idx, glyph, gaps_before }
+                    }));
+                    line.width += word.total_width;
+
+                    // Merge the word into the last fragment if the fragment belongs to the same run
+                    let extends_last_fragment = line.fragments.last().map(|f| f.run == run_idx).unwrap_or(false);
+                    if extends_last_fragment {
+                        let last_fragment = line.fragments.last_mut().unwrap();
+                        last_fragment.x_end = line.width;
+                        last_fragment.gaps_before_end = gaps_before;
+                    } else {
+                        line.fragments.push(LineFragment { run: run_idx, x_start, x_end: line.width, gaps_before_start: gaps_before, gaps_before_end: gaps_before });
+                    }
+
+                    line.above_baseline = line.above_baseline.max(above_baseline);
+                    line.below_baseline = line.below_baseline.max(below_baseline);
+                    pending_space = if word.has_trailing_space { space_width.0 } else { 0.0 };
+                },
+                Tab => {
+                    line.width += pending_space + tab_width.0;
+                    pending_space = 0.0;
+                },
+                Space => {
+                    line.width += pending_space + space_width.0;
+                    pending_space = 0.0;
+                    if !line.glyphs.is_empty() {
+                        line.gaps += 1;
+                    }
+                },
+                Return => {
+                    // Empty lines still take up the height of the current run
+                    line.above_baseline = line.above_baseline.max(above_baseline);
+                    line.below_baseline = line.below_baseline.max(below_baseline);
+                    finish_line(&mut line, runs, &mut layouted_runs, &mut line_top, &mut first_baseline, max_horizontal_width, horz_alignment, true);
+                    pending_space = 0.0;
+                },
+            }
+        }
+    }
+
+    if !line.glyphs.is_empty() {
+        finish_line(&mut line, runs, &mut layouted_runs, &mut line_top, &mut first_baseline, max_horizontal_width, horz_alignment, true);
+    }
+
+    InlineLayout {
+        runs: layouted_runs,
+        height: TextSizePx(line_top),
+        first_baseline,
+    }
+}
+
 // -------------------------- PUBLIC API -------------------------- //
 
 pub type IndexOfLineBreak = usize;
@@ -1028,6 +2316,8 @@ pub struct LayoutTextResult {
     pub words: Words,
     /// Left-aligned glyphs
     pub layouted_glyphs: Vec<GlyphInstance>,
+    /// Maps between the `layouted_glyphs` and the (NFC-normalized) text
+    pub index_map: TextIndexMap,
     /// The line_breaks contain:
     ///
     /// - The index of the glyph at which the line breaks (index into the `self.layouted_glyphs`)
@@ -1044,11 +2334,20 @@ pub struct LayoutTextResult {
 pub struct TextLayoutOptions {
     pub line_height: Option<StyleLineHeight>,
     pub letter_spacing: Option<StyleLetterSpacing>,
+    pub hyphens: Option<StyleHyphens>,
+    /// Base direction of the text, detected from the text itself if `None`
+    pub direction: Option<StyleDirection>,
+    pub white_space: Option<StyleWhiteSpace>,
+    pub tab_size: Option<StyleTabSize>,
+    pub text_overflow: Option<StyleTextOverflow>,
+    pub line_clamp: Option<StyleLineClamp>,
     pub horz_alignment: StyleTextAlignmentHorz,
     pub vert_alignment: StyleTextAlignmentVert,
 }
 
 /// Layout a string of text horizontally, given a font with its metrics.
+///
+/// The text is not shaped, since only the parsed font is available, see `TextFont`.
 pub fn layout_text<'a>(
     text: &str,
     font: &Font<'a>,
@@ -1060,17 +2359,102 @@ pub fn layout_text<'a>(
     //
     // This function simply lays out a text, without trying to fit it into a rectangle.
     // This function does not calculate any overflow.
-    let words = split_text_into_words(text, font, font_metrics.font_size_no_line_height, font_metrics.letter_spacing);
-    let (mut layouted_glyphs, line_breaks, min_width, min_height) =
+    let TextLayoutOptions { horz_alignment, direction, white_space, .. } = font_metrics.layout_options;
+    let words = split_text_into_words(text, &[TextFont::without_data(font)], font_metrics.font_size_no_line_height, font_metrics.letter_spacing, direction, white_space, None);
+    let (mut layouted_glyphs, index_map, line_breaks, min_width, min_height) =
         words_to_left_aligned_glyphs(&words, font, None, font_metrics);
 
-    align_text_horz(font_metrics.layout_options.horz_alignment, &mut layouted_glyphs, &line_breaks);
+    align_text_horz(horz_alignment.resolve(direction.unwrap_or_default()), &mut layouted_glyphs, &line_breaks);
 
     LayoutTextResult {
-        words, layouted_glyphs, line_breaks, min_width, min_height, font_metrics: *font_metrics,
+        words, layouted_glyphs, index_map, line_breaks, min_width, min_height, font_metrics: *font_metrics,
     }
 }
 
+/// Maps between the logical order of a text (byte offsets into the NFC-normalized text)
+/// and the layouted glyphs, which are in visual order for bidirectional text.
+/// Used to place the caret and the selection.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextIndexMap {
+    /// For each layouted glyph: the byte offset of the character it was created from (the first
+    /// character of a ligature) or `None` if the glyph isn't part of the text (ex. an inserted hyphen)
+    pub glyph_to_text: Vec<Option<usize>>,
+}
+
+impl TextIndexMap {
+
+    /// Returns the byte offset of the character that the glyph at `glyph_index` was created from
+    pub fn get_text_offset(&self, glyph_index: usize) -> Option<usize> {
+        self.glyph_to_text.get(glyph_index).and_then(|offset| *offset)
+    }
+
+    /// Returns the index of the glyph that displays the character at the byte offset `text_offset`.
+    /// Characters that are part of a ligature return the ligature glyph, characters that are not
+    /// displayed (ex. spaces) return the glyph of the nearest preceding character.
+    pub fn get_glyph_index(&self, text_offset: usize) -> Option<usize> {
+        self.glyph_to_text.iter().enumerate()
+            .filter_map(|(glyph_index, offset)| offset.map(|o| (glyph_index, o)))
+            .filter(|(_, offset)| *offset <= text_offset)
+            .max_by_key(|(_, offset)| *offset)
+            .map(|(glyph_index, _)| glyph_index)
+    }
+}
+
+/// Returns the byte offset of the next grapheme cluster boundary (UAX #29) after `offset`,
+/// i.e. where the caret should move when pressing the right arrow key
+pub fn next_grapheme_boundary(text: &str, offset: usize) -> usize {
+    use unicode_segmentation::GraphemeCursor;
+    let offset = offset.min(text.len());
+    GraphemeCursor::new(offset, text.len(), true).next_boundary(text, 0).ok().and_then(|b| b).unwrap_or(text.len())
+}
+
+/// Returns the byte offset of the previous grapheme cluster boundary (UAX #29) before `offset`,
+/// i.e. where the caret should move when pressing the left arrow key or backspace
+pub fn previous_grapheme_boundary(text: &str, offset: usize) -> usize {
+    use unicode_segmentation::GraphemeCursor;
+    let offset = offset.min(text.len());
+    GraphemeCursor::new(offset, text.len(), true).prev_boundary(text, 0).ok().and_then(|b| b).unwrap_or(0)
+}
+
+/// Returns the byte offset of the end of the next word (UAX #29) after `offset`,
+/// skipping the white space and punctuation in between (Ctrl + right arrow key)
+pub fn next_word_boundary(text: &str, offset: usize) -> usize {
+    use unicode_segmentation::UnicodeSegmentation;
+    text.split_word_bound_indices()
+        .map(|(start, word)| (start, start + word.len(), word))
+        .find(|(_, end, word)| *end > offset && is_word(word))
+        .map(|(_, end, _)| end)
+        .unwrap_or(text.len())
+}
+
+/// Returns the byte offset of the start of the previous word (UAX #29) before `offset`,
+/// skipping the white space and punctuation in between (Ctrl + left arrow key / Ctrl + backspace)
+pub fn previous_word_boundary(text: &str, offset: usize) -> usize {
+    use unicode_segmentation::UnicodeSegmentation;
+    text.split_word_bound_indices()
+        .take_while(|(start, _)| *start < offset)
+        .filter(|(_, word)| is_word(word))
+        .last()
+        .map(|(start, _)| start)
+        .unwrap_or(0)
+}
+
+/// Returns the byte range of the UAX #29 segment at `offset`, i.e. what is selected when
+/// double-clicking on the text: either a word or the white space / punctuation between words
+pub fn get_word_range(text: &str, offset: usize) -> (usize, usize) {
+    use unicode_segmentation::UnicodeSegmentation;
+    text.split_word_bound_indices()
+        .map(|(start, word)| (start, start + word.len()))
+        .find(|(_, end)| *end > offset)
+        .unwrap_or((text.len(), text.len()))
+}
+
+/// Whether a segment returned by the UAX #29 word segmentation is a word,
+/// as opposed to white space or punctuation
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(|c| c.is_alphanumeric())
+}
+
 #[test]
 fn test_it_should_add_origin() {
     let mut instances = vec![
@@ -1091,3 +2475,359 @@ fn test_it_should_add_origin() {
     assert_eq!(instances[1].point.x as usize, 33);
     assert_eq!(instances[1].point.y as usize, 10);
 }
+
+#[test]
+fn test_inline_runs_share_baseline() {
+
+    fn get_words(word_widths: &[f32]) -> Words {
+        Words {
+            items: word_widths.iter().map(|width| SemanticWordItem::Word(Word {
+                glyphs: vec![GlyphInstance { index: 0, point: TypedPoint2D::new(0.0, 0.0) }],
+                total_width: *width,
+                hyphenation_points: Vec::new(),
+                has_trailing_space: true,
+                bidi_level: 0,
+                clusters: vec![0],
+            })).collect(),
+            longest_word_width: word_widths.iter().cloned().fold(0.0, f32::max),
+            paragraph_direction: None,
+            fonts: Vec::new(),
+            white_space: None,
+        }
+    }
+
+    fn get_font_metrics(font_size: f32) -> FontMetrics {
+        FontMetrics {
+            space_width: TextSizePx(font_size / 4.0),
+            tab_width: TextSizePx(font_size),
+            hyphen_width: TextSizePx(font_size / 3.0),
+            vertical_advance: TextSizePx(font_size * 1.2),
+            ascent: TextSizePx(font_size * 0.8),
+            descent: TextSizePx(font_size * -0.2),
+            font_size_with_line_height: TextSizePx(font_size),
+            font_size_no_line_height: TextSizePx(font_size),
+            height_for_1px: 1.0,
+            letter_spacing: None,
+            layout_options: TextLayoutOptions::default(),
+        }
+    }
+
+    let small_words = get_words(&[20.0, 20.0]);
+    let large_words = get_words(&[40.0]);
+
+    let runs = [
+        InlineTextRun { words: &small_words, font_metrics: get_font_metrics(10.0), leading_space: false },
+        InlineTextRun { words: &large_words, font_metrics: get_font_metrics(20.0), leading_space: true },
+        InlineTextRun { words: &small_words, font_metrics: get_font_metrics(10.0), leading_space: false },
+    ];
+
+    // "aa aa AAAAaa" fits on the first line (20 + 2.5 + 20 + 5 + 40 + 20 = 107.5), the last "aa" wraps
+    let layout = layout_inline_runs(&runs, Some(110.0), StyleTextAlignmentHorz::Left);
+
+    // The first line is as high as the large run, so all glyphs on it sit on the baseline of the large run
+    let first_baseline = 2.0 + 16.0;
+    assert_eq!(layout.first_baseline, Some(TextSizePx(first_baseline)));
+    assert_eq!(layout.runs[0].glyphs[0].point.y, first_baseline);
+    assert_eq!(layout.runs[1].glyphs[0].point.y, first_baseline);
+    assert_eq!(layout.runs[1].glyphs[0].point.x, 47.5);
+
+    // The second word of the last run is wrapped onto the second line
+    let second_baseline = 24.0 + 1.0 + 8.0;
+    assert_eq!(layout.runs[2].glyphs[0].point, TypedPoint2D::new(87.5, first_baseline));
+    assert_eq!(layout.runs[2].glyphs[1].point, TypedPoint2D::new(0.0, second_baseline));
+    assert_eq!(layout.height, TextSizePx(24.0 + 12.0));
+
+    // The bounds of a run that spans two lines enclose both lines
+    assert_eq!(layout.runs[2].bounds, TypedRect::new(TypedPoint2D::new(0.0, first_baseline - 9.0), TypedSize2D::new(107.5, 24.0 + 12.0 - (first_baseline - 9.0))));
+    // ... but each line only covers the part of the run on that line
+    assert_eq!(layout.runs[2].line_rects, vec![
+        TypedRect::new(TypedPoint2D::new(87.5, first_baseline - 9.0), TypedSize2D::new(20.0, 12.0)),
+        TypedRect::new(TypedPoint2D::new(0.0, second_baseline - 9.0), TypedSize2D::new(20.0, 12.0)),
+    ]);
+    assert_eq!(get_line_extents(&layout.runs[2].glyphs, &[20.0, 20.0]), vec![(first_baseline, 87.5, 107.5), (second_baseline, 0.0, 20.0)]);
+
+    // Justified: the two spaces on the first line are stretched by (110 - 107.5) / 2, the last line isn't justified
+    let layout = layout_inline_runs(&runs, Some(110.0), StyleTextAlignmentHorz::Justify);
+    assert_eq!(layout.runs[0].glyphs[1].point.x, 22.5 + 1.25);
+    assert_eq!(layout.runs[1].glyphs[0].point.x, 47.5 + 2.5);
+    assert_eq!(layout.runs[2].glyphs[0].point.x, 87.5 + 2.5);
+    assert_eq!(layout.runs[2].glyphs[1].point.x, 0.0);
+    assert_eq!(layout.runs[2].line_rects[0], TypedRect::new(TypedPoint2D::new(90.0, first_baseline - 9.0), TypedSize2D::new(20.0, 12.0)));
+}
+
+#[test]
+fn test_split_text_into_words_shapes_words() {
+
+    const LATIN_FONT: &[u8] = include_bytes!("../../assets/fonts/weblysleekuil.ttf");
+    const THAI_FONT: &[u8] = include_bytes!("../../assets/fonts/KoHo-Light.ttf");
+
+    let latin = Font::from_bytes(LATIN_FONT).unwrap();
+    let thai = Font::from_bytes(THAI_FONT).unwrap();
+    let font_size = TextSizePx(20.0);
+
+    let get_word = |text: &str, font: TextFont, letter_spacing: Option<StyleLetterSpacing>| -> Word {
+        match split_text_into_words(text, &[font], font_size, letter_spacing, None, None, None).items.into_iter().next() {
+            Some(SemanticWordItem::Word(word)) => word,
+            _ => panic!("no word in text: {:?}", text),
+        }
+    };
+
+    // "AV" is a kerning pair: the glyphs move closer together than their advance widths
+    let av = get_word("AV", TextFont::new(&latin, LATIN_FONT), None);
+    let a = get_word("A", TextFont::new(&latin, LATIN_FONT), None);
+    let v = get_word("V", TextFont::new(&latin, LATIN_FONT), None);
+    assert!(av.total_width < a.total_width + v.total_width);
+    assert_eq!(av.glyphs[1].point.x, av.total_width - v.total_width);
+
+    // ... also if the font can't be shaped
+    assert_eq!(get_word("AV", TextFont::without_data(&latin), None).total_width, av.total_width);
+
+    // "fi" is replaced by the ligature glyph from the GSUB table,
+    // the glyphs stay separate with letter-spacing or at a hyphenation point
+    let fi = get_word("fi", TextFont::new(&thai, THAI_FONT), None);
+    assert_eq!(fi.glyphs.len(), 1);
+    assert_eq!(fi.clusters, vec![0]);
+    let letter_spacing = StyleLetterSpacing(::azul_css::PixelValue::px(1.0));
+    assert_eq!(get_word("fi", TextFont::new(&thai, THAI_FONT), Some(letter_spacing)).glyphs.len(), 2);
+    assert_eq!(get_word("of\u{AD}fice", TextFont::new(&thai, THAI_FONT), None).glyphs.len(), 6);
+
+    // The tone mark is placed above the vowel with the mark positioning of the GPOS table
+    let thai_word = get_word("\u{0E01}\u{0E35}\u{0E48}", TextFont::new(&thai, THAI_FONT), None);
+    assert_eq!(thai_word.glyphs.len(), 3);
+    assert_eq!(thai_word.glyphs[1].point.x, thai_word.glyphs[2].point.x);
+    assert!(thai_word.glyphs[2].point.y != thai_word.glyphs[1].point.y);
+}
+
+#[test]
+fn test_total_fit_line_breaking() {
+
+    let font = Font::from_bytes(&include_bytes!("../../assets/fonts/weblysleekuil.ttf")[..]).unwrap();
+    let font_size = StyleFontSize::px(20.0);
+    // Patterns from Liang's thesis, "hy-phen-ation"
+    let hyphenator = Hyphenator::new("hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n", "");
+    let words = split_text_into_words("the hyphenation of a hyphenation is a hyphenation", &[TextFont::without_data(&font)], TextSizePx(20.0), None, None, None, Some(&hyphenator));
+
+    let justified = TextLayoutOptions {
+        horz_alignment: StyleTextAlignmentHorz::Justify,
+        hyphens: Some(StyleHyphens::Auto),
+        .. Default::default()
+    };
+    let font_metrics = FontMetrics::new(&font, &font_size, &justified);
+    let hyphen = font.glyph('-').id().0;
+
+    let (glyphs, _, line_breaks, _, _) = words_to_left_aligned_glyphs(&words, &font, Some(150.0), &font_metrics);
+
+    // All lines except for the last line are stretched to the full width,
+    // the first two lines end with a hyphenated "hyphen-"
+    assert_eq!(line_breaks.len(), 3);
+    assert!(line_breaks[0].1.abs() < 0.01);
+    assert!(line_breaks[1].1.abs() < 0.01);
+    assert!(line_breaks[2].1 > 0.0);
+    assert_eq!(glyphs.iter().filter(|g| g.index == hyphen).count(), 2);
+
+    // The height estimation uses the same line breaks
+    let overflow = LayoutOverflow::default();
+    let vertical_info = words.get_vertical_height(&overflow, &font_metrics, TextSizePx(150.0));
+    assert_eq!(vertical_info.vertical_height, font_metrics.vertical_advance * 3.0);
+
+    // Greedy line breaking never hyphenates
+    let font_metrics = FontMetrics::new(&font, &font_size, &TextLayoutOptions::default());
+    let (glyphs, _, _, _, _) = words_to_left_aligned_glyphs(&words, &font, Some(150.0), &font_metrics);
+    assert!(glyphs.iter().all(|g| g.index != hyphen));
+
+    // ... but words with soft hyphens are broken with `hyphens: manual` (the default)
+    let words = split_text_into_words("hyphen\u{AD}ation", &[TextFont::without_data(&font)], TextSizePx(20.0), None, None, None, None);
+    let (glyphs, _, line_breaks, _, _) = words_to_left_aligned_glyphs(&words, &font, Some(60.0), &font_metrics);
+    assert_eq!(line_breaks.len(), 2);
+    assert_eq!(glyphs.iter().filter(|g| g.index == hyphen).count(), 1);
+    assert_eq!(words.get_vertical_height(&overflow, &font_metrics, TextSizePx(60.0)).vertical_height, font_metrics.vertical_advance * 2.0);
+}
+
+#[test]
+fn test_white_space_and_truncated_lines() {
+
+    let font = Font::from_bytes(&include_bytes!("../../assets/fonts/weblysleekuil.ttf")[..]).unwrap();
+    let font_size = StyleFontSize::px(20.0);
+
+    let get_items = |text: &str, white_space: Option<StyleWhiteSpace>| -> String {
+        split_text_into_words(text, &[TextFont::without_data(&font)], TextSizePx(20.0), None, None, white_space, None).items.iter().map(|item| match item {
+            SemanticWordItem::Word(w) if w.has_trailing_space => "w ",
+            SemanticWordItem::Word(_) => "w",
+            SemanticWordItem::Tab => "\t",
+            SemanticWordItem::Space => " ",
+            SemanticWordItem::Return => "\n",
+        }).collect()
+    };
+
+    assert_eq!(get_items("a  b\tc\nd", Some(StyleWhiteSpace::Normal)), "w w w w");
+    assert_eq!(get_items("a  b\tc\nd", Some(StyleWhiteSpace::PreLine)), "w w w\nw");
+    assert_eq!(get_items("  a  b\tc\nd", Some(StyleWhiteSpace::Pre)), "  w  w\tw\nw");
+    // Without `white-space`, the text is split like with `pre-line`
+    assert_eq!(get_items("a  b\tc\nd", None), "w w w\nw");
+
+    // Lines that don't wrap are cut off with an ellipsis
+    let font_metrics = FontMetrics::new(&font, &font_size, &TextLayoutOptions {
+        white_space: Some(StyleWhiteSpace::Nowrap),
+        text_overflow: Some(StyleTextOverflow::Ellipsis),
+        .. Default::default()
+    });
+    let words = split_text_into_words("a very long file name.txt", &[TextFont::without_data(&font)], TextSizePx(20.0), None, None, Some(StyleWhiteSpace::Nowrap), None);
+    let (mut glyphs, mut index_map, mut line_breaks, _, _) = words_to_left_aligned_glyphs(&words, &font, Some(100.0), &font_metrics);
+    assert!(glyphs.last().unwrap().point.x > 100.0);
+    truncate_lines(&mut glyphs, &mut index_map, &mut line_breaks, &[&font], &font_metrics, Some(100.0));
+    let advances = get_glyph_advances(&glyphs, &[&font], TextSizePx(20.0));
+    assert_eq!(line_breaks.len(), 1);
+    assert!(glyphs.iter().zip(advances.iter()).all(|(g, advance)| g.point.x + advance <= 100.0));
+    assert_eq!(index_map.glyph_to_text.last(), Some(&None));
+
+    // Only the first two lines are kept, the second line ends with an ellipsis
+    let font_metrics = FontMetrics::new(&font, &font_size, &TextLayoutOptions {
+        line_clamp: Some(StyleLineClamp::Lines(2)),
+        .. Default::default()
+    });
+    let words = split_text_into_words("one\ntwo\nthree\nfour", &[TextFont::without_data(&font)], TextSizePx(20.0), None, None, Some(StyleWhiteSpace::PreLine), None);
+    let (mut glyphs, mut index_map, mut line_breaks, _, _) = words_to_left_aligned_glyphs(&words, &font, Some(500.0), &font_metrics);
+    truncate_lines(&mut glyphs, &mut index_map, &mut line_breaks, &[&font], &font_metrics, Some(500.0));
+    assert_eq!(line_breaks.len(), 2);
+    assert_eq!(index_map.glyph_to_text.iter().filter(|offset| offset.is_some()).count(), 6);
+    assert_eq!(index_map.glyph_to_text.last(), Some(&None));
+    let vertical_info = words.get_vertical_height(&LayoutOverflow::default(), &font_metrics, TextSizePx(500.0));
+    assert_eq!(vertical_info.vertical_height, font_metrics.vertical_advance * 2.0);
+}
+
+#[test]
+fn test_labels_and_inline_runs_use_the_same_line_spacing() {
+
+    let font = Font::from_bytes(&include_bytes!("../../assets/fonts/weblysleekuil.ttf")[..]).unwrap();
+    let font_metrics = FontMetrics::new(&font, &StyleFontSize::px(20.0), &TextLayoutOptions::default());
+    let words = split_text_into_words("one\ntwo", &[TextFont::without_data(&font)], TextSizePx(20.0), None, None, Some(StyleWhiteSpace::PreLine), None);
+
+    // Both the baseline offset and the line advance are in px
+    let (glyphs, _, _, _, min_height) = words_to_left_aligned_glyphs(&words, &font, Some(500.0), &font_metrics);
+    let baseline_offset = font_metrics.get_baseline_offset().0;
+    let vertical_advance = font_metrics.vertical_advance.0;
+    assert_eq!(glyphs[0].point.y, baseline_offset);
+    assert!((glyphs[3].point.y - (baseline_offset + vertical_advance)).abs() < 0.01);
+    assert_eq!(min_height, font_metrics.vertical_advance + font_metrics.font_size_no_line_height);
+
+    let runs = [InlineTextRun { words: &words, font_metrics, leading_space: false }];
+    let layout = layout_inline_runs(&runs, Some(500.0), StyleTextAlignmentHorz::Left);
+    assert!((layout.runs[0].glyphs[0].point.y - glyphs[0].point.y).abs() < 0.01);
+    assert!((layout.runs[0].glyphs[3].point.y - glyphs[3].point.y).abs() < 0.01);
+}
+
+#[test]
+fn test_split_text_into_words_line_break_opportunities() {
+
+    let font = Font::from_bytes(&include_bytes!("../../assets/fonts/weblysleekuil.ttf")[..]).unwrap();
+
+    // (number of glyphs, has_trailing_space) of each word
+    let get_words = |text: &str| -> Vec<(usize, bool)> {
+        split_text_into_words(text, &[TextFont::without_data(&font)], TextSizePx(20.0), None, None, None, None).items.iter().filter_map(|item| match item {
+            SemanticWordItem::Word(w) => Some((w.glyphs.len(), w.has_trailing_space)),
+            _ => None,
+        }).collect()
+    };
+
+    // Hyphenated words can be broken after the hyphen
+    assert_eq!(get_words("a well-known fact"), vec![(1, true), (5, false), (5, true), (4, false)]);
+    // Every CJK ideograph is a break opportunity
+    assert_eq!(get_words("\u{6F22}\u{5B57}\u{304B}"), vec![(1, false), (1, false), (1, false)]);
+    // Non-breaking spaces don't break a word, zero-width spaces are break opportunities, but invisible
+    assert_eq!(get_words("100\u{00A0}km"), vec![(6, false)]);
+    assert_eq!(get_words("zero\u{200B}width"), vec![(4, false), (5, false)]);
+}
+
+#[test]
+fn test_grapheme_and_word_boundaries() {
+    // "g" + combining diaeresis is one grapheme cluster
+    let text = "ag\u{0308}b";
+    assert_eq!(next_grapheme_boundary(text, 1), 4);
+    assert_eq!(previous_grapheme_boundary(text, 4), 1);
+    assert_eq!(previous_grapheme_boundary(text, 0), 0);
+
+    let text = "hello, wonderful world";
+    assert_eq!(previous_word_boundary(text, text.len()), 17);
+    assert_eq!(previous_word_boundary(text, 17), 7);
+    assert_eq!(previous_word_boundary(text, 9), 7);
+    assert_eq!(next_word_boundary(text, 5), 16);
+    assert_eq!(next_word_boundary(text, 16), text.len());
+    assert_eq!(get_word_range(text, 9), (7, 16));
+    assert_eq!(get_word_range(text, 16), (16, 17));
+    assert_eq!(get_word_range(text, text.len()), (text.len(), text.len()));
+}
+
+#[test]
+fn test_bidi_reordering() {
+
+    // Rule L2: first the level 2 run is reversed, then the whole level 1 run
+    assert_eq!(reorder_by_bidi_levels(&[0, 0, 1, 1, 0]), vec![0, 1, 3, 2, 4]);
+    assert_eq!(reorder_by_bidi_levels(&[1, 1, 2, 2, 1]), vec![4, 2, 3, 1, 0]);
+
+    const FONT: &[u8] = include_bytes!("../../assets/fonts/weblysleekuil.ttf");
+    let font = Font::from_bytes(FONT).unwrap();
+    let font_metrics = FontMetrics::new(&font, &StyleFontSize::px(20.0), &TextLayoutOptions::default());
+
+    // "abc", followed by the two hebrew words "אבג" and "דהו"
+    let text = "abc \u{5D0}\u{5D1}\u{5D2} \u{5D3}\u{5D4}\u{5D5}";
+    let words = split_text_into_words(text, &[TextFont::new(&font, FONT)], TextSizePx(20.0), None, Some(StyleDirection::Ltr), None, None);
+    let (glyphs, index_map, _, _, _) = words_to_left_aligned_glyphs(&words, &font, None, &font_metrics);
+
+    // The characters of the hebrew words are in visual order
+    assert_eq!(index_map.glyph_to_text, vec![Some(0), Some(1), Some(2), Some(8), Some(6), Some(4), Some(15), Some(13), Some(11)]);
+
+    // ... and "דהו" is displayed to the left of "אבג", both after "abc"
+    assert!(glyphs[2].point.x < glyphs[6].point.x);
+    assert!(glyphs[8].point.x < glyphs[3].point.x);
+
+    // Logical -> visual: the first hebrew character is the rightmost glyph of its word
+    assert_eq!(index_map.get_glyph_index(4), Some(5));
+    assert_eq!(index_map.get_text_offset(5), Some(4));
+
+    // In a right-to-left paragraph, "abc" is displayed to the right of the hebrew words
+    let words = split_text_into_words(text, &[TextFont::new(&font, FONT)], TextSizePx(20.0), None, Some(StyleDirection::Rtl), None, None);
+    let (glyphs, _, _, _, _) = words_to_left_aligned_glyphs(&words, &font, None, &font_metrics);
+    assert!(glyphs[3].point.x < glyphs[0].point.x);
+    assert!(glyphs[8].point.x < glyphs[3].point.x);
+
+    // Brackets in right-to-left text are mirrored, with and without shaping
+    let mirrored = vec![font.glyph('(').id().0, font.glyph('\u{5D0}').id().0, font.glyph(')').id().0];
+    for text_font in &[TextFont::new(&font, FONT), TextFont::without_data(&font)] {
+        let words = split_text_into_words("(\u{5D0})", &[*text_font], TextSizePx(20.0), None, Some(StyleDirection::Rtl), None, None);
+        match &words.items[0] {
+            SemanticWordItem::Word(word) => {
+                assert_eq!(word.glyphs.iter().map(|g| g.index).collect::<Vec<_>>(), mirrored);
+                assert_eq!(word.clusters, vec![2, 1, 0]);
+            },
+            _ => panic!("no word in text: \"(\u{5D0})\""),
+        }
+    }
+}
+
+#[test]
+fn test_font_fallback() {
+
+    const LATIN_FONT: &[u8] = include_bytes!("../../assets/fonts/weblysleekuil.ttf");
+    const THAI_FONT: &[u8] = include_bytes!("../../assets/fonts/KoHo-Light.ttf");
+
+    let latin = Font::from_bytes(LATIN_FONT).unwrap();
+    let thai = Font::from_bytes(THAI_FONT).unwrap();
+    let text = "ab \u{E01}\u{E02}";
+
+    // The thai characters are missing from the first font
+    assert_eq!(get_missing_chars(text, &[&latin]), vec!['\u{E01}', '\u{E02}']);
+    assert!(get_missing_chars(text, &[&latin, &thai]).is_empty());
+
+    let font_metrics = FontMetrics::new(&latin, &StyleFontSize::px(20.0), &TextLayoutOptions::default());
+    let words = split_text_into_words(text, &[TextFont::new(&latin, LATIN_FONT), TextFont::new(&thai, THAI_FONT)], TextSizePx(20.0), None, None, None, None);
+    let (glyphs, _, _, _, _) = words_to_left_aligned_glyphs(&words, &latin, None, &font_metrics);
+
+    // One run per font, the glyph indices refer to the glyphs of the font of the run
+    let runs = split_into_font_runs(&glyphs);
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].0, 0);
+    assert_eq!(runs[0].1.iter().map(|g| g.index).collect::<Vec<_>>(), vec![latin.glyph('a').id().0, latin.glyph('b').id().0]);
+    assert_eq!(runs[1].0, 1);
+    assert_eq!(runs[1].1.iter().map(|g| g.index).collect::<Vec<_>>(), vec![thai.glyph('\u{E01}').id().0, thai.glyph('\u{E02}').id().0]);
+    assert!(runs[1].1[0].point.x > runs[0].1[1].point.x);
+}
@@ -0,0 +1,685 @@
+//! Selecting the text of `Label` and `Text` nodes with the mouse (dragging,
+//! double-click selects a word, triple-click selects a line) and copying it with Ctrl + C
+//!
+//! Only text with `user-select: text` (the default) can be selected. The glyphs of all
+//! text nodes are stored when the display list is built, so that the positions of the mouse
+//! can be mapped back to the text in the next frame (see also `FakeWindow::hit_test_text`
+//! and `FakeWindow::caret_rect`, for building text editors).
+
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+use glium::glutin::{Event, WindowEvent, ElementState, MouseButton, KeyboardInput, VirtualKeyCode};
+use webrender::api::{HitTestResult, LayoutPoint, LayoutRect, LayoutSize};
+use {
+    app_resources::AppResources,
+    dom::On,
+    id_tree::NodeId,
+    window_state::ClickCounter,
+    text_layout::{GlyphInstance, TextIndexMap, FontMetrics, get_word_range},
+    traits::Layout,
+    ui_state::UiState,
+};
+
+/// Maximum distance (in pixels) between the clicks of a double- or triple-click
+const MULTI_CLICK_DISTANCE: f32 = 4.0;
+/// Width of the rectangle returned by `LaidOutText::get_caret_rect`
+const CARET_WIDTH: f32 = 1.0;
+/// How long the caret of a text editor is visible / hidden while blinking
+const CARET_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A position in the text of a node, i.e. the byte offset into the (NFC-normalized) text.
+/// Cursors are ordered in document order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextCursor {
+    pub node_id: NodeId,
+    pub offset: usize,
+}
+
+/// The selected text of a window, may span multiple text nodes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TextSelection {
+    /// Where the selection was started, i.e. where the mouse was pressed
+    pub anchor: TextCursor,
+    /// Where the selection ends, moves while the mouse is dragged
+    pub focus: TextCursor,
+}
+
+impl TextSelection {
+
+    /// Returns the start and the end of the selection, in document order
+    pub fn get_ordered(&self) -> (TextCursor, TextCursor) {
+        if self.anchor <= self.focus {
+            (self.anchor, self.focus)
+        } else {
+            (self.focus, self.anchor)
+        }
+    }
+
+    /// Returns the selected byte range of the `text` of the node,
+    /// `None` if nothing of the text is selected
+    pub fn get_range(&self, node_id: NodeId, text: &str) -> Option<(usize, usize)> {
+        let (start, end) = self.get_ordered();
+
+        if node_id < start.node_id || node_id > end.node_id {
+            return None;
+        }
+
+        // The text may have changed since the selection was made
+        let range_start = if node_id == start.node_id { floor_char_boundary(text, start.offset) } else { 0 };
+        let range_end = if node_id == end.node_id { floor_char_boundary(text, end.offset) } else { text.len() };
+
+        if range_start < range_end { Some((range_start, range_end)) } else { None }
+    }
+}
+
+/// Which character a position in the text belongs to. Only makes a difference where a line is
+/// broken, since the end of the first line and the start of the next line are the same position
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CaretAffinity {
+    /// The position belongs to the character before it, i.e. the caret is drawn at the end of the first line
+    Upstream,
+    /// The position belongs to the character after it, i.e. the caret is drawn at the start of the next line
+    Downstream,
+}
+
+impl Default for CaretAffinity {
+    fn default() -> Self {
+        CaretAffinity::Downstream
+    }
+}
+
+/// The caret and the selected text of a text editor, drawn on top of the text of the `Label` /
+/// `Text` node it is set on (see `Dom::with_caret`). The caret blinks and is only drawn while the
+/// node or one of its parents is focused, the selection is always drawn.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TextCaret {
+    /// Byte offset of the caret in the text, see `FakeWindow::caret_rect`
+    pub offset: usize,
+    pub affinity: CaretAffinity,
+    /// Selected byte range of the text, if any
+    pub selection: Option<(usize, usize)>,
+    /// Whether the caret is drawn, `false` if the node only shows a part of the selection
+    /// (ex. the lines of a multi-line editor that don't contain the caret)
+    pub show_caret: bool,
+}
+
+impl TextCaret {
+    /// Creates a caret in front of the character at the byte `offset`, without any selected text
+    pub fn new(offset: usize) -> Self {
+        Self {
+            offset,
+            affinity: CaretAffinity::default(),
+            selection: None,
+            show_caret: true,
+        }
+    }
+
+    /// Creates a node that only shows the selected byte range `start..end`, without a caret
+    pub fn selection_only(start: usize, end: usize) -> Self {
+        Self {
+            offset: end,
+            affinity: CaretAffinity::default(),
+            selection: Some((start, end)),
+            show_caret: false,
+        }
+    }
+}
+
+/// Returns the byte offsets of the grapheme cluster boundaries in the `text` and the offsets of the
+/// same boundaries in the NFC-normalized text. The grapheme clusters are normalized separately,
+/// since NFC only combines the characters inside of a grapheme cluster.
+fn get_grapheme_boundaries(text: &str) -> Vec<(usize, usize)> {
+    use unicode_normalization::UnicodeNormalization;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let mut normalized_offset = 0;
+    let mut boundaries = vec![(0, 0)];
+    for (offset, grapheme) in text.grapheme_indices(true) {
+        normalized_offset += grapheme.nfc().map(|c| c.len_utf8()).sum::<usize>();
+        boundaries.push((offset + grapheme.len(), normalized_offset));
+    }
+    boundaries
+}
+
+/// Moves the `offset` into the `text` and back onto the start of the character it points into
+pub(crate) fn floor_char_boundary(text: &str, offset: usize) -> usize {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// The laid out text of a `Label` or `Text` node
+#[derive(Debug, Clone)]
+pub(crate) struct LaidOutText {
+    /// The NFC-normalized text, which the offsets of the `index_map` refer to
+    pub(crate) text: String,
+    /// The text of the node before it was normalized, `None` if it already was in NFC.
+    /// The public API (ex. `FakeWindow::hit_test_text`) uses offsets into this text.
+    pub(crate) original_text: Option<String>,
+    /// The positioned glyphs, in visual order
+    pub(crate) glyphs: Vec<GlyphInstance>,
+    /// Advance width of each glyph
+    pub(crate) advances: Vec<f32>,
+    pub(crate) index_map: TextIndexMap,
+    /// Origin of the rectangle of the node, the public API takes positions relative to this point
+    pub(crate) origin: LayoutPoint,
+    /// Origins of the hit-tested rectangles of the node (one per line for inline text), indexed by the
+    /// second part of the hit-test tag - hit-testing returns the position of the mouse relative to these
+    pub(crate) hit_test_origins: Vec<LayoutPoint>,
+    /// Whether the text has `user-select: text`
+    pub(crate) is_selectable: bool,
+    /// Distance from the top of a line to its baseline
+    pub(crate) baseline_offset: f32,
+    /// Height of a line (including the `line-height`)
+    pub(crate) line_height: f32,
+}
+
+impl LaidOutText {
+
+    /// Normalizes the `original_text` of the node, see `text`
+    pub(crate) fn new(
+        original_text: &str,
+        glyphs: Vec<GlyphInstance>,
+        advances: Vec<f32>,
+        index_map: TextIndexMap,
+        origin: LayoutPoint,
+        hit_test_origins: Vec<LayoutPoint>,
+        is_selectable: bool,
+        font_metrics: &FontMetrics)
+    -> Self
+    {
+        use unicode_normalization::UnicodeNormalization;
+
+        let text = original_text.nfc().collect::<String>();
+        let original_text = if text == original_text { None } else { Some(original_text.to_string()) };
+
+        Self {
+            text,
+            original_text,
+            glyphs,
+            advances,
+            index_map,
+            origin,
+            hit_test_origins,
+            is_selectable,
+            baseline_offset: font_metrics.get_baseline_offset().0,
+            line_height: font_metrics.vertical_advance.0,
+        }
+    }
+
+    /// Maps a byte offset into the normalized `text` to the same position in the `original_text`.
+    /// Offsets inside of a grapheme cluster are moved to the start of the cluster.
+    pub(crate) fn to_original_offset(&self, offset: usize) -> usize {
+        match &self.original_text {
+            Some(original_text) => get_grapheme_boundaries(original_text).iter()
+                .take_while(|(_, normalized)| *normalized <= offset)
+                .last().map(|(original, _)| *original).unwrap_or(0),
+            None => offset,
+        }
+    }
+
+    /// Maps a byte offset into the `original_text` to the same position in the normalized `text`,
+    /// the reverse of `to_original_offset`
+    pub(crate) fn from_original_offset(&self, offset: usize) -> usize {
+        match &self.original_text {
+            Some(original_text) => get_grapheme_boundaries(original_text).iter()
+                .take_while(|(original, _)| *original <= offset)
+                .last().map(|(_, normalized)| *normalized).unwrap_or(0),
+            None => offset,
+        }
+    }
+
+    /// Returns the baseline and the (visible) glyphs of each line from top to bottom,
+    /// the glyphs of each line are sorted from left to right
+    fn get_lines(&self) -> Vec<(f32, Vec<usize>)> {
+
+        let mut lines = Vec::<(f32, Vec<usize>)>::new();
+
+        for (glyph_index, glyph) in self.glyphs.iter().enumerate() {
+            // Skip the glyphs that aren't part of the text (ex. inserted hyphens)
+            if self.index_map.get_text_offset(glyph_index).is_none() {
+                continue;
+            }
+            match lines.iter_mut().find(|(baseline, _)| (*baseline - glyph.point.y).abs() < 0.5) {
+                Some(line) => line.1.push(glyph_index),
+                None => lines.push((glyph.point.y, vec![glyph_index])),
+            }
+        }
+
+        lines.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        for (_, glyphs) in lines.iter_mut() {
+            glyphs.sort_by(|a, b| self.glyphs[*a].point.x.partial_cmp(&self.glyphs[*b].point.x).unwrap_or(Ordering::Equal));
+        }
+
+        lines
+    }
+
+    /// Returns the line at the vertical position `y` (or the closest line)
+    fn get_line_at<'a>(&self, lines: &'a [(f32, Vec<usize>)], y: f32) -> Option<&'a [usize]> {
+        lines.iter()
+            .find(|(baseline, _)| y < baseline - self.baseline_offset + self.line_height)
+            .or(lines.last())
+            .map(|(_, glyphs)| &glyphs[..])
+    }
+
+    /// Returns the text offsets of all glyphs, sorted and deduplicated
+    fn get_sorted_offsets(&self) -> Vec<usize> {
+        let mut offsets = self.index_map.glyph_to_text.iter().filter_map(|o| *o).collect::<Vec<usize>>();
+        offsets.sort();
+        offsets.dedup();
+        offsets
+    }
+
+    /// Returns the byte range of the text that the glyph displays: from its own offset up to the
+    /// offset of the next character that has a glyph, without the white space in between
+    fn get_glyph_range(&self, glyph_index: usize, sorted_offsets: &[usize]) -> Option<(usize, usize)> {
+        let start = self.index_map.get_text_offset(glyph_index)?;
+        let next = match sorted_offsets.binary_search(&start) {
+            Ok(i) => sorted_offsets.get(i + 1).cloned().unwrap_or(self.text.len()),
+            Err(_) => self.text.len(),
+        };
+        Some((start, start + self.text[start..next].trim_end().len()))
+    }
+
+    /// Whether the glyph displays a right-to-left character, so that its
+    /// right half is the start of the character instead of the end
+    fn is_rtl_glyph(&self, glyph_index: usize) -> bool {
+        use unicode_bidi::{bidi_class, BidiClass};
+        self.index_map.get_text_offset(glyph_index)
+            .and_then(|offset| self.text[offset..].chars().next())
+            .map(|c| match bidi_class(c) { BidiClass::R | BidiClass::AL => true, _ => false })
+            .unwrap_or(false)
+    }
+
+    /// Returns the text offset that is closest to the `point` (relative to the `origin`)
+    pub(crate) fn get_offset_at(&self, point: LayoutPoint) -> usize {
+        self.get_position_at(point).0
+    }
+
+    /// Returns the text offset that is closest to the `point` (relative to the `origin`) and whether
+    /// the point is on the character before (`Upstream`) or after (`Downstream`) that offset
+    pub(crate) fn get_position_at(&self, point: LayoutPoint) -> (usize, CaretAffinity) {
+
+        let point = self.origin + point.to_vector();
+        let lines = self.get_lines();
+        let sorted_offsets = self.get_sorted_offsets();
+
+        let line = match self.get_line_at(&lines, point.y) {
+            Some(s) => s,
+            None => return (0, CaretAffinity::Downstream),
+        };
+
+        // The glyph that is closest to the point, so that clicking between two words or
+        // right of the last glyph selects up to the end of the previous glyph
+        let distance = |g: &usize| {
+            let x_start = self.glyphs[*g].point.x;
+            (x_start - point.x).max(point.x - (x_start + self.advances[*g])).max(0.0)
+        };
+        let glyph_index = line.iter().cloned()
+            .fold(line[0], |closest, g| if distance(&g) < distance(&closest) { g } else { closest });
+
+        let (start, end) = match self.get_glyph_range(glyph_index, &sorted_offsets) {
+            Some(s) => s,
+            None => return (0, CaretAffinity::Downstream),
+        };
+
+        let is_left_half = point.x < self.glyphs[glyph_index].point.x + self.advances[glyph_index] / 2.0;
+        if is_left_half != self.is_rtl_glyph(glyph_index) {
+            (start, CaretAffinity::Downstream)
+        } else {
+            (end, CaretAffinity::Upstream)
+        }
+    }
+
+    /// Returns the rectangle of the caret (one line high) in front of the character at the byte
+    /// `offset`, relative to the `origin`. If the offset is in white space between two glyphs, the
+    /// caret is drawn after the previous glyph.
+    pub(crate) fn get_caret_rect(&self, offset: usize, affinity: CaretAffinity) -> LayoutRect {
+
+        let sorted_offsets = self.get_sorted_offsets();
+        let ranges = (0..self.glyphs.len())
+            .filter_map(|g| self.get_glyph_range(g, &sorted_offsets).map(|(start, _)| (g, start)))
+            .collect::<Vec<(usize, usize)>>();
+
+        // The glyph of the character at the offset and the glyph of the character before it
+        let glyph_after = ranges.iter().find(|(_, start)| *start == offset).map(|(g, _)| *g);
+        let glyph_before = ranges.iter().filter(|(_, start)| *start < offset).max_by_key(|(_, start)| *start).map(|(g, _)| *g);
+
+        let is_on_other_line = |a: usize, b: usize| (self.glyphs[a].point.y - self.glyphs[b].point.y).abs() >= 0.5;
+
+        // (glyph, whether the caret is at the end of the glyph)
+        let caret_glyph = match (glyph_before, glyph_after) {
+            (Some(before), Some(after)) if affinity == CaretAffinity::Upstream && is_on_other_line(before, after) => Some((before, true)),
+            (_, Some(after)) => Some((after, false)),
+            (Some(before), None) => Some((before, true)),
+            (None, None) => None,
+        };
+
+        let (x, line_top) = match caret_glyph {
+            Some((glyph_index, is_end)) => {
+                let glyph = &self.glyphs[glyph_index];
+                let is_right_edge = is_end != self.is_rtl_glyph(glyph_index);
+                let x = if is_right_edge { glyph.point.x + self.advances[glyph_index] } else { glyph.point.x };
+                (x - self.origin.x, glyph.point.y - self.baseline_offset - self.origin.y)
+            },
+            None => (0.0, 0.0),
+        };
+
+        LayoutRect::new(LayoutPoint::new(x, line_top), LayoutSize::new(CARET_WIDTH, self.line_height))
+    }
+
+    /// Returns the byte range of the line at the `point` (relative to the `origin`)
+    pub(crate) fn get_line_range_at(&self, point: LayoutPoint) -> (usize, usize) {
+
+        let point = self.origin + point.to_vector();
+        let lines = self.get_lines();
+        let sorted_offsets = self.get_sorted_offsets();
+
+        self.get_line_at(&lines, point.y)
+            .and_then(|line| {
+                let ranges = line.iter().filter_map(|g| self.get_glyph_range(*g, &sorted_offsets)).collect::<Vec<_>>();
+                let start = ranges.iter().map(|r| r.0).min()?;
+                let end = ranges.iter().map(|r| r.1).max()?;
+                Some((start, end))
+            })
+            .unwrap_or((0, 0))
+    }
+
+    /// Returns the rectangles that have to be highlighted for the selected byte range,
+    /// one per line (in the same coordinates as the glyphs)
+    pub(crate) fn get_selection_rects(&self, start: usize, end: usize) -> Vec<LayoutRect> {
+
+        let sorted_offsets = self.get_sorted_offsets();
+
+        self.get_lines().into_iter().filter_map(|(baseline, glyphs)| {
+
+            let selected_glyphs = glyphs.into_iter().filter(|g| {
+                self.get_glyph_range(*g, &sorted_offsets).map(|(s, e)| s < end && e > start).unwrap_or(false)
+            }).collect::<Vec<usize>>();
+
+            if selected_glyphs.is_empty() {
+                return None;
+            }
+
+            let x_start = selected_glyphs.iter().map(|g| self.glyphs[*g].point.x).fold(::std::f32::MAX, f32::min);
+            let x_end = selected_glyphs.iter().map(|g| self.glyphs[*g].point.x + self.advances[*g]).fold(x_start, f32::max);
+
+            Some(LayoutRect::new(
+                LayoutPoint::new(x_start, baseline - self.baseline_offset),
+                LayoutSize::new(x_end - x_start, self.line_height)))
+        }).collect()
+    }
+}
+
+/// Keeps track of the text selection of one window
+#[derive(Debug, Default)]
+pub(crate) struct TextSelectionState {
+    /// The current selection, `None` if no text is selected
+    pub(crate) selection: Option<TextSelection>,
+    /// The laid out texts of the last frame, shared with the `FakeWindow`
+    pub(crate) laid_out_texts: Rc<BTreeMap<NodeId, LaidOutText>>,
+    /// Whether the left mouse button was pressed on a text and hasn't been released yet
+    is_dragging: bool,
+    /// Position of the last clicks, for detecting double- and triple-clicks
+    clicks: ClickCounter<LayoutPoint>,
+    /// The node and the offset of the caret of the focused text editor
+    caret: Option<(NodeId, usize)>,
+    /// When the caret was last moved, the caret blinks relative to this time
+    caret_moved: Option<Instant>,
+    /// Whether the caret was drawn in the last frame
+    caret_was_visible: bool,
+}
+
+impl TextSelectionState {
+
+    /// Updates the selection from a window event, copies the selected text to
+    /// the clipboard on Ctrl + C. Returns whether the selection has changed,
+    /// i.e. whether the window has to be redrawn.
+    ///
+    /// If the `focused_node` (ex. a text input) handles the keyboard itself, Ctrl + C is left to it.
+    pub(crate) fn handle_event<T: Layout>(
+        &mut self,
+        event: &Event,
+        hit_test_result: Option<&HitTestResult>,
+        ui_state: &UiState<T>,
+        focused_node: Option<NodeId>,
+        app_resources: &mut AppResources)
+    -> bool
+    {
+        let event = match event {
+            Event::WindowEvent { event, .. } => event,
+            _ => return false,
+        };
+
+        let previous_selection = self.selection;
+
+        match event {
+            WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, modifiers, .. } => {
+                match self.get_cursor_at(hit_test_result, ui_state) {
+                    Some((cursor, point)) => {
+                        let click_count = self.clicks.click_near(point, Instant::now(), 3, |last_point, point| {
+                            (*point - *last_point).length() < MULTI_CLICK_DISTANCE
+                        });
+                        self.is_dragging = true;
+
+                        let text = &self.laid_out_texts[&cursor.node_id];
+                        let node_id = cursor.node_id;
+
+                        self.selection = Some(match (click_count, previous_selection) {
+                            (2, _) => {
+                                let (start, end) = get_word_range(&text.text, cursor.offset);
+                                TextSelection { anchor: TextCursor { node_id, offset: start }, focus: TextCursor { node_id, offset: end } }
+                            },
+                            (3, _) => {
+                                let (start, end) = text.get_line_range_at(point - text.origin.to_vector());
+                                TextSelection { anchor: TextCursor { node_id, offset: start }, focus: TextCursor { node_id, offset: end } }
+                            },
+                            // Shift + click extends the existing selection
+                            (_, Some(selection)) if modifiers.shift => TextSelection { anchor: selection.anchor, focus: cursor },
+                            _ => TextSelection { anchor: cursor, focus: cursor },
+                        });
+                    },
+                    None => {
+                        // Clicking anywhere else clears the selection
+                        self.selection = None;
+                        self.clicks.reset();
+                    }
+                }
+            },
+            WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } => {
+                self.is_dragging = false;
+            },
+            WindowEvent::CursorMoved { .. } if self.is_dragging => {
+                // If the mouse isn't over any text, the selection stays as it is
+                if let Some((cursor, _)) = self.get_cursor_at(hit_test_result, ui_state) {
+                    if let Some(selection) = self.selection.as_mut() {
+                        selection.focus = cursor;
+                    }
+                }
+            },
+            WindowEvent::KeyboardInput { input: KeyboardInput {
+                state: ElementState::Pressed, virtual_keycode: Some(VirtualKeyCode::C), modifiers, ..
+            }, .. } if modifiers.ctrl && !focused_node.map(|node_id| ui_state.has_callback_in_ancestors(node_id, On::VirtualKeyDown)).unwrap_or(false) => {
+                if let Some(text) = self.get_selected_text() {
+                    app_resources.set_clipboard_string(text).unwrap_or(());
+                }
+            },
+            _ => { },
+        }
+
+        self.selection != previous_selection
+    }
+
+    /// Returns the position in the text under the mouse cursor (of the topmost selectable text node)
+    /// and the position of the mouse, in the same coordinates as the glyphs
+    fn get_cursor_at<T: Layout>(&self, hit_test_result: Option<&HitTestResult>, ui_state: &UiState<T>)
+    -> Option<(TextCursor, LayoutPoint)>
+    {
+        hit_test_result?.items.iter().rev().find_map(|item| {
+            let node_id = *ui_state.text_tags.get(&item.tag.0)?;
+            let text = self.laid_out_texts.get(&node_id).filter(|text| text.is_selectable)?;
+            let hit_test_origin = text.hit_test_origins.get(item.tag.1 as usize).cloned().unwrap_or(text.origin);
+            let point = hit_test_origin + item.point_relative_to_item.to_vector();
+            Some((TextCursor { node_id, offset: text.get_offset_at(point - text.origin.to_vector()) }, point))
+        })
+    }
+
+    /// Updates the caret of the focused text editor before a frame is drawn and returns whether the
+    /// caret is visible in this frame. Moving the caret restarts the blinking, so that the caret
+    /// doesn't disappear while typing.
+    pub(crate) fn update_caret(&mut self, caret: Option<(NodeId, usize)>) -> bool {
+        if caret != self.caret {
+            self.caret = caret;
+            self.caret_moved = Some(Instant::now());
+        }
+        self.caret_was_visible = self.is_caret_visible();
+        self.caret_was_visible
+    }
+
+    /// Moves the selection and the caret to the nodes of the new frame, see `Window::update_node_ids`.
+    /// The selection is removed if one of its ends doesn't exist anymore.
+    pub(crate) fn update_node_ids<F: Fn(NodeId) -> Option<NodeId>>(&mut self, get_node_id: F) {
+        self.selection = self.selection.and_then(|selection| Some(TextSelection {
+            anchor: TextCursor { node_id: get_node_id(selection.anchor.node_id)?, .. selection.anchor },
+            focus: TextCursor { node_id: get_node_id(selection.focus.node_id)?, .. selection.focus },
+        }));
+        if self.selection.is_none() {
+            self.is_dragging = false;
+        }
+        self.caret = self.caret.and_then(|(node_id, offset)| Some((get_node_id(node_id)?, offset)));
+    }
+
+    /// Whether the window has to be redrawn because the caret has to blink
+    pub(crate) fn caret_needs_redraw(&self) -> bool {
+        self.caret_was_visible != self.is_caret_visible()
+    }
+
+    fn is_caret_visible(&self) -> bool {
+        let elapsed = match (self.caret, self.caret_moved) {
+            (Some(_), Some(moved)) => moved.elapsed(),
+            _ => return false,
+        };
+        let elapsed_ms = elapsed.as_secs() * 1000 + u64::from(elapsed.subsec_millis());
+        let interval_ms = CARET_BLINK_INTERVAL.as_secs() * 1000 + u64::from(CARET_BLINK_INTERVAL.subsec_millis());
+        (elapsed_ms / interval_ms) % 2 == 0
+    }
+
+    /// Returns the selected text, the texts of multiple nodes are separated by newlines
+    pub(crate) fn get_selected_text(&self) -> Option<String> {
+        let selection = self.selection?;
+        let texts = self.laid_out_texts.iter().filter(|(_, text)| text.is_selectable).filter_map(|(node_id, text)| {
+            let (start, end) = selection.get_range(*node_id, &text.text)?;
+            Some(&text.text[start..end])
+        }).collect::<Vec<&str>>();
+
+        if texts.is_empty() { None } else { Some(texts.join("\n")) }
+    }
+}
+
+#[test]
+fn test_text_selection_range() {
+    let selection = TextSelection {
+        anchor: TextCursor { node_id: NodeId::new(3), offset: 2 },
+        focus: TextCursor { node_id: NodeId::new(1), offset: 4 },
+    };
+
+    assert_eq!(selection.get_range(NodeId::new(0), "hello"), None);
+    assert_eq!(selection.get_range(NodeId::new(1), "hello"), Some((4, 5)));
+    assert_eq!(selection.get_range(NodeId::new(2), "hello"), Some((0, 5)));
+    assert_eq!(selection.get_range(NodeId::new(3), "hello"), Some((0, 2)));
+    // The offset is not on a character boundary anymore, since the text has changed
+    assert_eq!(selection.get_range(NodeId::new(3), "\u{00E4}b"), Some((0, 2)));
+    assert_eq!(selection.get_range(NodeId::new(3), "\u{1F600}"), None);
+}
+
+#[test]
+fn test_selectable_text_offsets_and_rects() {
+    let glyph = |index, x, y| GlyphInstance { index, point: LayoutPoint::new(x, y) };
+
+    // "ab cd" on the first line, "ef" on the second line, each glyph is 10px wide
+    let text = LaidOutText {
+        text: "ab cd\nef".into(),
+        original_text: None,
+        glyphs: vec![glyph(1, 0.0, 10.0), glyph(2, 10.0, 10.0), glyph(3, 30.0, 10.0), glyph(4, 40.0, 10.0), glyph(5, 0.0, 22.0), glyph(6, 10.0, 22.0)],
+        advances: vec![10.0; 6],
+        index_map: TextIndexMap { glyph_to_text: vec![Some(0), Some(1), Some(3), Some(4), Some(6), Some(7)] },
+        origin: LayoutPoint::new(0.0, 0.0),
+        hit_test_origins: vec![LayoutPoint::new(0.0, 0.0)],
+        is_selectable: true,
+        baseline_offset: 8.0,
+        line_height: 12.0,
+    };
+
+    assert_eq!(text.get_offset_at(LayoutPoint::new(4.0, 5.0)), 0);
+    assert_eq!(text.get_offset_at(LayoutPoint::new(16.0, 5.0)), 2);
+    assert_eq!(text.get_offset_at(LayoutPoint::new(100.0, 5.0)), 5);
+    assert_eq!(text.get_offset_at(LayoutPoint::new(4.0, 20.0)), 6);
+    assert_eq!(text.get_line_range_at(LayoutPoint::new(5.0, 5.0)), (0, 5));
+
+    assert_eq!(text.get_selection_rects(1, 7), vec![
+        LayoutRect::new(LayoutPoint::new(10.0, 2.0), LayoutSize::new(40.0, 12.0)),
+        LayoutRect::new(LayoutPoint::new(0.0, 14.0), LayoutSize::new(10.0, 12.0)),
+    ]);
+}
+
+#[test]
+fn test_laid_out_text_caret_positions() {
+    let glyph = |index, x, y| GlyphInstance { index, point: LayoutPoint::new(x, y) };
+    let caret = |x, y| LayoutRect::new(LayoutPoint::new(x, y), LayoutSize::new(CARET_WIDTH, 12.0));
+
+    // "ab cd", broken into two lines after the space, each glyph is 10px wide.
+    // The node is at (100, 50), the hit-tested rectangle of the second line at (100, 62).
+    let text = LaidOutText {
+        text: "ab cd".into(),
+        original_text: None,
+        glyphs: vec![glyph(1, 100.0, 58.0), glyph(2, 110.0, 58.0), glyph(3, 100.0, 70.0), glyph(4, 110.0, 70.0)],
+        advances: vec![10.0; 4],
+        index_map: TextIndexMap { glyph_to_text: vec![Some(0), Some(1), Some(3), Some(4)] },
+        origin: LayoutPoint::new(100.0, 50.0),
+        hit_test_origins: vec![LayoutPoint::new(100.0, 50.0), LayoutPoint::new(100.0, 62.0)],
+        is_selectable: true,
+        baseline_offset: 8.0,
+        line_height: 12.0,
+    };
+
+    assert_eq!(text.get_position_at(LayoutPoint::new(4.0, 5.0)), (0, CaretAffinity::Downstream));
+    assert_eq!(text.get_position_at(LayoutPoint::new(16.0, 5.0)), (2, CaretAffinity::Upstream));
+    assert_eq!(text.get_position_at(LayoutPoint::new(4.0, 17.0)), (3, CaretAffinity::Downstream));
+
+    assert_eq!(text.get_caret_rect(0, CaretAffinity::Downstream), caret(0.0, 0.0));
+    assert_eq!(text.get_caret_rect(2, CaretAffinity::Downstream), caret(20.0, 0.0));
+    // The start of the second line is also the end of the first line
+    assert_eq!(text.get_caret_rect(3, CaretAffinity::Downstream), caret(0.0, 12.0));
+    assert_eq!(text.get_caret_rect(3, CaretAffinity::Upstream), caret(20.0, 0.0));
+    assert_eq!(text.get_caret_rect(5, CaretAffinity::Downstream), caret(20.0, 12.0));
+}
+
+#[test]
+fn test_laid_out_text_original_offsets() {
+    use azul_css::StyleFontSize;
+
+    let font = ::rusttype::Font::from_bytes(&include_bytes!("../../assets/fonts/weblysleekuil.ttf")[..]).unwrap();
+    let font_metrics = FontMetrics::new(&font, &StyleFontSize::px(20.0), &Default::default());
+    let laid_out_text = |text| LaidOutText::new(text, Vec::new(), Vec::new(), TextIndexMap { glyph_to_text: Vec::new() },
+        LayoutPoint::new(0.0, 0.0), Vec::new(), true, &font_metrics);
+
+    // "e" + combining acute accent is normalized to "\u{E9}", which is one byte shorter
+    let text = laid_out_text("ae\u{301}b");
+    assert_eq!(text.text, "a\u{E9}b");
+    assert_eq!(text.to_original_offset(1), 1);
+    assert_eq!(text.to_original_offset(3), 4);
+    assert_eq!(text.to_original_offset(4), 5);
+    assert_eq!(text.from_original_offset(4), 3);
+    // Inside of the "e" + accent cluster
+    assert_eq!(text.from_original_offset(2), 1);
+
+    let text = laid_out_text("ab");
+    assert_eq!(text.original_text, None);
+    assert_eq!(text.to_original_offset(1), 1);
+}
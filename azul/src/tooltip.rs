@@ -0,0 +1,405 @@
+//! Tooltips of the nodes (see `NodeData::tooltip`), similar to the `title` attribute in HTML
+//!
+//! A tooltip is shown when the mouse has rested on the node for the delay of the tooltip
+//! (the timing is done by a `Daemon`, so the window isn't redrawn while waiting). It is
+//! laid out outside of the window first to measure it, then drawn as an overlay near the
+//! mouse cursor and moved into the window if it would be cut off. Moving the mouse to another
+//! node, leaving the window, clicking, scrolling or pressing a key hides the tooltip.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt,
+    hash::{Hash, Hasher},
+    rc::Rc,
+    time::Duration,
+};
+use glium::glutin::{Event, WindowEvent, ElementState, KeyboardInput, dpi::LogicalSize};
+use webrender::api::{HitTestResult, LayoutRect};
+use azul_css::{CssProperty, LayoutLeft, LayoutTop, LayoutWidth, LayoutHeight};
+use {
+    app_resources::AppResources,
+    daemon::{Daemon, DaemonCallback, TerminateDaemon},
+    dom::{Dom, NodeType, NodeIdentity, UpdateScreen},
+    id_tree::{NodeId, NodeDataContainer},
+    text_selection::LaidOutText,
+    traits::Layout,
+    ui_state::UiState,
+    window_state::get_hit_test_items_above_overlay,
+};
+
+/// How long the mouse has to rest on a node before its tooltip is shown
+pub const DEFAULT_TOOLTIP_DELAY: Duration = Duration::from_millis(500);
+
+/// Distance of the tooltip from the mouse cursor (below the cursor, so that the tooltip doesn't cover it)
+const CURSOR_OFFSET: f32 = 20.0;
+/// Distance of the tooltip from the mouse cursor, if it is shown above the cursor
+const CURSOR_OFFSET_ABOVE: f32 = 4.0;
+
+/// Width of the tooltip (including the padding) while it is measured, longer lines of text are wrapped
+const TOOLTIP_MAX_WIDTH: f32 = 314.0;
+
+/// IDs of the dynamic position and size of the tooltip, see the native CSS
+const TOOLTIP_LEFT_ID: &str = "__azul-native-tooltip-left";
+const TOOLTIP_TOP_ID: &str = "__azul-native-tooltip-top";
+const TOOLTIP_WIDTH_ID: &str = "__azul-native-tooltip-width";
+const TOOLTIP_HEIGHT_ID: &str = "__azul-native-tooltip-height";
+
+/// The tooltip of a node, see `NodeData::tooltip`
+pub struct Tooltip<T: Layout> {
+    /// The content of the tooltip. The tooltip is as wide as the text and the other
+    /// leaf nodes (ex. images) of the content, up to `TOOLTIP_MAX_WIDTH`.
+    pub content: Dom<T>,
+    /// How long the mouse has to rest on the node before the tooltip is shown. Default: `DEFAULT_TOOLTIP_DELAY`
+    pub delay: Duration,
+}
+
+impl<T: Layout> Tooltip<T> {
+
+    /// Creates a tooltip that shows a text
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Self {
+            content: Dom::new(NodeType::Label(text.into())).with_class("__azul-native-tooltip-text"),
+            delay: DEFAULT_TOOLTIP_DELAY,
+        }
+    }
+
+    /// Creates a tooltip that shows any DOM
+    pub fn dom(content: Dom<T>) -> Self {
+        Self {
+            content,
+            delay: DEFAULT_TOOLTIP_DELAY,
+        }
+    }
+
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self { delay, .. self }
+    }
+}
+
+impl<T: Layout> From<String> for Tooltip<T> {
+    fn from(text: String) -> Self {
+        Tooltip::text(text)
+    }
+}
+
+impl<'a, T: Layout> From<&'a str> for Tooltip<T> {
+    fn from(text: &'a str) -> Self {
+        Tooltip::text(text)
+    }
+}
+
+// #[derive(Debug, Clone, PartialEq, Hash, Eq)] for Tooltip<T>
+
+impl<T: Layout> fmt::Debug for Tooltip<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+            "Tooltip {{ \
+                content: {:?}, \
+                delay: {:?}, \
+            }}",
+        self.content,
+        self.delay)
+    }
+}
+
+/// Copies the nodes of the content, since adding the content to another DOM
+/// moves the nodes out of the arena of the content
+impl<T: Layout> Clone for Tooltip<T> {
+    fn clone(&self) -> Self {
+        Self {
+            content: Dom {
+                arena: Rc::new(RefCell::new(self.content.arena.borrow().clone())),
+                root: self.content.root,
+                head: self.content.head,
+            },
+            delay: self.delay,
+        }
+    }
+}
+
+impl<T: Layout> PartialEq for Tooltip<T> {
+    fn eq(&self, rhs: &Self) -> bool {
+        *self.content.arena.borrow() == *rhs.content.arena.borrow() &&
+        self.content.root == rhs.content.root &&
+        self.content.head == rhs.content.head &&
+        self.delay == rhs.delay
+    }
+}
+
+impl<T: Layout> Eq for Tooltip<T> { }
+
+impl<T: Layout> Hash for Tooltip<T> {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        self.content.arena.borrow().hash(state);
+        self.content.root.hash(state);
+        self.content.head.hash(state);
+        self.delay.hash(state);
+    }
+}
+
+/// The tooltip of a window: which node is hovered and whether its tooltip is visible
+pub(crate) struct TooltipState<T: Layout> {
+    /// The node under the mouse cursor that has a tooltip, and a copy of its tooltip
+    hovered: Option<(NodeIdentity, Tooltip<T>)>,
+    /// Position of the mouse cursor, relative to the window
+    cursor_position: (f32, f32),
+    /// Position of the mouse cursor when the tooltip was shown, `None` while the tooltip is hidden
+    shown_at: Option<(f32, f32)>,
+    /// Laid out size of the visible tooltip, `None` until the tooltip has been measured
+    size: Option<(f32, f32)>,
+    /// Shows the tooltip of the `hovered` node after its delay
+    timer: Option<Daemon<TooltipState<T>>>,
+}
+
+impl<T: Layout> TooltipState<T> {
+
+    pub(crate) fn new() -> Self {
+        Self {
+            hovered: None,
+            cursor_position: (0.0, 0.0),
+            shown_at: None,
+            size: None,
+            timer: None,
+        }
+    }
+
+    /// Starts the timer when the mouse moves onto a node with a tooltip and hides the tooltip
+    /// when the mouse moves to another node, leaves the window or a button or key is pressed.
+    /// Returns whether the tooltip was hidden, i.e. whether the window has to be redrawn.
+    pub(crate) fn handle_event(&mut self, event: &Event, hit_test_result: Option<&HitTestResult>, ui_state: &UiState<T>) -> bool {
+
+        let event = match event {
+            Event::WindowEvent { event, .. } => event,
+            _ => return false,
+        };
+
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = (position.x as f32, position.y as f32);
+
+                let hovered_node = get_hovered_tooltip_node(hit_test_result, ui_state);
+                if hovered_node.as_ref().map(|(identity, _)| identity) == self.hovered.as_ref().map(|(identity, _)| identity) {
+                    return false;
+                }
+
+                // Only copy the tooltip (and the nodes of its content) when another node is hovered
+                let hovered = hovered_node.and_then(|(identity, node_id)| {
+                    let tooltip = ui_state.dom.arena.borrow().node_data[node_id].tooltip.clone()?;
+                    Some((identity, tooltip))
+                });
+
+                let was_visible = self.hide();
+                self.timer = hovered.as_ref().map(|(_, tooltip)| {
+                    Daemon::unique(DaemonCallback(show_tooltip::<T>)).with_delay(tooltip.delay)
+                });
+                self.hovered = hovered;
+                was_visible
+            },
+            WindowEvent::CursorLeft { .. } | WindowEvent::Focused(false) => {
+                self.hovered = None;
+                self.hide()
+            },
+            // The tooltip isn't shown again until the mouse moves to another node
+            WindowEvent::MouseInput { state: ElementState::Pressed, .. } |
+            WindowEvent::MouseWheel { .. } |
+            WindowEvent::KeyboardInput { input: KeyboardInput { state: ElementState::Pressed, .. }, .. } => {
+                self.hide()
+            },
+            _ => false,
+        }
+    }
+
+    /// Runs the timer of the tooltip, returns `UpdateScreen::Redraw` once the tooltip has to be shown
+    pub(crate) fn run_timer(&mut self, app_resources: &mut AppResources) -> UpdateScreen {
+
+        let mut timer = match self.timer.take() {
+            Some(timer) => timer,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let (should_update, should_terminate) = timer.invoke_callback_with_data(self, app_resources);
+
+        if should_terminate == TerminateDaemon::Continue {
+            self.timer = Some(timer);
+        }
+
+        should_update
+    }
+
+    /// Returns the DOM of the visible tooltip, which is added to the DOM of the window (as its last child).
+    /// Until the tooltip has been measured, it is laid out outside of the window with an automatic height.
+    pub(crate) fn get_dom(&self, window_size: LogicalSize) -> Option<Dom<T>> {
+
+        let (_, tooltip) = self.hovered.as_ref()?;
+        let cursor = self.shown_at?;
+
+        let dom = Dom::new(NodeType::Div)
+            .with_class("__azul-native-tooltip")
+            .with_key("__azul-native-tooltip")
+            .with_overlay(true);
+
+        let dom = match self.size {
+            Some(size) => {
+                let (left, top) = get_tooltip_position(cursor, size, (window_size.width as f32, window_size.height as f32));
+                dom.with_style_override(TOOLTIP_LEFT_ID, CssProperty::Left(LayoutLeft::px(left)))
+                   .with_style_override(TOOLTIP_TOP_ID, CssProperty::Top(LayoutTop::px(top)))
+                   .with_style_override(TOOLTIP_WIDTH_ID, CssProperty::Width(LayoutWidth::px(size.0)))
+                   .with_style_override(TOOLTIP_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(size.1)))
+            },
+            None => {
+                dom.with_style_override(TOOLTIP_LEFT_ID, CssProperty::Left(LayoutLeft::px(-TOOLTIP_MAX_WIDTH)))
+                   .with_style_override(TOOLTIP_WIDTH_ID, CssProperty::Width(LayoutWidth::px(TOOLTIP_MAX_WIDTH)))
+            },
+        };
+
+        Some(dom.with_child(tooltip.clone().content))
+    }
+
+    /// Measures the visible tooltip after it has been laid out outside of the window, returns whether the
+    /// window has to be drawn again to show the tooltip at its position, now that its size is known
+    pub(crate) fn measure(&mut self, ui_state: &UiState<T>, laid_out_rects: &NodeDataContainer<LayoutRect>, laid_out_texts: &BTreeMap<NodeId, LaidOutText>) -> bool {
+
+        if self.shown_at.is_none() || self.size.is_some() {
+            return false;
+        }
+
+        let arena = ui_state.dom.arena.borrow();
+        let tooltip = match arena.node_layout.get(NodeId::new(0)).and_then(|root| root.last_child) {
+            Some(tooltip) => tooltip,
+            None => return false,
+        };
+        let tooltip_rect = match laid_out_rects.get(tooltip) {
+            Some(rect) => *rect,
+            None => return false,
+        };
+
+        // The containers of the content are stretched to the width of the tooltip, so only the
+        // text and the other leaf nodes determine how wide the content is
+        let content_right = tooltip.descendants(&arena.node_layout).skip(1).filter_map(|node_id| {
+            match arena.node_data[node_id].node_type {
+                NodeType::Label(_) | NodeType::Text(_) => laid_out_texts.get(&node_id).and_then(get_text_right),
+                _ if arena.node_layout[node_id].first_child.is_none() => laid_out_rects.get(node_id).map(|rect| rect.origin.x + rect.size.width),
+                _ => None,
+            }
+        }).fold(tooltip_rect.origin.x, f32::max);
+
+        // The root of the content fills the content box of the tooltip, the rest of the
+        // tooltip to the right of it is the right padding and border
+        let padding_right = arena.node_layout[tooltip].first_child
+            .and_then(|content| laid_out_rects.get(content))
+            .map(|content_rect| (tooltip_rect.origin.x + tooltip_rect.size.width) - (content_rect.origin.x + content_rect.size.width))
+            .unwrap_or(0.0);
+
+        let width = (content_right - tooltip_rect.origin.x + padding_right).min(tooltip_rect.size.width);
+        self.size = Some((width.ceil(), tooltip_rect.size.height));
+        true
+    }
+
+    /// Hides the tooltip and stops the timer, returns whether the tooltip was visible
+    fn hide(&mut self) -> bool {
+        self.timer = None;
+        self.size = None;
+        self.shown_at.take().is_some()
+    }
+}
+
+impl<T: Layout> fmt::Debug for TooltipState<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+            "TooltipState {{ \
+                hovered: {:?}, \
+                cursor_position: {:?}, \
+                shown_at: {:?}, \
+                size: {:?}, \
+                timer: {:?}, \
+            }}",
+        self.hovered,
+        self.cursor_position,
+        self.shown_at,
+        self.size,
+        self.timer)
+    }
+}
+
+/// Callback of the timer of the tooltip, runs once after the delay of the tooltip
+fn show_tooltip<T: Layout>(state: &mut TooltipState<T>, _: &mut AppResources) -> (UpdateScreen, TerminateDaemon) {
+    state.shown_at = Some(state.cursor_position);
+    (UpdateScreen::Redraw, TerminateDaemon::Terminate)
+}
+
+/// Returns the topmost node under the mouse cursor that has a tooltip. Nodes that are
+/// covered by an overlay (ex. the backdrop of a modal dialog) don't show their tooltip.
+fn get_hovered_tooltip_node<T: Layout>(hit_test_result: Option<&HitTestResult>, ui_state: &UiState<T>) -> Option<(NodeIdentity, NodeId)> {
+    let hit_test_result = get_hit_test_items_above_overlay(hit_test_result?, ui_state);
+    let arena = ui_state.dom.arena.borrow();
+    hit_test_result.items.iter().rev().find_map(|item| {
+        let node_id = *ui_state.tooltip_tags.get(&item.tag.0)?;
+        if arena.node_data[node_id].tooltip.is_none() {
+            return None;
+        }
+        Some((ui_state.node_identities[node_id], node_id))
+    })
+}
+
+/// Returns the top left corner of the tooltip: below the cursor, or above the cursor if the tooltip
+/// would be cut off at the bottom of the window, and moved to the left if it would be cut off at the right
+fn get_tooltip_position(cursor: (f32, f32), size: (f32, f32), window_size: (f32, f32)) -> (f32, f32) {
+    let (x, y) = cursor;
+    let left = x.min(window_size.0 - size.0).max(0.0);
+    let top = if y + CURSOR_OFFSET + size.1 > window_size.1 {
+        y - CURSOR_OFFSET_ABOVE - size.1
+    } else {
+        y + CURSOR_OFFSET
+    };
+    (left, top.max(0.0))
+}
+
+/// Returns the right edge of the rightmost glyph of the text, `None` if the text is empty
+fn get_text_right(text: &LaidOutText) -> Option<f32> {
+    text.glyphs.iter().zip(text.advances.iter())
+        .map(|(glyph, advance)| glyph.point.x + advance)
+        .fold(None, |right, glyph_right| Some(right.unwrap_or(glyph_right).max(glyph_right)))
+}
+
+#[test]
+fn test_tooltip_position_and_size() {
+    // Below the cursor, moved to the left at the right edge of the window
+    assert_eq!(get_tooltip_position((10.0, 10.0), (100.0, 30.0), (800.0, 600.0)), (10.0, 30.0));
+    assert_eq!(get_tooltip_position((750.0, 10.0), (100.0, 30.0), (800.0, 600.0)), (700.0, 30.0));
+    // Above the cursor at the bottom of the window
+    assert_eq!(get_tooltip_position((10.0, 590.0), (100.0, 30.0), (800.0, 600.0)), (10.0, 556.0));
+
+    use webrender::api::{LayoutPoint, LayoutSize};
+    use test_support::Mock;
+
+    // root
+    //     |-> node 1: the content of the window
+    //     '-> node 2: the tooltip, laid out outside of the window
+    //         '-> node 3: a container of the content
+    //             '-> node 4: a 100px wide leaf node
+    let mut state = TooltipState::<Mock>::new();
+    state.hovered = Some((NodeIdentity::default(), Tooltip::dom(Dom::new(NodeType::Div))));
+    state.shown_at = Some((10.0, 10.0));
+    let ui_state = Dom::<Mock>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div))
+        .with_child(Dom::new(NodeType::Div).with_child(Dom::new(NodeType::Div).with_child(Dom::new(NodeType::Div))))
+        .into_ui_state();
+    let rect = |x, width, height| LayoutRect::new(LayoutPoint::new(x, 0.0), LayoutSize::new(width, height));
+    let laid_out_rects = NodeDataContainer::new(vec![
+        rect(0.0, 800.0, 600.0),
+        rect(0.0, 800.0, 600.0),
+        rect(-TOOLTIP_MAX_WIDTH, TOOLTIP_MAX_WIDTH, 40.0),
+        rect(-TOOLTIP_MAX_WIDTH + 7.0, TOOLTIP_MAX_WIDTH - 14.0, 30.0),
+        rect(-TOOLTIP_MAX_WIDTH + 7.0, 100.0, 30.0),
+    ]);
+
+    // The tooltip is as wide as its content, its height is laid out. It is only measured once.
+    assert!(state.measure(&ui_state, &laid_out_rects, &BTreeMap::new()));
+    assert_eq!(state.size, Some((114.0, 40.0)));
+    assert!(!state.measure(&ui_state, &laid_out_rects, &BTreeMap::new()));
+
+    // Hiding the tooltip measures it again the next time it is shown
+    state.hide();
+    assert_eq!(state.size, None);
+}
@@ -4,6 +4,7 @@ use azul_css::{
     LayoutPosition,
     LayoutMargin,
     LayoutPadding,
+    LayoutAlignItems,
     RectLayout,
 };
 use {
@@ -58,18 +59,33 @@ impl WhConstraint {
 }
 
 macro_rules! determine_preferred {
-    ($fn_name:ident, $width:ident, $min_width:ident, $max_width:ident) => (
+    ($fn_name:ident, $width:ident, $min_width:ident, $max_width:ident, $left:ident, $right:ident) => (
 
     /// - `preferred_inner_width` denotes the preferred width of the width or height got from the
     /// from the rectangles content.
     ///
     /// For example, if you have an image, the `preferred_inner_width` is the images width,
     /// if the node type is an text, the `preferred_inner_width` is the text height.
+    ///
+    /// The returned constraint always refers to the content size of the rectangle (without the
+    /// padding), so for `box-sizing: border-box`, the padding is subtracted from the `width`.
     fn $fn_name(layout: &RectLayout, preferred_inner_width: Option<f32>) -> WhConstraint {
 
-        let mut width = layout.$width.and_then(|w| Some(w.0.to_pixels()));
-        let min_width = layout.$min_width.and_then(|w| Some(w.0.to_pixels()));
-        let max_width = layout.$max_width.and_then(|w| Some(w.0.to_pixels()));
+        use azul_css::LayoutBoxSizing;
+
+        let padding = layout.padding.unwrap_or_default();
+        let padding = padding.$left.and_then(|px| Some(px.to_pixels())).unwrap_or(0.0) +
+                      padding.$right.and_then(|px| Some(px.to_pixels())).unwrap_or(0.0);
+
+        // With `box-sizing: border-box`, the padding is included in the width
+        let border_box_padding = match layout.box_sizing.unwrap_or_default() {
+            LayoutBoxSizing::ContentBox => 0.0,
+            LayoutBoxSizing::BorderBox => padding,
+        };
+
+        let mut width = layout.$width.and_then(|w| Some((w.0.to_pixels() - border_box_padding).max(0.0)));
+        let min_width = layout.$min_width.and_then(|w| Some((w.0.to_pixels() - border_box_padding).max(0.0)));
+        let max_width = layout.$max_width.and_then(|w| Some((w.0.to_pixels() - border_box_padding).max(0.0)));
 
         // TODO: correct for width / height less than 0 - "negative" width is impossible!
 
@@ -146,13 +162,13 @@ macro_rules! determine_preferred {
 /// or `None` if the height can't be determined from the node alone.
 ///
 // fn determine_preferred_width(layout: &RectLayout) -> Option<f32>
-determine_preferred!(determine_preferred_width, width, min_width, max_width);
+determine_preferred!(determine_preferred_width, width, min_width, max_width, left, right);
 
 /// Returns the preferred height, given [height, min_height, max_height] inside a RectLayout
 // or `None` if the height can't be determined from the node alone.
 ///
 // fn determine_preferred_height(layout: &RectLayout) -> Option<f32>
-determine_preferred!(determine_preferred_height, height, min_height, max_height);
+determine_preferred!(determine_preferred_height, height, min_height, max_height, top, bottom);
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 struct WidthCalculatedRect {
@@ -526,6 +542,8 @@ impl NodeDataContainer<$struct_name> {
             width_calculated_arena: &mut NodeDataContainer<$struct_name>,
             positioned_node_stack: &[NodeId])
         {
+            use azul_css::LayoutAxis;
+
             // The inner space of the parent node, without the padding
             let parent_node_inner_width = {
                 let parent_node = &width_calculated_arena[*node_id];
@@ -547,7 +565,19 @@ impl NodeDataContainer<$struct_name> {
                     last_relative_node_width
                 };
 
-                let preferred_width = {
+                // Only stretched items fill the cross axis, the other items keep the size of their
+                // content. Baselines can only be aligned in rows, in columns they are stretched.
+                let keeps_content_size = match get_align_self(&arena_data[*node_id], &arena_data[child_id]) {
+                    LayoutAlignItems::Stretch => false,
+                    LayoutAlignItems::Baseline => LayoutAxis::$main_axis == LayoutAxis::Vertical,
+                    LayoutAlignItems::Center | LayoutAlignItems::Start | LayoutAlignItems::End => true,
+                };
+
+                let preferred_width = if keeps_content_size {
+                    let child_node = &width_calculated_arena[child_id];
+                    child_node.$preferred_field.min_needed_space()
+                        .unwrap_or(child_node.min_inner_size_px + child_node.$get_padding_fn())
+                } else {
                     let min_width = width_calculated_arena[child_id].$preferred_field.min_needed_space().unwrap_or(0.0);
                     // In this case we want to overflow if the min width of the cross axis
                     if min_width > parent_node_inner_width {
@@ -651,6 +681,12 @@ typed_arena!(
     Vertical
 );
 
+/// Returns the alignment of the child on the cross axis, i.e. the `align-self` of the
+/// child or the `align-items` of the parent if the child has no `align-self`
+fn get_align_self(parent: &RectLayout, child: &RectLayout) -> LayoutAlignItems {
+    child.align_self.unwrap_or_default().resolve(parent.align_items.unwrap_or_default())
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub(crate) struct WidthSolvedResult {
     pub min_width: f32,
@@ -876,15 +912,19 @@ fn $fn_name(
     }
 
     fn determine_child_x_along_cross_axis(
+        cross_axis_alignment: LayoutAlignItems,
         arena_data: &NodeDataContainer<RectLayout>,
         solved_widths: &$width_layout,
         child_id: NodeId,
         positioned_node_stack: &[NodeId],
         arena_solved_data: &mut NodeDataContainer<$height_solved_position>,
-        parent_x_position: f32)
+        parent_x_position: f32,
+        parent_inner_width: f32)
     {
         let child_node = &arena_data[child_id];
-        let child_margin_left = child_node.margin.unwrap_or_default().$left.and_then(|x| Some(x.to_pixels())).unwrap_or(0.0);
+        let child_margin = child_node.margin.unwrap_or_default();
+        let child_margin_left = child_margin.$left.and_then(|x| Some(x.to_pixels())).unwrap_or(0.0);
+        let child_margin_right = child_margin.$right.and_then(|x| Some(x.to_pixels())).unwrap_or(0.0);
 
         if child_node.position.unwrap_or_default() == LayoutPosition::Absolute {
             determine_child_x_absolute(
@@ -895,7 +935,21 @@ fn $fn_name(
                 solved_widths
             );
         } else {
-            arena_solved_data[child_id].0 = parent_x_position + child_margin_left;
+            let child_width_with_padding = {
+                let child_node = &solved_widths.$solved_widths_field[child_id];
+                child_node.$min_width + child_node.space_added
+            };
+
+            // Stretched items already fill the parent, baseline-aligned
+            // items are moved later (see `align_baselines`)
+            let remaining_space = parent_inner_width - (child_margin_left + child_width_with_padding + child_margin_right);
+            let alignment_offset = match cross_axis_alignment {
+                LayoutAlignItems::Center => remaining_space / 2.0,
+                LayoutAlignItems::End => remaining_space,
+                LayoutAlignItems::Stretch | LayoutAlignItems::Start | LayoutAlignItems::Baseline => 0.0,
+            };
+
+            arena_solved_data[child_id].0 = parent_x_position + child_margin_left + alignment_offset;
         }
     }
 
@@ -980,23 +1034,27 @@ fn $fn_name(
             if parent_direction.is_reverse() {
                 for child_id in parent_id.reverse_children(node_hierarchy) {
                     determine_child_x_along_cross_axis(
+                        get_align_self(&parent_node, &node_data[child_id]),
                         node_data,
                         solved_widths,
                         child_id,
                         &positioned_node_stack,
                         &mut arena_solved_data,
                         parent_x_position,
+                        parent_inner_width,
                     );
                 }
             } else {
                 for child_id in parent_id.children(node_hierarchy) {
                     determine_child_x_along_cross_axis(
+                        get_align_self(&parent_node, &node_data[child_id]),
                         node_data,
                         solved_widths,
                         child_id,
                         &positioned_node_stack,
                         &mut arena_solved_data,
                         parent_x_position,
+                        parent_inner_width,
                     );
                 }
             }
@@ -1032,16 +1090,21 @@ pub(crate) fn get_x_positions(
     arena
 }
 
+/// - `baselines`: Distance from the top of each node to the baseline of its first line of text,
+///   `None` if the node doesn't contain any text
 pub(crate) fn get_y_positions(
     solved_heights: &SolvedHeightLayout,
     solved_widths: &SolvedWidthLayout,
     node_hierarchy: &NodeHierarchy,
+    baselines: &NodeDataContainer<Option<f32>>,
     origin: LogicalPosition)
 -> NodeDataContainer<VerticalSolvedPosition>
 {
     get_position!(get_pos_y, SolvedHeightLayout, VerticalSolvedPosition, solved_heights, min_height, top, bottom, Vertical);
     let mut arena = get_pos_y(node_hierarchy, &solved_widths.layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, solved_heights);
 
+    align_baselines(&mut arena, node_hierarchy, &solved_widths.layout_only_arena, &solved_widths.non_leaf_nodes_sorted_by_depth, solved_heights, baselines);
+
     // Add the origin on top of the position
     let y = origin.y as f32;
     if y > 0.5  || y < -0.5 {
@@ -1052,6 +1115,52 @@ pub(crate) fn get_y_positions(
     arena
 }
 
+/// Moves the baseline-aligned children of horizontal (row) parents so that
+/// their baselines line up. Children without a baseline (i.e. children that don't
+/// contain any text) use the bottom edge of their rectangle as the baseline.
+fn align_baselines(
+    arena: &mut NodeDataContainer<VerticalSolvedPosition>,
+    node_hierarchy: &NodeHierarchy,
+    node_data: &NodeDataContainer<RectLayout>,
+    non_leaf_nodes: &[(usize, NodeId)],
+    solved_heights: &SolvedHeightLayout,
+    baselines: &NodeDataContainer<Option<f32>>)
+{
+    use azul_css::LayoutAxis;
+
+    // Parents are sorted by depth, so the children are moved before their own children get aligned
+    for (_node_depth, parent_id) in non_leaf_nodes {
+
+        let parent_node = &node_data[*parent_id];
+        if parent_node.direction.unwrap_or_default().get_axis() != LayoutAxis::Horizontal {
+            continue;
+        }
+
+        // (child, distance from the top margin edge of the child to its baseline)
+        let baseline_aligned_children = parent_id.children(node_hierarchy)
+            .filter(|child_id| node_data[*child_id].position.unwrap_or_default() != LayoutPosition::Absolute)
+            .filter(|child_id| get_align_self(parent_node, &node_data[*child_id]) == LayoutAlignItems::Baseline)
+            .map(|child_id| {
+                let margin_top = node_data[child_id].margin.unwrap_or_default().top.and_then(|x| Some(x.to_pixels())).unwrap_or(0.0);
+                let baseline = baselines[child_id].unwrap_or(solved_heights.solved_heights[child_id].total());
+                (child_id, margin_top + baseline)
+            })
+            .collect::<Vec<_>>();
+
+        let max_baseline = baseline_aligned_children.iter().fold(0.0_f32, |max, (_, baseline)| max.max(*baseline));
+
+        for (child_id, baseline) in baseline_aligned_children {
+            let offset = max_baseline - baseline;
+            if offset == 0.0 {
+                continue;
+            }
+            for descendant_id in child_id.descendants(node_hierarchy) {
+                arena[descendant_id].0 += offset;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod layout_tests {
 
@@ -1345,4 +1454,176 @@ mod layout_tests {
             space_added: window_width - 200.0,
         });
     }
+
+    /// Tests that `box-sizing: content-box` adds the padding on top of the
+    /// `width`, while `box-sizing: border-box` includes it (the constraint
+    /// is the size of the content, the padding is added in the flex basis)
+    #[test]
+    fn test_determine_preferred_width_box_sizing() {
+        use azul_css::{LayoutMinWidth, LayoutBoxSizing, PixelValue, LayoutWidth};
+
+        let padding = Some(LayoutPadding {
+            left: Some(PixelValue::px(10.0)),
+            right: Some(PixelValue::px(20.0)),
+            .. Default::default()
+        });
+
+        let layout = RectLayout {
+            width: Some(LayoutWidth(PixelValue::px(500.0))),
+            padding,
+            .. Default::default()
+        };
+        assert_eq!(determine_preferred_width(&layout, None), WhConstraint::EqualTo(500.0));
+
+        let layout = RectLayout {
+            width: Some(LayoutWidth(PixelValue::px(500.0))),
+            padding,
+            box_sizing: Some(LayoutBoxSizing::BorderBox),
+            .. Default::default()
+        };
+        assert_eq!(determine_preferred_width(&layout, None), WhConstraint::EqualTo(470.0));
+
+        // The preferred width of the content (i.e. the width of an image) never includes the padding
+        let layout = RectLayout {
+            min_width: Some(LayoutMinWidth(PixelValue::px(100.0))),
+            padding,
+            box_sizing: Some(LayoutBoxSizing::BorderBox),
+            .. Default::default()
+        };
+        assert_eq!(determine_preferred_width(&layout, Some(200.0)), WhConstraint::EqualTo(200.0));
+
+        // The padding can't be larger than the border-box width
+        let layout = RectLayout {
+            width: Some(LayoutWidth(PixelValue::px(20.0))),
+            padding,
+            box_sizing: Some(LayoutBoxSizing::BorderBox),
+            .. Default::default()
+        };
+        assert_eq!(determine_preferred_width(&layout, None), WhConstraint::EqualTo(0.0));
+    }
+
+    /// Same layout as `test_fill_out_preferred_width`, but with `box-sizing: border-box`:
+    /// the `max-width` includes the padding, so the flex basis stays the same
+    #[test]
+    fn test_fill_out_preferred_width_border_box() {
+
+        use azul_css::*;
+
+        let (_, node_data) = get_display_rectangle_arena(&[
+            (0, RectLayout {
+                direction: Some(LayoutDirection::Row),
+                .. Default::default()
+            }),
+            (1, RectLayout {
+                max_width: Some(LayoutMaxWidth(PixelValue::px(200.0))),
+                padding: Some(LayoutPadding { left: Some(PixelValue::px(20.0)), right: Some(PixelValue::px(20.0)), .. Default::default() }),
+                box_sizing: Some(LayoutBoxSizing::BorderBox),
+                direction: Some(LayoutDirection::Row),
+                .. Default::default()
+            }),
+        ]);
+
+        let preferred_widths = node_data.transform(|_, _| None);
+        let width_filled_out_data = NodeDataContainer::<WidthCalculatedRect>::from_rect_layout_arena(&node_data, preferred_widths);
+
+        assert_eq!(width_filled_out_data[NodeId::new(1)].get_flex_basis_horizontal(), 40.0);
+        assert_eq!(width_filled_out_data[NodeId::new(1)].preferred_width, WhConstraint::Between(0.0, 160.0));
+    }
+
+    /// Tests that `align-items: baseline` doesn't stretch the children and
+    /// moves them so that their baselines line up
+    #[test]
+    fn test_align_items_baseline() {
+
+        use azul_css::*;
+
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[
+            (2, RectLayout {
+                direction: Some(LayoutDirection::Row),
+                align_items: Some(LayoutAlignItems::Baseline),
+                .. Default::default()
+            }),
+            (4, RectLayout {
+                margin: Some(LayoutMargin { top: Some(PixelValue::px(5.0)), .. Default::default() }),
+                .. Default::default()
+            }),
+        ]);
+
+        // Node 3 and 4 contain text with a height of 20px and 40px
+        let mut preferred_heights = node_data.transform(|_, _| None);
+        preferred_heights[NodeId::new(3)] = Some(20.0);
+        preferred_heights[NodeId::new(4)] = Some(40.0);
+
+        let non_leaf_nodes_sorted_by_depth = get_non_leaf_nodes_sorted_by_depth(&node_hierarchy);
+        let mut height_filled_out_data = NodeDataContainer::<HeightCalculatedRect>::from_rect_layout_arena(&node_data, preferred_heights);
+        height_filled_out_data.bubble_preferred_heights_to_parents(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth);
+        height_filled_out_data.apply_flex_grow(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth, 400.0);
+
+        let solved_heights = SolvedHeightLayout { solved_heights: height_filled_out_data.transform(|node, _| node.solved_result()) };
+
+        // The children of the row aren't stretched to the height of node 2
+        assert!(solved_heights.solved_heights[NodeId::new(2)].total() > 45.0);
+        assert_eq!(solved_heights.solved_heights[NodeId::new(3)].total(), 20.0);
+        assert_eq!(solved_heights.solved_heights[NodeId::new(4)].total(), 40.0);
+
+        let mut baselines = node_data.transform(|_, _| None);
+        baselines[NodeId::new(3)] = Some(15.0);
+        baselines[NodeId::new(4)] = Some(30.0);
+
+        let mut y_positions = node_data.transform(|_, _| VerticalSolvedPosition(0.0));
+        y_positions[NodeId::new(4)] = VerticalSolvedPosition(5.0);
+
+        align_baselines(&mut y_positions, &node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth, &solved_heights, &baselines);
+
+        // The baseline of node 4 is 35px below the top of node 2 (including the margin),
+        // so node 3 has to be moved down by 35px - 15px
+        assert_eq!(y_positions[NodeId::new(3)], VerticalSolvedPosition(20.0));
+        assert_eq!(y_positions[NodeId::new(4)], VerticalSolvedPosition(5.0));
+        assert_eq!(y_positions[NodeId::new(5)], VerticalSolvedPosition(0.0));
+    }
+
+    /// Tests that `align-self: center / end` items keep their width and are
+    /// moved to the center / end of the cross axis of their (column) parent
+    #[test]
+    fn test_align_self() {
+
+        use azul_css::*;
+
+        let (node_hierarchy, node_data) = get_display_rectangle_arena(&[
+            (2, RectLayout {
+                width: Some(LayoutWidth(PixelValue::px(100.0))),
+                align_self: Some(LayoutAlignSelf::Center),
+                align_items: Some(LayoutAlignItems::Start),
+                .. Default::default()
+            }),
+            (5, RectLayout {
+                width: Some(LayoutWidth(PixelValue::px(50.0))),
+                align_self: Some(LayoutAlignSelf::End),
+                .. Default::default()
+            }),
+        ]);
+
+        let non_leaf_nodes_sorted_by_depth = get_non_leaf_nodes_sorted_by_depth(&node_hierarchy);
+        let mut width_filled_out_data = NodeDataContainer::<WidthCalculatedRect>::from_rect_layout_arena(&node_data, node_data.transform(|_, _| None));
+        width_filled_out_data.bubble_preferred_widths_to_parents(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth);
+        width_filled_out_data.apply_flex_grow(&node_hierarchy, &node_data, &non_leaf_nodes_sorted_by_depth, 400.0);
+
+        let solved_widths = SolvedWidthLayout {
+            solved_widths: width_filled_out_data.transform(|node, _| node.solved_result()),
+            layout_only_arena: node_data.clone(),
+            non_leaf_nodes_sorted_by_depth,
+        };
+
+        assert_eq!(solved_widths.solved_widths[NodeId::new(1)].total(), 400.0);
+        assert_eq!(solved_widths.solved_widths[NodeId::new(2)].total(), 100.0);
+        assert_eq!(solved_widths.solved_widths[NodeId::new(5)].total(), 50.0);
+        // Node 3 is aligned to the start of node 2, so it isn't stretched to the width of node 2
+        assert_eq!(solved_widths.solved_widths[NodeId::new(3)].total(), 0.0);
+
+        let x_positions = get_x_positions(&solved_widths, &node_hierarchy, LogicalPosition::new(0.0, 0.0));
+        assert_eq!(x_positions[NodeId::new(1)], HorizontalSolvedPosition(0.0));
+        assert_eq!(x_positions[NodeId::new(2)], HorizontalSolvedPosition(150.0));
+        assert_eq!(x_positions[NodeId::new(3)], HorizontalSolvedPosition(150.0));
+        assert_eq!(x_positions[NodeId::new(5)], HorizontalSolvedPosition(350.0));
+    }
 }
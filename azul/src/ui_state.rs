@@ -1,17 +1,18 @@
 use std::{
     fmt,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
 };
 use azul_css::CssProperty;
 use {
     FastHashMap,
     window::{WindowInfo, WindowId},
     traits::Layout,
-    dom::{Callback, Dom, On, TabIndex},
+    dom::{Callback, Dom, On, TabIndex, NodeIdentity},
     app_state::AppState,
-    id_tree::NodeId,
+    id_tree::{NodeId, NodeDataContainer},
     dom::TagId,
     default_callbacks::DefaultCallbackId,
+    tooltip::TooltipState,
 };
 
 pub struct UiState<T: Layout> {
@@ -22,8 +23,23 @@ pub struct UiState<T: Layout> {
     pub tag_ids_to_node_ids: BTreeMap<TagId, NodeId>,
     pub tab_index_tags: BTreeMap<TagId, (NodeId, TabIndex)>,
     pub draggable_tags: BTreeMap<TagId, NodeId>,
+    /// Tags of the nodes that capture the pointer, see `NodeData::captures_pointer`
+    pub pointer_capture_tags: BTreeMap<TagId, NodeId>,
+    /// Tags of the overlays, see `NodeData::is_overlay`
+    pub overlay_tags: BTreeMap<TagId, NodeId>,
+    /// Tags of the `Label` and `Text` nodes, so that their text can be selected with the mouse
+    pub text_tags: BTreeMap<TagId, NodeId>,
+    /// The tags of `text_tags` that aren't needed for anything else than selecting the text,
+    /// they aren't pushed to the display list if the text has `user-select: none`
+    pub text_only_tags: BTreeSet<TagId>,
+    /// Tags of the nodes with a tooltip, see `NodeData::tooltip`
+    pub tooltip_tags: BTreeMap<TagId, NodeId>,
+    /// The node that keeps the focus inside of it, see `NodeData::traps_focus`
+    pub focus_trap: Option<NodeId>,
     /// The style properties that should be overridden for this frame, cloned from the `Css`
     pub dynamic_style_overrides: BTreeMap<NodeId, FastHashMap<String, CssProperty>>,
+    /// Identities of the nodes, for finding the nodes of this frame in the next frame, see `NodeData::key`
+    pub node_identities: NodeDataContainer<NodeIdentity>,
 }
 
 impl<T: Layout> fmt::Debug for UiState<T> {
@@ -35,6 +51,12 @@ impl<T: Layout> fmt::Debug for UiState<T> {
                 \ttag_ids_to_default_callbacks: {:?}, \
                 \ttab_index_tags: {:?}, \
                 \tdraggable_tags: {:?}, \
+                \tpointer_capture_tags: {:?}, \
+                \toverlay_tags: {:?}, \
+                \ttext_tags: {:?}, \
+                \ttext_only_tags: {:?}, \
+                \ttooltip_tags: {:?}, \
+                \tfocus_trap: {:?}, \
                 \tnode_ids_to_tag_ids: {:?} \
                 \ttag_ids_to_node_ids: {:?} \
             }}",
@@ -43,6 +65,12 @@ impl<T: Layout> fmt::Debug for UiState<T> {
             self.tag_ids_to_default_callbacks,
             self.tab_index_tags,
             self.draggable_tags,
+            self.pointer_capture_tags,
+            self.overlay_tags,
+            self.text_tags,
+            self.text_only_tags,
+            self.tooltip_tags,
+            self.focus_trap,
             self.node_ids_to_tag_ids,
             self.tag_ids_to_node_ids
         )
@@ -51,18 +79,20 @@ impl<T: Layout> fmt::Debug for UiState<T> {
 
 impl<T: Layout> UiState<T> {
     #[allow(unused_imports, unused_variables)]
-    pub(crate) fn from_app_state(app_state: &mut AppState<T>, window_id: WindowId) -> Self
+    pub(crate) fn from_app_state(app_state: &mut AppState<T>, window_id: WindowId, tooltip: &TooltipState<T>) -> Self
     {
         use dom::{Dom, On, NodeType};
         use std::sync::atomic::Ordering;
 
+        let tooltip_dom = tooltip.get_dom(app_state.windows[window_id.id].state.size.dimensions);
+
         let window_info = WindowInfo {
             window: &mut app_state.windows[window_id.id],
             resources: &app_state.resources,
         };
 
         // Only shortly lock the data to get the dom out
-        let dom: Dom<T> = {
+        let mut dom: Dom<T> = {
             let dom_lock = app_state.data.lock().unwrap();
             #[cfg(test)]{
                 Dom::<T>::new(NodeType::Div)
@@ -73,6 +103,11 @@ impl<T: Layout> UiState<T> {
             }
         };
 
+        // The visible tooltip is drawn on top of the DOM of the window, see `NodeData::tooltip`
+        if let Some(tooltip_dom) = tooltip_dom {
+            dom.add_child(tooltip_dom);
+        }
+
         Self::from_dom(dom)
     }
 
@@ -80,6 +115,29 @@ impl<T: Layout> UiState<T> {
     pub(crate) fn from_dom(dom: Dom<T>) -> Self {
         dom.into_ui_state()
     }
+
+    /// Returns a function that maps a node of the `previous_frame` to the node of this frame
+    /// with the same `NodeIdentity`, or to `None` if the node doesn't exist anymore
+    pub(crate) fn get_node_id_mapping<'a>(&self, previous_frame: &'a UiState<T>) -> impl Fn(NodeId) -> Option<NodeId> + 'a {
+        let node_ids_by_identity = self.node_identities.linear_iter()
+            .map(|node_id| (self.node_identities[node_id], node_id))
+            .collect::<FastHashMap<NodeIdentity, NodeId>>();
+        move |node_id| previous_frame.node_identities.get(node_id).and_then(|identity| node_ids_by_identity.get(identity)).cloned()
+    }
+
+    /// Returns whether the node or one of its parents has a callback or a default callback
+    /// for the `event`, i.e. whether the event is handled if the node is focused
+    pub(crate) fn has_callback_in_ancestors(&self, node_id: NodeId, event: On) -> bool {
+        let arena = self.dom.arena.borrow();
+        if node_id.index() >= arena.len() {
+            return false;
+        }
+        node_id.ancestors(&arena.node_layout).any(|node_id| match self.node_ids_to_tag_ids.get(&node_id) {
+            Some(tag_id) => self.tag_ids_to_callbacks.get(tag_id).map(|c| c.contains_key(&event)).unwrap_or(false)
+                         || self.tag_ids_to_default_callbacks.get(tag_id).map(|c| c.contains_key(&event)).unwrap_or(false),
+            None => false,
+        })
+    }
 }
 
 // Empty test, for some reason codecov doesn't detect any files (and therefore
@@ -0,0 +1,116 @@
+//! Checkbox, bound to a `bool` (demonstrates two-way data binding)
+
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen, TabIndex},
+    window::{FakeWindow, WindowEvent},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback, DefaultCallbackId},
+    app_state::AppStateNoData,
+};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Checkbox {
+    label: Option<String>,
+    callbacks: Option<ToggleCallbacks>,
+}
+
+/// Callbacks that toggle a `bool` on click and with the Space key, shared with the `Switch`
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct ToggleCallbacks {
+    mouse_up: DefaultCallbackId,
+    virtual_key_down: DefaultCallbackId,
+}
+
+impl ToggleCallbacks {
+
+    pub(crate) fn new<T: Layout>(window: &mut FakeWindow<T>, field: &bool, data: &T) -> Option<Self> {
+        let ptr = StackCheckedPointer::new(data, field)?;
+        Some(Self {
+            mouse_up: window.add_callback(ptr, DefaultCallback(on_mouse_up_private)),
+            virtual_key_down: window.add_callback(ptr, DefaultCallback(on_virtual_key_down_private)),
+        })
+    }
+
+    /// Captures the pointer, so that releasing the mouse only toggles if it was pressed on the same node
+    pub(crate) fn add_to<T: Layout>(&self, dom: &mut Dom<T>) {
+        dom.set_pointer_capture(true);
+        dom.add_default_callback_id(On::LeftMouseUp, self.mouse_up);
+        dom.add_default_callback_id(On::VirtualKeyDown, self.virtual_key_down);
+    }
+}
+
+impl Checkbox {
+
+    pub fn new() -> Self {
+        Self { label: None, callbacks: None }
+    }
+
+    /// Text next to the checkbox, clicking on it toggles the checkbox, too
+    pub fn with_label<S: Into<String>>(self, label: S) -> Self {
+        Self { label: Some(label.into()), .. self }
+    }
+
+    pub fn bind<T: Layout>(self, window: &mut FakeWindow<T>, field: &bool, data: &T) -> Self {
+        Self { callbacks: ToggleCallbacks::new(window, field, data), .. self }
+    }
+
+    pub fn dom<T: Layout>(&self, field: &bool) -> Dom<T> {
+
+        // div.__azul-native-checkbox
+        //     |-> div.__azul-native-checkbox-box (.__azul-native-checkbox-box-checked)
+        //     |   '-> p.__azul-native-checkbox-mark (only if checked)
+        //     '-> p.__azul-native-checkbox-label
+
+        let mut checkbox = Dom::new(NodeType::Div)
+            .with_class("__azul-native-checkbox")
+            .with_tab_index(TabIndex::Auto);
+
+        if let Some(callbacks) = self.callbacks {
+            callbacks.add_to(&mut checkbox);
+        }
+
+        let mut check_box = Dom::new(NodeType::Div).with_class("__azul-native-checkbox-box");
+
+        if *field {
+            check_box.add_class("__azul-native-checkbox-box-checked");
+            check_box.add_child(Dom::new(NodeType::Label("\u{2713}".into())).with_class("__azul-native-checkbox-mark"));
+        }
+
+        checkbox.add_child(check_box);
+
+        if let Some(label) = &self.label {
+            checkbox.add_child(Dom::new(NodeType::Label(label.clone())).with_class("__azul-native-checkbox-label"));
+        }
+
+        checkbox
+    }
+}
+
+fn on_mouse_up_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+    unsafe { data.invoke_mut(on_mouse_up, app_state_no_data, window_event) }
+}
+
+fn on_virtual_key_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+    unsafe { data.invoke_mut(on_virtual_key_down, app_state_no_data, window_event) }
+}
+
+/// Toggles if the mouse was pressed and released on the checkbox / switch, not when dragging on or off it
+fn on_mouse_up<T: Layout>(value: &mut bool, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+    if !event.has_pointer_capture() || !event.is_hovered() {
+        return UpdateScreen::DontRedraw;
+    }
+    *value = !*value;
+    UpdateScreen::Redraw
+}
+
+/// Space toggles the focused checkbox / switch
+fn on_virtual_key_down<T: Layout>(value: &mut bool, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+    match app_state_no_data.windows[event.window].get_keyboard_state().latest_virtual_keycode {
+        Some(VirtualKeyCode::Space) => {
+            *value = !*value;
+            UpdateScreen::Redraw
+        },
+        _ => UpdateScreen::DontRedraw,
+    }
+}
@@ -0,0 +1,633 @@
+//! Dropdown for choosing one of a list of options, optionally with an editable text
+//! field that filters the options (a combo box)
+//!
+//! The list of options is drawn as an overlay (see `Dom::with_overlay`), on top of all
+//! other content. It opens upwards if there isn't enough space below the dropdown.
+
+use std::time::{Duration, Instant};
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen, TabIndex},
+    window::{FakeWindow, WindowEvent},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback, DefaultCallbackId},
+    app_state::AppStateNoData,
+    id_tree::NodeId,
+    widgets::text_input::TextInputState,
+};
+
+/// Default number of options that are visible at once, the list scrolls if there are more options
+pub const DEFAULT_MAX_VISIBLE_OPTIONS: usize = 8;
+/// Typed characters within this time are joined into one search string for the type-ahead search
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Dropdown {
+    callbacks: Option<DropdownCallbacks>,
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+struct DropdownCallbacks {
+    field_mouse_down: DefaultCallbackId,
+    option_mouse_over: DefaultCallbackId,
+    option_mouse_up: DefaultCallbackId,
+    popup_scroll: DefaultCallbackId,
+    virtual_key_down: DefaultCallbackId,
+    text_input: DefaultCallbackId,
+    focus_lost: DefaultCallbackId,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DropdownState {
+    /// The options that can be chosen
+    pub options: Vec<String>,
+    /// Index of the chosen option
+    pub selected: Option<usize>,
+    /// Whether the list of options is currently shown
+    pub is_open: bool,
+    /// Index of the option that is highlighted in the list, i.e. chosen by Return
+    pub highlighted: Option<usize>,
+    /// The editable text of a combo box, `None` if the dropdown isn't editable
+    pub editor: Option<TextInputState>,
+    /// How many options are visible at once. Default: `DEFAULT_MAX_VISIBLE_OPTIONS`
+    pub max_visible_options: usize,
+    /// Index of the first visible option in `get_visible_options()`
+    scroll_offset: usize,
+    /// Whether the list is shown above the dropdown, decided when the list is opened
+    opens_upward: bool,
+    /// Whether the options are filtered by the text of the combo box, i.e. the text was edited
+    is_filtering: bool,
+    /// Characters typed for the type-ahead search and the time of the last typed character
+    type_ahead: String,
+    last_type_ahead: Option<Instant>,
+}
+
+impl Default for DropdownState {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl DropdownState {
+
+    /// Creates a closed dropdown with the `options`, nothing is selected
+    pub fn new(options: Vec<String>) -> Self {
+        Self {
+            options,
+            selected: None,
+            is_open: false,
+            highlighted: None,
+            editor: None,
+            max_visible_options: DEFAULT_MAX_VISIBLE_OPTIONS,
+            scroll_offset: 0,
+            opens_upward: false,
+            is_filtering: false,
+            type_ahead: String::new(),
+            last_type_ahead: None,
+        }
+    }
+
+    pub fn with_selected(mut self, index: usize) -> Self {
+        self.select(index);
+        self
+    }
+
+    /// Turns the dropdown into a combo box, where the text can be edited to filter the
+    /// options. The typed text doesn't have to match an option.
+    pub fn with_editable(self, editable: bool) -> Self {
+        let editor = if editable {
+            Some(TextInputState::new(self.get_selected_option().unwrap_or("")))
+        } else {
+            None
+        };
+        Self { editor, .. self }
+    }
+
+    pub fn with_max_visible_options(self, max_visible_options: usize) -> Self {
+        Self { max_visible_options: max_visible_options.max(1), .. self }
+    }
+
+    /// Returns the text of the chosen option
+    pub fn get_selected_option(&self) -> Option<&str> {
+        self.options.get(self.selected?).map(|option| option.as_str())
+    }
+
+    /// Chooses the option at the `index` and closes the list. Returns whether the index is valid.
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.options.len() {
+            return false;
+        }
+        self.selected = Some(index);
+        if let Some(editor) = &mut self.editor {
+            editor.set_text(self.options[index].clone());
+        }
+        self.close();
+        true
+    }
+
+    /// Shows the list of options, with the chosen option highlighted
+    pub fn open(&mut self) {
+        self.is_open = true;
+        let visible_options = self.get_visible_options();
+        self.highlighted = self.selected
+            .filter(|selected| visible_options.contains(selected))
+            .or(visible_options.first().cloned());
+        self.scroll_to_highlighted();
+    }
+
+    /// Hides the list of options, without changing the chosen option
+    pub fn close(&mut self) {
+        self.is_open = false;
+        self.is_filtering = false;
+        self.highlighted = None;
+        self.scroll_offset = 0;
+    }
+
+    /// Returns the indices of the options that are shown in the list: all options,
+    /// or only the ones that contain the edited text of the combo box (ignoring the case)
+    pub fn get_visible_options(&self) -> Vec<usize> {
+        let filter = match &self.editor {
+            Some(editor) if self.is_filtering => editor.text.to_lowercase(),
+            _ => String::new(),
+        };
+        (0..self.options.len())
+            .filter(|index| filter.is_empty() || self.options[*index].to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    /// Moves the highlight by `delta` options in the list (negative = upwards)
+    fn move_highlight(&mut self, delta: isize) {
+        let visible_options = self.get_visible_options();
+        if visible_options.is_empty() {
+            return;
+        }
+        let current = self.highlighted.and_then(|highlighted| visible_options.iter().position(|index| *index == highlighted));
+        let new_position = match current {
+            Some(position) => (position as isize + delta).max(0).min(visible_options.len() as isize - 1) as usize,
+            None if delta < 0 => visible_options.len() - 1,
+            None => 0,
+        };
+        self.highlighted = Some(visible_options[new_position]);
+        self.scroll_to_highlighted();
+    }
+
+    /// Scrolls the list so that the highlighted option is visible
+    fn scroll_to_highlighted(&mut self) {
+        let visible_options = self.get_visible_options();
+        if let Some(position) = self.highlighted.and_then(|highlighted| visible_options.iter().position(|index| *index == highlighted)) {
+            if position < self.scroll_offset {
+                self.scroll_offset = position;
+            } else if position >= self.scroll_offset + self.max_visible_options {
+                self.scroll_offset = position + 1 - self.max_visible_options;
+            }
+        }
+        self.scroll_offset = self.scroll_offset.min(visible_options.len().saturating_sub(self.max_visible_options));
+    }
+
+    /// Whether the last character of the type-ahead search was typed just now
+    fn is_type_ahead_active(&self, now: Instant) -> bool {
+        self.last_type_ahead.map(|last| now.duration_since(last) < TYPE_AHEAD_TIMEOUT).unwrap_or(false)
+    }
+
+    /// Adds the typed character to the type-ahead search and highlights (or, if the list is closed,
+    /// chooses) the first option that starts with the typed text. Returns whether an option was found.
+    fn type_ahead(&mut self, c: char, now: Instant) -> bool {
+
+        if !self.is_type_ahead_active(now) {
+            self.type_ahead.clear();
+        }
+        self.type_ahead.extend(c.to_lowercase());
+        self.last_type_ahead = Some(now);
+
+        // Search from the current option on, so that typing the same letter repeatedly cycles through the options
+        let start = self.highlighted.or(self.selected).unwrap_or(0);
+        let is_repeated_letter = self.type_ahead.chars().all(|typed| Some(typed) == self.type_ahead.chars().next());
+        let search = if is_repeated_letter { self.type_ahead.chars().take(1).collect() } else { self.type_ahead.clone() };
+        let skip_current = is_repeated_letter && self.type_ahead.chars().count() > 1;
+
+        let option_count = self.options.len();
+        let found = (0..option_count)
+            .map(|offset| (start + offset + if skip_current { 1 } else { 0 }) % option_count)
+            .find(|index| self.options[*index].to_lowercase().starts_with(&search));
+
+        match found {
+            Some(index) if self.is_open => {
+                self.highlighted = Some(index);
+                self.scroll_to_highlighted();
+                true
+            },
+            Some(index) => self.select(index),
+            None => false,
+        }
+    }
+
+    /// Decides whether the list opens upwards, if there isn't enough space below the `field_rect`
+    /// (the height of an option is assumed to be the height of the dropdown)
+    fn update_popup_direction<T: Layout>(&mut self, event: &WindowEvent<T>) {
+        let (field_rect, window_rect) = match (event.get_node_rect(event.hit_dom_node), event.get_node_rect(NodeId::new(0))) {
+            (Some(field_rect), Some(window_rect)) => (field_rect, window_rect),
+            _ => return,
+        };
+        let popup_height = self.get_visible_options().len().min(self.max_visible_options) as f32 * field_rect.size.height;
+        let space_above = field_rect.origin.y - window_rect.origin.y;
+        let space_below = (window_rect.origin.y + window_rect.size.height) - (field_rect.origin.y + field_rect.size.height);
+        self.opens_upward = space_below < popup_height && space_above > space_below;
+    }
+
+    /// Returns the option under the mouse, the options are the children of the list
+    fn get_hovered_option<T: Layout>(&self, event: &WindowEvent<T>) -> Option<usize> {
+        let (position, _) = event.get_index_in_parent(event.hit_dom_node)?;
+        self.get_visible_options().get(self.scroll_offset + position).cloned()
+    }
+}
+
+impl Dropdown {
+
+    pub fn new() -> Self {
+        Self { callbacks: None }
+    }
+
+    pub fn bind<T: Layout>(self, window: &mut FakeWindow<T>, field: &DropdownState, data: &T) -> Self {
+        let ptr = StackCheckedPointer::new(data, field);
+        let callbacks = ptr.and_then(|ptr| {
+            Some(DropdownCallbacks {
+                field_mouse_down: window.add_callback(ptr, DefaultCallback(DropdownState::on_field_mouse_down_private)),
+                option_mouse_over: window.add_callback(ptr, DefaultCallback(DropdownState::on_option_mouse_over_private)),
+                option_mouse_up: window.add_callback(ptr, DefaultCallback(DropdownState::on_option_mouse_up_private)),
+                popup_scroll: window.add_callback(ptr, DefaultCallback(DropdownState::on_popup_scroll_private)),
+                virtual_key_down: window.add_callback(ptr, DefaultCallback(DropdownState::on_virtual_key_down_private)),
+                text_input: window.add_callback(ptr, DefaultCallback(DropdownState::on_text_input_private)),
+                focus_lost: window.add_callback(ptr, DefaultCallback(DropdownState::on_focus_lost_private)),
+            })
+        });
+
+        Self { callbacks }
+    }
+
+    pub fn dom<T: Layout>(&self, field: &DropdownState) -> Dom<T> {
+
+        // div.__azul-native-dropdown
+        //     |-> div.__azul-native-dropdown-anchor (only if the list opens upwards)
+        //     |-> div.__azul-native-dropdown-field (.__azul-native-dropdown-field-open)
+        //     |   |-> p.__azul-native-dropdown-label (or p.__azul-native-dropdown-input in a combo box)
+        //     |   '-> div.__azul-native-dropdown-arrow
+        //     '-> div.__azul-native-dropdown-anchor (only if the list opens downwards)
+        //         '-> div.__azul-native-dropdown-popup (.__azul-native-dropdown-popup-above)
+        //             '-> p.__azul-native-dropdown-option (.__azul-native-dropdown-option-selected,
+        //                                                  .__azul-native-dropdown-option-highlighted)
+        //
+        // The anchor is a zero-height, relatively positioned div, the popup is positioned
+        // absolutely below (`top: 0px`) or above (`bottom: 0px`) the anchor.
+
+        let mut dropdown = Dom::new(NodeType::Div)
+            .with_class("__azul-native-dropdown")
+            .with_tab_index(TabIndex::Auto);
+
+        if let Some(callbacks) = self.callbacks {
+            dropdown.add_default_callback_id(On::VirtualKeyDown, callbacks.virtual_key_down);
+            dropdown.add_default_callback_id(On::TextInput, callbacks.text_input);
+            dropdown.add_default_callback_id(On::FocusLost, callbacks.focus_lost);
+        }
+
+        let mut field_dom = Dom::new(NodeType::Div).with_class("__azul-native-dropdown-field");
+        if field.is_open {
+            field_dom.add_class("__azul-native-dropdown-field-open");
+        }
+
+        let mut arrow = Dom::new(NodeType::Div)
+            .with_class("__azul-native-dropdown-arrow")
+            .with_child(Dom::new(NodeType::Label("\u{25BE}".into())));
+
+        match &field.editor {
+            Some(editor) => {
+                // Only the arrow opens the list of a combo box, so that the text can be clicked
+                if let Some(callbacks) = self.callbacks {
+                    arrow.add_default_callback_id(On::LeftMouseDown, callbacks.field_mouse_down);
+                }
+                field_dom.add_child(Dom::new(NodeType::Label(editor.text.clone()))
+                    .with_class("__azul-native-dropdown-input")
+                    .with_caret(editor.get_caret()));
+            },
+            None => {
+                if let Some(callbacks) = self.callbacks {
+                    field_dom.add_default_callback_id(On::LeftMouseDown, callbacks.field_mouse_down);
+                }
+                field_dom.add_child(Dom::new(NodeType::Label(field.get_selected_option().unwrap_or("").into()))
+                    .with_class("__azul-native-dropdown-label"));
+            },
+        }
+
+        field_dom.add_child(arrow);
+
+        let popup = if field.is_open { self.popup_dom(field) } else { None };
+
+        match popup {
+            Some(popup) if field.opens_upward => {
+                dropdown.add_child(Dom::new(NodeType::Div).with_class("__azul-native-dropdown-anchor").with_child(popup));
+                dropdown.add_child(field_dom);
+            },
+            Some(popup) => {
+                dropdown.add_child(field_dom);
+                dropdown.add_child(Dom::new(NodeType::Div).with_class("__azul-native-dropdown-anchor").with_child(popup));
+            },
+            None => {
+                dropdown.add_child(field_dom);
+            }
+        }
+
+        dropdown
+    }
+
+    /// Returns the list of the visible options, `None` if no option matches the text of the combo box
+    fn popup_dom<T: Layout>(&self, field: &DropdownState) -> Option<Dom<T>> {
+
+        let visible_options = field.get_visible_options();
+        if visible_options.is_empty() {
+            return None;
+        }
+
+        let mut popup = Dom::new(NodeType::Div)
+            .with_class("__azul-native-dropdown-popup")
+            .with_overlay(true);
+
+        if field.opens_upward {
+            popup.add_class("__azul-native-dropdown-popup-above");
+        }
+
+        if let Some(callbacks) = self.callbacks {
+            popup.add_default_callback_id(On::Scroll, callbacks.popup_scroll);
+        }
+
+        for index in visible_options.into_iter().skip(field.scroll_offset).take(field.max_visible_options) {
+
+            let mut option = Dom::new(NodeType::Label(field.options[index].clone()))
+                .with_class("__azul-native-dropdown-option");
+
+            if field.selected == Some(index) {
+                option.add_class("__azul-native-dropdown-option-selected");
+            }
+            if field.highlighted == Some(index) {
+                option.add_class("__azul-native-dropdown-option-highlighted");
+            }
+            if let Some(callbacks) = self.callbacks {
+                option.add_default_callback_id(On::MouseOver, callbacks.option_mouse_over);
+                option.add_default_callback_id(On::LeftMouseUp, callbacks.option_mouse_up);
+            }
+
+            popup.add_child(option);
+        }
+
+        Some(popup)
+    }
+}
+
+impl DropdownState {
+
+    fn on_field_mouse_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_field_mouse_down, app_state_no_data, window_event) }
+    }
+
+    fn on_option_mouse_over_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_option_mouse_over, app_state_no_data, window_event) }
+    }
+
+    fn on_option_mouse_up_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_option_mouse_up, app_state_no_data, window_event) }
+    }
+
+    fn on_popup_scroll_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_popup_scroll, app_state_no_data, window_event) }
+    }
+
+    fn on_virtual_key_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_virtual_key_down, app_state_no_data, window_event) }
+    }
+
+    fn on_text_input_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_text_input, app_state_no_data, window_event) }
+    }
+
+    fn on_focus_lost_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_focus_lost, app_state_no_data, window_event) }
+    }
+
+    /// Opens or closes the list
+    pub fn on_field_mouse_down<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        if self.is_open {
+            self.close();
+        } else {
+            self.update_popup_direction(&event);
+            self.open();
+        }
+        UpdateScreen::Redraw
+    }
+
+    /// Highlights the option under the mouse
+    pub fn on_option_mouse_over<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        match self.get_hovered_option(&event) {
+            Some(index) if self.highlighted != Some(index) => {
+                self.highlighted = Some(index);
+                UpdateScreen::Redraw
+            },
+            _ => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Chooses the clicked option and closes the list
+    pub fn on_option_mouse_up<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        match self.get_hovered_option(&event) {
+            Some(index) => {
+                self.select(index);
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Scrolls the list by whole options, at least one option per scroll event
+    pub fn on_popup_scroll<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let scroll_y = app_state_no_data.windows[event.window].get_mouse_state().scroll_y as f32;
+        let visible_option_count = self.get_visible_options().len();
+        let max_scroll_offset = visible_option_count.saturating_sub(self.max_visible_options);
+        if scroll_y == 0.0 || max_scroll_offset == 0 {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let option_height = event.get_node_rect(event.hit_dom_node)
+            .map(|rect| rect.size.height / visible_option_count.min(self.max_visible_options) as f32)
+            .filter(|height| *height > 0.0)
+            .unwrap_or(1.0);
+
+        let options = (scroll_y.abs() / option_height).ceil() as usize;
+        let new_scroll_offset = if scroll_y > 0.0 {
+            (self.scroll_offset + options).min(max_scroll_offset)
+        } else {
+            self.scroll_offset.saturating_sub(options)
+        };
+
+        if new_scroll_offset == self.scroll_offset {
+            UpdateScreen::DontRedraw
+        } else {
+            self.scroll_offset = new_scroll_offset;
+            UpdateScreen::Redraw
+        }
+    }
+
+    /// Up / Down / PageUp / PageDown move the highlight in the open list, Return chooses the
+    /// highlighted option, Escape closes the list. Up / Down (or Space / Return in a dropdown
+    /// that isn't editable) open the closed list. All other keys edit the text of a combo box.
+    pub fn on_virtual_key_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (virtual_keycode, shift_down, ctrl_down) = {
+            let keyboard_state = app_state_no_data.windows[event.window].get_keyboard_state();
+            (keyboard_state.latest_virtual_keycode, keyboard_state.shift_down, keyboard_state.ctrl_down)
+        };
+
+        let key = match virtual_keycode {
+            Some(key) => key,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let is_editable = self.editor.is_some();
+        let page = self.max_visible_options as isize;
+
+        if self.is_open {
+            match key {
+                VirtualKeyCode::Up => { self.move_highlight(-1); return UpdateScreen::Redraw; },
+                VirtualKeyCode::Down => { self.move_highlight(1); return UpdateScreen::Redraw; },
+                VirtualKeyCode::PageUp => { self.move_highlight(-page); return UpdateScreen::Redraw; },
+                VirtualKeyCode::PageDown => { self.move_highlight(page); return UpdateScreen::Redraw; },
+                VirtualKeyCode::Home if !is_editable => { self.move_highlight(-(self.options.len() as isize)); return UpdateScreen::Redraw; },
+                VirtualKeyCode::End if !is_editable => { self.move_highlight(self.options.len() as isize); return UpdateScreen::Redraw; },
+                VirtualKeyCode::Return => {
+                    match self.highlighted {
+                        Some(highlighted) => { self.select(highlighted); },
+                        None => self.close(),
+                    }
+                    return UpdateScreen::Redraw;
+                },
+                VirtualKeyCode::Escape | VirtualKeyCode::Tab => {
+                    self.close();
+                    return UpdateScreen::Redraw;
+                },
+                _ => { },
+            }
+        } else {
+            let opens_list = match key {
+                VirtualKeyCode::Up | VirtualKeyCode::Down => true,
+                // While typing ahead, Space is a part of the search text
+                VirtualKeyCode::Space => !is_editable && !self.is_type_ahead_active(Instant::now()),
+                VirtualKeyCode::Return => !is_editable,
+                _ => false,
+            };
+            if opens_list {
+                self.update_popup_direction(&event);
+                self.open();
+                return UpdateScreen::Redraw;
+            }
+        }
+
+        let text_changed = match &mut self.editor {
+            Some(editor) => {
+                let text_before = editor.text.clone();
+                if editor.handle_virtual_key(key, shift_down, ctrl_down, app_state_no_data.resources) == UpdateScreen::DontRedraw {
+                    return UpdateScreen::DontRedraw;
+                }
+                editor.text != text_before
+            },
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        if text_changed {
+            self.filter_options(&event);
+        }
+
+        UpdateScreen::Redraw
+    }
+
+    /// Edits the text of a combo box or searches the options of a dropdown (type-ahead)
+    pub fn on_text_input<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let c = match app_state_no_data.windows[event.window].get_keyboard_state().current_char {
+            Some(c) => c,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let text_changed = match &mut self.editor {
+            Some(editor) => editor.handle_text_input(c) == UpdateScreen::Redraw,
+            None => {
+                // Space opens the list instead, see `on_virtual_key_down`
+                if c == ' ' && !self.is_type_ahead_active(Instant::now()) {
+                    return UpdateScreen::DontRedraw;
+                }
+                return if self.type_ahead(c, Instant::now()) { UpdateScreen::Redraw } else { UpdateScreen::DontRedraw };
+            },
+        };
+
+        if text_changed {
+            self.filter_options(&event);
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    /// Closes the list when something else is clicked
+    pub fn on_focus_lost<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, _event: WindowEvent<T>) -> UpdateScreen {
+        if self.is_open {
+            self.close();
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    /// Shows only the options that match the edited text of the combo box and highlights the first one
+    fn filter_options<T: Layout>(&mut self, event: &WindowEvent<T>) {
+        self.is_filtering = true;
+        if !self.is_open {
+            self.update_popup_direction(event);
+        }
+        self.is_open = true;
+        self.highlighted = self.get_visible_options().first().cloned();
+        self.scroll_offset = 0;
+    }
+}
+
+#[test]
+fn test_dropdown_type_ahead_and_filtering() {
+    let options = vec!["Apple", "Banana", "Blueberry", "Cherry", "blackberry"];
+    let mut dropdown = DropdownState::new(options.into_iter().map(|option| option.to_string()).collect());
+
+    // Typing "bl" chooses the first option starting with "bl" (ignoring the case)
+    let now = Instant::now();
+    assert!(dropdown.type_ahead('b', now));
+    assert_eq!(dropdown.get_selected_option(), Some("Banana"));
+    assert!(dropdown.type_ahead('l', now));
+    assert_eq!(dropdown.get_selected_option(), Some("Blueberry"));
+
+    // Repeating a letter cycles through the options starting with it
+    let later = now + TYPE_AHEAD_TIMEOUT * 2;
+    dropdown.type_ahead('b', later);
+    dropdown.type_ahead('b', later);
+    assert_eq!(dropdown.get_selected_option(), Some("blackberry"));
+
+    let mut combo_box = dropdown.clone().with_editable(true);
+    assert_eq!(combo_box.editor.as_ref().map(|editor| editor.text.as_str()), Some("blackberry"));
+    assert_eq!(combo_box.get_visible_options().len(), 5);
+
+    combo_box.editor.as_mut().unwrap().set_text("ERR");
+    combo_box.is_filtering = true;
+    assert_eq!(combo_box.get_visible_options(), vec![2, 3, 4]);
+
+    combo_box.is_open = true;
+    combo_box.max_visible_options = 2;
+    combo_box.move_highlight(1);
+    assert_eq!(combo_box.highlighted, Some(2));
+    combo_box.move_highlight(5);
+    assert_eq!((combo_box.highlighted, combo_box.scroll_offset), (Some(4), 1));
+    assert!(combo_box.select(3));
+    assert_eq!(combo_box.editor.as_ref().map(|editor| editor.text.as_str()), Some("Cherry"));
+    assert_eq!(combo_box.get_visible_options().len(), 5);
+}
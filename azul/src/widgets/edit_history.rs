@@ -0,0 +1,288 @@
+//! Undo / redo history for the text editing widgets
+//!
+//! Every change to the text is recorded as a `TextEdit`, i.e. which text was replaced
+//! by which other text, plus the caret and the selection before and after the edit.
+//! Consecutive typing is merged into one edit (one word at a time), so that Ctrl + Z
+//! doesn't undo character by character.
+
+use std::{
+    ops::Range,
+    collections::VecDeque,
+};
+
+/// Default number of edits that can be undone
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum EditKind {
+    /// Text was inserted, nothing was deleted
+    Insert,
+    /// Text was deleted, nothing was inserted
+    Delete,
+    /// The deleted text was replaced by the inserted text (ex. typing over a selection)
+    Replace,
+}
+
+/// One change to the text, all offsets are byte offsets into the text
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Byte offset at which the text was changed
+    pub position: usize,
+    /// Text that was removed at the `position`
+    pub deleted: String,
+    /// Text that was inserted at the `position`
+    pub inserted: String,
+    pub cursor_before: usize,
+    pub selection_before: Option<Range<usize>>,
+    pub cursor_after: usize,
+    pub selection_after: Option<Range<usize>>,
+}
+
+impl TextEdit {
+    pub fn kind(&self) -> EditKind {
+        match (self.deleted.is_empty(), self.inserted.is_empty()) {
+            (true, _) => EditKind::Insert,
+            (false, true) => EditKind::Delete,
+            (false, false) => EditKind::Replace,
+        }
+    }
+
+    /// Whether the `next` typed edit continues this one, so that both can be undone in one step.
+    /// Typing is merged until the user starts a new word.
+    fn can_merge_with(&self, next: &TextEdit) -> bool {
+        let continues_typing = next.deleted.is_empty() &&
+                               next.position == self.position + self.inserted.len();
+        let starts_new_word = self.inserted.chars().last().map(|c| c.is_whitespace()).unwrap_or(false) &&
+                              !next.inserted.chars().all(|c| c.is_whitespace());
+        continues_typing && !starts_new_word
+    }
+}
+
+/// Undo / redo stack of the edits to one text
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct EditHistory {
+    undo_stack: VecDeque<TextEdit>,
+    redo_stack: Vec<TextEdit>,
+    /// Maximum number of edits that can be undone, older edits are forgotten
+    max_depth: usize,
+    /// Whether the last edit was typed and the next typed edit may be merged into it
+    is_typing: bool,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl EditHistory {
+
+    /// Creates an empty history that remembers up to `max_depth` edits
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            max_depth,
+            is_typing: false,
+        }
+    }
+
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Changes the number of edits that can be undone, forgets the oldest edits if necessary
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+        while self.undo_stack.len() > max_depth {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Forgets all edits, ex. after the document has been loaded or saved
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.is_typing = false;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Returns the edits that can be undone, from the oldest to the newest
+    pub fn get_undo_edits(&self) -> impl Iterator<Item=&TextEdit> {
+        self.undo_stack.iter()
+    }
+
+    /// Returns the edits that can be redone, from the next to the last one
+    pub fn get_redo_edits(&self) -> impl Iterator<Item=&TextEdit> {
+        self.redo_stack.iter().rev()
+    }
+
+    /// Records an edit that has already been applied to the text. If `is_typing` is set and
+    /// the previous edit was typed, too, both are merged into one step. Clears the redo stack.
+    pub fn push(&mut self, edit: TextEdit, is_typing: bool) {
+
+        self.redo_stack.clear();
+
+        let merged = self.is_typing && is_typing && match self.undo_stack.back_mut() {
+            Some(last) if last.can_merge_with(&edit) => {
+                last.inserted.push_str(&edit.inserted);
+                last.cursor_after = edit.cursor_after;
+                last.selection_after = edit.selection_after.clone();
+                true
+            },
+            _ => false,
+        };
+
+        if !merged {
+            self.undo_stack.push_back(edit);
+        }
+
+        while self.undo_stack.len() > self.max_depth {
+            self.undo_stack.pop_front();
+        }
+
+        self.is_typing = is_typing;
+    }
+
+    /// Prevents the next typed edit from being merged with the previous one,
+    /// should be called when the caret is moved without editing the text
+    pub fn stop_merging(&mut self) {
+        self.is_typing = false;
+    }
+
+    /// Reverts the last edit on the `text`, returns the reverted edit, so that the caller
+    /// can restore the `cursor_before` and `selection_before`
+    ///
+    /// If the `text` was changed without recording the edits (so that the last edit
+    /// doesn't match the text anymore), the history is cleared and `None` is returned.
+    pub fn undo(&mut self, text: &mut String) -> Option<&TextEdit> {
+        let edit = self.undo_stack.pop_back()?;
+        if !text_matches(text, edit.position, &edit.inserted) {
+            self.clear();
+            return None;
+        }
+        text.replace_range(edit.position..(edit.position + edit.inserted.len()), &edit.deleted);
+        self.is_typing = false;
+        self.redo_stack.push(edit);
+        self.redo_stack.last()
+    }
+
+    /// Re-applies the last undone edit on the `text`, returns the edit, so that the caller
+    /// can restore the `cursor_after` and `selection_after`
+    ///
+    /// Like `undo`, clears the history and returns `None` if the edit doesn't match the `text`.
+    pub fn redo(&mut self, text: &mut String) -> Option<&TextEdit> {
+        let edit = self.redo_stack.pop()?;
+        if !text_matches(text, edit.position, &edit.deleted) {
+            self.clear();
+            return None;
+        }
+        text.replace_range(edit.position..(edit.position + edit.deleted.len()), &edit.inserted);
+        self.is_typing = false;
+        self.undo_stack.push_back(edit);
+        self.undo_stack.back()
+    }
+}
+
+/// Whether the `text` contains the `expected` text at the byte offset `position`
+fn text_matches(text: &str, position: usize, expected: &str) -> bool {
+    text.get(position..(position + expected.len())) == Some(expected)
+}
+
+#[cfg(test)]
+fn type_text(history: &mut EditHistory, text: &mut String, typed: &str) {
+    for c in typed.chars() {
+        let position = text.len();
+        text.push(c);
+        history.push(TextEdit {
+            position,
+            deleted: String::new(),
+            inserted: c.to_string(),
+            cursor_before: position,
+            selection_before: None,
+            cursor_after: text.len(),
+            selection_after: None,
+        }, true);
+    }
+}
+
+#[test]
+fn test_edit_history_merges_typing_by_word() {
+    let mut history = EditHistory::default();
+    let mut text = String::new();
+    type_text(&mut history, &mut text, "hello world");
+    assert_eq!(history.get_undo_edits().count(), 2);
+
+    assert_eq!(history.undo(&mut text).map(|edit| edit.cursor_before), Some(6));
+    assert_eq!(text, "hello ");
+    assert_eq!(history.undo(&mut text).map(|edit| edit.kind()), Some(EditKind::Insert));
+    assert_eq!(text, "");
+    assert!(history.undo(&mut text).is_none());
+
+    history.redo(&mut text);
+    history.redo(&mut text);
+    assert_eq!(text, "hello world");
+    assert!(!history.can_redo());
+}
+
+#[test]
+fn test_edit_history_replace_and_max_depth() {
+    let mut history = EditHistory::new(2);
+    let mut text = String::from("abc");
+
+    for _ in 0..3 {
+        history.stop_merging();
+        type_text(&mut history, &mut text, "x");
+    }
+    assert_eq!(history.get_undo_edits().count(), 2);
+
+    // Replace "bc" by "ü"
+    text.replace_range(1..3, "ü");
+    history.push(TextEdit {
+        position: 1,
+        deleted: "bc".into(),
+        inserted: "ü".into(),
+        cursor_before: 3,
+        selection_before: Some(1..3),
+        cursor_after: 3,
+        selection_after: None,
+    }, false);
+    assert_eq!(text, "aüxxx");
+
+    let edit = history.undo(&mut text).cloned().unwrap();
+    assert_eq!(edit.kind(), EditKind::Replace);
+    assert_eq!(edit.selection_before, Some(1..3));
+    assert_eq!(text, "abcxxx");
+
+    // A new edit clears the redo stack
+    type_text(&mut history, &mut text, "y");
+    assert!(!history.can_redo());
+}
+
+#[test]
+fn test_edit_history_clears_on_mismatch() {
+    let mut history = EditHistory::default();
+    let mut text = String::new();
+    type_text(&mut history, &mut text, "hello");
+
+    // Text changed without recording the edit
+    text = String::from("hü");
+    assert!(history.undo(&mut text).is_none());
+    assert_eq!(text, "hü");
+    assert!(!history.can_undo());
+    assert!(!history.can_redo());
+
+    type_text(&mut history, &mut text, "x");
+    assert!(history.undo(&mut text).is_some());
+    text.clear();
+    assert!(history.redo(&mut text).is_none());
+    assert_eq!(text, "");
+    assert!(!history.can_undo());
+}
@@ -0,0 +1,481 @@
+//! Virtualized list for very large data sets
+//!
+//! The rows are rendered into an `IFrame`, which only creates DOM nodes for the rows
+//! that are visible, plus a few rows above and below them (the "overscan"), so the
+//! number of rows doesn't affect the size of the DOM. The scrollbar next to the
+//! `IFrame` reflects the height of all rows, not only the rendered ones.
+//!
+//! Rows either have a fixed height or an estimated height. Rows with an estimated height
+//! are at least as high as the estimate and are measured once they have been rendered,
+//! so the scrollbar gets more accurate the more rows have been seen.
+
+use std::fmt;
+use azul_css::{CssProperty, LayoutTop, LayoutHeight, LayoutMinHeight, LayoutFlexGrow, FloatValue};
+use {
+    dom::{Dom, On, NodeType, IFrameCallback, UpdateScreen, TabIndex},
+    app_state::AppStateNoData,
+    traits::Layout,
+    window::{FakeWindow, WindowEvent, WindowInfo, HidpiAdjustedBounds, ScrollState},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback},
+    widgets::row_offsets::{RowOffsets, get_scrollbar_segments},
+};
+
+/// ID of the dynamic `top` property of the rendered rows, see the native CSS
+const VIEWPORT_TOP_ID: &str = "__azul-native-list-view-viewport-top";
+/// ID of the dynamic `height` property of the rows with a fixed height
+const ROW_HEIGHT_ID: &str = "__azul-native-list-view-row-height";
+/// ID of the dynamic `min-height` property of the rows with an estimated height
+const ROW_MIN_HEIGHT_ID: &str = "__azul-native-list-view-row-min-height";
+/// ID of the dynamic `flex-grow` property of the scrollbar thumb and the track above and below it
+const SCROLLBAR_GROW_ID: &str = "__azul-native-list-view-scrollbar-grow";
+/// Default number of rows that are rendered above and below the visible rows
+pub const DEFAULT_OVERSCAN: usize = 3;
+
+/// Height of the rows of a `ListView` in pixels
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum RowHeight {
+    /// All rows have the same height
+    Fixed(f32),
+    /// The rows can be higher than the estimate, their actual height is measured after they
+    /// have been rendered. Rows that haven't been rendered yet are assumed to have the estimated height.
+    Estimated(f32),
+}
+
+impl RowHeight {
+    fn get(&self) -> f32 {
+        match self {
+            RowHeight::Fixed(height) | RowHeight::Estimated(height) => height.max(1.0),
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ListView {
+
+}
+
+pub struct ListViewState<D, T: Layout> {
+    /// Data for rendering the rows, passed to `render_row`
+    pub data: D,
+    /// Number of rows in the list
+    pub row_count: usize,
+    /// Call `invalidate_row_heights` after changing the height
+    pub row_height: RowHeight,
+    /// Number of rows that are rendered above and below the visible rows. Default: `DEFAULT_OVERSCAN`
+    pub overscan: usize,
+    /// Index of the selected row
+    pub selected: Option<usize>,
+    /// Renders the row with the index, the `bool` is whether the row is selected
+    pub render_row: fn(&D, usize, bool) -> Dom<T>,
+    /// Distance in pixels from the top of the first row to the top of the visible area
+    scroll: ScrollState,
+    /// Height of the visible area in pixels, updated every time the list is rendered
+    viewport_height: f32,
+    /// Heights of the rows that have been rendered, for `RowHeight::Estimated`
+    row_offsets: RowOffsets,
+    /// The rows that were rendered last and the index of their node in the DOM of the `IFrame`
+    rendered_rows: Vec<(usize, usize)>,
+}
+
+impl<D: fmt::Debug, T: Layout> fmt::Debug for ListViewState<D, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+            "ListViewState {{ data: {:?}, row_count: {:?}, row_height: {:?}, overscan: {:?}, selected: {:?}, \
+            render_row: 0x{:x}, scroll_y: {:?}, viewport_height: {:?}, measured_heights: {:?} }}",
+            self.data, self.row_count, self.row_height, self.overscan, self.selected,
+            self.render_row as usize, self.get_scroll_y(), self.viewport_height, self.row_offsets.get_measured_count())
+    }
+}
+
+impl<D, T: Layout> ListViewState<D, T> {
+
+    pub fn new(data: D, row_count: usize, row_height: RowHeight, render_row: fn(&D, usize, bool) -> Dom<T>) -> Self {
+        Self {
+            data,
+            row_count,
+            row_height,
+            overscan: DEFAULT_OVERSCAN,
+            selected: None,
+            render_row,
+            scroll: ScrollState::new(0.0, 0.0),
+            viewport_height: 0.0,
+            row_offsets: RowOffsets::new(row_height.get()),
+            rendered_rows: Vec::new(),
+        }
+    }
+
+    pub fn with_overscan(self, overscan: usize) -> Self {
+        Self { overscan, .. self }
+    }
+
+    /// Changes the number of rows, for example after rows were added to the `data`
+    pub fn set_row_count(&mut self, row_count: usize) {
+        self.row_count = row_count;
+        // Forget the heights of the removed rows
+        self.row_offsets.forget_from(row_count);
+        if self.selected.map(|selected| selected >= row_count).unwrap_or(false) {
+            self.selected = None;
+        }
+        self.update_overflow();
+    }
+
+    /// Forgets the measured heights of the rows, necessary if the content of the rows
+    /// changed and the list uses `RowHeight::Estimated`, or if the `row_height` changed
+    pub fn invalidate_row_heights(&mut self) {
+        self.row_offsets.set_estimate(self.row_height.get());
+        self.row_offsets.clear();
+        self.update_overflow();
+    }
+
+    /// Returns the height of the row: the measured height, or the estimated height if it hasn't been rendered yet
+    pub fn get_row_height(&self, row: usize) -> f32 {
+        self.row_offsets.get_row_height(row)
+    }
+
+    /// Returns the height of all rows together, i.e. the height that the scrollbar reflects
+    pub fn get_total_height(&self) -> f32 {
+        self.row_offsets.get_total_height(self.row_count)
+    }
+
+    /// Returns the distance in pixels from the top of the first row to the top of the row
+    pub fn get_row_offset(&self, row: usize) -> f32 {
+        self.row_offsets.get_row_offset(row)
+    }
+
+    /// Returns the row at the distance `y` from the top of the first row and the offset of
+    /// that row, `None` if the list is empty or `y` is below the last row
+    pub fn get_row_at_offset(&self, y: f32) -> Option<(usize, f32)> {
+        self.row_offsets.get_row_at_offset(y, self.row_count)
+    }
+
+    /// Returns the distance in pixels from the top of the first row to the top of the visible area
+    pub fn get_scroll_y(&self) -> f32 {
+        self.scroll.get_scroll_amount().1
+    }
+
+    /// Scrolls to the distance `y` from the top of the first row, returns whether the list has been scrolled
+    pub fn scroll_to_offset(&mut self, y: f32) -> bool {
+        self.update_overflow();
+        let old_scroll_y = self.get_scroll_y();
+        self.scroll.add(0.0, y - old_scroll_y);
+        self.get_scroll_y() != old_scroll_y
+    }
+
+    /// Scrolls so that the row is completely visible
+    pub fn scroll_to_row(&mut self, row: usize) {
+        let scroll_y = self.get_scroll_y();
+        let row_top = self.get_row_offset(row);
+        let row_bottom = row_top + self.get_row_height(row);
+        if row_top < scroll_y {
+            self.scroll_to_offset(row_top);
+        } else if row_bottom > scroll_y + self.viewport_height {
+            self.scroll_to_offset(row_bottom - self.viewport_height);
+        } else {
+            self.update_overflow();
+        }
+    }
+
+    /// The scroll position can't go below the last row
+    fn update_overflow(&mut self) {
+        let max_scroll_y = self.get_total_height() - self.viewport_height;
+        self.scroll.set_overflow(0.0, max_scroll_y);
+    }
+
+    /// Selects the row and scrolls it into view
+    pub fn select(&mut self, row: usize) {
+        if row < self.row_count {
+            self.selected = Some(row);
+            self.scroll_to_row(row);
+        }
+    }
+
+    /// Returns the first and the last (exclusive) row that are rendered, i.e. the visible
+    /// rows and the overscan, together with the offset of the first rendered row
+    fn get_rendered_rows(&self) -> (usize, usize, f32) {
+        self.row_offsets.get_rendered_rows(self.get_scroll_y(), self.viewport_height, self.row_count, self.overscan)
+    }
+
+    /// Stores the heights of the rows that were rendered in the last frame. The first visible
+    /// row stays where it is, even if the rows above it turned out to be higher than estimated.
+    fn measure_rendered_rows(&mut self, window: &FakeWindow<T>) {
+        if let RowHeight::Fixed(_) = self.row_height {
+            return;
+        }
+
+        let rects = match window.get_iframe_rects(self) {
+            Some(rects) => rects,
+            None => return,
+        };
+
+        let scroll_y = self.get_scroll_y();
+        let anchor = self.get_row_at_offset(scroll_y).map(|(row, row_offset)| (row, scroll_y - row_offset));
+
+        for (row, node_index) in self.rendered_rows.iter() {
+            if let Some(rect) = rects.get(*node_index) {
+                self.row_offsets.measure(*row, rect.size.height);
+            }
+        }
+
+        if let Some((row, distance_into_row)) = anchor {
+            let row_offset = self.get_row_offset(row);
+            self.scroll_to_offset(row_offset + distance_into_row);
+        }
+    }
+
+    pub fn render(state: &mut ListViewState<D, T>, info: WindowInfo<T>, dimensions: HidpiAdjustedBounds)
+    -> Dom<T>
+    {
+        state.row_offsets.set_estimate(state.row_height.get());
+        state.measure_rendered_rows(info.window);
+        state.viewport_height = dimensions.logical_size.height as f32;
+        state.update_overflow();
+
+        let (first_row, last_row, first_row_offset) = state.get_rendered_rows();
+
+        // div.__azul-native-list-view-rows
+        //     '-> div.__azul-native-list-view-viewport (positioned at the offset of the first rendered row)
+        //         '-> div.__azul-native-list-view-row (.__azul-native-list-view-row-selected)
+        //             '-> the DOM returned by `render_row`
+
+        let (row_height_id, row_height) = match state.row_height {
+            RowHeight::Fixed(height) => (ROW_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(height))),
+            RowHeight::Estimated(height) => (ROW_MIN_HEIGHT_ID, CssProperty::MinHeight(LayoutMinHeight::px(height))),
+        };
+
+        let mut viewport = Dom::new(NodeType::Div)
+            .with_class("__azul-native-list-view-viewport")
+            .with_style_override(VIEWPORT_TOP_ID, CssProperty::Top(LayoutTop::px(first_row_offset - state.get_scroll_y())));
+
+        // The root and the viewport are the first two nodes of the DOM
+        let mut node_index = 2;
+        state.rendered_rows.clear();
+
+        for row in first_row..last_row {
+            let is_selected = state.selected == Some(row);
+            let mut row_dom = Dom::new(NodeType::Div)
+                .with_class("__azul-native-list-view-row")
+                .with_style_override(row_height_id, row_height.clone())
+                .with_child((state.render_row)(&state.data, row, is_selected));
+            if is_selected {
+                row_dom.add_class("__azul-native-list-view-row-selected");
+            }
+            state.rendered_rows.push((row, node_index));
+            node_index += row_dom.len();
+            viewport.add_child(row_dom);
+        }
+
+        Dom::new(NodeType::Div)
+            .with_class("__azul-native-list-view-rows")
+            .with_child(viewport)
+    }
+}
+
+impl ListView {
+    pub fn new() -> Self {
+        Self {
+
+        }
+    }
+
+    pub fn dom<T: Layout, D>(&self, state: &ListViewState<D, T>, t: &T, window: &mut FakeWindow<T>) -> Dom<T> {
+
+        let ptr = match StackCheckedPointer::new(t, state) {
+            Some(ptr) => ptr,
+            None => return Dom::new(NodeType::Label(
+                "Cannot create list from heap-allocated ListViewState, \
+                 please call ListViewState::render manually".into())
+            ),
+        };
+
+        // div.__azul-native-list-view
+        //     |-> iframe.__azul-native-list-view-iframe
+        //     '-> div.__azul-native-list-view-scrollbar
+        //         |-> div.__azul-native-list-view-scrollbar-track (above the thumb)
+        //         |-> div.__azul-native-list-view-scrollbar-thumb
+        //         '-> div.__azul-native-list-view-scrollbar-track (below the thumb)
+
+        let mut iframe = Dom::new(NodeType::IFrame((IFrameCallback(render_list_callback::<T, D>), ptr)))
+            .with_class("__azul-native-list-view-iframe")
+            .with_tab_index(TabIndex::Auto);
+
+        let mouse_down = window.add_callback(ptr, DefaultCallback(Self::list_view_on_mouse_down::<T, D>));
+        let scroll = window.add_callback(ptr, DefaultCallback(Self::list_view_on_scroll::<T, D>));
+        let virtual_key_down = window.add_callback(ptr, DefaultCallback(Self::list_view_on_virtual_key_down::<T, D>));
+        let scrollbar_drag = window.add_callback(ptr, DefaultCallback(Self::list_view_on_scrollbar_drag::<T, D>));
+
+        iframe.add_default_callback_id(On::LeftMouseDown, mouse_down);
+        iframe.add_default_callback_id(On::Scroll, scroll);
+        iframe.add_default_callback_id(On::VirtualKeyDown, virtual_key_down);
+
+        // The thumb and the track above and below it are sized like the visible area and the
+        // rows above and below it, so that the thumb doesn't need the height of the scrollbar
+        let (above, thumb, below) = get_scrollbar_segments(state.get_scroll_y(), state.viewport_height, state.get_total_height());
+        let segment = |class: &str, grow: f32| Dom::new(NodeType::Div)
+            .with_class(class)
+            .with_style_override(SCROLLBAR_GROW_ID, CssProperty::FlexGrow(LayoutFlexGrow(FloatValue::new(grow))));
+
+        let mut scrollbar = Dom::new(NodeType::Div)
+            .with_class("__azul-native-list-view-scrollbar")
+            .with_pointer_capture(true)
+            .with_child(segment("__azul-native-list-view-scrollbar-track", above))
+            .with_child(segment("__azul-native-list-view-scrollbar-thumb", thumb))
+            .with_child(segment("__azul-native-list-view-scrollbar-track", below));
+
+        scrollbar.add_default_callback_id(On::LeftMouseDown, scrollbar_drag);
+        scrollbar.add_default_callback_id(On::MouseOver, scrollbar_drag);
+
+        Dom::new(NodeType::Div)
+            .with_class("__azul-native-list-view")
+            .with_child(iframe)
+            .with_child(scrollbar)
+    }
+
+    fn list_view_on_mouse_down<T: Layout, D>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(ListViewState::<D, T>::on_mouse_down, data, event) }
+    }
+
+    fn list_view_on_scroll<T: Layout, D>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(ListViewState::<D, T>::on_scroll, data, event) }
+    }
+
+    fn list_view_on_virtual_key_down<T: Layout, D>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(ListViewState::<D, T>::on_virtual_key_down, data, event) }
+    }
+
+    fn list_view_on_scrollbar_drag<T: Layout, D>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(ListViewState::<D, T>::on_scrollbar_drag, data, event) }
+    }
+}
+
+fn render_list_callback<T: Layout, D>(ptr: &StackCheckedPointer<T>, info: WindowInfo<T>, dimensions: HidpiAdjustedBounds)
+-> Dom<T>
+{
+    unsafe { ptr.invoke_mut_iframe(ListViewState::<D, T>::render, info, dimensions) }
+}
+
+impl<D, T: Layout> ListViewState<D, T> {
+
+    /// Selects the clicked row
+    pub fn on_mouse_down(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let (_, y) = event.cursor_relative_to_item;
+        let scroll_y = self.get_scroll_y();
+        match self.get_row_at_offset(scroll_y + y) {
+            Some((row, _)) if self.selected != Some(row) => {
+                self.select(row);
+                UpdateScreen::Redraw
+            },
+            _ => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Scrolls by the scroll amount of the mouse wheel in pixels
+    pub fn on_scroll(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let scroll_y = app_state_no_data.windows[event.window].get_mouse_state().scroll_y as f32;
+        let old_scroll_y = self.get_scroll_y();
+        if self.scroll_to_offset(old_scroll_y + scroll_y) {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    /// Up / Down / PageUp / PageDown / Home / End move the selection
+    pub fn on_virtual_key_down(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let key = match app_state_no_data.windows[event.window].get_keyboard_state().latest_virtual_keycode {
+            Some(key) => key,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        if self.row_count == 0 {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let last_row = self.row_count - 1;
+        let page = self.get_rows_per_page();
+
+        let new_row = match (key, self.selected) {
+            (VirtualKeyCode::Up, Some(row)) => row.saturating_sub(1),
+            (VirtualKeyCode::Down, Some(row)) => (row + 1).min(last_row),
+            (VirtualKeyCode::PageUp, Some(row)) => row.saturating_sub(page),
+            (VirtualKeyCode::PageDown, Some(row)) => (row + page).min(last_row),
+            (VirtualKeyCode::Up, None) | (VirtualKeyCode::PageUp, None) | (VirtualKeyCode::End, _) => last_row,
+            (VirtualKeyCode::Down, None) | (VirtualKeyCode::PageDown, None) | (VirtualKeyCode::Home, _) => 0,
+            _ => return UpdateScreen::DontRedraw,
+        };
+
+        self.select(new_row);
+        UpdateScreen::Redraw
+    }
+
+    /// Number of rows that PageUp / PageDown move the selection, the number of rows that fit into the visible area
+    fn get_rows_per_page(&self) -> usize {
+        ((self.viewport_height / self.row_height.get()).floor() as usize).max(1)
+    }
+
+    /// Pressing the mouse on the scrollbar (and dragging it) scrolls so that the
+    /// thumb is centered at the cursor
+    pub fn on_scrollbar_drag(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        if !event.has_pointer_capture() {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let scrollbar_height = match event.get_node_rect(event.hit_dom_node) {
+            Some(rect) if rect.size.height > 0.0 => rect.size.height,
+            _ => return UpdateScreen::DontRedraw,
+        };
+
+        let (_, y) = event.cursor_relative_to_item;
+        let fraction = (y / scrollbar_height).max(0.0).min(1.0);
+        if self.scroll_to_offset(fraction * self.get_total_height() - self.viewport_height / 2.0) {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+}
+
+#[test]
+fn test_list_view_estimated_row_offsets() {
+    use test_support::Mock;
+
+    fn render_row<T: Layout>(_: &(), _: usize, _: bool) -> Dom<T> { unreachable!() }
+
+    let mut list = ListViewState::<(), Mock>::new((), 1000, RowHeight::Estimated(20.0), render_row);
+    list.viewport_height = 100.0;
+    assert_eq!(list.get_total_height(), 20_000.0);
+    assert_eq!(list.get_row_at_offset(45.0), Some((2, 40.0)));
+
+    // Row 1 is 50px high instead of 20px, so all rows below it move down by 30px
+    list.row_offsets.measure(1, 50.0);
+    assert_eq!(list.get_total_height(), 20_030.0);
+    assert_eq!(list.get_row_offset(2), 70.0);
+    assert_eq!(list.get_row_at_offset(45.0), Some((1, 20.0)));
+    assert_eq!(list.get_row_at_offset(75.0), Some((2, 70.0)));
+    assert_eq!(list.get_row_at_offset(20_030.0), None);
+
+    // Rows 0 - 3 are visible (the last one partially), 3 more rows are rendered below
+    assert_eq!(list.get_rendered_rows(), (0, 7, 0.0));
+    list.scroll_to_offset(75.0);
+    assert_eq!(list.get_rendered_rows(), (0, 11, 0.0));
+    list.scroll_to_offset(200.0);
+    assert_eq!(list.get_rendered_rows(), (5, 17, 130.0));
+
+    list.select(999);
+    assert_eq!(list.get_scroll_y(), 20_030.0 - 100.0);
+
+    // Rows can't be lower than the estimate, so the estimate never renders too few rows
+    list.row_offsets.measure(4, 5.0);
+    assert_eq!(list.get_row_height(4), 20.0);
+    assert_eq!(list.get_total_height(), 20_030.0);
+}
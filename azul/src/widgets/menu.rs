@@ -0,0 +1,925 @@
+//! Menu bar and context menus, drawn by azul itself so that they look and work the
+//! same on all platforms
+//!
+//! A `Menu` wraps the content of a window: it shows the menu bar above the content and
+//! draws the open menus as overlays (see `Dom::with_overlay`) on top of it. Context menus
+//! are opened by the application, usually from a `On::RightMouseUp` callback:
+//!
+//! ```rust,ignore
+//! fn on_right_click(app_state: &mut AppState<MyApp>, event: WindowEvent<MyApp>) -> UpdateScreen {
+//!     let items = vec![
+//!         MenuItem::new("&Copy", Callback(copy)).with_accelerator("Ctrl+C"),
+//!         MenuItem::new("&Paste", Callback(paste)).with_accelerator("Ctrl+V"),
+//!     ];
+//!     app_state.data.modify(|data| data.menu.open_context_menu(items, event.cursor_in_viewport));
+//!     UpdateScreen::Redraw
+//! }
+//! ```
+//!
+//! Since the menu wraps the whole content, it receives the keyboard events even if another
+//! node is focused: Alt or F10 activate the menu bar, Alt + a letter opens the menu with that
+//! mnemonic and the arrow keys, Return and Escape navigate the open menus.
+
+use std::{fmt, mem};
+use azul_css::{CssProperty, LayoutLeft, LayoutTop, LayoutHeight};
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen, Callback},
+    window::{FakeWindow, WindowEvent},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback, DefaultCallbackId},
+    app_state::AppStateNoData,
+    id_tree::NodeId,
+};
+
+/// IDs of the dynamic CSS properties of the menus
+const CONTEXT_MENU_LEFT_ID: &str = "__azul-native-menu-context-left";
+const CONTEXT_MENU_TOP_ID: &str = "__azul-native-menu-context-top";
+const POPUP_HEIGHT_ID: &str = "__azul-native-menu-popup-height";
+
+/// Size of the parts of a menu, must match the native CSS. A menu has the size of its items,
+/// but absolutely positioned nodes can't be sized by their content, so the height is set from here.
+const POPUP_WIDTH: f32 = 200.0;
+const POPUP_PADDING: f32 = 4.0;
+const ITEM_HEIGHT: f32 = 22.0;
+const SEPARATOR_HEIGHT: f32 = 7.0;
+
+/// Child indices of the anchor that a submenu is positioned in, see `MenuState::get_item_path`
+const ITEM_ANCHOR_INDEX: usize = 3;
+const BAR_ITEM_ANCHOR_INDEX: usize = 1;
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Menu {
+
+}
+
+/// An item of a menu: an action, a checkable item, a submenu or a separator line
+pub struct MenuItem<T: Layout> {
+    /// Text of the item. An `&` marks the next character as the mnemonic, ex. "&File" is opened
+    /// with Alt + F. Write "&&" for a literal `&`.
+    pub label: String,
+    /// Keyboard shortcut that is shown right of the label, ex. "Ctrl+S". The menu only
+    /// shows the shortcut, the application has to handle the key itself.
+    pub accelerator: Option<String>,
+    /// Whether a check mark is shown in front of the label, `None` if the item isn't checkable.
+    /// Activating a checkable item toggles the check mark before the callback is called.
+    pub checked: Option<bool>,
+    /// Disabled items are greyed out and can't be activated. Default: `true`
+    pub enabled: bool,
+    /// Called when the item is activated (clicked or chosen with Return)
+    pub callback: Option<Callback<T>>,
+    /// Items of the submenu, which is opened instead of calling the callback
+    pub children: Vec<MenuItem<T>>,
+    /// Whether the item is a separator line between groups of items
+    pub is_separator: bool,
+}
+
+impl<T: Layout> Clone for MenuItem<T> {
+    fn clone(&self) -> Self {
+        Self {
+            label: self.label.clone(),
+            accelerator: self.accelerator.clone(),
+            checked: self.checked,
+            enabled: self.enabled,
+            callback: self.callback,
+            children: self.children.clone(),
+            is_separator: self.is_separator,
+        }
+    }
+}
+
+impl<T: Layout> fmt::Debug for MenuItem<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MenuItem {{ label: {:?}, accelerator: {:?}, checked: {:?}, enabled: {:?}, callback: {:?}, children: {:?}, is_separator: {:?} }}",
+            self.label, self.accelerator, self.checked, self.enabled, self.callback, self.children, self.is_separator)
+    }
+}
+
+impl<T: Layout> MenuItem<T> {
+
+    fn empty<S: Into<String>>(label: S) -> Self {
+        Self {
+            label: label.into(),
+            accelerator: None,
+            checked: None,
+            enabled: true,
+            callback: None,
+            children: Vec::new(),
+            is_separator: false,
+        }
+    }
+
+    /// Creates an item that calls the `callback` when it is activated
+    pub fn new<S: Into<String>>(label: S, callback: Callback<T>) -> Self {
+        Self { callback: Some(callback), .. Self::empty(label) }
+    }
+
+    /// Creates an item with a check mark that is toggled when the item is activated
+    pub fn checkable<S: Into<String>>(label: S, checked: bool) -> Self {
+        Self { checked: Some(checked), .. Self::empty(label) }
+    }
+
+    /// Creates an item that opens a submenu with the `children`
+    pub fn sub_menu<S: Into<String>>(label: S, children: Vec<MenuItem<T>>) -> Self {
+        Self { children, .. Self::empty(label) }
+    }
+
+    /// Creates a separator line
+    pub fn separator() -> Self {
+        Self { is_separator: true, .. Self::empty("") }
+    }
+
+    pub fn with_callback(self, callback: Callback<T>) -> Self {
+        Self { callback: Some(callback), .. self }
+    }
+
+    pub fn with_accelerator<S: Into<String>>(self, accelerator: S) -> Self {
+        Self { accelerator: Some(accelerator.into()), .. self }
+    }
+
+    pub fn with_checked(self, checked: bool) -> Self {
+        Self { checked: Some(checked), .. self }
+    }
+
+    pub fn with_enabled(self, enabled: bool) -> Self {
+        Self { enabled, .. self }
+    }
+
+    /// Returns the label without the `&` that marks the mnemonic
+    pub fn get_text(&self) -> String {
+        let mut text = String::with_capacity(self.label.len());
+        let mut chars = self.label.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '&' => text.extend(chars.next()),
+                c => text.push(c),
+            }
+        }
+        text
+    }
+
+    /// Returns the (lowercase) character after the first single `&` of the label
+    pub fn get_mnemonic(&self) -> Option<char> {
+        let mut chars = self.label.chars();
+        while let Some(c) = chars.next() {
+            if c == '&' {
+                match chars.next() {
+                    Some('&') => { },
+                    Some(mnemonic) => return mnemonic.to_lowercase().next(),
+                    None => return None,
+                }
+            }
+        }
+        None
+    }
+
+    /// Whether the item can be highlighted and activated
+    fn is_selectable(&self) -> bool {
+        self.enabled && !self.is_separator
+    }
+}
+
+#[derive(Debug)]
+pub struct MenuState<T: Layout> {
+    /// Items of the menu bar, usually submenus. No menu bar is shown if this is empty.
+    pub menu_bar: Vec<MenuItem<T>>,
+    /// The menus that are currently open
+    open_menus: OpenMenus<T>,
+    /// The menus that were open before the last mouse press closed them, see `on_window_mouse_down`
+    closed_menus: Option<OpenMenus<T>>,
+}
+
+#[derive(Debug)]
+struct OpenMenus<T: Layout> {
+    /// Items and position (relative to the top left corner of the `Menu`) of the open context menu
+    context_menu: Option<(Vec<MenuItem<T>>, (f32, f32))>,
+    /// Indices of the items whose submenus are open, starting at the menu bar (or the context menu)
+    open: Vec<usize>,
+    /// Index of the highlighted item in the innermost open menu (in the menu bar, if no menu is open)
+    highlighted: Option<usize>,
+    /// Whether the menu bar is used with the keyboard while none of its menus is open
+    is_bar_active: bool,
+}
+
+impl<T: Layout> Default for OpenMenus<T> {
+    fn default() -> Self {
+        Self {
+            context_menu: None,
+            open: Vec::new(),
+            highlighted: None,
+            is_bar_active: false,
+        }
+    }
+}
+
+impl<T: Layout> Default for MenuState<T> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<T: Layout> MenuState<T> {
+
+    pub fn new(menu_bar: Vec<MenuItem<T>>) -> Self {
+        Self {
+            menu_bar,
+            open_menus: OpenMenus::default(),
+            closed_menus: None,
+        }
+    }
+
+    /// Whether a menu is open or the menu bar is active, i.e. the menu handles the keyboard
+    pub fn is_open(&self) -> bool {
+        let open_menus = &self.open_menus;
+        open_menus.context_menu.is_some() || !open_menus.open.is_empty() || open_menus.is_bar_active
+    }
+
+    /// Opens a context menu with the `items` at the `position` (relative to the top left corner
+    /// of the `Menu`, usually `WindowEvent::cursor_in_viewport`) and closes all other menus
+    pub fn open_context_menu(&mut self, items: Vec<MenuItem<T>>, position: (f32, f32)) {
+        self.open_menus = OpenMenus {
+            context_menu: Some((items, position)),
+            .. OpenMenus::default()
+        };
+    }
+
+    /// Closes all menus
+    pub fn close(&mut self) {
+        self.open_menus = OpenMenus::default();
+    }
+
+    /// Returns the item of the menu bar at the `path` of indices, ex. `[0, 2]` is the third item in the first menu
+    pub fn get_item(&self, path: &[usize]) -> Option<&MenuItem<T>> {
+        let (first, rest) = path.split_first()?;
+        let mut item = self.menu_bar.get(*first)?;
+        for index in rest {
+            item = item.children.get(*index)?;
+        }
+        Some(item)
+    }
+
+    /// Same as `get_item`, ex. for updating the check mark of an item
+    pub fn get_item_mut(&mut self, path: &[usize]) -> Option<&mut MenuItem<T>> {
+        let (first, rest) = path.split_first()?;
+        let mut item = self.menu_bar.get_mut(*first)?;
+        for index in rest {
+            item = item.children.get_mut(*index)?;
+        }
+        Some(item)
+    }
+
+    /// Whether the menus start at the menu bar, not at a context menu
+    fn is_bar_root(&self) -> bool {
+        self.open_menus.context_menu.is_none()
+    }
+
+    /// Returns the items of the open menu at the `depth`: 0 is the menu bar or the context menu,
+    /// 1 is the submenu of the item `open[0]` and so on
+    fn get_menu_items(&self, depth: usize) -> Option<&[MenuItem<T>]> {
+        let mut items = match &self.open_menus.context_menu {
+            Some((items, _)) => items,
+            None => &self.menu_bar,
+        };
+        for index in self.open_menus.open.iter().take(depth) {
+            items = &items.get(*index)?.children;
+        }
+        Some(items)
+    }
+
+    fn get_menu_items_mut(&mut self, depth: usize) -> Option<&mut Vec<MenuItem<T>>> {
+        let OpenMenus { context_menu, open, .. } = &mut self.open_menus;
+        let mut items = match context_menu {
+            Some((items, _)) => items,
+            None => &mut self.menu_bar,
+        };
+        for index in open.iter().take(depth) {
+            items = &mut items.get_mut(*index)?.children;
+        }
+        Some(items)
+    }
+
+    /// Returns the items of the innermost open menu, where the highlighted item is
+    fn get_innermost_items(&self) -> &[MenuItem<T>] {
+        self.get_menu_items(self.open_menus.open.len()).unwrap_or(&[])
+    }
+
+    /// Returns the index of the next selectable item after the `start` in the direction of
+    /// the `delta` (wrapping around at the ends), or the first / last item if there is no `start`
+    fn find_selectable(items: &[MenuItem<T>], start: Option<usize>, delta: isize) -> Option<usize> {
+        let count = items.len() as isize;
+        let start = match start {
+            Some(start) => start as isize,
+            None if delta < 0 => count,
+            None => -1,
+        };
+        (1..=count)
+            .map(|offset| ((start + offset * delta.signum()) % count + count) % count)
+            .map(|index| index as usize)
+            .find(|index| items[*index].is_selectable())
+    }
+
+    /// Moves the highlight to the next (or previous, if `delta` is negative) selectable item
+    fn move_highlight(&mut self, delta: isize) {
+        let highlighted = Self::find_selectable(self.get_innermost_items(), self.open_menus.highlighted, delta);
+        self.open_menus.highlighted = highlighted.or(self.open_menus.highlighted);
+    }
+
+    /// Opens the submenu of the item at the `index` of the innermost menu and highlights its first item
+    fn open_sub_menu(&mut self, index: usize) {
+        self.open_menus.open.push(index);
+        self.open_menus.is_bar_active = false;
+        self.open_menus.highlighted = Self::find_selectable(self.get_innermost_items(), None, 1);
+    }
+
+    /// Closes the innermost menu and highlights the item that opened it
+    fn close_innermost(&mut self) {
+        match self.open_menus.open.pop() {
+            Some(index) => {
+                self.open_menus.highlighted = Some(index);
+                // The keyboard stays in the menu bar, like after pressing Alt
+                if self.open_menus.open.is_empty() && self.is_bar_root() {
+                    self.open_menus.is_bar_active = true;
+                }
+            },
+            None => self.close(),
+        }
+    }
+
+    /// Opens the menu of the next (or previous) item of the menu bar, when the
+    /// keyboard moves past the end of the open menu
+    fn switch_bar_menu(&mut self, delta: isize) {
+        let current = self.open_menus.open.first().cloned();
+        if let Some(index) = Self::find_selectable(&self.menu_bar, current, delta) {
+            self.open_menus.open.clear();
+            self.open_menus.highlighted = Some(index);
+            self.activate(index);
+        }
+    }
+
+    /// Activates the item at the `index` of the innermost menu: opens its submenu, or toggles its
+    /// check mark, closes all menus and returns its callback
+    fn activate(&mut self, index: usize) -> Option<Callback<T>> {
+
+        let depth = self.open_menus.open.len();
+        let (is_selectable, has_sub_menu) = match self.get_innermost_items().get(index) {
+            Some(item) => (item.is_selectable(), !item.children.is_empty()),
+            None => return None,
+        };
+
+        if !is_selectable {
+            return None;
+        }
+
+        if has_sub_menu {
+            self.open_sub_menu(index);
+            return None;
+        }
+
+        let callback = self.get_menu_items_mut(depth).and_then(|items| items.get_mut(index)).and_then(|item| {
+            item.checked = item.checked.map(|checked| !checked);
+            item.callback
+        });
+
+        self.close();
+        callback
+    }
+
+    /// Returns the index of the item with the `mnemonic` in the innermost menu
+    fn find_mnemonic(&self, mnemonic: char) -> Option<usize> {
+        self.get_innermost_items().iter().position(|item| item.is_selectable() && item.get_mnemonic() == Some(mnemonic))
+    }
+
+    /// Returns the indices of the hovered item and of the items that opened its menu, starting at
+    /// the menu bar or the context menu. Every open submenu is in an anchor that is the last child
+    /// of the item that opened it, see `Menu::popup_dom`.
+    fn get_item_path(event: &WindowEvent<T>, item: NodeId) -> Option<Vec<usize>> {
+
+        let mut path = Vec::new();
+        let mut item = item;
+
+        loop {
+            let (index, popup) = event.get_index_in_parent(item)?;
+            path.push(index);
+            let (_, anchor) = event.get_index_in_parent(popup)?;
+            match event.get_index_in_parent(anchor) {
+                Some((ITEM_ANCHOR_INDEX, parent_item)) => item = parent_item,
+                Some((BAR_ITEM_ANCHOR_INDEX, bar_item)) => {
+                    let (bar_index, _) = event.get_index_in_parent(bar_item)?;
+                    path.push(bar_index);
+                    break;
+                },
+                // The anchor of the context menu
+                _ => break,
+            }
+        }
+
+        path.reverse();
+        Some(path)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+struct MenuCallbacks {
+    item_mouse_over: DefaultCallbackId,
+    item_mouse_up: DefaultCallbackId,
+    menu_mouse_down: DefaultCallbackId,
+}
+
+impl Menu {
+
+    pub fn new() -> Self {
+        Self {
+
+        }
+    }
+
+    /// Renders the menu bar, the open menus and the `content` of the window below the menu bar
+    pub fn dom<T: Layout>(&self, state: &MenuState<T>, t: &T, window: &mut FakeWindow<T>, content: Dom<T>) -> Dom<T> {
+
+        let ptr = match StackCheckedPointer::new(t, state) {
+            Some(ptr) => ptr,
+            None => return Dom::new(NodeType::Label(
+                "Cannot create menu from heap-allocated MenuState, \
+                 please store the MenuState in the application data".into())
+            ),
+        };
+
+        // div.__azul-native-menu-window
+        //     |-> div.__azul-native-menu-context-anchor (only if a context menu is open)
+        //     |   '-> popup
+        //     |-> div.__azul-native-menu-bar (only if there are items in the menu bar)
+        //     |   '-> div.__azul-native-menu-bar-item (.__azul-native-menu-bar-item-highlighted)
+        //     |       |-> p.__azul-native-menu-bar-label
+        //     |       '-> div.__azul-native-menu-anchor (only if the menu of the item is open)
+        //     |           '-> popup
+        //     '-> the content
+        //
+        // popup: div.__azul-native-menu-popup
+        //     |-> div.__azul-native-menu-item (.__azul-native-menu-item-highlighted, .__azul-native-menu-item-disabled)
+        //     |   |-> p.__azul-native-menu-item-check
+        //     |   |-> p.__azul-native-menu-item-label
+        //     |   |-> p.__azul-native-menu-item-accelerator (the accelerator, or an arrow for a submenu)
+        //     |   '-> div.__azul-native-menu-anchor (only if the submenu of the item is open)
+        //     |       '-> popup
+        //     '-> div.__azul-native-menu-separator
+        //
+        // The anchors are zero-sized, positioned nodes, the popups are absolutely positioned
+        // overlays in them. The children are keyed, so that opening a context menu doesn't
+        // change the identity of the content (and move the focus away from it).
+
+        let callbacks = MenuCallbacks {
+            item_mouse_over: window.add_callback(ptr, DefaultCallback(Self::menu_on_item_mouse_over::<T>)),
+            item_mouse_up: window.add_callback(ptr, DefaultCallback(Self::menu_on_item_mouse_up::<T>)),
+            menu_mouse_down: window.add_callback(ptr, DefaultCallback(Self::menu_on_menu_mouse_down::<T>)),
+        };
+
+        let window_mouse_down = window.add_callback(ptr, DefaultCallback(Self::menu_on_window_mouse_down::<T>));
+        let bar_item_mouse_down = window.add_callback(ptr, DefaultCallback(Self::menu_on_bar_item_mouse_down::<T>));
+        let bar_item_mouse_over = window.add_callback(ptr, DefaultCallback(Self::menu_on_bar_item_mouse_over::<T>));
+        let virtual_key_down = window.add_callback(ptr, DefaultCallback(Self::menu_on_virtual_key_down::<T>));
+
+        let mut menu_window = Dom::new(NodeType::Div).with_class("__azul-native-menu-window");
+        menu_window.add_default_callback_id(On::LeftMouseDown, window_mouse_down);
+        menu_window.add_default_callback_id(On::RightMouseDown, window_mouse_down);
+        menu_window.add_default_callback_id(On::VirtualKeyDown, virtual_key_down);
+
+        let open_menus = &state.open_menus;
+
+        if let Some((items, (x, y))) = &open_menus.context_menu {
+            // Move the context menu into the window, if it would be cut off at the right or bottom edge
+            let window_size = window.state.size.dimensions;
+            let left = x.min(window_size.width as f32 - POPUP_WIDTH).max(0.0);
+            let top = y.min(window_size.height as f32 - Self::get_popup_height(items)).max(0.0);
+            menu_window.add_child(Dom::new(NodeType::Div)
+                .with_class("__azul-native-menu-context-anchor")
+                .with_key("context-menu")
+                .with_style_override(CONTEXT_MENU_LEFT_ID, CssProperty::Left(LayoutLeft::px(left)))
+                .with_style_override(CONTEXT_MENU_TOP_ID, CssProperty::Top(LayoutTop::px(top)))
+                .with_child(Self::popup_dom(items, 0, open_menus, callbacks)));
+        }
+
+        if !state.menu_bar.is_empty() {
+
+            let mut bar = Dom::new(NodeType::Div).with_class("__azul-native-menu-bar").with_key("menu-bar");
+            bar.add_default_callback_id(On::LeftMouseDown, callbacks.menu_mouse_down);
+
+            for (index, item) in state.menu_bar.iter().enumerate() {
+
+                let is_open = state.is_bar_root() && open_menus.open.first() == Some(&index);
+                let is_highlighted = is_open || (state.is_bar_root() && open_menus.open.is_empty() && open_menus.is_bar_active && open_menus.highlighted == Some(index));
+
+                let mut bar_item = Dom::new(NodeType::Div)
+                    .with_class("__azul-native-menu-bar-item")
+                    .with_child(Dom::new(NodeType::Label(item.get_text())).with_class("__azul-native-menu-bar-label"));
+
+                if is_highlighted {
+                    bar_item.add_class("__azul-native-menu-bar-item-highlighted");
+                }
+                if !item.enabled {
+                    bar_item.add_class("__azul-native-menu-bar-item-disabled");
+                }
+                if is_open && !item.children.is_empty() {
+                    bar_item.add_child(Dom::new(NodeType::Div)
+                        .with_class("__azul-native-menu-anchor")
+                        .with_child(Self::popup_dom(&item.children, 1, open_menus, callbacks)));
+                }
+
+                bar_item.add_default_callback_id(On::LeftMouseDown, bar_item_mouse_down);
+                bar_item.add_default_callback_id(On::MouseOver, bar_item_mouse_over);
+                bar.add_child(bar_item);
+            }
+
+            menu_window.add_child(bar);
+        }
+
+        menu_window.with_child(content.with_key("content"))
+    }
+
+    /// Renders the menu at the `depth` with its open submenus
+    fn popup_dom<T: Layout>(items: &[MenuItem<T>], depth: usize, open_menus: &OpenMenus<T>, callbacks: MenuCallbacks) -> Dom<T> {
+
+        let open_item = open_menus.open.get(depth).cloned();
+        let highlighted = if depth == open_menus.open.len() { open_menus.highlighted } else { open_item };
+
+        let mut popup = Dom::new(NodeType::Div)
+            .with_class("__azul-native-menu-popup")
+            .with_overlay(true)
+            .with_style_override(POPUP_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(Self::get_popup_height(items))));
+
+        popup.add_default_callback_id(On::LeftMouseDown, callbacks.menu_mouse_down);
+        popup.add_default_callback_id(On::RightMouseDown, callbacks.menu_mouse_down);
+
+        for (index, item) in items.iter().enumerate() {
+
+            if item.is_separator {
+                popup.add_child(Dom::new(NodeType::Div).with_class("__azul-native-menu-separator"));
+                continue;
+            }
+
+            let check = if item.checked == Some(true) { "\u{2713}" } else { "" };
+            let accelerator = if item.children.is_empty() { item.accelerator.clone().unwrap_or_default() } else { "\u{25B8}".into() };
+
+            let mut item_dom = Dom::new(NodeType::Div)
+                .with_class("__azul-native-menu-item")
+                .with_child(Dom::new(NodeType::Label(check.into())).with_class("__azul-native-menu-item-check"))
+                .with_child(Dom::new(NodeType::Label(item.get_text())).with_class("__azul-native-menu-item-label"))
+                .with_child(Dom::new(NodeType::Label(accelerator)).with_class("__azul-native-menu-item-accelerator"));
+
+            if highlighted == Some(index) {
+                item_dom.add_class("__azul-native-menu-item-highlighted");
+            }
+            if !item.enabled {
+                item_dom.add_class("__azul-native-menu-item-disabled");
+            }
+            if open_item == Some(index) && !item.children.is_empty() {
+                item_dom.add_child(Dom::new(NodeType::Div)
+                    .with_class("__azul-native-menu-anchor")
+                    .with_child(Self::popup_dom(&item.children, depth + 1, open_menus, callbacks)));
+            }
+
+            item_dom.add_default_callback_id(On::MouseOver, callbacks.item_mouse_over);
+            item_dom.add_default_callback_id(On::LeftMouseUp, callbacks.item_mouse_up);
+            popup.add_child(item_dom);
+        }
+
+        popup
+    }
+
+    fn get_popup_height<T: Layout>(items: &[MenuItem<T>]) -> f32 {
+        let item_heights: f32 = items.iter().map(|item| if item.is_separator { SEPARATOR_HEIGHT } else { ITEM_HEIGHT }).sum();
+        item_heights + 2.0 * POPUP_PADDING
+    }
+
+    fn menu_on_window_mouse_down<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(MenuState::<T>::on_window_mouse_down, data, event) }
+    }
+
+    fn menu_on_menu_mouse_down<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(MenuState::<T>::on_menu_mouse_down, data, event) }
+    }
+
+    fn menu_on_bar_item_mouse_down<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(MenuState::<T>::on_bar_item_mouse_down, data, event) }
+    }
+
+    fn menu_on_bar_item_mouse_over<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(MenuState::<T>::on_bar_item_mouse_over, data, event) }
+    }
+
+    fn menu_on_item_mouse_over<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(MenuState::<T>::on_item_mouse_over, data, event) }
+    }
+
+    fn menu_on_item_mouse_up<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(MenuState::<T>::on_item_mouse_up, data, event) }
+    }
+
+    fn menu_on_virtual_key_down<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(MenuState::<T>::on_virtual_key_down, data, event) }
+    }
+}
+
+impl<T: Layout> MenuState<T> {
+
+    /// Pressing the mouse anywhere closes the open menus. The callbacks of the window run before the
+    /// callbacks of its children, so if the mouse was pressed on a menu, `on_menu_mouse_down` reopens them.
+    pub fn on_window_mouse_down(&mut self, _app_state_no_data: AppStateNoData<T>, _event: WindowEvent<T>) -> UpdateScreen {
+        if self.is_open() {
+            self.closed_menus = Some(mem::replace(&mut self.open_menus, OpenMenus::default()));
+            UpdateScreen::Redraw
+        } else {
+            self.closed_menus = None;
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    /// Reopens the menus that `on_window_mouse_down` closed, since the mouse was pressed on a menu
+    pub fn on_menu_mouse_down(&mut self, _app_state_no_data: AppStateNoData<T>, _event: WindowEvent<T>) -> UpdateScreen {
+        match self.closed_menus.take() {
+            Some(closed_menus) => {
+                self.open_menus = closed_menus;
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Opens or closes the menu of the item of the menu bar
+    pub fn on_bar_item_mouse_down(&mut self, mut app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let index = match event.get_index_in_parent(event.hit_dom_node) {
+            Some((index, _)) => index,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        if self.is_bar_root() && self.open_menus.open.first() == Some(&index) {
+            self.close();
+            return UpdateScreen::Redraw;
+        }
+
+        self.open_menus = OpenMenus::default();
+        self.open_menus.highlighted = Some(index);
+        // Items of the menu bar without a submenu are activated on click
+        if let Some(callback) = self.activate(index) {
+            app_state_no_data.defer_callback(callback);
+            return UpdateScreen::Redraw;
+        }
+        // Opened with the mouse, so no item of the menu is highlighted
+        if !self.open_menus.open.is_empty() {
+            self.open_menus.highlighted = None;
+        }
+        UpdateScreen::Redraw
+    }
+
+    /// While a menu of the menu bar is open, hovering another item of the menu bar opens its menu instead
+    pub fn on_bar_item_mouse_over(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let index = match event.get_index_in_parent(event.hit_dom_node) {
+            Some((index, _)) => index,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let is_selectable = self.menu_bar.get(index).map(|item| item.is_selectable()).unwrap_or(false);
+        if !self.is_bar_root() || !is_selectable {
+            return UpdateScreen::DontRedraw;
+        }
+
+        match self.open_menus.open.first().cloned() {
+            Some(open) if open != index && !self.menu_bar[index].children.is_empty() => {
+                self.open_menus.open = vec![index];
+                self.open_menus.highlighted = None;
+                UpdateScreen::Redraw
+            },
+            None if self.open_menus.is_bar_active && self.open_menus.highlighted != Some(index) => {
+                self.open_menus.highlighted = Some(index);
+                UpdateScreen::Redraw
+            },
+            _ => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Highlights the hovered item and opens its submenu, closing the submenus of other items
+    pub fn on_item_mouse_over(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let path = match Self::get_item_path(&event, event.hit_dom_node) {
+            Some(path) => path,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let (index, parents) = match path.split_last() {
+            Some(split) => split,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let is_already_highlighted =
+            (self.open_menus.open.as_slice() == parents && self.open_menus.highlighted == Some(*index)) ||
+            self.open_menus.open.starts_with(&path);
+
+        if is_already_highlighted {
+            return UpdateScreen::DontRedraw;
+        }
+
+        self.open_menus.open = parents.to_vec();
+        self.open_menus.highlighted = Some(*index);
+
+        let opens_sub_menu = self.get_innermost_items().get(*index).map(|item| item.is_selectable() && !item.children.is_empty()).unwrap_or(false);
+        if opens_sub_menu {
+            self.open_menus.open.push(*index);
+            self.open_menus.highlighted = None;
+        }
+
+        UpdateScreen::Redraw
+    }
+
+    /// Activates the clicked item
+    pub fn on_item_mouse_up(&mut self, mut app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let path = match Self::get_item_path(&event, event.hit_dom_node) {
+            Some(path) => path,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let (index, parents) = match path.split_last() {
+            Some(split) => split,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        // Releasing the mouse on an item that opens a submenu keeps the submenu open
+        if self.open_menus.open.starts_with(&path) {
+            return UpdateScreen::DontRedraw;
+        }
+
+        self.open_menus.open = parents.to_vec();
+        if let Some(callback) = self.activate(*index) {
+            app_state_no_data.defer_callback(callback);
+        }
+
+        UpdateScreen::Redraw
+    }
+
+    /// Alt or F10 toggle the menu bar, Alt + a letter opens the menu with that mnemonic. In an open
+    /// menu, the arrow keys move the highlight and open / close submenus, Return or Space activate the
+    /// highlighted item, a letter activates the item with that mnemonic and Escape closes the innermost menu.
+    /// The keys that the menu uses don't propagate to the focused node inside of the menu.
+    pub fn on_virtual_key_down(&mut self, mut app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (key, alt_down) = {
+            let keyboard_state = app_state_no_data.windows[event.window].get_keyboard_state();
+            match keyboard_state.latest_virtual_keycode {
+                Some(key) => (key, keyboard_state.alt_down),
+                None => return UpdateScreen::DontRedraw,
+            }
+        };
+
+        match key {
+            VirtualKeyCode::LAlt | VirtualKeyCode::RAlt | VirtualKeyCode::F10 => {
+                if self.is_open() {
+                    self.close();
+                } else if !self.menu_bar.is_empty() {
+                    self.open_menus.is_bar_active = true;
+                    self.open_menus.highlighted = Self::find_selectable(&self.menu_bar, None, 1);
+                } else {
+                    return UpdateScreen::DontRedraw;
+                }
+                app_state_no_data.stop_propagation();
+                return UpdateScreen::Redraw;
+            },
+            _ => { },
+        }
+
+        let mnemonic = get_key_letter(key);
+
+        // Alt + letter opens the menu of the menu bar with the mnemonic
+        if alt_down && self.is_bar_root() {
+            let index = mnemonic.and_then(|mnemonic| {
+                self.menu_bar.iter().position(|item| item.is_selectable() && item.get_mnemonic() == Some(mnemonic))
+            });
+            return match index {
+                Some(index) => {
+                    self.open_menus = OpenMenus::default();
+                    self.open_menus.highlighted = Some(index);
+                    if let Some(callback) = self.activate(index) {
+                        app_state_no_data.defer_callback(callback);
+                    }
+                    app_state_no_data.stop_propagation();
+                    UpdateScreen::Redraw
+                },
+                None => UpdateScreen::DontRedraw,
+            };
+        }
+
+        if !self.is_open() {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let is_in_bar = self.is_bar_root() && self.open_menus.open.is_empty();
+        let top_depth = if self.is_bar_root() { 1 } else { 0 };
+        let highlighted_has_sub_menu = self.open_menus.highlighted
+            .and_then(|highlighted| self.get_innermost_items().get(highlighted))
+            .map(|item| item.is_selectable() && !item.children.is_empty())
+            .unwrap_or(false);
+
+        let mut callback = None;
+
+        match key {
+            VirtualKeyCode::Escape => self.close_innermost(),
+            VirtualKeyCode::Tab => self.close(),
+            VirtualKeyCode::Left | VirtualKeyCode::Right if is_in_bar => {
+                self.move_highlight(if key == VirtualKeyCode::Left { -1 } else { 1 });
+            },
+            VirtualKeyCode::Right if highlighted_has_sub_menu => {
+                if let Some(highlighted) = self.open_menus.highlighted {
+                    self.open_sub_menu(highlighted);
+                }
+            },
+            VirtualKeyCode::Right if self.is_bar_root() => self.switch_bar_menu(1),
+            VirtualKeyCode::Left if self.open_menus.open.len() > top_depth => self.close_innermost(),
+            VirtualKeyCode::Left if self.is_bar_root() => self.switch_bar_menu(-1),
+            VirtualKeyCode::Up if !is_in_bar => self.move_highlight(-1),
+            VirtualKeyCode::Down if !is_in_bar => self.move_highlight(1),
+            VirtualKeyCode::Down | VirtualKeyCode::Return | VirtualKeyCode::Space | VirtualKeyCode::NumpadEnter => {
+                if let Some(highlighted) = self.open_menus.highlighted {
+                    callback = self.activate(highlighted);
+                }
+            },
+            _ => {
+                match mnemonic.and_then(|mnemonic| self.find_mnemonic(mnemonic)) {
+                    Some(index) => {
+                        self.open_menus.highlighted = Some(index);
+                        callback = self.activate(index);
+                    },
+                    None => return UpdateScreen::DontRedraw,
+                }
+            },
+        }
+
+        if let Some(callback) = callback {
+            app_state_no_data.defer_callback(callback);
+        }
+
+        // The key was used by the menu, so a focused text input below the menu doesn't insert it
+        app_state_no_data.stop_propagation();
+        UpdateScreen::Redraw
+    }
+}
+
+/// Returns the (lowercase) letter of the key, for mnemonics
+fn get_key_letter(key: VirtualKeyCode) -> Option<char> {
+    use self::VirtualKeyCode::*;
+    let letter = match key {
+        A => 'a', B => 'b', C => 'c', D => 'd', E => 'e', F => 'f', G => 'g', H => 'h', I => 'i',
+        J => 'j', K => 'k', L => 'l', M => 'm', N => 'n', O => 'o', P => 'p', Q => 'q', R => 'r',
+        S => 's', T => 't', U => 'u', V => 'v', W => 'w', X => 'x', Y => 'y', Z => 'z',
+        _ => return None,
+    };
+    Some(letter)
+}
+
+#[test]
+fn test_menu_keyboard_navigation() {
+    use test_support::Mock;
+
+    fn save(_: &mut ::app_state::AppState<Mock>, _: WindowEvent<Mock>) -> UpdateScreen { UpdateScreen::Redraw }
+
+    let mut menu = MenuState::new(vec![
+        MenuItem::sub_menu("&File", vec![
+            MenuItem::new("&Save", Callback(save)).with_accelerator("Ctrl+S"),
+            MenuItem::new("Save &As", Callback(save)).with_enabled(false),
+            MenuItem::separator(),
+            MenuItem::sub_menu("&Recent", vec![MenuItem::new("a.txt", Callback(save))]),
+        ]),
+        MenuItem::sub_menu("&View", vec![MenuItem::checkable("&Grid && Rulers", false)]),
+    ]);
+
+    assert_eq!(menu.menu_bar[1].children[0].get_text(), "Grid & Rulers");
+    assert_eq!(menu.menu_bar[1].children[0].get_mnemonic(), Some('g'));
+
+    // Opening the file menu highlights "Save", moving down skips the disabled item and the separator
+    menu.activate(0);
+    assert_eq!((menu.open_menus.open.clone(), menu.open_menus.highlighted), (vec![0], Some(0)));
+    menu.move_highlight(1);
+    assert_eq!(menu.open_menus.highlighted, Some(3));
+    menu.move_highlight(1);
+    assert_eq!(menu.open_menus.highlighted, Some(0));
+
+    // Submenus are opened and closed again
+    assert!(menu.activate(3).is_none());
+    assert_eq!((menu.open_menus.open.clone(), menu.open_menus.highlighted), (vec![0, 3], Some(0)));
+    menu.close_innermost();
+    assert_eq!((menu.open_menus.open.clone(), menu.open_menus.highlighted), (vec![0], Some(3)));
+
+    // Moving past the end of the menu opens the next menu of the menu bar
+    menu.switch_bar_menu(1);
+    assert_eq!(menu.open_menus.open, vec![1]);
+    assert_eq!(menu.find_mnemonic('g'), Some(0));
+    assert!(menu.activate(0).is_none());
+    assert!(!menu.is_open());
+    assert_eq!(menu.get_item(&[1, 0]).and_then(|item| item.checked), Some(true));
+
+    // Activating an action closes the context menu and returns the callback
+    menu.open_context_menu(menu.menu_bar[0].children.clone(), (10.0, 10.0));
+    assert!(menu.activate(1).is_none());
+    assert!(menu.is_open());
+    assert!(menu.activate(0).is_some());
+    assert!(!menu.is_open());
+}
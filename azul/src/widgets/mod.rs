@@ -1,11 +1,29 @@
 #[cfg(feature = "svg")]
 pub mod svg;
 pub mod button;
+pub mod checkbox;
+pub mod dropdown;
+pub mod edit_history;
 pub mod label;
+pub mod list_view;
+pub mod menu;
+pub mod modal;
+pub mod radio_group;
+pub mod rich_text;
+mod row_offsets;
+pub mod slider;
+pub mod splitter;
+pub mod stack;
 pub mod text_input;
+pub mod text_area;
+pub mod switch;
 pub mod table_view;
+pub mod tabs;
+pub mod toast;
+pub mod tree_view;
 
 pub mod errors {
     #[cfg(all(feature = "svg", feature = "svg_parsing"))]
     pub use super::svg::SvgParseError;
+    pub use super::table_view::CsvError;
 }
\ No newline at end of file
@@ -0,0 +1,383 @@
+//! Modal dialogs that are drawn inside of the window, as an alternative to the native
+//! message boxes of the `dialogs` module
+//!
+//! A `Modal` wraps the content of a window: the open dialogs are drawn on top of it, over
+//! a dimmed backdrop that covers the content (see `Dom::with_overlay`), so the content
+//! can't be clicked while a dialog is open. The keyboard focus is kept inside of the topmost
+//! dialog (see `Dom::with_focus_trap`) and Escape closes it.
+//!
+//! Unlike the native message boxes, the dialogs don't block the thread. The content of a
+//! dialog is rendered by the application from its own dialog type `D` (the widgets of the
+//! content can be bound to the state in `D`), and the result is delivered to the `on_close`
+//! callback of the dialog. When a dialog is closed, the node that was focused before it was
+//! opened is focused again.
+//!
+//! ```rust,ignore
+//! enum MyDialog { ConfirmDelete(usize) }
+//!
+//! fn on_delete_clicked(app_state: &mut AppState<MyApp>, _event: WindowEvent<MyApp>) -> UpdateScreen {
+//!     let dialog = ModalDialog::new("Delete file?", MyDialog::ConfirmDelete(0))
+//!         .with_button("Delete", ModalResult::Accepted)
+//!         .with_button("Cancel", ModalResult::Rejected)
+//!         .with_on_close(Callback(on_dialog_closed));
+//!     app_state.data.modify(|data| data.modal.open(dialog));
+//!     UpdateScreen::Redraw
+//! }
+//!
+//! fn on_dialog_closed(app_state: &mut AppState<MyApp>, _event: WindowEvent<MyApp>) -> UpdateScreen {
+//!     let mut result = None;
+//!     app_state.data.modify(|data| result = data.modal.take_result());
+//!     if let Some((MyDialog::ConfirmDelete(file), ModalResult::Accepted)) = result {
+//!         // delete the file
+//!     }
+//!     UpdateScreen::Redraw
+//! }
+//! ```
+
+use std::{fmt, collections::VecDeque};
+use azul_css::{CssProperty, LayoutWidth, LayoutHeight};
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen, Callback, TabIndex},
+    window::{FakeWindow, WindowEvent},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback, with_page_scope},
+    app_state::AppStateNoData,
+    widgets::stack::new_page_key,
+};
+
+/// IDs of the dynamic CSS properties of the dialogs
+const DIALOG_WIDTH_ID: &str = "__azul-native-modal-dialog-width";
+const DIALOG_HEIGHT_ID: &str = "__azul-native-modal-dialog-height";
+
+/// Size of a dialog if no size is given, see `ModalDialog::with_size`
+pub const DEFAULT_DIALOG_SIZE: (f32, f32) = (400.0, 200.0);
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Modal {
+
+}
+
+/// How a dialog was closed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ModalResult {
+    /// Closed with an "Ok" / "Yes" button
+    Accepted,
+    /// Closed with a "No" button
+    Rejected,
+    /// Closed with Escape or a "Cancel" button
+    Cancelled,
+    /// Closed with a button that has an application-defined meaning
+    Custom(usize),
+}
+
+/// A button in the button row at the bottom of a dialog, closes the dialog with the `result`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ModalButton {
+    pub label: String,
+    pub result: ModalResult,
+}
+
+/// A dialog that is shown by a `Modal`. `D` is the application-defined type that the
+/// content of the dialog is rendered from, and that is returned with the result.
+pub struct ModalDialog<T: Layout, D> {
+    /// Shown in the title bar of the dialog
+    pub title: String,
+    /// What the dialog is about, passed to the function that renders the content of the dialog
+    pub dialog: D,
+    /// Buttons at the bottom of the dialog, from left to right
+    pub buttons: Vec<ModalButton>,
+    /// Width and height of the dialog in pixels. Absolutely positioned nodes can't be
+    /// sized by their content, so the dialog needs a fixed size.
+    pub size: (f32, f32),
+    /// Called after the dialog is closed, the result can be retrieved with `ModalState::take_result`
+    pub on_close: Option<Callback<T>>,
+}
+
+impl<T: Layout, D: Clone> Clone for ModalDialog<T, D> {
+    fn clone(&self) -> Self {
+        Self {
+            title: self.title.clone(),
+            dialog: self.dialog.clone(),
+            buttons: self.buttons.clone(),
+            size: self.size,
+            on_close: self.on_close,
+        }
+    }
+}
+
+impl<T: Layout, D: fmt::Debug> fmt::Debug for ModalDialog<T, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ModalDialog {{ title: {:?}, dialog: {:?}, buttons: {:?}, size: {:?}, on_close: {:?} }}",
+            self.title, self.dialog, self.buttons, self.size, self.on_close)
+    }
+}
+
+impl<T: Layout, D> ModalDialog<T, D> {
+
+    /// Creates a dialog without buttons, it can only be closed with Escape
+    pub fn new<S: Into<String>>(title: S, dialog: D) -> Self {
+        Self {
+            title: title.into(),
+            dialog,
+            buttons: Vec::new(),
+            size: DEFAULT_DIALOG_SIZE,
+            on_close: None,
+        }
+    }
+
+    /// Adds a button that closes the dialog with the `result`
+    pub fn with_button<S: Into<String>>(mut self, label: S, result: ModalResult) -> Self {
+        self.buttons.push(ModalButton { label: label.into(), result });
+        self
+    }
+
+    pub fn with_size(self, width: f32, height: f32) -> Self {
+        Self { size: (width, height), .. self }
+    }
+
+    pub fn with_on_close(self, on_close: Callback<T>) -> Self {
+        Self { on_close: Some(on_close), .. self }
+    }
+}
+
+/// The open dialogs and the results of the closed dialogs
+pub struct ModalState<T: Layout, D> {
+    /// The open dialogs and their keys, the last dialog is on top and receives the keyboard input
+    dialogs: Vec<(usize, ModalDialog<T, D>)>,
+    /// Results of the closed dialogs that haven't been taken by the application yet
+    results: VecDeque<(D, ModalResult)>,
+}
+
+impl<T: Layout, D> Default for ModalState<T, D> {
+    fn default() -> Self {
+        Self {
+            dialogs: Vec::new(),
+            results: VecDeque::new(),
+        }
+    }
+}
+
+impl<T: Layout, D: Clone> Clone for ModalState<T, D> {
+    fn clone(&self) -> Self {
+        Self {
+            dialogs: self.dialogs.clone(),
+            results: self.results.clone(),
+        }
+    }
+}
+
+impl<T: Layout, D: fmt::Debug> fmt::Debug for ModalState<T, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ModalState {{ dialogs: {:?}, results: {:?} }}", self.dialogs, self.results)
+    }
+}
+
+impl<T: Layout, D> ModalState<T, D> {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows the `dialog` on top of the open dialogs
+    pub fn open(&mut self, dialog: ModalDialog<T, D>) {
+        self.dialogs.push((new_page_key(), dialog));
+    }
+
+    /// Whether a dialog is open
+    pub fn is_open(&self) -> bool {
+        !self.dialogs.is_empty()
+    }
+
+    /// Returns the topmost dialog
+    pub fn get_top_dialog(&self) -> Option<&ModalDialog<T, D>> {
+        self.dialogs.last().map(|(_, dialog)| dialog)
+    }
+
+    /// Closes the topmost dialog with the `result`. Returns the `on_close` callback of
+    /// the dialog, which should be called with `AppStateNoData::defer_callback`.
+    pub fn close(&mut self, result: ModalResult) -> Option<Callback<T>> {
+        let (_, dialog) = self.dialogs.pop()?;
+        self.results.push_back((dialog.dialog, result));
+        dialog.on_close
+    }
+
+    /// Returns the result of the earliest closed dialog that hasn't been taken yet
+    pub fn take_result(&mut self) -> Option<(D, ModalResult)> {
+        self.results.pop_front()
+    }
+}
+
+impl Modal {
+
+    pub fn new() -> Self {
+        Self {
+
+        }
+    }
+
+    /// Renders the `content` of the window and the open dialogs on top of it. The content
+    /// of a dialog is rendered by `render_dialog`, the title and buttons are added by the `Modal`.
+    pub fn dom<T, D, F>(&self, state: &ModalState<T, D>, t: &T, window: &mut FakeWindow<T>, content: Dom<T>, render_dialog: F)
+    -> Dom<T> where T: Layout, F: Fn(&D) -> Dom<T>
+    {
+        let ptr = match StackCheckedPointer::new(t, state) {
+            Some(ptr) => ptr,
+            None => return Dom::new(NodeType::Label(
+                "Cannot create modal from heap-allocated ModalState, \
+                 please store the ModalState in the application data".into())
+            ),
+        };
+
+        // div.__azul-native-modal-window
+        //     |-> the content
+        //     '-> div.__azul-native-modal-backdrop (for each open dialog)
+        //         '-> div.__azul-native-modal-dialog
+        //             |-> div.__azul-native-modal-title
+        //             |   '-> p.__azul-native-modal-title-label
+        //             |-> div.__azul-native-modal-content
+        //             |   '-> the content of the dialog
+        //             '-> div.__azul-native-modal-buttons
+        //                 '-> div.__azul-native-modal-button
+        //                     '-> p.__azul-native-modal-button-label
+        //
+        // The children are keyed, so that opening a dialog doesn't change the identity
+        // of the content (and its focus and text selection). The backdrops are keyed by
+        // their dialog, so that the focus doesn't move to another dialog.
+
+        let mut modal_window = Dom::new(NodeType::Div)
+            .with_class("__azul-native-modal-window")
+            .with_child(content.with_key("content"));
+
+        if !state.is_open() {
+            return modal_window;
+        }
+
+        let key_down = window.add_callback(ptr, DefaultCallback(Self::modal_on_virtual_key_down::<T, D>));
+        let button_mouse_up = window.add_callback(ptr, DefaultCallback(Self::modal_on_button_mouse_up::<T, D>));
+        let top_dialog = state.dialogs.len() - 1;
+
+        for (dialog_index, (key, dialog)) in state.dialogs.iter().enumerate() {
+
+            let is_top_dialog = dialog_index == top_dialog;
+
+            let title = Dom::new(NodeType::Div)
+                .with_class("__azul-native-modal-title")
+                .with_child(Dom::new(NodeType::Label(dialog.title.clone())).with_class("__azul-native-modal-title-label"));
+
+            let dialog_content = Dom::new(NodeType::Div)
+                .with_class("__azul-native-modal-content")
+                .with_child(with_page_scope(&state.dialogs, *key, &dialog.dialog, find_dialog::<T, D>, || render_dialog(&dialog.dialog)));
+
+            let mut buttons = Dom::new(NodeType::Div).with_class("__azul-native-modal-buttons");
+            for button in &dialog.buttons {
+                let mut button_dom = Dom::new(NodeType::Div)
+                    .with_class("__azul-native-modal-button")
+                    .with_child(Dom::new(NodeType::Label(button.label.clone())).with_class("__azul-native-modal-button-label"));
+                if is_top_dialog {
+                    button_dom.add_default_callback_id(On::LeftMouseUp, button_mouse_up);
+                }
+                buttons.add_child(button_dom);
+            }
+
+            let mut dialog_dom = Dom::new(NodeType::Div)
+                .with_class("__azul-native-modal-dialog")
+                .with_style_override(DIALOG_WIDTH_ID, CssProperty::Width(LayoutWidth::px(dialog.size.0)))
+                .with_style_override(DIALOG_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(dialog.size.1)))
+                .with_child(title)
+                .with_child(dialog_content)
+                .with_child(buttons);
+
+            // Only the topmost dialog receives the keyboard input, the others are covered by its backdrop
+            if is_top_dialog {
+                dialog_dom = dialog_dom.with_tab_index(TabIndex::Auto).with_focus_trap(true);
+                dialog_dom.add_default_callback_id(On::VirtualKeyDown, key_down);
+            }
+
+            modal_window.add_child(Dom::new(NodeType::Div)
+                .with_class("__azul-native-modal-backdrop")
+                .with_key(*key)
+                .with_overlay(true)
+                .with_child(dialog_dom));
+        }
+
+        modal_window
+    }
+
+    fn modal_on_virtual_key_down<T: Layout, D>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(ModalState::<T, D>::on_virtual_key_down, data, event) }
+    }
+
+    fn modal_on_button_mouse_up<T: Layout, D>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(ModalState::<T, D>::on_button_mouse_up, data, event) }
+    }
+}
+
+/// Finds the content of the dialog with the key in the dialogs of a `ModalState`, see `with_page_scope`
+unsafe fn find_dialog<T: Layout, D>(dialogs: *const (), key: usize) -> Option<*const u8> {
+    // SAFETY: Only used with `&state.dialogs` of a `ModalState<T, D>`, see `Modal::dom`
+    let dialogs = &*(dialogs as *const Vec<(usize, ModalDialog<T, D>)>);
+    dialogs.iter().find(|(dialog_key, _)| *dialog_key == key).map(|(_, dialog)| &dialog.dialog as *const D as *const u8)
+}
+
+impl<T: Layout, D> ModalState<T, D> {
+
+    /// Escape closes the topmost dialog with `ModalResult::Cancelled`
+    pub fn on_virtual_key_down(&mut self, mut app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let key = app_state_no_data.windows[event.window].get_keyboard_state().latest_virtual_keycode;
+        if key != Some(VirtualKeyCode::Escape) || !self.is_open() {
+            return UpdateScreen::DontRedraw;
+        }
+
+        if let Some(callback) = self.close(ModalResult::Cancelled) {
+            app_state_no_data.defer_callback(callback);
+        }
+        UpdateScreen::Redraw
+    }
+
+    /// Closes the topmost dialog with the result of the clicked button
+    pub fn on_button_mouse_up(&mut self, mut app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let result = event.get_index_in_parent(event.hit_dom_node)
+            .and_then(|(index, _)| self.get_top_dialog()?.buttons.get(index))
+            .map(|button| button.result);
+
+        let result = match result {
+            Some(result) => result,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        if let Some(callback) = self.close(result) {
+            app_state_no_data.defer_callback(callback);
+        }
+        UpdateScreen::Redraw
+    }
+}
+
+#[test]
+fn test_modal_close_and_take_result() {
+    use test_support::Mock;
+
+    fn on_close(_: &mut ::app_state::AppState<Mock>, _: WindowEvent<Mock>) -> UpdateScreen { UpdateScreen::Redraw }
+
+    let mut modal = ModalState::<Mock, &str>::new();
+    assert!(!modal.is_open());
+    assert!(modal.close(ModalResult::Accepted).is_none());
+
+    modal.open(ModalDialog::new("Save changes?", "save")
+        .with_button("Save", ModalResult::Accepted)
+        .with_button("Discard", ModalResult::Rejected)
+        .with_on_close(Callback(on_close)));
+    modal.open(ModalDialog::new("Error", "error").with_size(300.0, 120.0));
+    assert_eq!(modal.get_top_dialog().map(|dialog| dialog.size), Some((300.0, 120.0)));
+
+    // Dialogs are closed from the top, the results are taken in the order the dialogs were closed
+    assert!(modal.close(ModalResult::Cancelled).is_none());
+    assert!(modal.is_open());
+    assert!(modal.close(ModalResult::Rejected).is_some());
+    assert!(!modal.is_open());
+    assert_eq!(modal.take_result(), Some(("error", ModalResult::Cancelled)));
+    assert_eq!(modal.take_result(), Some(("save", ModalResult::Rejected)));
+    assert_eq!(modal.take_result(), None);
+}
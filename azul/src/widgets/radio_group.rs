@@ -0,0 +1,155 @@
+//! Group of radio buttons, bound to an enum (or any other type that implements `RadioOption`)
+
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen, TabIndex},
+    window::{FakeWindow, WindowEvent},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback, DefaultCallbackId},
+    app_state::AppStateNoData,
+};
+
+/// A value that can be chosen with a `RadioGroup`, usually a C-like enum:
+///
+/// ```rust,ignore
+/// #[derive(Copy, Clone, PartialEq)]
+/// enum Alignment { Left, Center, Right }
+///
+/// impl RadioOption for Alignment {
+///     fn options() -> Vec<Self> {
+///         vec![Alignment::Left, Alignment::Center, Alignment::Right]
+///     }
+///     fn label(&self) -> String {
+///         format!("{:?}", self)
+///     }
+/// }
+/// ```
+pub trait RadioOption: Sized + Copy + PartialEq + 'static {
+    /// All values that can be chosen, in the order in which they are shown
+    fn options() -> Vec<Self>;
+    /// Text next to the radio button of this value
+    fn label(&self) -> String;
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct RadioGroup {
+    callbacks: Option<RadioGroupCallbacks>,
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+struct RadioGroupCallbacks {
+    option_mouse_up: DefaultCallbackId,
+    virtual_key_down: DefaultCallbackId,
+}
+
+impl RadioGroup {
+
+    pub fn new() -> Self {
+        Self { callbacks: None }
+    }
+
+    pub fn bind<T: Layout, U: RadioOption>(self, window: &mut FakeWindow<T>, field: &U, data: &T) -> Self {
+        let ptr = StackCheckedPointer::new(data, field);
+        let callbacks = ptr.and_then(|ptr| {
+            Some(RadioGroupCallbacks {
+                option_mouse_up: window.add_callback(ptr, DefaultCallback(on_option_mouse_up_private::<T, U>)),
+                virtual_key_down: window.add_callback(ptr, DefaultCallback(on_virtual_key_down_private::<T, U>)),
+            })
+        });
+
+        Self { callbacks }
+    }
+
+    pub fn dom<T: Layout, U: RadioOption>(&self, field: &U) -> Dom<T> {
+
+        // div.__azul-native-radio-group
+        //     '-> div.__azul-native-radio-option (one per option)
+        //         |-> div.__azul-native-radio-button (.__azul-native-radio-button-selected)
+        //         |   '-> div.__azul-native-radio-button-dot (only if selected)
+        //         '-> p.__azul-native-radio-label
+
+        let mut group = Dom::new(NodeType::Div)
+            .with_class("__azul-native-radio-group")
+            .with_tab_index(TabIndex::Auto);
+
+        if let Some(callbacks) = self.callbacks {
+            group.add_default_callback_id(On::VirtualKeyDown, callbacks.virtual_key_down);
+        }
+
+        for option in U::options() {
+
+            let mut radio_button = Dom::new(NodeType::Div).with_class("__azul-native-radio-button");
+
+            if option == *field {
+                radio_button.add_class("__azul-native-radio-button-selected");
+                radio_button.add_child(Dom::new(NodeType::Div).with_class("__azul-native-radio-button-dot"));
+            }
+
+            let mut option_dom = Dom::new(NodeType::Div)
+                .with_class("__azul-native-radio-option")
+                .with_child(radio_button)
+                .with_child(Dom::new(NodeType::Label(option.label())).with_class("__azul-native-radio-label"));
+
+            // Captures the pointer, so that releasing the mouse only selects the option it was pressed on
+            if let Some(callbacks) = self.callbacks {
+                option_dom.set_pointer_capture(true);
+                option_dom.add_default_callback_id(On::LeftMouseUp, callbacks.option_mouse_up);
+            }
+
+            group.add_child(option_dom);
+        }
+
+        group
+    }
+}
+
+fn on_option_mouse_up_private<T: Layout, U: RadioOption>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+    unsafe { data.invoke_mut(on_option_mouse_up::<T, U>, app_state_no_data, window_event) }
+}
+
+fn on_virtual_key_down_private<T: Layout, U: RadioOption>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+    unsafe { data.invoke_mut(on_virtual_key_down::<T, U>, app_state_no_data, window_event) }
+}
+
+/// Selects the clicked option, the options are the children of the group in the order of `U::options()`.
+/// Dragging on or off an option doesn't select it, the mouse has to be pressed and released on it.
+fn on_option_mouse_up<T: Layout, U: RadioOption>(value: &mut U, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+    if !event.has_pointer_capture() || !event.is_hovered() {
+        return UpdateScreen::DontRedraw;
+    }
+
+    let option = event.get_index_in_parent(event.hit_dom_node)
+        .and_then(|(index, _)| U::options().get(index).cloned());
+
+    match option {
+        Some(option) if option != *value => {
+            *value = option;
+            UpdateScreen::Redraw
+        },
+        _ => UpdateScreen::DontRedraw,
+    }
+}
+
+/// The arrow keys select the previous / next option, wrapping around at the ends
+fn on_virtual_key_down<T: Layout, U: RadioOption>(value: &mut U, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+    let direction = match app_state_no_data.windows[event.window].get_keyboard_state().latest_virtual_keycode {
+        Some(VirtualKeyCode::Up) | Some(VirtualKeyCode::Left) => -1,
+        Some(VirtualKeyCode::Down) | Some(VirtualKeyCode::Right) => 1,
+        _ => return UpdateScreen::DontRedraw,
+    };
+
+    let options = U::options();
+    if options.is_empty() {
+        return UpdateScreen::DontRedraw;
+    }
+
+    let new_index = match options.iter().position(|option| *option == *value) {
+        Some(index) => (index as isize + options.len() as isize + direction) as usize % options.len(),
+        None => 0,
+    };
+
+    *value = options[new_index];
+    UpdateScreen::Redraw
+}
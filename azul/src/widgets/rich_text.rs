@@ -0,0 +1,96 @@
+//! Paragraph that is made of differently styled spans of text
+
+use {
+    traits::Layout,
+    dom::{Dom, On, Callback},
+};
+
+/// A paragraph of text that is made of multiple `TextSpan`s, ex. to highlight a search match,
+/// to emphasize a word or to embed a link. The spans are laid out as one paragraph, so the
+/// lines are wrapped across the span boundaries.
+///
+/// Each span is a `display: inline` label (see the `__azul-native-rich-text-span` class of the
+/// native style), so the spans are styled with CSS: `font-family`, `font-size`, `color`,
+/// `background-color`, `text-decoration`, etc. For bold text, use a bold font as the `font-family`.
+#[derive(Debug, Clone)]
+pub struct RichText<T: Layout> {
+    pub spans: Vec<TextSpan<T>>,
+}
+
+/// A span of text inside of a `RichText`, styled by its IDs and classes
+#[derive(Debug, Clone)]
+pub struct TextSpan<T: Layout> {
+    pub text: String,
+    pub ids: Vec<String>,
+    pub classes: Vec<String>,
+    /// Callbacks of the span, ex. a `On::MouseUp` callback that opens a link.
+    /// Only the text of the span is hit-tested, not the other spans on the same lines.
+    pub callbacks: Vec<(On, Callback<T>)>,
+}
+
+impl<T: Layout> TextSpan<T> {
+
+    pub fn new<S>(text: S)
+    -> Self where S: Into<String>
+    {
+        Self {
+            text: text.into(),
+            ids: Vec::new(),
+            classes: Vec::new(),
+            callbacks: Vec::new(),
+        }
+    }
+
+    pub fn with_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.ids.push(id.into());
+        self
+    }
+
+    pub fn with_class<S: Into<String>>(mut self, class: S) -> Self {
+        self.classes.push(class.into());
+        self
+    }
+
+    pub fn with_callback(mut self, on: On, callback: Callback<T>) -> Self {
+        self.callbacks.push((on, callback));
+        self
+    }
+}
+
+impl<T: Layout> Default for RichText<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Layout> RichText<T> {
+
+    pub fn new() -> Self {
+        Self { spans: Vec::new() }
+    }
+
+    pub fn with_span(mut self, span: TextSpan<T>) -> Self {
+        self.spans.push(span);
+        self
+    }
+
+    pub fn dom(self) -> Dom<T> {
+        let mut paragraph = Dom::div().with_class("__azul-native-rich-text");
+
+        for span in self.spans {
+            let mut span_dom = Dom::label(span.text).with_class("__azul-native-rich-text-span");
+            for id in span.ids {
+                span_dom.add_id(id);
+            }
+            for class in span.classes {
+                span_dom.add_class(class);
+            }
+            for (on, callback) in span.callbacks {
+                span_dom.add_callback(on, callback);
+            }
+            paragraph.add_child(span_dom);
+        }
+
+        paragraph
+    }
+}
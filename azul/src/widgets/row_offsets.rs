@@ -0,0 +1,157 @@
+//! Vertical offsets of rows that are only rendered when they are visible
+//!
+//! Shared by the virtualized widgets (`ListView`, `TextArea`): the rows that have been rendered
+//! are measured, all other rows are assumed to have an estimated height. Rows can't be lower
+//! than the estimate, so that the estimate never renders too few rows to fill the visible area.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RowOffsets {
+    /// Height of the rows that haven't been measured yet
+    estimate: f32,
+    /// Heights of the rows that have been rendered, by the index of the row
+    measured: BTreeMap<usize, f32>,
+}
+
+impl RowOffsets {
+
+    pub(crate) fn new(estimate: f32) -> Self {
+        Self {
+            estimate: estimate.max(1.0),
+            measured: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn get_estimate(&self) -> f32 {
+        self.estimate
+    }
+
+    /// Changes the estimated height, forgets the measured heights if it changed
+    pub(crate) fn set_estimate(&mut self, estimate: f32) {
+        let estimate = estimate.max(1.0);
+        if estimate != self.estimate {
+            self.estimate = estimate;
+            self.measured.clear();
+        }
+    }
+
+    /// Stores the height of a rendered row, a row is at least as high as the estimate
+    pub(crate) fn measure(&mut self, row: usize, height: f32) {
+        self.measured.insert(row, height.max(self.estimate));
+    }
+
+    /// Forgets the heights of the row `row` and all rows after it
+    pub(crate) fn forget_from(&mut self, row: usize) {
+        let _ = self.measured.split_off(&row);
+    }
+
+    /// Forgets the heights of all rows
+    pub(crate) fn clear(&mut self) {
+        self.measured.clear();
+    }
+
+    /// Returns how many rows have been measured
+    pub(crate) fn get_measured_count(&self) -> usize {
+        self.measured.len()
+    }
+
+    /// Returns the measured height of the row, or the estimate if it hasn't been rendered yet
+    pub(crate) fn get_row_height(&self, row: usize) -> f32 {
+        self.measured.get(&row).cloned().unwrap_or(self.estimate)
+    }
+
+    /// Returns the height of the first `row_count` rows together
+    pub(crate) fn get_total_height(&self, row_count: usize) -> f32 {
+        self.get_row_offset(row_count)
+    }
+
+    /// Returns the distance from the top of the first row to the top of the row
+    pub(crate) fn get_row_offset(&self, row: usize) -> f32 {
+        let correction: f32 = self.measured.range(..row).map(|(_, height)| height - self.estimate).sum();
+        row as f32 * self.estimate + correction
+    }
+
+    /// Returns the row at the distance `y` from the top of the first row and the offset of
+    /// that row, `None` if there are no rows or `y` is below the last row
+    pub(crate) fn get_row_at_offset(&self, y: f32, row_count: usize) -> Option<(usize, f32)> {
+        if row_count == 0 || y < 0.0 {
+            return None;
+        }
+
+        let mut correction = 0.0;
+
+        for (row, height) in self.measured.range(..row_count) {
+            let row_offset = *row as f32 * self.estimate + correction;
+            if y < row_offset {
+                break;
+            }
+            if y < row_offset + height {
+                return Some((*row, row_offset));
+            }
+            correction += height - self.estimate;
+        }
+
+        // The row is between two measured rows, so all rows in between have the estimated height
+        let row = ((y - correction) / self.estimate).floor() as usize;
+        if row < row_count {
+            Some((row, row as f32 * self.estimate + correction))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the first and the last (exclusive) row that have to be rendered to fill the
+    /// visible area starting at `scroll_y`, plus `overscan` rows above and below it, together
+    /// with the offset of the first rendered row
+    pub(crate) fn get_rendered_rows(&self, scroll_y: f32, viewport_height: f32, row_count: usize, overscan: usize) -> (usize, usize, f32) {
+        let (first_visible_row, first_visible_row_offset) = match self.get_row_at_offset(scroll_y, row_count) {
+            Some(s) => s,
+            None => return (0, 0, 0.0),
+        };
+
+        let first_row = first_visible_row.saturating_sub(overscan);
+        let first_row_offset = first_visible_row_offset - (first_row..first_visible_row).map(|row| self.get_row_height(row)).sum::<f32>();
+
+        // Since `measure` doesn't store heights below the estimate, the rows
+        // that are actually rendered are at least as high as calculated here
+        let mut last_row = first_visible_row;
+        let mut row_bottom = first_visible_row_offset;
+        while last_row < row_count && row_bottom < scroll_y + viewport_height {
+            row_bottom += self.get_row_height(last_row);
+            last_row += 1;
+        }
+
+        (first_row, (last_row + overscan).min(row_count), first_row_offset)
+    }
+}
+
+/// Returns the `flex-grow` of the track above the thumb, of the thumb and of the track below the
+/// thumb of a scrollbar, so that the thumb doesn't need the height of the scrollbar to be sized
+pub(crate) fn get_scrollbar_segments(scroll_y: f32, viewport_height: f32, total_height: f32) -> (f32, f32, f32) {
+    let viewport_height = viewport_height.min(total_height);
+    let below_height = total_height - viewport_height - scroll_y;
+    (scroll_y.max(0.0), viewport_height.max(1.0), below_height.max(0.0))
+}
+
+#[test]
+fn test_row_offsets() {
+    let mut rows = RowOffsets::new(20.0);
+    rows.measure(1, 50.0);
+    // Rows can't be lower than the estimate
+    rows.measure(3, 5.0);
+    assert_eq!(rows.get_row_height(3), 20.0);
+    assert_eq!(rows.get_total_height(10), 230.0);
+    assert_eq!(rows.get_row_offset(2), 70.0);
+    assert_eq!(rows.get_row_at_offset(45.0, 10), Some((1, 20.0)));
+    assert_eq!(rows.get_row_at_offset(230.0, 10), None);
+    assert_eq!(rows.get_rendered_rows(75.0, 40.0, 10, 1), (1, 6, 20.0));
+
+    rows.forget_from(2);
+    assert_eq!(rows.get_row_offset(5), 130.0);
+    rows.set_estimate(10.0);
+    assert_eq!(rows.get_total_height(10), 100.0);
+
+    assert_eq!(get_scrollbar_segments(30.0, 100.0, 400.0), (30.0, 100.0, 270.0));
+    assert_eq!(get_scrollbar_segments(0.0, 100.0, 50.0), (0.0, 50.0, 0.0));
+}
@@ -0,0 +1,421 @@
+//! Slider for choosing a number between a minimum and a maximum, either with one
+//! thumb or with two thumbs for choosing a range (ex. for filtering by price)
+//!
+//! The thumbs are positioned by the `flex-grow` of the track segments between them,
+//! so that the slider doesn't need to know its own size until it is clicked.
+
+use azul_css::{CssProperty, LayoutFlexGrow, FloatValue};
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen, TabIndex},
+    window::{FakeWindow, WindowEvent},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback, DefaultCallbackId},
+    app_state::AppStateNoData,
+};
+
+/// ID of the dynamic `flex-grow` property of the track segments, see the native CSS
+const SEGMENT_GROW_ID: &str = "__azul-native-slider-segment-grow";
+/// Number of arrow key presses that move the thumb from the minimum to the
+/// maximum if the slider has no `step`
+const DEFAULT_KEY_STEPS: f32 = 100.0;
+/// Number of PageUp / PageDown presses that move the thumb from the minimum to the maximum
+const DEFAULT_PAGE_STEPS: f32 = 10.0;
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum SliderOrientation {
+    /// The minimum is on the left, the maximum on the right
+    Horizontal,
+    /// The minimum is at the bottom, the maximum at the top
+    Vertical,
+}
+
+impl Default for SliderOrientation {
+    fn default() -> Self {
+        SliderOrientation::Horizontal
+    }
+}
+
+/// One of the two thumbs of a range slider, a slider with one thumb only has the `Lower` thumb
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum SliderThumb {
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Slider {
+    callbacks: Option<SliderCallbacks>,
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+struct SliderCallbacks {
+    mouse_down: DefaultCallbackId,
+    mouse_over: DefaultCallbackId,
+    virtual_key_down: DefaultCallbackId,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SliderState {
+    /// The current value, or the lower end of the range if the slider has two thumbs
+    pub value: f32,
+    /// The upper end of the range, `None` if the slider only has one thumb
+    pub upper_value: Option<f32>,
+    pub min: f32,
+    pub max: f32,
+    /// The values are rounded to multiples of the `step` (counted from the `min`),
+    /// the arrow keys move the thumb by one step. Default: `0.0` (no rounding)
+    pub step: f32,
+    /// How far PageUp / PageDown move the thumb. Default: a tenth of the range
+    pub page_step: f32,
+    pub orientation: SliderOrientation,
+    /// The thumb that was last clicked or dragged, which is moved by the keyboard
+    pub active_thumb: SliderThumb,
+}
+
+impl SliderState {
+
+    /// Creates a slider with one thumb at the `value` (clamped to `min..=max`)
+    pub fn new(value: f32, min: f32, max: f32) -> Self {
+        let mut state = Self {
+            value: min,
+            upper_value: None,
+            min,
+            max,
+            step: 0.0,
+            page_step: (max - min) / DEFAULT_PAGE_STEPS,
+            orientation: SliderOrientation::default(),
+            active_thumb: SliderThumb::Lower,
+        };
+        state.set_value(SliderThumb::Lower, value);
+        state
+    }
+
+    /// Creates a slider with two thumbs, for choosing the range `lower..=upper`
+    pub fn range(lower: f32, upper: f32, min: f32, max: f32) -> Self {
+        let mut state = Self { upper_value: Some(max), .. Self::new(min, min, max) };
+        state.set_value(SliderThumb::Upper, upper);
+        state.set_value(SliderThumb::Lower, lower);
+        state
+    }
+
+    /// Rounds the value(s) to multiples of the `step`
+    pub fn with_step(self, step: f32) -> Self {
+        let mut state = Self { step, .. self };
+        let (value, upper_value) = (state.value, state.upper_value);
+        if let Some(upper) = upper_value {
+            state.set_value(SliderThumb::Upper, upper);
+        }
+        state.set_value(SliderThumb::Lower, value);
+        state
+    }
+
+    pub fn with_page_step(self, page_step: f32) -> Self {
+        Self { page_step, .. self }
+    }
+
+    pub fn with_orientation(self, orientation: SliderOrientation) -> Self {
+        Self { orientation, .. self }
+    }
+
+    /// Returns the value of the `thumb` (the `value` for the `Lower` thumb)
+    pub fn get_value(&self, thumb: SliderThumb) -> f32 {
+        match (thumb, self.upper_value) {
+            (SliderThumb::Upper, Some(upper)) => upper,
+            _ => self.value,
+        }
+    }
+
+    /// Sets the value of the `thumb`, rounded to the `step` and clamped to the slider range.
+    /// In range mode, the thumbs can't be moved past each other.
+    /// Returns whether the value has changed.
+    pub fn set_value(&mut self, thumb: SliderThumb, value: f32) -> bool {
+        let value = self.snap(value);
+        match (thumb, self.upper_value) {
+            (SliderThumb::Upper, Some(upper)) => {
+                let new_upper = value.max(self.value);
+                self.upper_value = Some(new_upper);
+                new_upper != upper
+            },
+            (_, upper) => {
+                let new_value = upper.map(|upper| value.min(upper)).unwrap_or(value);
+                let changed = new_value != self.value;
+                self.value = new_value;
+                changed
+            },
+        }
+    }
+
+    /// Position of the `thumb` on the track, from `0.0` (at the `min`) to `1.0` (at the `max`)
+    pub fn get_fraction(&self, thumb: SliderThumb) -> f32 {
+        if self.max <= self.min {
+            return 0.0;
+        }
+        ((self.get_value(thumb) - self.min) / (self.max - self.min)).max(0.0).min(1.0)
+    }
+
+    /// Clamps the value to `min..=max` and rounds it to the nearest step
+    fn snap(&self, value: f32) -> f32 {
+        let value = if self.step > 0.0 {
+            self.min + ((value - self.min) / self.step).round() * self.step
+        } else {
+            value
+        };
+        value.max(self.min).min(self.max)
+    }
+
+    /// Returns the thumb that should move to the clicked `value`
+    fn get_closest_thumb(&self, value: f32) -> SliderThumb {
+        match self.upper_value {
+            None => SliderThumb::Lower,
+            Some(upper) => {
+                let lower_distance = (value - self.value).abs();
+                let upper_distance = (value - upper).abs();
+                // If both thumbs are at the same position, move the one in the direction of the click
+                if upper_distance < lower_distance || (upper_distance == lower_distance && value > upper) {
+                    SliderThumb::Upper
+                } else {
+                    SliderThumb::Lower
+                }
+            }
+        }
+    }
+
+    /// How far the arrow keys move the thumb
+    fn get_key_step(&self) -> f32 {
+        if self.step > 0.0 {
+            self.step
+        } else {
+            (self.max - self.min) / DEFAULT_KEY_STEPS
+        }
+    }
+
+    /// Returns the value under the mouse cursor for the `thumb`, from the rectangles of the
+    /// track and the thumb of the last frame (the track is the first child of the slider)
+    fn get_value_at_cursor<T: Layout>(&self, thumb: SliderThumb, event: &WindowEvent<T>) -> Option<f32> {
+
+        let (track, thumb_node) = {
+            let arena = event.ui_state.dom.arena.borrow();
+            let track = arena.node_layout[event.hit_dom_node].first_child?;
+            let thumb_node = arena.node_layout[track].first_child.and_then(|segment| arena.node_layout[segment].next_sibling)?;
+            (track, thumb_node)
+        };
+
+        let track_rect = event.get_node_rect(track)?;
+        let thumb_rect = event.get_node_rect(thumb_node)?;
+        let (cursor_x, cursor_y) = event.cursor_in_viewport;
+
+        // Distance of the cursor from the `min` end of the track, length of the track and the thumb
+        let (offset, track_length, thumb_length) = match self.orientation {
+            SliderOrientation::Horizontal => (cursor_x - track_rect.origin.x, track_rect.size.width, thumb_rect.size.width),
+            SliderOrientation::Vertical => (track_rect.origin.y + track_rect.size.height - cursor_y, track_rect.size.height, thumb_rect.size.height),
+        };
+
+        // The thumbs take up space on the track, so the center of the
+        // thumb with the index `i` can only move in `(i + 0.5) * thumb_length..`
+        let thumb_count = if self.upper_value.is_some() { 2.0 } else { 1.0 };
+        let thumb_index = if thumb == SliderThumb::Upper && self.upper_value.is_some() { 1.0 } else { 0.0 };
+        let usable_length = track_length - thumb_count * thumb_length;
+        if usable_length <= 0.0 {
+            return None;
+        }
+
+        let fraction = ((offset - (thumb_index + 0.5) * thumb_length) / usable_length).max(0.0).min(1.0);
+        Some(self.min + fraction * (self.max - self.min))
+    }
+}
+
+impl Slider {
+
+    pub fn new() -> Self {
+        Self { callbacks: None }
+    }
+
+    pub fn bind<T: Layout>(self, window: &mut FakeWindow<T>, field: &SliderState, data: &T) -> Self {
+        let ptr = StackCheckedPointer::new(data, field);
+        let callbacks = ptr.and_then(|ptr| {
+            Some(SliderCallbacks {
+                mouse_down: window.add_callback(ptr, DefaultCallback(SliderState::on_mouse_down_private)),
+                mouse_over: window.add_callback(ptr, DefaultCallback(SliderState::on_mouse_over_private)),
+                virtual_key_down: window.add_callback(ptr, DefaultCallback(SliderState::on_virtual_key_down_private)),
+            })
+        });
+
+        Self { callbacks }
+    }
+
+    pub fn dom<T: Layout>(&self, field: &SliderState) -> Dom<T> {
+
+        // div.__azul-native-slider (.__azul-native-slider-vertical)
+        //     '-> div.__azul-native-slider-track (.__azul-native-slider-track-vertical)
+        //         |-> div.__azul-native-slider-segment (.__azul-native-slider-segment-filled)
+        //         |-> div.__azul-native-slider-thumb (.__azul-native-slider-thumb-active)
+        //         |-> div.__azul-native-slider-segment (.__azul-native-slider-segment-filled)
+        //         |-> div.__azul-native-slider-thumb (only in range mode)
+        //         '-> div.__azul-native-slider-segment (only in range mode)
+        //
+        // The segments also have either the class `.__azul-native-slider-segment-horizontal`
+        // or `.__azul-native-slider-segment-vertical`, which sets their thickness.
+        //
+        // The segments are filled between the minimum and the thumb or between the two thumbs.
+        // Vertical tracks are laid out with `flex-direction: column-reverse`.
+
+        let is_vertical = field.orientation == SliderOrientation::Vertical;
+
+        let mut slider = Dom::new(NodeType::Div)
+            .with_class("__azul-native-slider")
+            .with_tab_index(TabIndex::Auto)
+            .with_pointer_capture(true);
+
+        if is_vertical {
+            slider.add_class("__azul-native-slider-vertical");
+        }
+
+        if let Some(callbacks) = self.callbacks {
+            slider.add_default_callback_id(On::LeftMouseDown, callbacks.mouse_down);
+            slider.add_default_callback_id(On::MouseOver, callbacks.mouse_over);
+            slider.add_default_callback_id(On::VirtualKeyDown, callbacks.virtual_key_down);
+        }
+
+        let mut track = Dom::new(NodeType::Div).with_class("__azul-native-slider-track");
+        if is_vertical {
+            track.add_class("__azul-native-slider-track-vertical");
+        }
+
+        let lower = field.get_fraction(SliderThumb::Lower);
+
+        match field.upper_value {
+            None => {
+                track.add_child(segment(lower, true, is_vertical));
+                track.add_child(thumb(false));
+                track.add_child(segment(1.0 - lower, false, is_vertical));
+            },
+            Some(_) => {
+                let upper = field.get_fraction(SliderThumb::Upper);
+                track.add_child(segment(lower, false, is_vertical));
+                track.add_child(thumb(field.active_thumb == SliderThumb::Lower));
+                track.add_child(segment(upper - lower, true, is_vertical));
+                track.add_child(thumb(field.active_thumb == SliderThumb::Upper));
+                track.add_child(segment(1.0 - upper, false, is_vertical));
+            },
+        }
+
+        slider.with_child(track)
+    }
+}
+
+/// Part of the track between the thumbs, `length` is the fraction of the track that it takes up
+fn segment<T: Layout>(length: f32, filled: bool, is_vertical: bool) -> Dom<T> {
+    let mut segment = Dom::new(NodeType::Div)
+        .with_class("__azul-native-slider-segment")
+        .with_style_override(SEGMENT_GROW_ID, CssProperty::FlexGrow(LayoutFlexGrow(FloatValue::new(length.max(0.0)))));
+    if filled {
+        segment.add_class("__azul-native-slider-segment-filled");
+    }
+    // The thickness of the segment, its length is determined by the `flex-grow`
+    segment.add_class(if is_vertical { "__azul-native-slider-segment-vertical" } else { "__azul-native-slider-segment-horizontal" });
+    segment
+}
+
+fn thumb<T: Layout>(active: bool) -> Dom<T> {
+    let mut thumb = Dom::new(NodeType::Div).with_class("__azul-native-slider-thumb");
+    if active {
+        thumb.add_class("__azul-native-slider-thumb-active");
+    }
+    thumb
+}
+
+impl SliderState {
+
+    fn on_mouse_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_mouse_down, app_state_no_data, window_event) }
+    }
+
+    fn on_mouse_over_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_mouse_over, app_state_no_data, window_event) }
+    }
+
+    fn on_virtual_key_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_virtual_key_down, app_state_no_data, window_event) }
+    }
+
+    /// Clicking on the track moves the closest thumb to the cursor, dragging continues from there
+    pub fn on_mouse_down<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        // Which thumb is closer can only be decided on the value without thumb offsets
+        let value = match self.get_value_at_cursor(SliderThumb::Lower, &event) {
+            Some(value) => value,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let thumb = self.get_closest_thumb(value);
+        let value = self.get_value_at_cursor(thumb, &event).unwrap_or(value);
+        let thumb_changed = self.active_thumb != thumb;
+        self.active_thumb = thumb;
+
+        if self.set_value(thumb, value) || thumb_changed {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    /// Moves the active thumb while the mouse is dragged, even outside of the slider
+    /// (the slider captures the pointer while the mouse button is down)
+    pub fn on_mouse_over<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        if !event.has_pointer_capture() || !app_state_no_data.windows[event.window].get_mouse_state().left_down {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let thumb = self.active_thumb;
+        match self.get_value_at_cursor(thumb, &event) {
+            Some(value) if self.set_value(thumb, value) => UpdateScreen::Redraw,
+            _ => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// The arrow keys move the active thumb by one step, PageUp / PageDown by one
+    /// page step, Home / End move it to the minimum / maximum
+    pub fn on_virtual_key_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let thumb = self.active_thumb;
+        let value = self.get_value(thumb);
+
+        let new_value = match app_state_no_data.windows[event.window].get_keyboard_state().latest_virtual_keycode {
+            Some(VirtualKeyCode::Left) | Some(VirtualKeyCode::Down) => value - self.get_key_step(),
+            Some(VirtualKeyCode::Right) | Some(VirtualKeyCode::Up) => value + self.get_key_step(),
+            Some(VirtualKeyCode::PageDown) => value - self.page_step,
+            Some(VirtualKeyCode::PageUp) => value + self.page_step,
+            Some(VirtualKeyCode::Home) => self.min,
+            Some(VirtualKeyCode::End) => self.max,
+            _ => return UpdateScreen::DontRedraw,
+        };
+
+        if self.set_value(thumb, new_value) {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+}
+
+#[test]
+fn test_slider_state_step_and_range() {
+    let mut slider = SliderState::new(0.33, 0.0, 1.0).with_step(0.25);
+    assert_eq!(slider.value, 0.25);
+    assert!(slider.set_value(SliderThumb::Lower, 2.0));
+    assert_eq!(slider.value, 1.0);
+    assert!(!slider.set_value(SliderThumb::Lower, 0.9));
+
+    // The thumbs of a range slider can't be moved past each other
+    let mut range = SliderState::range(20.0, 80.0, 0.0, 100.0).with_step(10.0);
+    assert!(range.set_value(SliderThumb::Lower, 95.0));
+    assert_eq!((range.value, range.upper_value), (80.0, Some(80.0)));
+    assert_eq!(range.get_closest_thumb(90.0), SliderThumb::Upper);
+    assert_eq!(range.get_closest_thumb(10.0), SliderThumb::Lower);
+    assert_eq!(range.get_fraction(SliderThumb::Lower), 0.8);
+    assert!(!range.set_value(SliderThumb::Upper, 56.0));
+    assert!(range.set_value(SliderThumb::Lower, 31.0));
+    assert!(range.set_value(SliderThumb::Upper, 56.0));
+    assert_eq!((range.value, range.upper_value), (30.0, Some(60.0)));
+}
@@ -0,0 +1,416 @@
+//! Panes separated by draggable dividers, ex. for the side bars and panels of an IDE
+//!
+//! All panes have a size in pixels, except for the last pane that isn't collapsed, which
+//! takes up the remaining space. The sizes are stored in the `SplitterState`, so they can
+//! be saved and restored. Double-clicking a divider collapses the pane next to it.
+
+use std::{f32, time::Instant};
+use azul_css::{CssProperty, LayoutWidth, LayoutHeight};
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen},
+    window::{FakeWindow, WindowEvent},
+    default_callbacks::{StackCheckedPointer, DefaultCallback},
+    app_state::AppStateNoData,
+    id_tree::NodeId,
+    window_state::ClickCounter,
+};
+
+/// IDs of the dynamic `width` / `height` properties of the panes, see the native CSS
+const PANE_WIDTH_ID: &str = "__azul-native-splitter-pane-width";
+const PANE_HEIGHT_ID: &str = "__azul-native-splitter-pane-height";
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum SplitterOrientation {
+    /// The panes are next to each other, the dividers are vertical lines
+    Horizontal,
+    /// The panes are below each other, the dividers are horizontal lines
+    Vertical,
+}
+
+impl Default for SplitterOrientation {
+    fn default() -> Self {
+        SplitterOrientation::Horizontal
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SplitterPane {
+    /// Width (or height, in a vertical splitter) of the pane in pixels. The size of the pane that
+    /// fills the remaining space is only updated when one of its dividers is dragged.
+    pub size: f32,
+    /// The pane can't be dragged smaller than this. Default: `0.0`
+    pub min_size: f32,
+    /// The pane can't be dragged larger than this. Default: `f32::INFINITY`
+    pub max_size: f32,
+    /// Whether double-clicking a divider next to the pane collapses it. Default: `true`
+    pub collapsible: bool,
+    /// Collapsed panes are hidden, but keep their size for when they are expanded again
+    pub collapsed: bool,
+}
+
+impl SplitterPane {
+
+    pub fn new(size: f32) -> Self {
+        Self {
+            size,
+            min_size: 0.0,
+            max_size: f32::INFINITY,
+            collapsible: true,
+            collapsed: false,
+        }
+    }
+
+    pub fn with_min_size(self, min_size: f32) -> Self {
+        Self { min_size, size: self.size.max(min_size), .. self }
+    }
+
+    pub fn with_max_size(self, max_size: f32) -> Self {
+        Self { max_size, size: self.size.min(max_size), .. self }
+    }
+
+    pub fn with_collapsible(self, collapsible: bool) -> Self {
+        Self { collapsible, .. self }
+    }
+
+    pub fn with_collapsed(self, collapsed: bool) -> Self {
+        Self { collapsed, .. self }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Splitter {
+
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SplitterState {
+    pub orientation: SplitterOrientation,
+    pub panes: Vec<SplitterPane>,
+    /// The divider that is being dragged
+    drag: Option<SplitterDrag>,
+    /// Clicks on the dividers, for detecting double clicks
+    clicks: ClickCounter<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SplitterDrag {
+    /// Index of the divider, the divider `i` is between the panes `i` and `i + 1`
+    divider: usize,
+    /// Position of the cursor along the splitter when the divider was pressed
+    start_cursor: f32,
+    /// Laid out sizes of the panes when the divider was pressed
+    start_sizes: Vec<f32>,
+}
+
+impl SplitterState {
+
+    pub fn new(orientation: SplitterOrientation, panes: Vec<SplitterPane>) -> Self {
+        Self {
+            orientation,
+            panes,
+            drag: None,
+            clicks: ClickCounter::default(),
+        }
+    }
+
+    /// Returns the index of the pane that takes up the remaining space: the last pane that isn't collapsed
+    pub fn get_fill_pane(&self) -> Option<usize> {
+        self.panes.iter().rposition(|pane| !pane.collapsed)
+    }
+
+    /// Collapses one of the two panes next to the divider, or expands it if it is collapsed.
+    /// The last visible pane can't be collapsed. Returns whether a pane was collapsed or expanded.
+    pub fn toggle_collapsed(&mut self, divider: usize) -> bool {
+
+        let adjacent_panes = [divider, divider + 1];
+
+        let collapsed = adjacent_panes.iter().cloned()
+            .find(|index| self.panes.get(*index).map(|pane| pane.collapsible && pane.collapsed).unwrap_or(false));
+
+        if let Some(index) = collapsed {
+            self.panes[index].collapsed = false;
+            return true;
+        }
+
+        let visible_panes = self.panes.iter().filter(|pane| !pane.collapsed).count();
+        let collapsible = adjacent_panes.iter().cloned()
+            .find(|index| self.panes.get(*index).map(|pane| pane.collapsible).unwrap_or(false));
+
+        match collapsible {
+            Some(index) if visible_panes > 1 => {
+                self.panes[index].collapsed = true;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Moves the divider by `delta` pixels from where it was when the panes had the `start_sizes`.
+    /// The panes next to the divider are resized (and expanded, if they were collapsed) within their
+    /// minimum and maximum sizes, the other panes keep their size. Returns whether a size has changed.
+    pub fn move_divider(&mut self, divider: usize, start_sizes: &[f32], delta: f32) -> bool {
+
+        let (left, right) = (divider, divider + 1);
+        if right >= self.panes.len() || start_sizes.len() != self.panes.len() {
+            return false;
+        }
+
+        let (left_size, right_size) = (start_sizes[left], start_sizes[right]);
+        let (left_pane, right_pane) = (self.panes[left], self.panes[right]);
+
+        let min_delta = (left_pane.min_size - left_size).max(right_size - right_pane.max_size);
+        let max_delta = (left_pane.max_size - left_size).min(right_size - right_pane.min_size);
+        let delta = delta.min(max_delta).max(min_delta);
+
+        let panes_before = self.panes.clone();
+
+        for (pane, size) in self.panes.iter_mut().zip(start_sizes.iter()) {
+            if !pane.collapsed {
+                pane.size = *size;
+            }
+        }
+
+        self.panes[left] = SplitterPane { size: left_size + delta, collapsed: false, .. left_pane };
+        self.panes[right] = SplitterPane { size: right_size - delta, collapsed: false, .. right_pane };
+
+        self.panes != panes_before
+    }
+
+    /// Returns the divider that the event was fired on and the splitter node,
+    /// the dividers are every second child of the splitter
+    fn get_divider<T: Layout>(event: &WindowEvent<T>) -> Option<(usize, NodeId)> {
+        let (child_index, splitter) = event.get_index_in_parent(event.hit_dom_node)?;
+        Some((child_index / 2, splitter))
+    }
+
+    /// Returns the position of the cursor along the splitter
+    fn get_cursor_position<T: Layout>(&self, event: &WindowEvent<T>) -> f32 {
+        match self.orientation {
+            SplitterOrientation::Horizontal => event.cursor_in_viewport.0,
+            SplitterOrientation::Vertical => event.cursor_in_viewport.1,
+        }
+    }
+
+    /// Returns the laid out sizes of the panes (every other child of the `splitter`) in the last frame
+    fn measure_panes<T: Layout>(&self, event: &WindowEvent<T>, splitter: NodeId) -> Option<Vec<f32>> {
+
+        let pane_nodes = {
+            let arena = event.ui_state.dom.arena.borrow();
+            let mut pane_nodes = Vec::new();
+            let mut child = arena.node_layout[splitter].first_child;
+            while let Some(pane) = child {
+                pane_nodes.push(pane);
+                child = arena.node_layout[pane].next_sibling.and_then(|divider| arena.node_layout[divider].next_sibling);
+            }
+            pane_nodes
+        };
+
+        pane_nodes.into_iter().map(|pane| {
+            let rect = event.get_node_rect(pane)?;
+            Some(match self.orientation {
+                SplitterOrientation::Horizontal => rect.size.width,
+                SplitterOrientation::Vertical => rect.size.height,
+            })
+        }).collect()
+    }
+}
+
+impl Splitter {
+
+    pub fn new() -> Self {
+        Self {
+
+        }
+    }
+
+    /// Renders the `panes` with dividers between them, there should be one DOM for each pane of the `state`
+    pub fn dom<T: Layout>(&self, state: &SplitterState, t: &T, window: &mut FakeWindow<T>, panes: Vec<Dom<T>>) -> Dom<T> {
+
+        let ptr = match StackCheckedPointer::new(t, state) {
+            Some(ptr) => ptr,
+            None => return Dom::new(NodeType::Label(
+                "Cannot create splitter from heap-allocated SplitterState, \
+                 please store the SplitterState in the application data".into())
+            ),
+        };
+
+        let divider_mouse_down = window.add_callback(ptr, DefaultCallback(SplitterState::on_divider_mouse_down_private));
+        let divider_mouse_over = window.add_callback(ptr, DefaultCallback(SplitterState::on_divider_mouse_over_private));
+        let divider_mouse_up = window.add_callback(ptr, DefaultCallback(SplitterState::on_divider_mouse_up_private));
+
+        // div.__azul-native-splitter (.__azul-native-splitter-vertical)
+        //     |-> div.__azul-native-splitter-pane (.__azul-native-splitter-pane-fill, .__azul-native-splitter-pane-collapsed)
+        //     |   '-> the DOM of the pane
+        //     |-> div.__azul-native-splitter-divider.__azul-native-splitter-divider-(horizontal|vertical) (.__azul-native-splitter-divider-active)
+        //     '-> div.__azul-native-splitter-pane ...
+        //
+        // The size of a pane is set with the dynamic `width` (or `height`) property, the
+        // pane that fills the remaining space has `flex-grow: 1` instead.
+
+        let is_vertical = state.orientation == SplitterOrientation::Vertical;
+        let fill_pane = state.get_fill_pane();
+        let dragged_divider = state.drag.as_ref().map(|drag| drag.divider);
+
+        let mut splitter = Dom::new(NodeType::Div).with_class("__azul-native-splitter");
+        if is_vertical {
+            splitter.add_class("__azul-native-splitter-vertical");
+        }
+
+        let mut panes = panes.into_iter();
+
+        for (index, pane) in state.panes.iter().enumerate() {
+
+            if index > 0 {
+                let mut divider = Dom::new(NodeType::Div)
+                    .with_class("__azul-native-splitter-divider")
+                    .with_pointer_capture(true);
+                divider.add_class(if is_vertical {
+                    "__azul-native-splitter-divider-vertical"
+                } else {
+                    "__azul-native-splitter-divider-horizontal"
+                });
+                if dragged_divider == Some(index - 1) {
+                    divider.add_class("__azul-native-splitter-divider-active");
+                }
+                divider.add_default_callback_id(On::LeftMouseDown, divider_mouse_down);
+                divider.add_default_callback_id(On::MouseOver, divider_mouse_over);
+                divider.add_default_callback_id(On::LeftMouseUp, divider_mouse_up);
+                splitter.add_child(divider);
+            }
+
+            let mut pane_dom = Dom::new(NodeType::Div).with_class("__azul-native-splitter-pane");
+
+            if pane.collapsed {
+                pane_dom.add_class("__azul-native-splitter-pane-collapsed");
+            }
+
+            if fill_pane == Some(index) {
+                pane_dom.add_class("__azul-native-splitter-pane-fill");
+            } else {
+                let size = if pane.collapsed { 0.0 } else { pane.size };
+                pane_dom = if is_vertical {
+                    pane_dom.with_style_override(PANE_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(size)))
+                } else {
+                    pane_dom.with_style_override(PANE_WIDTH_ID, CssProperty::Width(LayoutWidth::px(size)))
+                };
+            }
+
+            // The content of collapsed panes stays in the DOM, so that their state isn't lost
+            if let Some(content) = panes.next() {
+                pane_dom.add_child(content);
+            }
+
+            splitter.add_child(pane_dom);
+        }
+
+        splitter
+    }
+}
+
+impl SplitterState {
+
+    fn on_divider_mouse_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_divider_mouse_down, app_state_no_data, window_event) }
+    }
+
+    fn on_divider_mouse_over_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_divider_mouse_over, app_state_no_data, window_event) }
+    }
+
+    fn on_divider_mouse_up_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_divider_mouse_up, app_state_no_data, window_event) }
+    }
+
+    /// Starts dragging the divider, or collapses / expands the pane next to it on a double click
+    pub fn on_divider_mouse_down<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (divider, splitter) = match Self::get_divider(&event) {
+            Some(divider) => divider,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        if self.clicks.click(divider, Instant::now(), 2) == 2 {
+            self.drag = None;
+            return if self.toggle_collapsed(divider) { UpdateScreen::Redraw } else { UpdateScreen::DontRedraw };
+        }
+
+        match self.measure_panes(&event, splitter) {
+            Some(start_sizes) => {
+                self.drag = Some(SplitterDrag {
+                    divider,
+                    start_cursor: self.get_cursor_position(&event),
+                    start_sizes,
+                });
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Moves the dragged divider with the cursor, even outside of the divider
+    /// (the divider captures the pointer while the mouse button is down)
+    pub fn on_divider_mouse_over<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        if !event.has_pointer_capture() || !app_state_no_data.windows[event.window].get_mouse_state().left_down {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let (divider, start_cursor, start_sizes) = match &self.drag {
+            Some(drag) => (drag.divider, drag.start_cursor, drag.start_sizes.clone()),
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let delta = self.get_cursor_position(&event) - start_cursor;
+        if self.move_divider(divider, &start_sizes, delta) {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    pub fn on_divider_mouse_up<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, _event: WindowEvent<T>) -> UpdateScreen {
+        match self.drag.take() {
+            Some(_) => UpdateScreen::Redraw,
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+}
+
+#[test]
+fn test_splitter_move_divider_and_collapse() {
+    let mut splitter = SplitterState::new(SplitterOrientation::Horizontal, vec![
+        SplitterPane::new(200.0).with_min_size(100.0).with_max_size(300.0),
+        SplitterPane::new(400.0).with_min_size(50.0).with_collapsible(false),
+        SplitterPane::new(200.0),
+    ]);
+    let start_sizes = [200.0, 400.0, 300.0];
+
+    // The panes next to the divider are clamped to their minimum and maximum sizes
+    assert!(splitter.move_divider(0, &start_sizes, 150.0));
+    assert_eq!((splitter.panes[0].size, splitter.panes[1].size), (300.0, 300.0));
+    assert!(splitter.move_divider(1, &start_sizes, 500.0));
+    assert_eq!((splitter.panes[1].size, splitter.panes[2].size), (700.0, 0.0));
+    assert!(splitter.move_divider(1, &start_sizes, -500.0));
+    assert_eq!((splitter.panes[0].size, splitter.panes[1].size, splitter.panes[2].size), (200.0, 50.0, 650.0));
+    assert!(!splitter.move_divider(2, &start_sizes, 10.0));
+
+    // The last visible pane fills the remaining space, it can't be collapsed
+    assert_eq!(splitter.get_fill_pane(), Some(2));
+    assert!(splitter.toggle_collapsed(1));
+    assert!(splitter.panes[2].collapsed);
+    assert_eq!(splitter.get_fill_pane(), Some(1));
+    assert!(splitter.toggle_collapsed(0));
+    assert!(splitter.panes[0].collapsed);
+    assert!(splitter.toggle_collapsed(0));
+    assert!(!splitter.panes[0].collapsed);
+    assert!(splitter.toggle_collapsed(1));
+    assert!(!splitter.panes[2].collapsed);
+
+    let mut splitter = SplitterState::new(SplitterOrientation::Vertical, vec![
+        SplitterPane::new(200.0),
+        SplitterPane::new(200.0).with_collapsible(false).with_collapsed(true),
+    ]);
+    assert!(!splitter.toggle_collapsed(0));
+    assert_eq!(splitter.get_fill_pane(), Some(0));
+}
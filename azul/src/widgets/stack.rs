@@ -0,0 +1,195 @@
+//! Container that shows one of its pages at a time, ex. for multi-page dialogs or
+//! for navigating from page to page (push a page to show it, pop it to go back)
+//!
+//! The DOM is rebuilt every frame, so the state of the widgets of a page has to be stored
+//! in the page itself, which is kept in the `StackState` while the page isn't shown. The
+//! widgets of the page can be bound to it, even though the pages are stored on the heap.
+//! Every page has a unique key (see `NodeData::key`), so that the focus or the text
+//! selection of a hidden page are removed instead of ending up on a node of the page
+//! that is shown in its place.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use {
+    traits::Layout,
+    dom::{Dom, NodeType},
+    default_callbacks::with_page_scope,
+};
+
+static PAGE_KEY: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a new unique key for a page of a `Stack` or a tab of a `Tabs` widget
+pub(crate) fn new_page_key() -> usize {
+    PAGE_KEY.fetch_add(1, Ordering::SeqCst)
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Stack {
+
+}
+
+#[derive(Debug, Clone)]
+pub struct StackState<P> {
+    /// The pages and their keys
+    pages: Vec<(usize, P)>,
+    /// Index of the page that is shown
+    current: usize,
+}
+
+impl<P> Default for StackState<P> {
+    fn default() -> Self {
+        Self {
+            pages: Vec::new(),
+            current: 0,
+        }
+    }
+}
+
+impl<P> StackState<P> {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a stack of the pages that shows the first page
+    pub fn from_pages(pages: Vec<P>) -> Self {
+        Self {
+            pages: pages.into_iter().map(|page| (new_page_key(), page)).collect(),
+            current: 0,
+        }
+    }
+
+    /// Adds the page on top of the stack and shows it
+    pub fn push(&mut self, page: P) {
+        self.pages.push((new_page_key(), page));
+        self.current = self.pages.len() - 1;
+    }
+
+    /// Removes the page on top of the stack. If it was shown, the page below it is shown
+    pub fn pop(&mut self) -> Option<P> {
+        let (_, page) = self.pages.pop()?;
+        self.current = self.current.min(self.pages.len().saturating_sub(1));
+        Some(page)
+    }
+
+    /// Shows the page with the index, returns `false` if there is no such page
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.pages.len() {
+            return false;
+        }
+        self.current = index;
+        true
+    }
+
+    /// Index of the page that is shown, `None` if the stack is empty
+    pub fn get_current_index(&self) -> Option<usize> {
+        if self.pages.is_empty() { None } else { Some(self.current) }
+    }
+
+    pub fn get_current(&self) -> Option<&P> {
+        self.get_page(self.current)
+    }
+
+    pub fn get_current_mut(&mut self) -> Option<&mut P> {
+        let current = self.current;
+        self.get_page_mut(current)
+    }
+
+    pub fn get_page(&self, index: usize) -> Option<&P> {
+        self.pages.get(index).map(|(_, page)| page)
+    }
+
+    pub fn get_page_mut(&mut self, index: usize) -> Option<&mut P> {
+        self.pages.get_mut(index).map(|(_, page)| page)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+impl Stack {
+
+    pub fn new() -> Self {
+        Self {
+
+        }
+    }
+
+    /// Renders the page that is shown with `render_page`, the other pages aren't rendered
+    pub fn dom<T: Layout, P, F: FnOnce(&P) -> Dom<T>>(&self, state: &StackState<P>, render_page: F) -> Dom<T> {
+
+        // div.__azul-native-stack
+        //     '-> div.__azul-native-stack-page (keyed by the page)
+        //         '-> the DOM returned by `render_page`
+
+        let mut stack = Dom::new(NodeType::Div).with_class("__azul-native-stack");
+
+        if let Some((key, page)) = state.pages.get(state.current) {
+            stack.add_child(Dom::new(NodeType::Div)
+                .with_class("__azul-native-stack-page")
+                .with_key(*key)
+                .with_child(with_page_scope(&state.pages, *key, page, find_page::<P>, || render_page(page))));
+        }
+
+        stack
+    }
+}
+
+/// Finds the page with the key in the pages of a `StackState`, see `with_page_scope`
+unsafe fn find_page<P>(pages: *const (), key: usize) -> Option<*const u8> {
+    // SAFETY: `Stack::dom` passes `&state.pages` to `with_page_scope` together with this function
+    let pages = &*(pages as *const Vec<(usize, P)>);
+    pages.iter().find(|(page_key, _)| *page_key == key).map(|(_, page)| page as *const P as *const u8)
+}
+
+#[test]
+fn test_stack_state_push_pop() {
+    let mut stack = StackState::from_pages(vec!["settings"]);
+    stack.push("network");
+    stack.push("proxy");
+    assert_eq!(stack.get_current(), Some(&"proxy"));
+
+    assert!(stack.select(1));
+    assert!(!stack.select(3));
+    assert_eq!(stack.pop(), Some("proxy"));
+    assert_eq!(stack.get_current(), Some(&"network"));
+    assert_eq!(stack.pop(), Some("network"));
+    assert_eq!(stack.get_current_index(), Some(0));
+    assert_eq!(stack.pop(), Some("settings"));
+    assert_eq!(stack.get_current_index(), None);
+    assert_eq!(stack.pop(), None);
+}
+
+#[test]
+fn test_stack_page_widgets_can_be_bound() {
+    use default_callbacks::StackCheckedPointer;
+    use test_support::Mock;
+
+    let mut mock = Mock { data: StackState::from_pages(vec![false]) };
+
+    // The pages are on the heap, so they can't be bound outside of the stack
+    assert!(StackCheckedPointer::new(&mock, mock.data.get_page(0).unwrap()).is_none());
+
+    // While the page is rendered, a widget of the page can be bound to it (ex. with `Checkbox::bind`)
+    let mut pointer = None;
+    let _ = Stack::new().dom(&mock.data, |checked| {
+        pointer = StackCheckedPointer::new(&mock, checked);
+        Dom::<Mock<StackState<bool>>>::new(NodeType::Div)
+    });
+    let pointer = pointer.unwrap();
+
+    // Pushing pages moves the first page, the pointer follows it
+    for _ in 0..100 {
+        mock.data.push(true);
+    }
+    let first_page = mock.data.get_page(0).unwrap() as *const bool as *const ();
+    assert_eq!(unsafe { pointer.get_pointer() }, Some(first_page));
+
+    // Once the page is removed, the widget isn't called anymore
+    while mock.data.pop().is_some() { }
+    assert_eq!(unsafe { pointer.get_pointer() }, None);
+}
@@ -0,0 +1,60 @@
+//! Toggle switch, bound to a `bool`
+
+use {
+    traits::Layout,
+    dom::{Dom, NodeType, TabIndex},
+    window::FakeWindow,
+    widgets::checkbox::ToggleCallbacks,
+};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Switch {
+    label: Option<String>,
+    callbacks: Option<ToggleCallbacks>,
+}
+
+impl Switch {
+
+    pub fn new() -> Self {
+        Self { label: None, callbacks: None }
+    }
+
+    /// Text next to the switch, clicking on it toggles the switch, too
+    pub fn with_label<S: Into<String>>(self, label: S) -> Self {
+        Self { label: Some(label.into()), .. self }
+    }
+
+    pub fn bind<T: Layout>(self, window: &mut FakeWindow<T>, field: &bool, data: &T) -> Self {
+        Self { callbacks: ToggleCallbacks::new(window, field, data), .. self }
+    }
+
+    pub fn dom<T: Layout>(&self, field: &bool) -> Dom<T> {
+
+        // div.__azul-native-switch
+        //     |-> div.__azul-native-switch-track (.__azul-native-switch-track-on)
+        //     |   '-> div.__azul-native-switch-knob
+        //     '-> p.__azul-native-switch-label
+
+        let mut switch = Dom::new(NodeType::Div)
+            .with_class("__azul-native-switch")
+            .with_tab_index(TabIndex::Auto);
+
+        if let Some(callbacks) = self.callbacks {
+            callbacks.add_to(&mut switch);
+        }
+
+        let mut track = Dom::new(NodeType::Div).with_class("__azul-native-switch-track");
+
+        if *field {
+            track.add_class("__azul-native-switch-track-on");
+        }
+
+        switch.add_child(track.with_child(Dom::new(NodeType::Div).with_class("__azul-native-switch-knob")));
+
+        if let Some(label) = &self.label {
+            switch.add_child(Dom::new(NodeType::Label(label.clone())).with_class("__azul-native-switch-label"));
+        }
+
+        switch
+    }
+}
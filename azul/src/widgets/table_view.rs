@@ -1,26 +1,91 @@
 //! Table view
+//!
+//! The cells are rendered into an `IFrame`, which only creates DOM nodes for the
+//! rows and columns that are visible. Nodes inside of an `IFrame` can't be hit-tested,
+//! so the mouse callbacks are attached to the `IFrame` itself and map the cursor
+//! position to the header, the column borders and the cells.
+//!
+//! Double-clicking a cell (or pressing F2 or simply typing) edits it. The editor is drawn
+//! on top of the `IFrame`, since a caret can only be drawn in the DOM of the window.
 
-use std::collections::BTreeMap;
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    mem,
+    time::Instant,
+};
+use azul_css::{CssProperty, LayoutTop, LayoutLeft, LayoutWidth, LayoutHeight};
 use {
-    dom::{Dom, On, NodeData, NodeType, IFrameCallback, UpdateScreen},
+    dom::{Dom, On, NodeType, IFrameCallback, UpdateScreen, TabIndex},
     app_state::AppStateNoData,
     traits::Layout,
     window::WindowInfo,
+    prelude::VirtualKeyCode,
     default_callbacks::{StackCheckedPointer, DefaultCallback},
     window::{HidpiAdjustedBounds, WindowEvent, FakeWindow},
+    widgets::text_input::TextInputState,
+    window_state::ClickCounter,
 };
 
+/// ID of the dynamic `width` property of a column, see the native CSS
+const COLUMN_WIDTH_ID: &str = "__azul-native-table-column-width";
+/// ID of the dynamic `height` property of the cells, row numbers and column names
+const ROW_HEIGHT_ID: &str = "__azul-native-table-row-height";
+/// IDs of the dynamic position and size of the rectangle around the selected cells
+const SELECTION_TOP_ID: &str = "__azul-native-table-selection-top";
+const SELECTION_LEFT_ID: &str = "__azul-native-table-selection-left";
+const SELECTION_WIDTH_ID: &str = "__azul-native-table-selection-width";
+const SELECTION_HEIGHT_ID: &str = "__azul-native-table-selection-height";
+/// IDs of the dynamic position and size of the editor of the edited cell
+const EDITOR_TOP_ID: &str = "__azul-native-table-editor-top";
+const EDITOR_LEFT_ID: &str = "__azul-native-table-editor-left";
+const EDITOR_WIDTH_ID: &str = "__azul-native-table-editor-width";
+const EDITOR_HEIGHT_ID: &str = "__azul-native-table-editor-height";
+
+/// Width of the column with the row numbers, has to match the `width` in the native CSS
+const ROW_NUMBER_WIDTH: f32 = 30.0;
+/// Columns can't be resized to be narrower than this
+pub const MIN_COLUMN_WIDTH: f32 = 20.0;
+/// Distance in pixels from the border between two column names at which dragging resizes the column
+const RESIZE_HANDLE_WIDTH: f32 = 4.0;
+
 #[derive(Debug, Default, Copy, Clone)]
 pub struct TableView {
 
 }
 
+/// Order in which `Worksheet::sort_rows_by_column` sorts the rows
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
 #[derive(Debug, Clone)]
 pub struct TableViewState {
     pub work_sheet: Worksheet,
+    /// Width of the columns that don't have a width in `column_widths`
     pub column_width: f32,
+    /// Width of the columns that have been resized, by the index of the column
+    pub column_widths: BTreeMap<usize, f32>,
     pub row_height: f32,
+    /// The active cell (column, row), which is moved by the arrow keys and edited by F2
     pub selected_cell: Option<(usize, usize)>,
+    /// The column the rows were last sorted by, shown as an arrow in the column name
+    pub sorted_by: Option<(usize, SortOrder)>,
+    /// The other corner of the selected range, the `selected_cell` is the first corner
+    selection_anchor: Option<(usize, usize)>,
+    /// The cell that is currently edited and the text of the editor
+    editor: Option<((usize, usize), TextInputState)>,
+    /// The top left cell that is visible (column, row)
+    first_column: usize,
+    first_row: usize,
+    /// Size of the `IFrame` in pixels, updated every time the table is rendered
+    viewport_size: (f32, f32),
+    /// What happens when the mouse is moved while the left mouse button is pressed
+    drag: Option<TableDrag>,
+    /// Clicks on the cells, for detecting double-clicks
+    clicks: ClickCounter<(usize, usize)>,
 }
 
 impl Default for TableViewState {
@@ -28,12 +93,52 @@ impl Default for TableViewState {
         Self {
             work_sheet: Worksheet::default(),
             column_width: 100.0,
+            column_widths: BTreeMap::new(),
             row_height: 20.0,
             selected_cell: None,
+            sorted_by: None,
+            selection_anchor: None,
+            editor: None,
+            first_column: 0,
+            first_row: 0,
+            viewport_size: (0.0, 0.0),
+            drag: None,
+            clicks: ClickCounter::default(),
         }
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum TableDrag {
+    /// Dragging the right border of the column name, `column_left` is the left edge of the column in the `IFrame`
+    ResizeColumn { column: usize, column_left: f32 },
+    /// Dragging over the cells extends the selection
+    SelectCells,
+}
+
+/// What is at a position in the `IFrame`, see `TableViewState::hit_test`
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum TableHit {
+    ColumnName(usize),
+    /// The border to the right of the name of the column, `f32` is the left edge of the column
+    ColumnBorder(usize, f32),
+    Cell((usize, usize)),
+}
+
+/// Error when parsing a `Worksheet` from CSV, the lines start at 1
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    /// A quoted field isn't closed before the end of the input, `line` is where the field starts
+    UnterminatedQuote { line: usize },
+    /// The closing quote of a quoted field isn't followed by a comma or a line break
+    CharacterAfterQuote { line: usize, character: char },
+}
+
+impl_display!{ CsvError, {
+    UnterminatedQuote { line } => format!("Quoted field starting in line {} is never closed", line),
+    CharacterAfterQuote { line, character } => format!("Unexpected character '{}' after the closing quote in line {}", character, line),
+}}
+
 #[derive(Debug, Default, Clone)]
 pub struct Worksheet {
     pub data: BTreeMap<usize, BTreeMap<usize, String>>,
@@ -46,6 +151,190 @@ impl Worksheet {
             .or_insert_with(|| BTreeMap::new())
             .insert(y, value.into());
     }
+
+    pub fn get_cell(&self, x: usize, y: usize) -> Option<&String> {
+        self.data.get(&x).and_then(|column| column.get(&y))
+    }
+
+    /// Removes the value of the cell, returns the removed value
+    pub fn clear_cell(&mut self, x: usize, y: usize) -> Option<String> {
+        let (removed, column_is_empty) = match self.data.get_mut(&x) {
+            Some(column) => (column.remove(&y), column.is_empty()),
+            None => return None,
+        };
+        if column_is_empty {
+            self.data.remove(&x);
+        }
+        removed
+    }
+
+    /// Number of columns up to and including the last column that has a value
+    pub fn get_column_count(&self) -> usize {
+        self.data.iter().rev().find(|(_, column)| !column.is_empty()).map(|(x, _)| x + 1).unwrap_or(0)
+    }
+
+    /// Number of rows up to and including the last row that has a value
+    pub fn get_row_count(&self) -> usize {
+        self.data.values().filter_map(|column| column.keys().next_back()).max().map(|y| y + 1).unwrap_or(0)
+    }
+
+    /// Sorts the rows by their value in the `column`. Numbers are sorted before text and by
+    /// their value, text is sorted case-insensitively, empty cells are always sorted last.
+    /// The sort is stable, rows with the same value keep their order.
+    pub fn sort_rows_by_column(&mut self, column: usize, order: SortOrder) {
+
+        let row_count = self.get_row_count();
+        let mut rows = (0..row_count).collect::<Vec<usize>>();
+
+        {
+            let empty_column = BTreeMap::new();
+            let values = self.data.get(&column).unwrap_or(&empty_column);
+            rows.sort_by(|a, b| compare_cells(values.get(a), values.get(b), order));
+        }
+
+        // `rows` is the old row for each new row, but moving the cells needs the new row for each old row
+        let mut new_rows = vec![0; row_count];
+        for (new_row, old_row) in rows.into_iter().enumerate() {
+            new_rows[old_row] = new_row;
+        }
+
+        for cells in self.data.values_mut() {
+            *cells = mem::replace(cells, BTreeMap::new()).into_iter().map(|(y, value)| (new_rows[y], value)).collect();
+        }
+    }
+
+    /// Parses comma-separated values (RFC 4180): fields containing commas, quotes or line
+    /// breaks are enclosed in double quotes, a double quote inside of a quoted field is
+    /// written as two double quotes. Lines can end with `\r\n` or `\n`. Empty fields
+    /// don't create a cell.
+    pub fn from_csv(csv: &str) -> Result<Self, CsvError> {
+
+        let mut worksheet = Worksheet::default();
+        let mut chars = csv.chars().peekable();
+        let mut field = String::new();
+        let (mut x, mut y, mut line) = (0, 0, 1);
+
+        loop {
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                let start_line = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => { chars.next(); field.push('"'); },
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        },
+                        None => return Err(CsvError::UnterminatedQuote { line: start_line }),
+                    }
+                }
+                match chars.peek() {
+                    None | Some(',') | Some('\r') | Some('\n') => { },
+                    Some(&character) => return Err(CsvError::CharacterAfterQuote { line, character }),
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if c == ',' || c == '\r' || c == '\n' {
+                        break;
+                    }
+                    field.push(c);
+                    chars.next();
+                }
+            }
+
+            if !field.is_empty() {
+                worksheet.set_cell(x, y, mem::replace(&mut field, String::new()));
+            }
+
+            match chars.next() {
+                Some(',') => x += 1,
+                Some(c) => {
+                    // Line break, a line break at the end of the input doesn't start a new row
+                    if c == '\r' && chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    if chars.peek().is_none() {
+                        break;
+                    }
+                    line += 1;
+                    y += 1;
+                    x = 0;
+                },
+                None => break,
+            }
+        }
+
+        Ok(worksheet)
+    }
+
+    /// Writes all cells as comma-separated values, see `from_csv`. Every row has the same
+    /// number of fields, the rows are separated by `\r\n`.
+    pub fn to_csv(&self) -> String {
+        match (self.get_column_count(), self.get_row_count()) {
+            (0, _) | (_, 0) => String::new(),
+            (columns, rows) => self.to_delimited((0, 0), (columns - 1, rows - 1), ','),
+        }
+    }
+
+    /// Writes the cells between the two corners (inclusive) as tab-separated values, the format
+    /// that spreadsheet applications copy to and paste from the clipboard. Fields containing tabs,
+    /// quotes or line breaks are quoted like in `to_csv`.
+    pub fn to_tsv(&self, top_left: (usize, usize), bottom_right: (usize, usize)) -> String {
+        self.to_delimited(top_left, bottom_right, '\t')
+    }
+
+    fn to_delimited(&self, top_left: (usize, usize), bottom_right: (usize, usize), delimiter: char) -> String {
+        let mut output = String::new();
+        for y in top_left.1..(bottom_right.1 + 1) {
+            if y != top_left.1 {
+                output.push_str("\r\n");
+            }
+            for x in top_left.0..(bottom_right.0 + 1) {
+                if x != top_left.0 {
+                    output.push(delimiter);
+                }
+                if let Some(value) = self.get_cell(x, y) {
+                    push_field(&mut output, value, delimiter);
+                }
+            }
+        }
+        output
+    }
+}
+
+/// Appends the field, enclosed in quotes if it contains the delimiter, quotes or line breaks
+fn push_field(output: &mut String, value: &str, delimiter: char) {
+    if value.contains(|c| c == delimiter || c == '"' || c == '\r' || c == '\n') {
+        output.push('"');
+        output.push_str(&value.replace('"', "\"\""));
+        output.push('"');
+    } else {
+        output.push_str(value);
+    }
+}
+
+/// Compares two cells for `Worksheet::sort_rows_by_column`
+fn compare_cells(a: Option<&String>, b: Option<&String>, order: SortOrder) -> Ordering {
+    match (a.filter(|a| !a.is_empty()), b.filter(|b| !b.is_empty())) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let ordering = match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+                (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.to_lowercase().cmp(&b.to_lowercase()),
+            };
+            match order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        },
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -61,23 +350,105 @@ impl TableView {
     }
 
     pub fn dom<T: Layout>(&self, data: &TableViewState, t: &T, window: &mut FakeWindow<T>) -> Dom<T> {
-        if let Some(ptr) =  StackCheckedPointer::new(t, data) {
-            let mut dom = Dom::new(NodeType::IFrame((IFrameCallback(render_table_callback), ptr)));
-            let callback_id = window.add_callback(ptr, DefaultCallback(Self::table_view_on_click));
-            dom.add_default_callback_id(On::MouseUp, callback_id);
-            dom
-        } else {
-            Dom::new(NodeType::Label(
+
+        let ptr = match StackCheckedPointer::new(t, data) {
+            Some(ptr) => ptr,
+            None => return Dom::new(NodeType::Label(
                 "Cannot create table from heap-allocated TableViewState, \
                  please call TableViewState::render_dom manually".into())
-            )
+            ),
+        };
+
+        // div.__azul-native-table-view (focusable, receives the keyboard events)
+        //     |-> iframe.__azul-native-table-iframe (receives the mouse events)
+        //     '-> div.__azul-native-table-editor (only while a cell is edited)
+        //         '-> label.__azul-native-table-editor-label
+
+        let mut iframe = Dom::new(NodeType::IFrame((IFrameCallback(render_table_callback), ptr)))
+            .with_class("__azul-native-table-iframe")
+            .with_pointer_capture(true);
+
+        let mouse_down = window.add_callback(ptr, DefaultCallback(Self::table_view_on_mouse_down));
+        let mouse_over = window.add_callback(ptr, DefaultCallback(Self::table_view_on_mouse_over));
+        let mouse_up = window.add_callback(ptr, DefaultCallback(Self::table_view_on_mouse_up));
+        let scroll = window.add_callback(ptr, DefaultCallback(Self::table_view_on_scroll));
+        let virtual_key_down = window.add_callback(ptr, DefaultCallback(Self::table_view_on_virtual_key_down));
+        let text_input = window.add_callback(ptr, DefaultCallback(Self::table_view_on_text_input));
+        let focus_lost = window.add_callback(ptr, DefaultCallback(Self::table_view_on_focus_lost));
+
+        iframe.add_default_callback_id(On::LeftMouseDown, mouse_down);
+        iframe.add_default_callback_id(On::MouseOver, mouse_over);
+        iframe.add_default_callback_id(On::LeftMouseUp, mouse_up);
+        iframe.add_default_callback_id(On::Scroll, scroll);
+
+        let mut table = Dom::new(NodeType::Div)
+            .with_class("__azul-native-table-view")
+            .with_tab_index(TabIndex::Auto)
+            .with_child(iframe);
+
+        table.add_default_callback_id(On::VirtualKeyDown, virtual_key_down);
+        table.add_default_callback_id(On::TextInput, text_input);
+        table.add_default_callback_id(On::FocusLost, focus_lost);
+
+        if let Some(((column, row), editor)) = &data.editor {
+            if data.is_cell_visible((*column, *row)) {
+                let left = ROW_NUMBER_WIDTH + data.get_columns_width(data.first_column, *column);
+                let top = data.row_height * (1 + row - data.first_row) as f32;
+                table.add_child(Dom::new(NodeType::Div)
+                    .with_class("__azul-native-table-editor")
+                    .with_style_override(EDITOR_TOP_ID, CssProperty::Top(LayoutTop::px(top)))
+                    .with_style_override(EDITOR_LEFT_ID, CssProperty::Left(LayoutLeft::px(left)))
+                    .with_style_override(EDITOR_WIDTH_ID, CssProperty::Width(LayoutWidth::px(data.get_column_width(*column))))
+                    .with_style_override(EDITOR_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(data.row_height)))
+                    .with_child(Dom::new(NodeType::Label(editor.text.clone()))
+                        .with_class("__azul-native-table-editor-label")
+                        .with_caret(editor.get_caret())));
+            }
         }
+
+        table
     }
 
-    fn table_view_on_click<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    fn table_view_on_mouse_down<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
     -> UpdateScreen
     {
-        unsafe { ptr.invoke_mut(TableViewState::on_click, data, event) }
+        unsafe { ptr.invoke_mut(TableViewState::on_mouse_down, data, event) }
+    }
+
+    fn table_view_on_mouse_over<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TableViewState::on_mouse_over, data, event) }
+    }
+
+    fn table_view_on_mouse_up<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TableViewState::on_mouse_up, data, event) }
+    }
+
+    fn table_view_on_scroll<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TableViewState::on_scroll, data, event) }
+    }
+
+    fn table_view_on_virtual_key_down<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TableViewState::on_virtual_key_down, data, event) }
+    }
+
+    fn table_view_on_text_input<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TableViewState::on_text_input, data, event) }
+    }
+
+    fn table_view_on_focus_lost<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TableViewState::on_focus_lost, data, event) }
     }
 }
 
@@ -87,13 +458,195 @@ fn render_table_callback<T: Layout>(ptr: &StackCheckedPointer<T>, info: WindowIn
     unsafe { ptr.invoke_mut_iframe(TableViewState::render, info, dimensions) }
 }
 
-
 impl TableViewState {
+
+    /// Width of the column, either from `column_widths` or the default `column_width`
+    pub fn get_column_width(&self, column: usize) -> f32 {
+        self.column_widths.get(&column).cloned().unwrap_or(self.column_width).max(MIN_COLUMN_WIDTH)
+    }
+
+    pub fn set_column_width(&mut self, column: usize, width: f32) {
+        self.column_widths.insert(column, width.max(MIN_COLUMN_WIDTH));
+    }
+
+    /// Summed up width of the columns from `start` to `end` (exclusive)
+    fn get_columns_width(&self, start: usize, end: usize) -> f32 {
+        (start..end).map(|column| self.get_column_width(column)).sum()
+    }
+
+    /// Returns the top left and the bottom right corner of the selected cells
+    pub fn get_selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        let (column, row) = self.selected_cell?;
+        let (anchor_column, anchor_row) = self.selection_anchor.unwrap_or((column, row));
+        Some(((column.min(anchor_column), row.min(anchor_row)), (column.max(anchor_column), row.max(anchor_row))))
+    }
+
+    /// Makes the cell the active cell, if `extend_selection` is set, the selection reaches
+    /// from the previous selection anchor to the cell (ex. for Shift + click)
+    pub fn select_cell(&mut self, cell: (usize, usize), extend_selection: bool) {
+        if !extend_selection || self.selected_cell.is_none() {
+            self.selection_anchor = Some(cell);
+        } else if self.selection_anchor.is_none() {
+            self.selection_anchor = self.selected_cell;
+        }
+        self.selected_cell = Some(cell);
+        self.scroll_to_cell(cell);
+    }
+
+    /// Moves the active cell by the number of columns and rows
+    fn move_selected_cell(&mut self, columns: isize, rows: isize, extend_selection: bool) {
+        let (column, row) = self.selected_cell.unwrap_or((0, 0));
+        let move_by = |value: usize, by: isize| if by < 0 { value.saturating_sub(by.abs() as usize) } else { value + by as usize };
+        self.select_cell((move_by(column, columns), move_by(row, rows)), extend_selection);
+    }
+
+    /// Returns the selected cells as tab-separated values, see `Worksheet::to_tsv`
+    pub fn get_selection_as_tsv(&self) -> Option<String> {
+        self.get_selection().map(|(top_left, bottom_right)| self.work_sheet.to_tsv(top_left, bottom_right))
+    }
+
+    /// Sorts the rows by the column and shows the sort order in the column name
+    pub fn sort_by_column(&mut self, column: usize, order: SortOrder) {
+        self.commit_editing();
+        self.work_sheet.sort_rows_by_column(column, order);
+        self.sorted_by = Some((column, order));
+    }
+
+    /// Returns the cell that is currently edited
+    pub fn get_edited_cell(&self) -> Option<(usize, usize)> {
+        self.editor.as_ref().map(|(cell, _)| *cell)
+    }
+
+    /// Starts editing the active cell, returns whether there is an active cell
+    pub fn start_editing(&mut self) -> bool {
+        let cell = match self.selected_cell {
+            Some(cell) => cell,
+            None => return false,
+        };
+        let text = self.work_sheet.get_cell(cell.0, cell.1).cloned().unwrap_or_default();
+        self.editor = Some((cell, TextInputState::new(text)));
+        self.scroll_to_cell(cell);
+        true
+    }
+
+    /// Writes the text of the editor into the cell and stops editing. Since the value
+    /// of the cell changed, the rows aren't necessarily sorted anymore.
+    pub fn commit_editing(&mut self) {
+        if let Some(((column, row), editor)) = self.editor.take() {
+            if self.work_sheet.get_cell(column, row).map(|value| value.as_str()).unwrap_or("") == editor.text {
+                return;
+            }
+            if editor.text.is_empty() {
+                self.work_sheet.clear_cell(column, row);
+            } else {
+                self.work_sheet.set_cell(column, row, editor.text);
+            }
+            self.sorted_by = None;
+        }
+    }
+
+    /// Stops editing without changing the cell
+    pub fn cancel_editing(&mut self) {
+        self.editor = None;
+    }
+
+    /// Number of rows that are completely visible
+    fn get_visible_row_count(&self) -> usize {
+        ((self.viewport_size.1 - self.row_height) / self.row_height).floor().max(1.0) as usize
+    }
+
+    fn is_cell_visible(&self, (column, row): (usize, usize)) -> bool {
+        column >= self.first_column && row >= self.first_row &&
+        row < self.first_row + self.get_visible_row_count() + 1 &&
+        self.get_columns_width(self.first_column, column) < self.viewport_size.0 - ROW_NUMBER_WIDTH
+    }
+
+    /// Scrolls so that the cell is visible, does nothing before the table has been rendered
+    pub fn scroll_to_cell(&mut self, (column, row): (usize, usize)) {
+
+        if self.viewport_size.1 <= 0.0 {
+            return;
+        }
+
+        let visible_rows = self.get_visible_row_count();
+        if row < self.first_row {
+            self.first_row = row;
+        } else if row >= self.first_row + visible_rows {
+            self.first_row = row + 1 - visible_rows;
+        }
+
+        let available_width = self.viewport_size.0 - ROW_NUMBER_WIDTH;
+        if column < self.first_column {
+            self.first_column = column;
+        }
+        while self.first_column < column && self.get_columns_width(self.first_column, column + 1) > available_width {
+            self.first_column += 1;
+        }
+    }
+
+    /// Returns the column at the horizontal position in the `IFrame` and the left edge of that column
+    fn get_column_at(&self, x: f32) -> (usize, f32) {
+        let mut column = self.first_column;
+        let mut column_left = ROW_NUMBER_WIDTH;
+        loop {
+            let column_right = column_left + self.get_column_width(column);
+            if x < column_right {
+                return (column, column_left);
+            }
+            column_left = column_right;
+            column += 1;
+        }
+    }
+
+    /// Returns the cell at the position in the `IFrame`, positions outside of
+    /// the cells are moved to the nearest cell (ex. while dragging a selection)
+    fn get_cell_at(&self, x: f32, y: f32) -> (usize, usize) {
+        let (column, _) = self.get_column_at(x);
+        let row = self.first_row + ((y - self.row_height).max(0.0) / self.row_height) as usize;
+        (column, row)
+    }
+
+    fn hit_test(&self, x: f32, y: f32) -> Option<TableHit> {
+
+        if x < ROW_NUMBER_WIDTH || y < 0.0 {
+            return None;
+        }
+
+        if y >= self.row_height {
+            return Some(TableHit::Cell(self.get_cell_at(x, y)));
+        }
+
+        let (column, column_left) = self.get_column_at(x);
+        let column_right = column_left + self.get_column_width(column);
+
+        if column_right - x <= RESIZE_HANDLE_WIDTH {
+            Some(TableHit::ColumnBorder(column, column_left))
+        } else if x - column_left <= RESIZE_HANDLE_WIDTH && column > self.first_column {
+            let previous_column = column - 1;
+            Some(TableHit::ColumnBorder(previous_column, column_left - self.get_column_width(previous_column)))
+        } else {
+            Some(TableHit::ColumnName(column))
+        }
+    }
+
     pub fn render<T: Layout>(state: &mut TableViewState, _info: WindowInfo<T>, dimensions: HidpiAdjustedBounds)
     -> Dom<T>
     {
-        let necessary_columns = (dimensions.logical_size.width as f32 / state.column_width).ceil() as usize;
-        let necessary_rows = (dimensions.logical_size.height as f32 / state.row_height).ceil() as usize;
+        state.viewport_size = (dimensions.logical_size.width as f32, dimensions.logical_size.height as f32);
+
+        let necessary_rows = ((state.viewport_size.1 - state.row_height) / state.row_height).ceil().max(0.0) as usize;
+        let rows = state.first_row..(state.first_row + necessary_rows);
+
+        let mut columns = Vec::new();
+        let mut columns_width = 0.0;
+        while columns_width < state.viewport_size.0 - ROW_NUMBER_WIDTH {
+            let column = state.first_column + columns.len();
+            columns_width += state.get_column_width(column);
+            columns.push(column);
+        }
+
+        let row_height = CssProperty::Height(LayoutHeight::px(state.row_height));
+        let edited_cell = state.get_edited_cell();
 
         // div.__azul-native-table-container
         //     |-> div.__azul-native-table-column (Column 0)
@@ -105,6 +658,66 @@ impl TableViewState {
         //             |-> div.__azul-native-table-column-name
         //             '-> div.__azul-native-table-row
         //                 '-> div.__azul-native-table-cell
+        //         '-> div.__azul-native-table-selection
+
+        let mut column_container = columns.iter().map(|column| {
+
+            let mut column_name = column_name_from_number(*column);
+            match state.sorted_by {
+                Some((sorted_column, SortOrder::Ascending)) if sorted_column == *column => column_name.push_str(" \u{25B4}"),
+                Some((sorted_column, SortOrder::Descending)) if sorted_column == *column => column_name.push_str(" \u{25BE}"),
+                _ => { },
+            }
+
+            Dom::new(NodeType::Div)
+            .with_class("__azul-native-table-column")
+            .with_style_override(COLUMN_WIDTH_ID, CssProperty::Width(LayoutWidth::px(state.get_column_width(*column))))
+            .with_child(
+                Dom::new(NodeType::Label(column_name))
+                .with_class("__azul-native-table-column-name")
+                .with_style_override(ROW_HEIGHT_ID, row_height.clone())
+            )
+            .with_child(
+                // Actual rows - if no content is given, they are simply empty. The
+                // content of the edited cell is shown in the editor instead.
+                rows.clone().map(|row| {
+                    let node_type = match state.work_sheet.get_cell(*column, row) {
+                        Some(data) if edited_cell != Some((*column, row)) => NodeType::Label(data.clone()),
+                        _ => NodeType::Div,
+                    };
+                    Dom::new(node_type)
+                        .with_class("__azul-native-table-cell")
+                        .with_style_override(ROW_HEIGHT_ID, row_height.clone())
+                })
+                .collect::<Dom<T>>()
+                .with_class("__azul-native-table-rows")
+            )
+        })
+        .collect::<Dom<T>>()
+        .with_class("__azul-native-table-column-container");
+
+        // current active selection, clipped to the visible cells
+        if let Some(((left_column, top_row), (right_column, bottom_row))) = state.get_selection() {
+            let left_column = left_column.max(state.first_column);
+            let top_row = top_row.max(state.first_row);
+            let right_column = right_column.min(state.first_column + columns.len().saturating_sub(1));
+            let bottom_row = bottom_row.min(rows.end.saturating_sub(1));
+            if left_column <= right_column && top_row <= bottom_row {
+                let left = state.get_columns_width(state.first_column, left_column);
+                let width = state.get_columns_width(left_column, right_column + 1);
+                let top = state.row_height * (1 + top_row - state.first_row) as f32;
+                let height = state.row_height * (1 + bottom_row - top_row) as f32;
+                column_container.add_child(
+                    Dom::new(NodeType::Div)
+                    .with_class("__azul-native-table-selection")
+                    .with_style_override(SELECTION_TOP_ID, CssProperty::Top(LayoutTop::px(top)))
+                    .with_style_override(SELECTION_LEFT_ID, CssProperty::Left(LayoutLeft::px(left)))
+                    .with_style_override(SELECTION_WIDTH_ID, CssProperty::Width(LayoutWidth::px(width)))
+                    .with_style_override(SELECTION_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(height)))
+                    .with_child(Dom::new(NodeType::Div).with_class("__azul-native-table-selection-handle"))
+                );
+            }
+        }
 
         Dom::new(NodeType::Div)
         .with_class("__azul-native-table-container")
@@ -115,66 +728,226 @@ impl TableViewState {
                 // Empty rectangle at the top left of the table
                 Dom::new(NodeType::Div)
                 .with_class("__azul-native-table-top-left-rect")
+                .with_style_override(ROW_HEIGHT_ID, row_height.clone())
             )
             .with_child(
                 // Rows - "1", "2", "3"
-                (0..necessary_rows.saturating_sub(1))
+                rows.clone()
                 .map(|row_idx|
-                    NodeData {
-                        node_type: NodeType::Label(format!("{}", row_idx + 1)),
-                        classes: vec![String::from("__azul-native-table-row")],
-                        .. Default::default()
-                    }
+                    Dom::new(NodeType::Label(format!("{}", row_idx + 1)))
+                    .with_class("__azul-native-table-row")
+                    .with_style_override(ROW_HEIGHT_ID, row_height.clone())
                 )
                 .collect::<Dom<T>>()
                 .with_class("__azul-native-table-row-numbers")
             )
         )
-        .with_child(
-            (0..necessary_columns)
-            .map(|col_idx|
-                // Column name
-                Dom::new(NodeType::Div)
-                .with_class("__azul-native-table-column")
-                .with_child(Dom::new(NodeType::Label(column_name_from_number(col_idx))).with_class("__azul-native-table-column-name"))
-                .with_child(
-                    // Actual rows - if no content is given, they are simply empty
-                    (0..necessary_rows)
-                    .map(|row_idx|
-                        NodeData {
-                            node_type: if let Some(data) = state.work_sheet.data.get(&col_idx).and_then(|col| col.get(&row_idx)) {
-                                NodeType::Label(data.clone())
-                            } else {
-                                NodeType::Div
-                            },
-                            classes: vec![String::from("__azul-native-table-cell")],
-                            .. Default::default()
-                        }
-                    )
-                    .collect::<Dom<T>>()
-                    .with_class("__azul-native-table-rows")
-                )
-            )
-            .collect::<Dom<T>>()
-            .with_class("__azul-native-table-column-container")
-            // current active selection (s)
-            .with_child(
-                Dom::new(NodeType::Div)
-                    .with_class("__azul-native-table-selection")
-                    .with_child(Dom::new(NodeType::Div).with_class("__azul-native-table-selection-handle"))
-            )
-        )
+        .with_child(column_container)
     }
 
-    pub fn on_click<T: Layout>(
-        &mut self,
-        _app_state: AppStateNoData<T>,
-        _window_event: WindowEvent<T>)
-    -> UpdateScreen
-    {
-        println!("table was clicked");
+    /// Clicking a cell selects it (Shift + click extends the selection), double-clicking a cell edits it.
+    /// Clicking a column name sorts the rows by that column, dragging the border between two column names
+    /// resizes the column.
+    pub fn on_mouse_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (x, y) = event.cursor_relative_to_item;
+        let shift_down = app_state_no_data.windows[event.window].get_keyboard_state().shift_down;
+
+        match self.hit_test(x, y) {
+            Some(TableHit::ColumnBorder(column, column_left)) => {
+                self.drag = Some(TableDrag::ResizeColumn { column, column_left });
+                UpdateScreen::DontRedraw
+            },
+            Some(TableHit::ColumnName(column)) => {
+                let order = match self.sorted_by {
+                    Some((sorted_column, SortOrder::Ascending)) if sorted_column == column => SortOrder::Descending,
+                    _ => SortOrder::Ascending,
+                };
+                self.sort_by_column(column, order);
+                UpdateScreen::Redraw
+            },
+            Some(TableHit::Cell(cell)) => {
+                let is_double_click = self.clicks.click(cell, Instant::now(), 2) == 2;
+
+                // Clicking the edited cell again keeps editing it
+                if self.get_edited_cell() == Some(cell) {
+                    return UpdateScreen::DontRedraw;
+                }
+
+                self.commit_editing();
+                self.select_cell(cell, shift_down);
+                if is_double_click {
+                    self.start_editing();
+                } else {
+                    self.drag = Some(TableDrag::SelectCells);
+                }
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Resizes the column or extends the selection while the mouse is dragged
+    pub fn on_mouse_over<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        if !event.has_pointer_capture() || !app_state_no_data.windows[event.window].get_mouse_state().left_down {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let (x, y) = event.cursor_relative_to_item;
+
+        match self.drag {
+            Some(TableDrag::ResizeColumn { column, column_left }) => {
+                let width = (x - column_left).max(MIN_COLUMN_WIDTH);
+                if width == self.get_column_width(column) {
+                    return UpdateScreen::DontRedraw;
+                }
+                self.set_column_width(column, width);
+                UpdateScreen::Redraw
+            },
+            Some(TableDrag::SelectCells) => {
+                let cell = self.get_cell_at(x, y);
+                if self.selected_cell == Some(cell) {
+                    return UpdateScreen::DontRedraw;
+                }
+                self.select_cell(cell, true);
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    pub fn on_mouse_up<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, _event: WindowEvent<T>) -> UpdateScreen {
+        self.drag = None;
         UpdateScreen::DontRedraw
     }
+
+    /// Scrolls by whole rows (vertical scrolling) or columns (horizontal scrolling)
+    pub fn on_scroll<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (scroll_x, scroll_y) = {
+            let mouse_state = app_state_no_data.windows[event.window].get_mouse_state();
+            (mouse_state.scroll_x as f32, mouse_state.scroll_y as f32)
+        };
+
+        // Scrolls at least one row / column, even if the scroll amount is less than the row height
+        let scroll_by = |value: usize, amount: f32, step: f32| -> usize {
+            let steps = (amount / step).round().abs().max(1.0) as usize;
+            if amount < 0.0 { value.saturating_sub(steps) } else { value + steps }
+        };
+
+        let (first_column, first_row) = (self.first_column, self.first_row);
+        if scroll_y != 0.0 {
+            self.first_row = scroll_by(self.first_row, scroll_y, self.row_height);
+        }
+        if scroll_x != 0.0 {
+            self.first_column = scroll_by(self.first_column, scroll_x, self.column_width.max(MIN_COLUMN_WIDTH));
+        }
+
+        if (first_column, first_row) == (self.first_column, self.first_row) {
+            UpdateScreen::DontRedraw
+        } else {
+            UpdateScreen::Redraw
+        }
+    }
+
+    /// Arrow keys, Tab, Return, PageUp / PageDown and Home move the active cell (Shift extends the selection),
+    /// F2 edits the active cell and Ctrl + C copies the selected cells as tab-separated values.
+    /// While a cell is edited, Return, Tab and Up / Down save the text and move to the next cell,
+    /// Escape cancels editing and all other keys go to the editor.
+    pub fn on_virtual_key_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (key, shift_down, ctrl_down) = {
+            let keyboard_state = app_state_no_data.windows[event.window].get_keyboard_state();
+            (keyboard_state.latest_virtual_keycode, keyboard_state.shift_down, keyboard_state.ctrl_down)
+        };
+
+        let key = match key {
+            Some(key) => key,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let forwards = if shift_down { -1 } else { 1 };
+
+        if let Some((_, editor)) = &mut self.editor {
+            let movement = match key {
+                VirtualKeyCode::Return => (0, forwards),
+                VirtualKeyCode::Tab => (forwards, 0),
+                VirtualKeyCode::Up => (0, -1),
+                VirtualKeyCode::Down => (0, 1),
+                VirtualKeyCode::Escape => {
+                    self.cancel_editing();
+                    return UpdateScreen::Redraw;
+                },
+                _ => return editor.handle_virtual_key(key, shift_down, ctrl_down, app_state_no_data.resources),
+            };
+            self.commit_editing();
+            self.move_selected_cell(movement.0, movement.1, false);
+            return UpdateScreen::Redraw;
+        }
+
+        let page = self.get_visible_row_count() as isize;
+
+        match key {
+            VirtualKeyCode::C if ctrl_down => {
+                if let Some(tsv) = self.get_selection_as_tsv() {
+                    app_state_no_data.resources.set_clipboard_string(tsv).unwrap_or(());
+                }
+                return UpdateScreen::DontRedraw;
+            },
+            VirtualKeyCode::F2 => {
+                return if self.start_editing() { UpdateScreen::Redraw } else { UpdateScreen::DontRedraw };
+            },
+            VirtualKeyCode::Home if ctrl_down => self.select_cell((0, 0), shift_down),
+            VirtualKeyCode::Home => {
+                let row = self.selected_cell.map(|(_, row)| row).unwrap_or(0);
+                self.select_cell((0, row), shift_down);
+            },
+            VirtualKeyCode::Left => self.move_selected_cell(-1, 0, shift_down),
+            VirtualKeyCode::Right => self.move_selected_cell(1, 0, shift_down),
+            VirtualKeyCode::Up => self.move_selected_cell(0, -1, shift_down),
+            VirtualKeyCode::Down => self.move_selected_cell(0, 1, shift_down),
+            VirtualKeyCode::PageUp => self.move_selected_cell(0, -page, shift_down),
+            VirtualKeyCode::PageDown => self.move_selected_cell(0, page, shift_down),
+            VirtualKeyCode::Tab => self.move_selected_cell(forwards, 0, false),
+            VirtualKeyCode::Return => self.move_selected_cell(0, forwards, false),
+            _ => return UpdateScreen::DontRedraw,
+        }
+
+        UpdateScreen::Redraw
+    }
+
+    /// Typing while no cell is edited starts editing the active cell, replacing its text
+    pub fn on_text_input<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let c = match app_state_no_data.windows[event.window].get_keyboard_state().current_char {
+            Some(c) if !c.is_control() => c,
+            _ => return UpdateScreen::DontRedraw,
+        };
+
+        if self.editor.is_none() {
+            let cell = match self.selected_cell {
+                Some(cell) => cell,
+                None => return UpdateScreen::DontRedraw,
+            };
+            self.editor = Some((cell, TextInputState::new("")));
+            self.scroll_to_cell(cell);
+        }
+
+        match &mut self.editor {
+            Some((_, editor)) => { editor.handle_text_input(c); UpdateScreen::Redraw },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Clicking outside of the table saves the edited cell
+    pub fn on_focus_lost<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, _event: WindowEvent<T>) -> UpdateScreen {
+        if self.editor.is_none() {
+            return UpdateScreen::DontRedraw;
+        }
+        self.commit_editing();
+        UpdateScreen::Redraw
+    }
 }
 
 /// Maps an index number to a value, necessary for creating the column name:
@@ -225,4 +998,29 @@ fn test_column_name_from_number() {
     assert_eq!(column_name_from_number(26), String::from("AA"));
     assert_eq!(column_name_from_number(27), String::from("AB"));
     assert_eq!(column_name_from_number(225), String::from("HR"));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_worksheet_csv_and_sorting() {
+    let mut sheet = Worksheet::default();
+    sheet.set_cell(0, 0, "plain");
+    sheet.set_cell(1, 0, "with, comma");
+    sheet.set_cell(2, 0, "with \"quotes\"");
+    sheet.set_cell(0, 1, "two\r\nlines");
+    sheet.set_cell(2, 2, "10");
+
+    let csv = sheet.to_csv();
+    assert_eq!(csv, "plain,\"with, comma\",\"with \"\"quotes\"\"\"\r\n\"two\r\nlines\",,\r\n,,10");
+    assert_eq!(Worksheet::from_csv(&csv).unwrap().data, sheet.data);
+    assert_eq!(Worksheet::from_csv("a,b\nc\n").unwrap().data, Worksheet::from_csv("a,b\r\nc").unwrap().data);
+    assert_eq!(Worksheet::from_csv("a,\"b\n").unwrap_err(), CsvError::UnterminatedQuote { line: 1 });
+    assert_eq!(Worksheet::from_csv("a\n\"b\"c").unwrap_err(), CsvError::CharacterAfterQuote { line: 2, character: 'c' });
+    assert_eq!(sheet.to_tsv((1, 0), (2, 1)), "with, comma\t\"with \"\"quotes\"\"\"\r\n\t");
+
+    // Numbers before text, empty cells last, the other columns move with the sorted column
+    let mut sheet = Worksheet::from_csv("b,1\n10,2\n,3\n9,4\nA,5").unwrap();
+    sheet.sort_rows_by_column(0, SortOrder::Ascending);
+    assert_eq!(sheet.to_csv(), "9,4\r\n10,2\r\nA,5\r\nb,1\r\n,3");
+    sheet.sort_rows_by_column(0, SortOrder::Descending);
+    assert_eq!(sheet.to_csv(), "b,1\r\nA,5\r\n10,2\r\n9,4\r\n,3");
+}
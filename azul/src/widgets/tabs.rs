@@ -0,0 +1,565 @@
+//! Tab strip with a content area that shows the page of the selected tab
+//!
+//! Tabs can be closed with the button on the tab and reordered by dragging them. If the
+//! tabs don't fit into the strip, it can be scrolled with the mouse wheel or with the
+//! buttons at its ends. The size of the tabs is only known after they have been laid out,
+//! so the tabs are measured whenever the mouse moves over the strip.
+//!
+//! Like in a `Stack`, the page of each tab is stored in the `TabsState` and the content
+//! area is keyed by the selected tab, so the state of the widgets of a page is kept
+//! while another tab is selected. The widgets of the page can be bound to the page.
+
+use azul_css::{CssProperty, LayoutLeft};
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen, TabIndex},
+    window::{FakeWindow, WindowEvent},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback, with_page_scope},
+    app_state::AppStateNoData,
+    id_tree::NodeId,
+    widgets::stack::new_page_key,
+};
+
+/// ID of the dynamic `left` property of the list of tabs, for scrolling the tab strip
+const LIST_LEFT_ID: &str = "__azul-native-tabs-list-left";
+/// How far the buttons at the ends of the tab strip scroll it, in pixels
+const SCROLL_STEP: f32 = 100.0;
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Tabs {
+
+}
+
+#[derive(Debug, Clone)]
+pub struct Tab<P> {
+    pub title: String,
+    /// Whether the tab has a button that closes it. Default: `true`
+    pub closable: bool,
+    /// The content of the tab, including the state of its widgets
+    pub page: P,
+    /// Unique key of the tab, see `NodeData::key`
+    key: usize,
+}
+
+impl<P> Tab<P> {
+
+    pub fn new<S: Into<String>>(title: S, page: P) -> Self {
+        Self {
+            title: title.into(),
+            closable: true,
+            page,
+            key: new_page_key(),
+        }
+    }
+
+    pub fn with_closable(self, closable: bool) -> Self {
+        Self { closable, .. self }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TabsState<P> {
+    pub tabs: Vec<Tab<P>>,
+    /// Index of the selected tab, whose page is shown
+    pub selected: Option<usize>,
+    /// How far the tab strip is scrolled, in pixels
+    scroll_offset: f32,
+    /// Left edge (relative to the first tab) and width of the tabs in the last frame
+    tab_extents: Vec<(f32, f32)>,
+    /// Left edge (relative to the window) and width of the visible part of the tab strip in the last frame
+    strip_extent: (f32, f32),
+    /// The tab that the mouse was pressed on
+    drag: Option<TabDrag>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct TabDrag {
+    index: usize,
+    /// Whether the mouse was pressed on the close button, the tab is closed if the mouse is released on it
+    on_close_button: bool,
+}
+
+impl<P> TabsState<P> {
+
+    /// Creates the tabs and selects the first tab
+    pub fn new(tabs: Vec<Tab<P>>) -> Self {
+        Self {
+            selected: if tabs.is_empty() { None } else { Some(0) },
+            tabs,
+            scroll_offset: 0.0,
+            tab_extents: Vec::new(),
+            strip_extent: (0.0, 0.0),
+            drag: None,
+        }
+    }
+
+    /// Adds the tab at the end and selects it
+    pub fn add_tab(&mut self, tab: Tab<P>) {
+        self.tabs.push(tab);
+        let index = self.tabs.len() - 1;
+        self.select(index);
+    }
+
+    /// Selects the tab and scrolls it into view, returns `false` if there is no such tab
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.tabs.len() {
+            return false;
+        }
+        self.selected = Some(index);
+        self.scroll_to_tab(index);
+        true
+    }
+
+    pub fn get_selected_tab(&self) -> Option<&Tab<P>> {
+        self.selected.and_then(|index| self.tabs.get(index))
+    }
+
+    pub fn get_selected_tab_mut(&mut self) -> Option<&mut Tab<P>> {
+        match self.selected {
+            Some(index) => self.tabs.get_mut(index),
+            None => None,
+        }
+    }
+
+    /// Removes the tab. If it was selected, the tab after it (or the last tab) is selected
+    pub fn close_tab(&mut self, index: usize) -> Option<Tab<P>> {
+
+        if index >= self.tabs.len() {
+            return None;
+        }
+
+        let tab = self.tabs.remove(index);
+        self.tab_extents.clear();
+        self.selected = match self.selected {
+            Some(selected) if selected > index => Some(selected - 1),
+            Some(selected) if selected == index && self.tabs.is_empty() => None,
+            Some(selected) if selected == index => Some(index.min(self.tabs.len() - 1)),
+            selected => selected,
+        };
+
+        Some(tab)
+    }
+
+    /// Moves the tab to another position, the selection stays on the selected tab
+    pub fn move_tab(&mut self, from: usize, to: usize) -> bool {
+
+        if from >= self.tabs.len() || to >= self.tabs.len() {
+            return false;
+        }
+
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+        self.tab_extents.clear();
+        self.selected = self.selected.map(|selected| {
+            if selected == from {
+                to
+            } else if from < selected && selected <= to {
+                selected - 1
+            } else if to <= selected && selected < from {
+                selected + 1
+            } else {
+                selected
+            }
+        });
+
+        true
+    }
+
+    /// How far the tab strip can be scrolled, 0 if all tabs fit into the strip
+    fn get_max_scroll_offset(&self) -> f32 {
+        let tabs_width = self.tab_extents.last().map(|(left, width)| left + width).unwrap_or(0.0);
+        (tabs_width - self.strip_extent.1).max(0.0)
+    }
+
+    /// Scrolls the tab strip by the number of pixels, returns whether the strip has been scrolled
+    pub fn scroll_by(&mut self, amount: f32) -> bool {
+        let scroll_offset = (self.scroll_offset + amount).min(self.get_max_scroll_offset()).max(0.0);
+        if scroll_offset == self.scroll_offset {
+            return false;
+        }
+        self.scroll_offset = scroll_offset;
+        true
+    }
+
+    /// Scrolls the tab strip so that the tab is visible, only works once the tab has been measured
+    fn scroll_to_tab(&mut self, index: usize) {
+        if let Some((left, width)) = self.tab_extents.get(index).cloned() {
+            if left < self.scroll_offset {
+                self.scroll_offset = left;
+            } else if left + width > self.scroll_offset + self.strip_extent.1 {
+                self.scroll_offset = left + width - self.strip_extent.1;
+            }
+        }
+    }
+
+    /// Measures the visible part of the tab strip and the tabs (the children of the `list` node) in the last frame
+    fn measure<T: Layout>(&mut self, event: &WindowEvent<T>, list: NodeId) {
+
+        let strip_rect = match event.get_index_in_parent(list).and_then(|(_, viewport)| event.get_node_rect(viewport)) {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        self.strip_extent = (strip_rect.origin.x, strip_rect.size.width);
+        self.tab_extents.clear();
+
+        let tab_nodes: Vec<NodeId> = list.children(&event.ui_state.dom.arena.borrow().node_layout).collect();
+
+        let mut first_tab_left = None;
+        for tab in tab_nodes {
+            let rect = match event.get_node_rect(tab) {
+                Some(rect) => rect,
+                None => break,
+            };
+            let first_tab_left = *first_tab_left.get_or_insert(rect.origin.x);
+            self.tab_extents.push((rect.origin.x - first_tab_left, rect.size.width));
+        }
+    }
+}
+
+/// Whether the cursor is over the node in the last frame
+fn is_cursor_over<T: Layout>(event: &WindowEvent<T>, node_id: NodeId) -> bool {
+    let (x, y) = event.cursor_in_viewport;
+    event.get_node_rect(node_id).map(|rect| {
+        x >= rect.origin.x && x < rect.origin.x + rect.size.width &&
+        y >= rect.origin.y && y < rect.origin.y + rect.size.height
+    }).unwrap_or(false)
+}
+
+/// Finds the page of the tab with the key in the tabs of a `TabsState`, see `with_page_scope`
+unsafe fn find_page<P>(tabs: *const (), key: usize) -> Option<*const u8> {
+    // SAFETY: `Tabs::dom` passes `&state.tabs` to `with_page_scope` together with this function
+    let tabs = &*(tabs as *const Vec<Tab<P>>);
+    tabs.iter().find(|tab| tab.key == key).map(|tab| &tab.page as *const P as *const u8)
+}
+
+/// Whether the cursor is over the close button of the tab, the second child of the tab after the title
+fn is_cursor_over_close_button<T: Layout>(event: &WindowEvent<T>, tab: NodeId) -> bool {
+    let close_button = {
+        let arena = event.ui_state.dom.arena.borrow();
+        arena.node_layout[tab].first_child.and_then(|title| arena.node_layout[title].next_sibling)
+    };
+    close_button.map(|close_button| is_cursor_over(event, close_button)).unwrap_or(false)
+}
+
+/// The list of the tabs is the first child of the viewport
+fn get_list<T: Layout>(event: &WindowEvent<T>, viewport: NodeId) -> Option<NodeId> {
+    event.ui_state.dom.arena.borrow().node_layout[viewport].first_child
+}
+
+impl Tabs {
+
+    pub fn new() -> Self {
+        Self {
+
+        }
+    }
+
+    /// Renders the tab strip and the page of the selected tab with `render_page`, the pages of the other tabs aren't rendered
+    pub fn dom<T: Layout, P, F: FnOnce(&P) -> Dom<T>>(&self, state: &TabsState<P>, t: &T, window: &mut FakeWindow<T>, render_page: F) -> Dom<T> {
+
+        let ptr = match StackCheckedPointer::new(t, state) {
+            Some(ptr) => ptr,
+            None => return Dom::new(NodeType::Label(
+                "Cannot create tabs from heap-allocated TabsState, \
+                 please store the TabsState in the application data".into())
+            ),
+        };
+
+        // div.__azul-native-tabs
+        //     |-> div.__azul-native-tabs-strip
+        //     |   |-> p.__azul-native-tabs-scroll-button (only if the tabs don't fit into the strip)
+        //     |   |-> div.__azul-native-tabs-viewport
+        //     |   |   '-> div.__azul-native-tabs-list (moved to the left when the strip is scrolled)
+        //     |   |       '-> div.__azul-native-tab (.__azul-native-tab-selected, keyed by the tab)
+        //     |   |           |-> p.__azul-native-tab-title
+        //     |   |           '-> p.__azul-native-tab-close (only if the tab is closable)
+        //     |   '-> p.__azul-native-tabs-scroll-button (only if the tabs don't fit into the strip)
+        //     '-> div.__azul-native-tabs-content (keyed by the selected tab)
+        //         '-> the DOM returned by `render_page`
+
+        let tab_mouse_down = window.add_callback(ptr, DefaultCallback(Self::tabs_on_tab_mouse_down::<T, P>));
+        let tab_mouse_over = window.add_callback(ptr, DefaultCallback(Self::tabs_on_tab_mouse_over::<T, P>));
+        let tab_mouse_up = window.add_callback(ptr, DefaultCallback(Self::tabs_on_tab_mouse_up::<T, P>));
+        let strip_mouse_over = window.add_callback(ptr, DefaultCallback(Self::tabs_on_strip_mouse_over::<T, P>));
+        let strip_scroll = window.add_callback(ptr, DefaultCallback(Self::tabs_on_strip_scroll::<T, P>));
+        let scroll_left = window.add_callback(ptr, DefaultCallback(Self::tabs_on_scroll_left::<T, P>));
+        let scroll_right = window.add_callback(ptr, DefaultCallback(Self::tabs_on_scroll_right::<T, P>));
+        let virtual_key_down = window.add_callback(ptr, DefaultCallback(Self::tabs_on_virtual_key_down::<T, P>));
+
+        let mut list = Dom::new(NodeType::Div)
+            .with_class("__azul-native-tabs-list")
+            .with_style_override(LIST_LEFT_ID, CssProperty::Left(LayoutLeft::px(-state.scroll_offset)));
+
+        for (index, tab) in state.tabs.iter().enumerate() {
+
+            let mut tab_dom = Dom::new(NodeType::Div)
+                .with_class("__azul-native-tab")
+                .with_key(tab.key)
+                .with_pointer_capture(true)
+                .with_child(Dom::new(NodeType::Label(tab.title.clone())).with_class("__azul-native-tab-title"));
+
+            if state.selected == Some(index) {
+                tab_dom.add_class("__azul-native-tab-selected");
+            }
+
+            if tab.closable {
+                tab_dom.add_child(Dom::new(NodeType::Label("\u{00D7}".into())).with_class("__azul-native-tab-close"));
+            }
+
+            tab_dom.add_default_callback_id(On::LeftMouseDown, tab_mouse_down);
+            tab_dom.add_default_callback_id(On::MouseOver, tab_mouse_over);
+            tab_dom.add_default_callback_id(On::LeftMouseUp, tab_mouse_up);
+            list.add_child(tab_dom);
+        }
+
+        // The viewport is keyed, so that the scroll buttons appearing before it don't change the identity of the tabs
+        let mut viewport = Dom::new(NodeType::Div)
+            .with_class("__azul-native-tabs-viewport")
+            .with_key("viewport")
+            .with_child(list);
+
+        viewport.add_default_callback_id(On::MouseOver, strip_mouse_over);
+        viewport.add_default_callback_id(On::Scroll, strip_scroll);
+
+        let mut strip = Dom::new(NodeType::Div)
+            .with_class("__azul-native-tabs-strip")
+            .with_tab_index(TabIndex::Auto);
+
+        strip.add_default_callback_id(On::VirtualKeyDown, virtual_key_down);
+
+        let tabs_overflow = state.scroll_offset > 0.0 || state.get_max_scroll_offset() > 0.0;
+        let scroll_button = |text: &str, callback_id| {
+            let mut button = Dom::new(NodeType::Label(text.into())).with_class("__azul-native-tabs-scroll-button");
+            button.add_default_callback_id(On::LeftMouseDown, callback_id);
+            button
+        };
+
+        if tabs_overflow {
+            strip.add_child(scroll_button("\u{2039}", scroll_left));
+        }
+        strip.add_child(viewport);
+        if tabs_overflow {
+            strip.add_child(scroll_button("\u{203A}", scroll_right));
+        }
+
+        let mut content = Dom::new(NodeType::Div).with_class("__azul-native-tabs-content");
+        if let Some(tab) = state.get_selected_tab() {
+            let page = with_page_scope(&state.tabs, tab.key, &tab.page, find_page::<P>, || render_page(&tab.page));
+            content = content.with_key(tab.key).with_child(page);
+        }
+
+        Dom::new(NodeType::Div)
+            .with_class("__azul-native-tabs")
+            .with_child(strip)
+            .with_child(content)
+    }
+
+    fn tabs_on_tab_mouse_down<T: Layout, P>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TabsState::<P>::on_tab_mouse_down, data, event) }
+    }
+
+    fn tabs_on_tab_mouse_over<T: Layout, P>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TabsState::<P>::on_tab_mouse_over, data, event) }
+    }
+
+    fn tabs_on_tab_mouse_up<T: Layout, P>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TabsState::<P>::on_tab_mouse_up, data, event) }
+    }
+
+    fn tabs_on_strip_mouse_over<T: Layout, P>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TabsState::<P>::on_strip_mouse_over, data, event) }
+    }
+
+    fn tabs_on_strip_scroll<T: Layout, P>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TabsState::<P>::on_strip_scroll, data, event) }
+    }
+
+    fn tabs_on_scroll_left<T: Layout, P>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TabsState::<P>::on_scroll_left, data, event) }
+    }
+
+    fn tabs_on_scroll_right<T: Layout, P>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TabsState::<P>::on_scroll_right, data, event) }
+    }
+
+    fn tabs_on_virtual_key_down<T: Layout, P>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TabsState::<P>::on_virtual_key_down, data, event) }
+    }
+}
+
+impl<P> TabsState<P> {
+
+    /// Pressing the mouse on a tab selects it and starts dragging it, unless the mouse was pressed on the close button
+    pub fn on_tab_mouse_down<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (index, list) = match event.get_index_in_parent(event.hit_dom_node) {
+            Some(index_and_list) => index_and_list,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        self.measure(&event, list);
+
+        let is_closable = self.tabs.get(index).map(|tab| tab.closable).unwrap_or(false);
+        let on_close_button = is_closable && is_cursor_over_close_button(&event, event.hit_dom_node);
+        self.drag = Some(TabDrag { index, on_close_button });
+
+        if on_close_button || self.selected == Some(index) || !self.select(index) {
+            UpdateScreen::DontRedraw
+        } else {
+            UpdateScreen::Redraw
+        }
+    }
+
+    /// Dragging a tab moves it to the position of the cursor among the other tabs
+    pub fn on_tab_mouse_over<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        if !event.has_pointer_capture() || !app_state_no_data.windows[event.window].get_mouse_state().left_down {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let drag = match self.drag {
+            Some(drag) if !drag.on_close_button => drag,
+            _ => return UpdateScreen::DontRedraw,
+        };
+
+        let list = match event.get_index_in_parent(event.hit_dom_node) {
+            Some((_, list)) => list,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        self.measure(&event, list);
+
+        let cursor_x = event.cursor_in_viewport.0 - self.strip_extent.0 + self.scroll_offset;
+        let new_index = self.tab_extents.iter().enumerate()
+            .filter(|(index, (left, width))| *index != drag.index && left + width / 2.0 < cursor_x)
+            .count()
+            .min(self.tabs.len().saturating_sub(1));
+
+        if new_index == drag.index || !self.move_tab(drag.index, new_index) {
+            return UpdateScreen::DontRedraw;
+        }
+
+        self.drag = Some(TabDrag { index: new_index, .. drag });
+        UpdateScreen::Redraw
+    }
+
+    /// Releasing the mouse on the close button that it was pressed on closes the tab
+    pub fn on_tab_mouse_up<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        match self.drag.take() {
+            Some(drag) if drag.on_close_button && is_cursor_over_close_button(&event, event.hit_dom_node) => {
+                self.close_tab(drag.index);
+                UpdateScreen::Redraw
+            },
+            _ => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Measures the tabs, the scroll buttons are shown or hidden if the tabs (don't) fit into the strip anymore
+    pub fn on_strip_mouse_over<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let tabs_overflowed = self.get_max_scroll_offset() > 0.0;
+        let scroll_offset = self.scroll_offset;
+
+        if let Some(list) = get_list(&event, event.hit_dom_node) {
+            self.measure(&event, list);
+        }
+        self.scroll_offset = self.scroll_offset.min(self.get_max_scroll_offset());
+
+        if tabs_overflowed != (self.get_max_scroll_offset() > 0.0) || scroll_offset != self.scroll_offset {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    /// Scrolls the tab strip horizontally, with the horizontal or the vertical mouse wheel
+    pub fn on_strip_scroll<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (scroll_x, scroll_y) = {
+            let mouse_state = app_state_no_data.windows[event.window].get_mouse_state();
+            (mouse_state.scroll_x as f32, mouse_state.scroll_y as f32)
+        };
+
+        if let Some(list) = get_list(&event, event.hit_dom_node) {
+            self.measure(&event, list);
+        }
+
+        if self.scroll_by(if scroll_x != 0.0 { scroll_x } else { scroll_y }) {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    pub fn on_scroll_left<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, _event: WindowEvent<T>) -> UpdateScreen {
+        if self.scroll_by(-SCROLL_STEP) { UpdateScreen::Redraw } else { UpdateScreen::DontRedraw }
+    }
+
+    pub fn on_scroll_right<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, _event: WindowEvent<T>) -> UpdateScreen {
+        if self.scroll_by(SCROLL_STEP) { UpdateScreen::Redraw } else { UpdateScreen::DontRedraw }
+    }
+
+    /// Left / Right select the previous / next tab, Home / End the first / last tab
+    pub fn on_virtual_key_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        if self.tabs.is_empty() {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let last_tab = self.tabs.len() - 1;
+        let new_index = match (app_state_no_data.windows[event.window].get_keyboard_state().latest_virtual_keycode, self.selected) {
+            (Some(VirtualKeyCode::Left), Some(selected)) => selected.saturating_sub(1),
+            (Some(VirtualKeyCode::Right), Some(selected)) => (selected + 1).min(last_tab),
+            (Some(VirtualKeyCode::Left), None) | (Some(VirtualKeyCode::Right), None) | (Some(VirtualKeyCode::Home), _) => 0,
+            (Some(VirtualKeyCode::End), _) => last_tab,
+            _ => return UpdateScreen::DontRedraw,
+        };
+
+        if self.selected == Some(new_index) {
+            return UpdateScreen::DontRedraw;
+        }
+
+        self.select(new_index);
+        UpdateScreen::Redraw
+    }
+}
+
+#[test]
+fn test_tabs_state_close_and_move() {
+    let mut tabs = TabsState::new(vec![Tab::new("A", 'a'), Tab::new("B", 'b'), Tab::new("C", 'c')]);
+    tabs.select(1);
+
+    // Moving other tabs around keeps the selection on "B"
+    assert!(tabs.move_tab(2, 0));
+    assert_eq!(tabs.get_selected_tab().map(|tab| tab.page), Some('b'));
+    assert!(tabs.move_tab(2, 0));
+    assert_eq!(tabs.tabs.iter().map(|tab| tab.page).collect::<String>(), "bca");
+    assert_eq!(tabs.selected, Some(0));
+
+    // Closing the selected tab selects the tab after it, or the last tab
+    assert_eq!(tabs.close_tab(0).map(|tab| tab.page), Some('b'));
+    assert_eq!(tabs.get_selected_tab().map(|tab| tab.page), Some('c'));
+    tabs.select(1);
+    tabs.close_tab(1);
+    assert_eq!(tabs.get_selected_tab().map(|tab| tab.page), Some('c'));
+    tabs.close_tab(0);
+    assert_eq!(tabs.selected, None);
+}
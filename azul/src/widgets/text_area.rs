@@ -0,0 +1,1021 @@
+//! Multi-line text editor with soft wrapping, scrolling and optional line numbers
+//!
+//! Only the lines that fit into the text area are put into the DOM, so that large
+//! documents don't have to be laid out completely on every frame. Lines that haven't
+//! been rendered yet are assumed to be one line high, the wrapped lines are measured
+//! once they have been rendered (see `RowOffsets`).
+
+use std::{ops::Range, borrow::Cow};
+use unicode_segmentation::UnicodeSegmentation;
+use azul_css::{CssProperty, LayoutTop, LayoutFlexGrow, FloatValue, LayoutRect};
+use {
+    traits::Layout,
+    dom::{Dom, On, NodeType, UpdateScreen, TabIndex},
+    id_tree::NodeId,
+    window::{FakeWindow, WindowEvent, ScrollState},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback, DefaultCallbackId},
+    app_state::AppStateNoData,
+    text_selection::{TextCaret, CaretAffinity, floor_char_boundary},
+    widgets::{
+        text_input::TextInputState,
+        row_offsets::{RowOffsets, get_scrollbar_segments},
+    },
+};
+
+/// Number of lines that are laid out until the size of the text area is known
+const DEFAULT_VISIBLE_LINES: usize = 40;
+/// Line height that is used until the text has been laid out
+const DEFAULT_LINE_HEIGHT: f32 = 15.0;
+/// Number of lines that are rendered above and below the visible lines, so that
+/// Up / Down can move the caret into them using their layout
+const OVERSCAN_LINES: usize = 2;
+/// ID of the dynamic `top` property of the rendered lines, see the native CSS
+const VIEWPORT_TOP_ID: &str = "__azul-native-text-area-viewport-top";
+/// ID of the dynamic `flex-grow` property of the scrollbar thumb and the track above and below it
+const SCROLLBAR_GROW_ID: &str = "__azul-native-text-area-scrollbar-grow";
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct TextArea {
+    callbacks: Option<TextAreaCallbacks>,
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+struct TextAreaCallbacks {
+    text_input: DefaultCallbackId,
+    virtual_key_down: DefaultCallbackId,
+    mouse_down: DefaultCallbackId,
+    mouse_over: DefaultCallbackId,
+    scroll: DefaultCallbackId,
+    scrollbar_drag: DefaultCallbackId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextAreaState {
+    /// The text, caret, selection and undo history, edited the same way as the text of a
+    /// `TextInput`, except that line breaks are kept
+    pub editor: TextInputState,
+    /// Show the line numbers in front of the lines. Default: `false`
+    pub show_line_numbers: bool,
+    /// Distance in pixels from the top of the text to the top of the visible area,
+    /// can be scrolled as far as the lines are higher than the visible area
+    scroll: ScrollState,
+    /// Height of the visible area in pixels, updated to the last frame on every event
+    viewport_height: f32,
+    /// Start of the lines of the text, updated with the changes of the `editor`
+    lines: LineIndex,
+    /// Heights of the lines, a wrapped line is higher than one line
+    line_heights: RowOffsets,
+    /// Horizontal position that Up / Down try to keep when moving over shorter lines
+    preferred_x: Option<f32>,
+}
+
+impl Default for TextAreaState {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+impl TextAreaState {
+
+    /// Creates a new state with the caret at the start of the `text`
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        let mut editor = TextInputState::new(text);
+        editor.cursor = 0;
+        editor.multi_line = true;
+        let lines = LineIndex::new(&editor.text);
+        let mut state = Self {
+            editor,
+            lines,
+            show_line_numbers: false,
+            scroll: ScrollState::new(0.0, 0.0),
+            viewport_height: DEFAULT_VISIBLE_LINES as f32 * DEFAULT_LINE_HEIGHT,
+            line_heights: RowOffsets::new(DEFAULT_LINE_HEIGHT),
+            preferred_x: None,
+        };
+        state.update_overflow();
+        state
+    }
+
+    #[inline]
+    pub fn with_line_numbers(self, show_line_numbers: bool) -> Self {
+        Self { show_line_numbers, .. self }
+    }
+
+    /// Number of lines of the text, a trailing line break starts a new (empty) line
+    pub fn get_line_count(&self) -> usize {
+        self.get_lines().get_line_count()
+    }
+
+    /// Returns the (0-based) line that the caret is on
+    pub fn get_cursor_line(&self) -> usize {
+        self.get_lines().get_line_index(self.editor.get_cursor())
+    }
+
+    /// Returns the height of all lines together in pixels, i.e. the height that the scrollbar
+    /// reflects. Lines that haven't been rendered yet are assumed to be one line high.
+    pub fn get_total_height(&self) -> f32 {
+        self.line_heights.get_total_height(self.get_line_count())
+    }
+
+    /// Returns the distance in pixels from the top of the text to the top of the visible area
+    pub fn get_scroll_y(&self) -> f32 {
+        self.scroll.get_scroll_amount().1
+    }
+
+    /// Scrolls to the distance `y` from the top of the text, returns whether the text has been scrolled
+    pub fn scroll_to_offset(&mut self, y: f32) -> bool {
+        self.update_overflow();
+        let old_scroll_y = self.get_scroll_y();
+        self.scroll.add(0.0, y - old_scroll_y);
+        self.get_scroll_y() != old_scroll_y
+    }
+
+    /// Scrolls by the number of `pixels` (negative to scroll up), returns whether the text has been scrolled
+    pub fn scroll_by(&mut self, pixels: f32) -> bool {
+        let scroll_y = self.get_scroll_y();
+        self.scroll_to_offset(scroll_y + pixels)
+    }
+
+    /// Scrolls the caret into view after it has been moved by the application. Since the layout
+    /// isn't known here, the caret is assumed to be in the first row of its line - the callbacks
+    /// of the `TextArea` keep the caret visible using the layout of the wrapped line.
+    pub fn scroll_to_cursor(&mut self) {
+        self.scroll_caret_into_view(&NoLayout);
+    }
+
+    /// Forgets the measured heights of the lines, necessary if the `text` was changed
+    /// without the `editor` (which keeps track of the lines it changes) or the font changed
+    pub fn invalidate_line_heights(&mut self) {
+        self.editor.take_changed_range();
+        self.lines = LineIndex::new(&self.editor.text);
+        self.line_heights.clear();
+        self.update_overflow();
+    }
+
+    /// Returns the lines of the text. If the text was changed since the lines were updated
+    /// (i.e. outside of the callbacks of the `TextArea`), the lines are searched in the text.
+    fn get_lines(&self) -> Cow<LineIndex> {
+        if self.editor.is_changed() || self.lines.text_len != self.editor.text.len() {
+            Cow::Owned(LineIndex::new(&self.editor.text))
+        } else {
+            Cow::Borrowed(&self.lines)
+        }
+    }
+
+    /// Updates the lines to the changes of the `editor`, so that only the changed
+    /// part of the text has to be searched for line breaks
+    fn update_lines(&mut self) {
+        let text_len = self.editor.text.len();
+        match self.editor.take_changed_range() {
+            // The text could have been changed without the `editor`, in that case
+            // the changes don't add up to the length of the text
+            Some((start, old_end, new_end)) if old_end <= self.lines.text_len &&
+                                               new_end <= text_len &&
+                                               self.lines.text_len - old_end + new_end == text_len => {
+                self.lines.update(&self.editor.text, start, old_end, new_end);
+            },
+            None if self.lines.text_len == text_len => { },
+            _ => self.lines = LineIndex::new(&self.editor.text),
+        }
+    }
+
+    /// The scroll position can't go below the last line
+    fn update_overflow(&mut self) {
+        let max_scroll_y = self.get_total_height() - self.viewport_height;
+        self.scroll.set_overflow(0.0, max_scroll_y);
+    }
+
+    /// Returns the first and the last (exclusive) line that are rendered, i.e. the visible
+    /// lines and the overscan, together with the offset of the first rendered line
+    fn get_rendered_lines(&self) -> (usize, usize, f32) {
+        self.line_heights.get_rendered_rows(self.get_scroll_y(), self.viewport_height, self.get_line_count(), OVERSCAN_LINES)
+    }
+
+    /// Updates the height of the visible area and of the rendered lines to the last frame.
+    /// The first visible line stays where it is, even if the lines above it turned out to be wrapped.
+    fn update_layout<L: LinesLayout>(&mut self, layout: &L) {
+
+        self.update_lines();
+        let line_count = self.get_line_count();
+        let scroll_y = self.get_scroll_y();
+        let anchor = self.line_heights.get_row_at_offset(scroll_y, line_count).map(|(line, line_offset)| (line, scroll_y - line_offset));
+
+        if let Some(line_height) = layout.get_line_height() {
+            self.line_heights.set_estimate(line_height);
+        }
+        if let Some(viewport_height) = layout.get_viewport_height() {
+            self.viewport_height = viewport_height;
+        }
+        for (line, height) in layout.get_rendered_line_heights() {
+            if line < line_count {
+                self.line_heights.measure(line, height);
+            }
+        }
+
+        match anchor {
+            Some((line, distance_into_line)) => {
+                let line_offset = self.line_heights.get_row_offset(line);
+                self.scroll_to_offset(line_offset + distance_into_line);
+            },
+            None => self.update_overflow(),
+        }
+    }
+
+    /// Runs an edit of the text. Since the lines are measured by their index, the heights of
+    /// the lines after the edit are forgotten if the edit added or removed lines.
+    fn edit<R, F: FnOnce(&mut Self) -> R>(&mut self, edit: F) -> R {
+        self.update_lines();
+        let line_count = self.get_line_count();
+        let edited_line = self.get_edited_line();
+        let result = edit(self);
+        self.update_lines();
+        if self.get_line_count() != line_count {
+            let first_changed_line = edited_line.min(self.get_edited_line());
+            self.line_heights.forget_from(first_changed_line);
+            self.update_overflow();
+        }
+        result
+    }
+
+    /// Returns the line of the caret or the start of the selection, whichever comes first
+    fn get_edited_line(&self) -> usize {
+        let text = &self.editor.text;
+        let cursor = self.editor.get_cursor();
+        let start = self.editor.selection.as_ref().map(|selection| selection.get_range(text).start.min(cursor)).unwrap_or(cursor);
+        self.get_lines().get_line_index(start)
+    }
+
+    /// Returns the top and the height of the caret (relative to the top of the text) and its
+    /// horizontal position. If its line wasn't rendered, the caret is assumed to be at the
+    /// start of the line.
+    fn get_caret_position<L: LinesLayout>(&self, layout: &L) -> (f32, f32, f32) {
+        let cursor = self.editor.get_cursor();
+        let lines = self.get_lines();
+        let line = lines.get_line_index(cursor);
+        let line_start = lines.get_line_range(line).map(|line_range| line_range.start).unwrap_or(0);
+        let line_offset = self.line_heights.get_row_offset(line);
+        match layout.get_caret_rect(line, cursor - line_start) {
+            Some(caret_rect) => (line_offset + caret_rect.origin.y, caret_rect.size.height, caret_rect.origin.x),
+            None => (line_offset, self.line_heights.get_estimate(), 0.0),
+        }
+    }
+
+    /// Scrolls so that the caret is completely visible
+    fn scroll_caret_into_view<L: LinesLayout>(&mut self, layout: &L) {
+        let (caret_top, caret_height, _) = self.get_caret_position(layout);
+        let scroll_y = self.get_scroll_y();
+        if caret_top < scroll_y {
+            self.scroll_to_offset(caret_top);
+        } else if caret_top + caret_height > scroll_y + self.viewport_height {
+            self.scroll_to_offset(caret_top + caret_height - self.viewport_height);
+        }
+    }
+
+    /// Moves the caret up (negative) or down by the number of `rows` that the lines are wrapped into,
+    /// keeping the horizontal position of the caret. Moving up from the first row goes to the start
+    /// of the text, down from the last row to the end. Lines that weren't rendered are assumed to
+    /// be one row high, the caret keeps its column (in grapheme clusters) on them.
+    fn move_cursor_vertically<L: LinesLayout>(&mut self, layout: &L, rows: isize, extend_selection: bool) {
+
+        let (new_cursor, x) = {
+            let text = &self.editor.text;
+            let cursor = self.editor.get_cursor();
+            let lines = self.get_lines();
+            let (caret_top, caret_height, caret_x) = self.get_caret_position(layout);
+            let x = self.preferred_x.unwrap_or(caret_x);
+            let target_y = caret_top + caret_height / 2.0 + rows as f32 * caret_height;
+
+            let new_cursor = if target_y < 0.0 {
+                0
+            } else {
+                match self.line_heights.get_row_at_offset(target_y, self.get_line_count()) {
+                    Some((line, line_offset)) => {
+                        let line_range = lines.get_line_range(line).unwrap_or(0..0);
+                        let offset = layout.hit_test(line, (x, target_y - line_offset)).unwrap_or_else(|| {
+                            let cursor_line_start = lines.get_line_range(lines.get_line_index(cursor)).map(|line_range| line_range.start).unwrap_or(0);
+                            let column = text[cursor_line_start..cursor].graphemes(true).count();
+                            get_offset_at_column(&text[line_range.clone()], column)
+                        });
+                        line_range.start + floor_char_boundary(&text[line_range], offset)
+                    },
+                    None => text.len(),
+                }
+            };
+
+            (new_cursor, x)
+        };
+
+        self.editor.move_cursor(new_cursor, extend_selection);
+        self.preferred_x = Some(x);
+    }
+
+    /// Number of rows that PageUp / PageDown move the caret, one row less than fit into the visible area
+    fn get_rows_per_page<L: LinesLayout>(&self, layout: &L) -> isize {
+        let (_, caret_height, _) = self.get_caret_position(layout);
+        ((self.viewport_height / caret_height).floor() as isize - 1).max(1)
+    }
+
+    /// Handles the keys that depend on the lines, returns `None` for the keys that are handled by the `editor`
+    fn handle_line_key<L: LinesLayout>(&mut self, layout: &L, key: VirtualKeyCode, shift_down: bool, ctrl_down: bool) -> Option<UpdateScreen> {
+
+        // The horizontal position is only kept while moving up and down
+        match key {
+            VirtualKeyCode::Up | VirtualKeyCode::Down |
+            VirtualKeyCode::PageUp | VirtualKeyCode::PageDown => { },
+            _ => self.preferred_x = None,
+        }
+
+        match key {
+            VirtualKeyCode::Up => self.move_cursor_vertically(layout, -1, shift_down),
+            VirtualKeyCode::Down => self.move_cursor_vertically(layout, 1, shift_down),
+            VirtualKeyCode::PageUp | VirtualKeyCode::PageDown => {
+                let (_, caret_height, _) = self.get_caret_position(layout);
+                let rows = if key == VirtualKeyCode::PageUp { -self.get_rows_per_page(layout) } else { self.get_rows_per_page(layout) };
+                self.move_cursor_vertically(layout, rows, shift_down);
+                self.scroll_by(rows as f32 * caret_height);
+            },
+            // Home / End move to the start / end of the line, Ctrl + Home / End to the start / end of the text
+            VirtualKeyCode::Home | VirtualKeyCode::End if !ctrl_down => {
+                let line_range = {
+                    let lines = self.get_lines();
+                    lines.get_line_range(lines.get_line_index(self.editor.get_cursor())).unwrap_or(0..0)
+                };
+                let new_cursor = if key == VirtualKeyCode::Home { line_range.start } else { line_range.end };
+                self.editor.move_cursor(new_cursor, shift_down);
+            },
+            VirtualKeyCode::Return => {
+                return Some(if self.editor.insert_text("\n") { UpdateScreen::Redraw } else { UpdateScreen::DontRedraw });
+            },
+            _ => return None,
+        }
+
+        Some(UpdateScreen::Redraw)
+    }
+}
+
+impl TextArea {
+
+    pub fn new() -> Self {
+        TextArea { callbacks: None }
+    }
+
+    pub fn bind<T: Layout>(self, window: &mut FakeWindow<T>, field: &TextAreaState, data: &T) -> Self {
+        let ptr = StackCheckedPointer::new(data, field);
+        let callbacks = ptr.and_then(|ptr|{
+            Some(TextAreaCallbacks {
+                text_input: window.add_callback(ptr, DefaultCallback(TextAreaState::on_text_input_private)),
+                virtual_key_down: window.add_callback(ptr, DefaultCallback(TextAreaState::on_virtual_key_down_private)),
+                mouse_down: window.add_callback(ptr, DefaultCallback(TextAreaState::on_mouse_down_private)),
+                mouse_over: window.add_callback(ptr, DefaultCallback(TextAreaState::on_mouse_over_private)),
+                scroll: window.add_callback(ptr, DefaultCallback(TextAreaState::on_scroll_private)),
+                scrollbar_drag: window.add_callback(ptr, DefaultCallback(TextAreaState::on_scrollbar_drag_private)),
+            })
+        });
+
+        Self {
+            callbacks,
+            .. self
+        }
+    }
+
+    pub fn dom<T: Layout>(&self, field: &TextAreaState) -> Dom<T> {
+
+        // div.__azul-native-text-area
+        //     |-> div.__azul-native-text-area-lines
+        //     |   '-> div.__azul-native-text-area-viewport (positioned at the offset of the first rendered line)
+        //     |       '-> div.__azul-native-text-area-line (one per rendered line)
+        //     |           |-> p.__azul-native-text-area-line-number (optional)
+        //     |           '-> p.__azul-native-text-area-line-text
+        //     '-> div.__azul-native-text-area-scrollbar
+        //         |-> div.__azul-native-text-area-scrollbar-track (above the thumb)
+        //         |-> div.__azul-native-text-area-scrollbar-thumb
+        //         '-> div.__azul-native-text-area-scrollbar-track (below the thumb)
+
+        let mut container = Dom::new(NodeType::Div)
+            .with_class("__azul-native-text-area")
+            .with_tab_index(TabIndex::Auto);
+
+        let mut lines = Dom::new(NodeType::Div)
+            .with_class("__azul-native-text-area-lines");
+
+        let text = &field.editor.text;
+        let cursor = field.editor.get_cursor();
+        let selection = field.editor.selection.as_ref()
+            .map(|selection| selection.get_range(text))
+            .filter(|range| range.start < range.end);
+
+        let scroll_y = field.get_scroll_y();
+        let (first_line, last_line, first_line_offset) = field.get_rendered_lines();
+
+        let mut viewport = Dom::new(NodeType::Div)
+            .with_class("__azul-native-text-area-viewport")
+            .with_style_override(VIEWPORT_TOP_ID, CssProperty::Top(LayoutTop::px(first_line_offset - scroll_y)));
+
+        let line_ranges = field.get_lines();
+        let rendered_lines = (first_line..last_line)
+            .filter_map(|line| Some((line, line_ranges.get_line_range(line)?)));
+
+        for (line_index, line) in rendered_lines {
+
+            // Part of the selection on this line, relative to the start of the line
+            let line_selection = selection.as_ref()
+                .map(|selection| (selection.start.max(line.start), selection.end.min(line.end)))
+                .filter(|(start, end)| start < end)
+                .map(|(start, end)| (start - line.start, end - line.start));
+
+            // The lines without the caret still get a (hidden) caret, so that
+            // they can be hit-tested like any other text editor
+            let caret = if cursor >= line.start && cursor <= line.end {
+                TextCaret {
+                    selection: line_selection,
+                    .. TextCaret::new(cursor - line.start)
+                }
+            } else {
+                match line_selection {
+                    Some((start, end)) => TextCaret::selection_only(start, end),
+                    None => TextCaret { show_caret: false, .. TextCaret::new(0) },
+                }
+            };
+
+            let line_text = Dom::new(NodeType::Label(text[line].to_string()))
+                .with_class("__azul-native-text-area-line-text")
+                .with_caret(caret);
+
+            let mut row = Dom::new(NodeType::Div).with_class("__azul-native-text-area-line");
+
+            if field.show_line_numbers {
+                row.add_child(Dom::new(NodeType::Label(format!("{}", line_index + 1)))
+                    .with_class("__azul-native-text-area-line-number"));
+            }
+
+            viewport.add_child(row.with_child(line_text));
+        }
+
+        lines.add_child(viewport);
+
+        let (above, thumb, below) = get_scrollbar_segments(scroll_y, field.viewport_height, field.get_total_height());
+        let segment = |class: &str, grow: f32| Dom::new(NodeType::Div)
+            .with_class(class)
+            .with_style_override(SCROLLBAR_GROW_ID, CssProperty::FlexGrow(LayoutFlexGrow(FloatValue::new(grow))));
+
+        let mut scrollbar = Dom::new(NodeType::Div)
+            .with_class("__azul-native-text-area-scrollbar")
+            .with_pointer_capture(true)
+            .with_child(segment("__azul-native-text-area-scrollbar-track", above))
+            .with_child(segment("__azul-native-text-area-scrollbar-thumb", thumb))
+            .with_child(segment("__azul-native-text-area-scrollbar-track", below));
+
+        // The mouse callbacks are attached to the lines, so that
+        // clicking on the scrollbar doesn't move the caret
+        if let Some(callbacks) = self.callbacks {
+            container.add_default_callback_id(On::TextInput, callbacks.text_input);
+            container.add_default_callback_id(On::VirtualKeyDown, callbacks.virtual_key_down);
+            container.add_default_callback_id(On::Scroll, callbacks.scroll);
+            lines.add_default_callback_id(On::LeftMouseDown, callbacks.mouse_down);
+            lines.add_default_callback_id(On::MouseOver, callbacks.mouse_over);
+            scrollbar.add_default_callback_id(On::LeftMouseDown, callbacks.scrollbar_drag);
+            scrollbar.add_default_callback_id(On::MouseOver, callbacks.scrollbar_drag);
+        }
+
+        container
+            .with_child(lines)
+            .with_child(scrollbar)
+    }
+}
+
+impl TextAreaState {
+
+    fn on_virtual_key_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_virtual_key_down, app_state_no_data, window_event) }
+    }
+
+    fn on_text_input_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_text_input, app_state_no_data, window_event) }
+    }
+
+    fn on_mouse_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_mouse_down, app_state_no_data, window_event) }
+    }
+
+    fn on_mouse_over_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_mouse_over, app_state_no_data, window_event) }
+    }
+
+    fn on_scroll_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_scroll, app_state_no_data, window_event) }
+    }
+
+    fn on_scrollbar_drag_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_scrollbar_drag, app_state_no_data, window_event) }
+    }
+
+    /// Returns the layout of the lines in the last frame, the `lines_node` is the parent of the rendered lines
+    fn get_layout<'a, T: Layout>(&self, window: &'a FakeWindow<T>, event: &WindowEvent<T>, lines_node: Option<NodeId>) -> WindowLinesLayout<'a, T> {
+        let (first_line, _, _) = self.get_rendered_lines();
+        WindowLinesLayout::new(window, event, lines_node, first_line)
+    }
+
+    pub fn on_virtual_key_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let window = &app_state_no_data.windows[event.window];
+        let (virtual_keycode, shift_down, ctrl_down) = {
+            let keyboard_state = window.get_keyboard_state();
+            (keyboard_state.latest_virtual_keycode, keyboard_state.shift_down, keyboard_state.ctrl_down)
+        };
+
+        let key = match virtual_keycode {
+            Some(key) => key,
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        // The callbacks are attached to the text area, the lines are its first child
+        let lines_node = event.ui_state.dom.arena.borrow().node_layout[event.hit_dom_node].first_child;
+        let layout = self.get_layout(window, &event, lines_node);
+        self.update_layout(&layout);
+
+        let resources = app_state_no_data.resources;
+        let update_screen = self.edit(|state| match state.handle_line_key(&layout, key, shift_down, ctrl_down) {
+            Some(update_screen) => update_screen,
+            None => state.editor.handle_virtual_key(key, shift_down, ctrl_down, resources),
+        });
+
+        self.scroll_caret_into_view(&layout);
+        update_screen
+    }
+
+    pub fn on_text_input<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let window = &app_state_no_data.windows[event.window];
+        match window.get_keyboard_state().current_char {
+            Some(c) => {
+                let lines_node = event.ui_state.dom.arena.borrow().node_layout[event.hit_dom_node].first_child;
+                let layout = self.get_layout(window, &event, lines_node);
+                self.update_layout(&layout);
+                self.preferred_x = None;
+                let update_screen = self.edit(|state| state.editor.handle_text_input(c));
+                self.scroll_caret_into_view(&layout);
+                update_screen
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Places the caret at the clicked character, Shift + click extends the selection
+    pub fn on_mouse_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let window = &app_state_no_data.windows[event.window];
+        let shift_down = window.get_keyboard_state().shift_down;
+        let layout = self.get_layout(window, &event, Some(event.hit_dom_node));
+        self.update_layout(&layout);
+        match self.get_offset_at_cursor(&layout, &event) {
+            Some(offset) => {
+                self.preferred_x = None;
+                self.editor.move_cursor(offset, shift_down);
+                self.scroll_caret_into_view(&layout);
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Selects the text while the mouse is dragged over the text area,
+    /// dragging above or below the visible lines scrolls
+    pub fn on_mouse_over<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let window = &app_state_no_data.windows[event.window];
+        if !window.get_mouse_state().left_down {
+            return UpdateScreen::DontRedraw;
+        }
+        let layout = self.get_layout(window, &event, Some(event.hit_dom_node));
+        self.update_layout(&layout);
+        match self.get_offset_at_cursor(&layout, &event) {
+            Some(offset) if offset != self.editor.get_cursor() => {
+                self.preferred_x = None;
+                self.editor.move_cursor(offset, true);
+                self.scroll_caret_into_view(&layout);
+                UpdateScreen::Redraw
+            },
+            _ => UpdateScreen::DontRedraw,
+        }
+    }
+
+    /// Scrolls by the scroll amount of the mouse wheel in pixels
+    pub fn on_scroll<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let window = &app_state_no_data.windows[event.window];
+        let scroll_y = window.get_mouse_state().scroll_y as f32;
+        if scroll_y == 0.0 {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let lines_node = event.ui_state.dom.arena.borrow().node_layout[event.hit_dom_node].first_child;
+        let layout = self.get_layout(window, &event, lines_node);
+        self.update_layout(&layout);
+
+        if self.scroll_by(scroll_y) {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    /// Pressing the mouse on the scrollbar (and dragging it) scrolls so that the
+    /// thumb is centered at the cursor
+    pub fn on_scrollbar_drag<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        if !event.has_pointer_capture() {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let scrollbar_height = match event.get_node_rect(event.hit_dom_node) {
+            Some(rect) if rect.size.height > 0.0 => rect.size.height,
+            _ => return UpdateScreen::DontRedraw,
+        };
+
+        // The lines are the sibling in front of the scrollbar
+        let window = &app_state_no_data.windows[event.window];
+        let lines_node = event.ui_state.dom.arena.borrow().node_layout[event.hit_dom_node].previous_sibling;
+        let layout = self.get_layout(window, &event, lines_node);
+        self.update_layout(&layout);
+
+        let (_, y) = event.cursor_relative_to_item;
+        let fraction = (y / scrollbar_height).max(0.0).min(1.0);
+        let total_height = self.get_total_height();
+        let viewport_height = self.viewport_height;
+        if self.scroll_to_offset(fraction * total_height - viewport_height / 2.0) {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
+
+    /// Returns the position in the text under the mouse cursor, or in the closest rendered line
+    fn get_offset_at_cursor<T: Layout>(&self, layout: &WindowLinesLayout<T>, event: &WindowEvent<T>) -> Option<usize> {
+        let (line_index, offset) = layout.get_position_at_cursor(event)?;
+        let line = self.get_lines().get_line_range(line_index)?;
+        Some(line.start + floor_char_boundary(&self.editor.text[line.clone()], offset))
+    }
+}
+
+/// Layout of the lines of a `TextArea` in the last frame. Positions in a line are relative to the
+/// top of the line and the left of its text, offsets are byte offsets into the text of the line.
+trait LinesLayout {
+    /// Height of the area that the lines are shown in
+    fn get_viewport_height(&self) -> Option<f32>;
+    /// Height of a line that isn't wrapped
+    fn get_line_height(&self) -> Option<f32>;
+    /// Index and height of the lines that were rendered
+    fn get_rendered_line_heights(&self) -> Vec<(usize, f32)>;
+    /// Rectangle of the caret in front of the `offset`, `None` if the line wasn't rendered
+    fn get_caret_rect(&self, line: usize, offset: usize) -> Option<LayoutRect>;
+    /// Offset that is closest to the `point`, `None` if the line wasn't rendered
+    fn hit_test(&self, line: usize, point: (f32, f32)) -> Option<usize>;
+}
+
+/// Used when the layout isn't known, all lines are assumed to be one line high
+struct NoLayout;
+
+impl LinesLayout for NoLayout {
+    fn get_viewport_height(&self) -> Option<f32> { None }
+    fn get_line_height(&self) -> Option<f32> { None }
+    fn get_rendered_line_heights(&self) -> Vec<(usize, f32)> { Vec::new() }
+    fn get_caret_rect(&self, _: usize, _: usize) -> Option<LayoutRect> { None }
+    fn hit_test(&self, _: usize, _: (f32, f32)) -> Option<usize> { None }
+}
+
+/// Layout of the lines of a `TextArea`, read from the window in the callbacks
+struct WindowLinesLayout<'a, T: 'a + Layout> {
+    window: &'a FakeWindow<T>,
+    /// Rectangle of the area that the lines are shown in
+    lines_rect: Option<LayoutRect>,
+    /// The rendered lines: index of the line, rectangle of the line, the label
+    /// with the text of the line and the rectangle of the label
+    rows: Vec<(usize, LayoutRect, NodeId, LayoutRect)>,
+}
+
+impl<'a, T: 'a + Layout> WindowLinesLayout<'a, T> {
+
+    /// Reads the layout of the rendered lines, the `lines_node` is the parent of the viewport
+    /// that contains the lines and `first_line` is the index of the first rendered line
+    fn new(window: &'a FakeWindow<T>, event: &WindowEvent<T>, lines_node: Option<NodeId>, first_line: usize) -> Self {
+        let arena = event.ui_state.dom.arena.borrow();
+        let node_layout = &arena.node_layout;
+        let rows = lines_node
+            .and_then(|lines| node_layout[lines].first_child)
+            .map(|viewport| viewport.children(node_layout).enumerate().filter_map(|(row_index, row)| {
+                // The text is the last child of the line, after the line number
+                let label = node_layout[row].last_child?;
+                Some((first_line + row_index, event.get_node_rect(row)?, label, event.get_node_rect(label)?))
+            }).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        Self {
+            window,
+            lines_rect: lines_node.and_then(|lines| event.get_node_rect(lines)),
+            rows,
+        }
+    }
+
+    fn get_row(&self, line: usize) -> Option<&(usize, LayoutRect, NodeId, LayoutRect)> {
+        self.rows.iter().find(|(row_line, _, _, _)| *row_line == line)
+    }
+
+    /// Returns the line under the mouse cursor (or the last rendered line if the mouse
+    /// is below the text) and the byte offset in the line
+    fn get_position_at_cursor(&self, event: &WindowEvent<T>) -> Option<(usize, usize)> {
+        let cursor_y = event.cursor_in_viewport.1;
+        let (line, _, label, _) = self.rows.iter()
+            .find(|(_, row_rect, _, _)| cursor_y < row_rect.origin.y + row_rect.size.height)
+            .or(self.rows.last())?;
+        let offset = event.hit_test_text_at_cursor(*label).map(|(offset, _)| offset).unwrap_or(0);
+        Some((*line, offset))
+    }
+}
+
+impl<'a, T: 'a + Layout> LinesLayout for WindowLinesLayout<'a, T> {
+
+    fn get_viewport_height(&self) -> Option<f32> {
+        self.lines_rect.map(|rect| rect.size.height)
+    }
+
+    fn get_line_height(&self) -> Option<f32> {
+        self.rows.iter()
+            .filter_map(|(_, _, label, _)| self.window.caret_rect(*label, 0, CaretAffinity::Downstream))
+            .map(|caret_rect| caret_rect.size.height)
+            .find(|line_height| *line_height > 0.0)
+    }
+
+    fn get_rendered_line_heights(&self) -> Vec<(usize, f32)> {
+        self.rows.iter().map(|(line, row_rect, _, _)| (*line, row_rect.size.height)).collect()
+    }
+
+    fn get_caret_rect(&self, line: usize, offset: usize) -> Option<LayoutRect> {
+        let (_, row_rect, label, label_rect) = self.get_row(line)?;
+        let mut caret_rect = self.window.caret_rect(*label, offset, CaretAffinity::Downstream)?;
+        caret_rect.origin.y += label_rect.origin.y - row_rect.origin.y;
+        Some(caret_rect)
+    }
+
+    fn hit_test(&self, line: usize, point: (f32, f32)) -> Option<usize> {
+        let (_, row_rect, label, label_rect) = self.get_row(line)?;
+        let label_top = label_rect.origin.y - row_rect.origin.y;
+        self.window.hit_test_text(*label, (point.0, point.1 - label_top)).map(|(offset, _)| offset)
+    }
+}
+
+/// Byte offsets at which the lines of a text start, so that the line of an offset can be
+/// found without searching the whole text. A trailing line break starts a new (empty) line.
+#[derive(Debug, Clone, PartialEq)]
+struct LineIndex {
+    /// Start of each line, the first line starts at 0
+    line_starts: Vec<usize>,
+    /// Length of the text in bytes
+    text_len: usize,
+}
+
+impl LineIndex {
+
+    fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(get_line_breaks(text, 0..text.len()).map(|line_break| line_break + 1));
+        Self { line_starts, text_len: text.len() }
+    }
+
+    /// Updates the lines after the bytes `start..old_end` of the text have been replaced by the
+    /// bytes `start..new_end` of the new `text`. The lines in front of the replaced part stay the same,
+    /// the lines after it are moved by the difference in length.
+    fn update(&mut self, text: &str, start: usize, old_end: usize, new_end: usize) {
+        // Lines that start in the replaced part (after a line break in it) are replaced
+        let first_replaced_line = self.get_line_index(start) + 1;
+        let first_moved_line = self.get_line_index(old_end) + 1;
+        let moved_lines = self.line_starts[first_moved_line..].iter()
+            .map(|line_start| line_start - old_end + new_end)
+            .collect::<Vec<_>>();
+        self.line_starts.truncate(first_replaced_line);
+        self.line_starts.extend(get_line_breaks(text, start..new_end).map(|line_break| line_break + 1));
+        self.line_starts.extend(moved_lines);
+        self.text_len = text.len();
+    }
+
+    fn get_line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the (0-based) line that the byte `offset` is on
+    fn get_line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        }
+    }
+
+    /// Returns the byte range of the `line`, without the line break
+    fn get_line_range(&self, line: usize) -> Option<Range<usize>> {
+        let line_start = *self.line_starts.get(line)?;
+        let line_end = self.line_starts.get(line + 1).map(|next_line_start| next_line_start - 1).unwrap_or(self.text_len);
+        Some(line_start..line_end)
+    }
+}
+
+/// Returns the byte offsets of the line breaks in the `range` of the `text`
+fn get_line_breaks<'a>(text: &'a str, range: Range<usize>) -> impl Iterator<Item=usize> + 'a {
+    let start = range.start;
+    text.as_bytes()[range].iter().enumerate()
+        .filter(|(_, byte)| **byte == b'\n')
+        .map(move |(index, _)| start + index)
+}
+
+/// Returns the byte offset of the grapheme cluster `column` in the `line`,
+/// or the end of the line if the line is shorter
+fn get_offset_at_column(line: &str, column: usize) -> usize {
+    line.grapheme_indices(true).nth(column).map(|(offset, _)| offset).unwrap_or(line.len())
+}
+
+#[test]
+fn test_text_area_lines() {
+    let text = "first\n\nthird line\n";
+    let lines = LineIndex::new(text);
+    assert_eq!((0..5).map(|line| lines.get_line_range(line)).collect::<Vec<_>>(), vec![Some(0..5), Some(6..6), Some(7..17), Some(18..18), None]);
+    assert_eq!(lines.get_line_index(8), 2);
+    assert_eq!(lines.get_line_index(6), 1);
+    assert_eq!(lines.get_line_index(18), 3);
+    assert_eq!(get_offset_at_column("ñandú", 4), 5);
+    assert_eq!(get_offset_at_column("ab", 10), 2);
+}
+
+/// Lays out the rendered lines of a `TextAreaState` in a monospace font, wrapped after
+/// `columns` characters, with 10px wide characters and 20px high rows
+#[cfg(test)]
+struct MonospaceLayout {
+    lines: Vec<(usize, String)>,
+    columns: usize,
+    viewport_height: f32,
+}
+
+#[cfg(test)]
+impl MonospaceLayout {
+
+    const CHAR_WIDTH: f32 = 10.0;
+    const ROW_HEIGHT: f32 = 20.0;
+
+    fn new(state: &TextAreaState, columns: usize, viewport_height: f32) -> Self {
+        let (first_line, last_line, _) = state.get_rendered_lines();
+        let line_ranges = state.get_lines();
+        let lines = (first_line..last_line)
+            .filter_map(|line| Some((line, state.editor.text[line_ranges.get_line_range(line)?].to_string())))
+            .collect();
+        Self { lines, columns, viewport_height }
+    }
+
+    fn get_line(&self, line: usize) -> Option<&str> {
+        self.lines.iter().find(|(index, _)| *index == line).map(|(_, text)| text.as_str())
+    }
+}
+
+#[cfg(test)]
+impl LinesLayout for MonospaceLayout {
+
+    fn get_viewport_height(&self) -> Option<f32> {
+        Some(self.viewport_height)
+    }
+
+    fn get_line_height(&self) -> Option<f32> {
+        Some(Self::ROW_HEIGHT)
+    }
+
+    fn get_rendered_line_heights(&self) -> Vec<(usize, f32)> {
+        self.lines.iter().map(|(line, text)| {
+            let rows = ((text.len() + self.columns - 1) / self.columns).max(1);
+            (*line, rows as f32 * Self::ROW_HEIGHT)
+        }).collect()
+    }
+
+    fn get_caret_rect(&self, line: usize, offset: usize) -> Option<LayoutRect> {
+        let text = self.get_line(line)?;
+        // The caret at the end of a full row stays in that row
+        let row = if offset == text.len() && offset > 0 { (offset - 1) / self.columns } else { offset / self.columns };
+        let column = offset - row * self.columns;
+        Some(LayoutRect {
+            origin: ::azul_css::LayoutPoint { x: column as f32 * Self::CHAR_WIDTH, y: row as f32 * Self::ROW_HEIGHT },
+            size: ::azul_css::LayoutSize::new(1.0, Self::ROW_HEIGHT),
+        })
+    }
+
+    fn hit_test(&self, line: usize, point: (f32, f32)) -> Option<usize> {
+        let text = self.get_line(line)?;
+        let row = (point.1 / Self::ROW_HEIGHT).max(0.0).floor() as usize;
+        let column = ((point.0 / Self::CHAR_WIDTH).round().max(0.0) as usize).min(self.columns);
+        Some((row * self.columns + column).min(text.len()))
+    }
+}
+
+#[test]
+fn test_text_area_moves_by_wrapped_rows() {
+    // The first line is wrapped into three rows: "aaaaaaaaaa", "bbbbbbbbbb", "ccccc"
+    let mut state = TextAreaState::new("aaaaaaaaaabbbbbbbbbbccccc\nshort\nlast line");
+    let layout = MonospaceLayout::new(&state, 10, 200.0);
+    state.update_layout(&layout);
+    assert_eq!(state.get_total_height(), 100.0);
+
+    state.editor.move_cursor(8, false);
+    state.handle_line_key(&layout, VirtualKeyCode::Down, false, false);
+    assert_eq!(state.editor.get_cursor(), 18);
+    // The last row and the next line are shorter, the caret moves to their end
+    // and keeps its horizontal position for the next line
+    state.handle_line_key(&layout, VirtualKeyCode::Down, false, false);
+    assert_eq!(state.editor.get_cursor(), 25);
+    state.handle_line_key(&layout, VirtualKeyCode::Down, false, false);
+    assert_eq!(state.editor.get_cursor(), 26 + 5);
+    state.handle_line_key(&layout, VirtualKeyCode::Down, false, false);
+    assert_eq!(state.editor.get_cursor(), 32 + 8);
+    state.handle_line_key(&layout, VirtualKeyCode::Down, false, false);
+    assert_eq!(state.editor.get_cursor(), state.editor.text.len());
+
+    state.editor.move_cursor(13, false);
+    state.handle_line_key(&layout, VirtualKeyCode::Home, false, false);
+    state.editor.move_cursor(13, false);
+    state.handle_line_key(&layout, VirtualKeyCode::Up, true, false);
+    assert_eq!(state.editor.get_cursor(), 3);
+    assert_eq!(state.editor.get_selected_text(), Some("aaaaaaabbb"));
+    state.handle_line_key(&layout, VirtualKeyCode::Up, false, false);
+    assert_eq!(state.editor.get_cursor(), 0);
+}
+
+#[test]
+fn test_text_area_keeps_caret_visible() {
+    let text = (0..1000).map(|line| format!("line {}", line)).collect::<Vec<_>>().join("\n");
+    let mut state = TextAreaState::new(text);
+
+    // 100px show five rows, the caret moves into the overscan and the text scrolls by one row
+    let layout = MonospaceLayout::new(&state, 10, 100.0);
+    state.update_layout(&layout);
+    for _ in 0..5 {
+        state.handle_line_key(&layout, VirtualKeyCode::Down, false, false);
+    }
+    state.scroll_caret_into_view(&layout);
+    assert_eq!(state.get_cursor_line(), 5);
+    assert_eq!(state.get_scroll_y(), 20.0);
+    assert_eq!(state.get_rendered_lines(), (0, 8, 0.0));
+
+    // Line 7 becomes three rows high, the caret in its last row is scrolled completely into view
+    state.editor.text = state.editor.text.replacen("line 7", "line 7 wraps into three", 1);
+    let layout = MonospaceLayout::new(&state, 10, 100.0);
+    state.update_layout(&layout);
+    let line_7_end = state.get_lines().get_line_range(7).unwrap().end;
+    state.editor.move_cursor(line_7_end, false);
+    state.scroll_caret_into_view(&layout);
+    assert_eq!(state.get_scroll_y(), 7.0 * 20.0 + 60.0 - 100.0);
+
+    // PageDown moves by four rows and scrolls along, without layout the caret keeps its column
+    let layout = MonospaceLayout::new(&state, 10, 100.0);
+    state.update_layout(&layout);
+    state.editor.move_cursor(state.editor.text.find("line 9").unwrap() + 3, false);
+    state.handle_line_key(&layout, VirtualKeyCode::PageDown, false, false);
+    state.scroll_caret_into_view(&layout);
+    assert_eq!(state.get_cursor_line(), 13);
+    assert_eq!(state.editor.get_cursor() - state.get_lines().get_line_range(13).unwrap().start, 3);
+    let caret_top = state.line_heights.get_row_offset(13);
+    assert!(caret_top >= state.get_scroll_y() && caret_top + 20.0 <= state.get_scroll_y() + 100.0);
+
+    // Scrolling can't go past the last line
+    assert!(state.scroll_by(1_000_000.0));
+    assert_eq!(state.get_scroll_y(), state.get_total_height() - 100.0);
+}
+
+#[test]
+fn test_text_area_edits_forget_moved_line_heights() {
+    let mut state = TextAreaState::new("aaaaaaaaaabbbbb\nx\nyyyyyyyyyyzz");
+    let layout = MonospaceLayout::new(&state, 10, 200.0);
+    state.update_layout(&layout);
+    assert_eq!(state.get_total_height(), 100.0);
+
+    // Typing in the first line keeps the heights, a new second line moves the last line down,
+    // so the heights of the lines after the first one are forgotten
+    state.edit(|state| state.editor.handle_text_input('c'));
+    assert_eq!(state.get_total_height(), 100.0);
+    state.editor.move_cursor(17, false);
+    state.edit(|state| state.handle_line_key(&layout, VirtualKeyCode::Return, false, false));
+    assert_eq!(state.get_line_count(), 4);
+    assert_eq!(state.get_total_height(), 40.0 + 3.0 * 20.0);
+}
+
+#[test]
+fn test_text_area_updates_lines_on_edits() {
+    let mut state = TextAreaState::new("first\nsecond\nthird");
+    let layout = NoLayout;
+
+    // Replace "st\nsec" by a line break, then undo and redo it
+    state.editor.selection = Some(::widgets::text_input::Selection::FromTo(3..9));
+    state.edit(|state| state.editor.insert_text("\n"));
+    assert_eq!(state.editor.text, "fir\nond\nthird");
+    assert_eq!(state.lines, LineIndex::new(&state.editor.text));
+    state.edit(|state| state.editor.undo());
+    assert_eq!(state.lines, LineIndex::new(&state.editor.text));
+    state.edit(|state| state.editor.redo());
+    assert_eq!(state.lines, LineIndex::new(&state.editor.text));
+
+    // Several edits before the lines are updated
+    state.editor.move_cursor(0, false);
+    state.editor.insert_text("zero\n");
+    state.editor.move_cursor(state.editor.text.len(), false);
+    state.edit(|state| state.handle_line_key(&layout, VirtualKeyCode::Return, false, false));
+    assert_eq!(state.get_line_count(), 5);
+    assert_eq!(state.lines, LineIndex::new(&state.editor.text));
+}
@@ -1,39 +1,123 @@
-//! Text input (demonstrates two-way data binding)
+//! Single-line text input (demonstrates two-way data binding)
 
-use std::ops::Range;
+use std::{
+    fmt,
+    ops::Range,
+    hash::{Hash, Hasher},
+};
 use {
     traits::Layout,
-    dom::{Dom, On, NodeType, UpdateScreen},
+    dom::{Dom, On, NodeType, UpdateScreen, TabIndex},
     window::{FakeWindow, WindowEvent},
     prelude::{VirtualKeyCode},
     default_callbacks::{StackCheckedPointer, DefaultCallback, DefaultCallbackId},
     app_state::AppStateNoData,
+    text_layout::{
+        next_grapheme_boundary, previous_grapheme_boundary,
+        next_word_boundary, previous_word_boundary,
+    },
+    app_resources::AppResources,
+    text_selection::{TextCaret, floor_char_boundary},
+    widgets::edit_history::{EditHistory, TextEdit},
 };
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct TextInput {
-    on_text_input_callback: Option<(DefaultCallbackId, DefaultCallbackId)>,
+    callbacks: Option<TextInputCallbacks>,
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+struct TextInputCallbacks {
+    text_input: DefaultCallbackId,
+    virtual_key_down: DefaultCallbackId,
+    mouse_down: DefaultCallbackId,
+    mouse_over: DefaultCallbackId,
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct TextInputState {
     pub text: String,
     pub selection: Option<Selection>,
+    /// Position of the caret (byte offset into the `text`, at a grapheme cluster boundary).
+    /// Offsets outside of the `text` or inside of a character are moved onto the previous
+    /// character boundary, see `get_cursor`.
     pub cursor: usize,
+    /// Maximum number of characters of the `text`, longer input is cut off. Default: `None` (no limit)
+    pub max_length: Option<usize>,
+    /// Called for every typed or pasted character, only the characters for which it returns `true`
+    /// are inserted (ex. `|c| c.is_ascii_digit()` for a number input). Default: `None` (all characters)
+    pub input_filter: Option<InputFilter>,
+    /// Edits that can be undone with Ctrl + Z and redone with Ctrl + Shift + Z / Ctrl + Y.
+    /// Can be cleared (ex. after saving) or cloned to take a snapshot of the history.
+    pub history: EditHistory,
+    /// Whether line breaks are kept, only set for the state of a `TextArea`
+    pub(crate) multi_line: bool,
+    /// Part of the `text` that was changed since the last `take_changed_range`: the start and
+    /// the end before and after the changes (byte offsets), used by the `TextArea` to update its lines
+    changed_range: Option<(usize, usize, usize)>,
+}
+
+/// Decides whether a typed or pasted character is inserted into a `TextInputState`
+pub struct InputFilter(pub fn(char) -> bool);
+
+// #[derive(Debug, Clone, PartialEq, Hash, Eq)] for InputFilter, compares the function pointers, see `Callback`
+
+impl fmt::Debug for InputFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "InputFilter @ 0x{:x}", self.0 as usize)
+    }
+}
+
+impl Clone for InputFilter {
+    fn clone(&self) -> Self {
+        InputFilter(self.0)
+    }
+}
+
+impl Copy for InputFilter { }
+
+impl Hash for InputFilter {
+    fn hash<H>(&self, state: &mut H) where H: Hasher {
+        state.write_usize(self.0 as usize);
+    }
 }
 
+impl PartialEq for InputFilter {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.0 as usize == rhs.0 as usize
+    }
+}
+
+impl Eq for InputFilter { }
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum Selection {
     All,
+    /// Selected byte range of the text
     FromTo(Range<usize>),
 }
 
+impl Selection {
+    /// Returns the selected byte range of the `text`
+    pub fn get_range(&self, text: &str) -> Range<usize> {
+        match self {
+            Selection::All => 0..text.len(),
+            Selection::FromTo(range) => floor_char_boundary(text, range.start)..floor_char_boundary(text, range.end),
+        }
+    }
+}
+
 impl Default for TextInputState {
     fn default() -> Self {
         TextInputState {
             text: String::new(),
             selection: None,
             cursor: 0,
+            max_length: None,
+            input_filter: None,
+            history: EditHistory::default(),
+            multi_line: false,
+            changed_range: None,
         }
     }
 }
@@ -44,47 +128,259 @@ impl TextInputState {
         let len = input_str.len();
         Self {
             text: input_str,
-            selection: None,
             cursor: len,
+            .. Default::default()
         }
     }
-}
 
-struct TextInputCallback<'a> {
-    ptr: &'a TextInputState,
+    #[inline]
+    pub fn with_max_length(self, max_length: usize) -> Self {
+        Self { max_length: Some(max_length), .. self }
+    }
+
+    #[inline]
+    pub fn with_input_filter(self, input_filter: fn(char) -> bool) -> Self {
+        Self { input_filter: Some(InputFilter(input_filter)), .. self }
+    }
+
+    /// Sets how many edits can be undone, the default is `DEFAULT_HISTORY_DEPTH`
+    #[inline]
+    pub fn with_history_depth(mut self, depth: usize) -> Self {
+        self.history.set_max_depth(depth);
+        self
+    }
+
+    /// Replaces the whole text and places the caret at the end (ex. when an option of a
+    /// combo box is chosen). The edit can't be undone, the undo history is cleared.
+    pub fn set_text<S: Into<String>>(&mut self, text: S) {
+        let old_len = self.text.len();
+        self.text = text.into();
+        let new_len = self.text.len();
+        self.record_change(0, old_len, new_len);
+        self.cursor = self.text.len();
+        self.selection = None;
+        self.history.clear();
+    }
+
+    /// Returns the position of the caret, moved into the `text` and onto a character boundary,
+    /// since the `cursor` and the `text` can be changed independently
+    pub fn get_cursor(&self) -> usize {
+        floor_char_boundary(&self.text, self.cursor)
+    }
+
+    /// Returns the selected text, `None` if nothing is selected
+    pub fn get_selected_text(&self) -> Option<&str> {
+        let range = self.selection.as_ref()?.get_range(&self.text);
+        if range.start < range.end { Some(&self.text[range]) } else { None }
+    }
+
+    /// Replaces the selected text (or inserts at the caret if nothing is selected) with the `text`,
+    /// respecting the `input_filter` and the `max_length`. Line breaks are replaced by spaces
+    /// (except in a `TextArea`). Returns whether the text has changed.
+    pub fn insert_text(&mut self, text: &str) -> bool {
+        self.insert_text_internal(text, false)
+    }
+
+    /// Same as `insert_text`, but consecutive `is_typing` inserts are undone together
+    fn insert_text_internal(&mut self, text: &str, is_typing: bool) -> bool {
+        let range = match &self.selection {
+            Some(selection) => selection.get_range(&self.text),
+            None => {
+                let cursor = self.get_cursor();
+                cursor..cursor
+            },
+        };
+
+        let input_filter = self.input_filter;
+        let multi_line = self.multi_line;
+        let filtered = text.chars()
+            .filter(|c| *c != '\r')
+            .map(|c| if c == '\n' && !multi_line { ' ' } else { c })
+            .filter(|c| !c.is_control() || (multi_line && (*c == '\n' || *c == '\t')))
+            .filter(|c| input_filter.map(|f| (f.0)(*c)).unwrap_or(true));
+
+        let inserted = match self.max_length {
+            Some(max_length) => {
+                let remaining_length = self.text.chars().count() - self.text[range.clone()].chars().count();
+                filtered.take(max_length.saturating_sub(remaining_length)).collect::<String>()
+            },
+            None => filtered.collect::<String>(),
+        };
+
+        // Nothing to insert, either because all characters were filtered out or because
+        // nothing should be deleted
+        if inserted.is_empty() && (!text.is_empty() || range.start == range.end) {
+            return false;
+        }
+
+        self.replace_text(range, &inserted, is_typing);
+        true
+    }
+
+    /// Replaces the `range` of the text, places the caret after the inserted text
+    /// and records the edit in the `history`
+    fn replace_text(&mut self, range: Range<usize>, inserted: &str, is_typing: bool) {
+        if range.start == range.end && inserted.is_empty() {
+            return;
+        }
+
+        let cursor_before = self.get_cursor();
+        let selection_before = self.selection.as_ref().map(|selection| selection.get_range(&self.text));
+        let deleted = self.text[range.clone()].to_string();
+
+        self.text.replace_range(range.clone(), inserted);
+        self.record_change(range.start, range.end - range.start, inserted.len());
+        self.cursor = range.start + inserted.len();
+        self.selection = None;
+
+        self.history.push(TextEdit {
+            position: range.start,
+            deleted,
+            inserted: inserted.to_string(),
+            cursor_before,
+            selection_before,
+            cursor_after: self.cursor,
+            selection_after: None,
+        }, is_typing);
+    }
+
+    /// Reverts the last edit, returns `false` if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let (cursor, selection, position, deleted_len, inserted_len) = match self.history.undo(&mut self.text) {
+            Some(edit) => (edit.cursor_before, edit.selection_before.clone(), edit.position, edit.inserted.len(), edit.deleted.len()),
+            None => return false,
+        };
+        self.record_change(position, deleted_len, inserted_len);
+        self.cursor = cursor;
+        self.selection = selection.map(Selection::FromTo);
+        true
+    }
+
+    /// Re-applies the last undone edit, returns `false` if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let (cursor, selection, position, deleted_len, inserted_len) = match self.history.redo(&mut self.text) {
+            Some(edit) => (edit.cursor_after, edit.selection_after.clone(), edit.position, edit.deleted.len(), edit.inserted.len()),
+            None => return false,
+        };
+        self.record_change(position, deleted_len, inserted_len);
+        self.cursor = cursor;
+        self.selection = selection.map(Selection::FromTo);
+        true
+    }
+
+    /// Adds the replacement of `deleted_len` bytes at the `position` by `inserted_len` bytes
+    /// to the `changed_range`
+    fn record_change(&mut self, position: usize, deleted_len: usize, inserted_len: usize) {
+        let deleted_end = position + deleted_len;
+        self.changed_range = Some(match self.changed_range {
+            // The end of the changes in the text before this change is moved back to the text
+            // before all changes and forward to the text after this change
+            Some((start, old_end, new_end)) => {
+                let end = deleted_end.max(new_end);
+                (start.min(position), end - new_end + old_end, end - deleted_len + inserted_len)
+            },
+            None => (position, deleted_end, position + inserted_len),
+        });
+    }
+
+    /// Returns the part of the text that was changed since the last call (the start and the
+    /// end before and after the changes) and starts recording the changes again
+    pub(crate) fn take_changed_range(&mut self) -> Option<(usize, usize, usize)> {
+        self.changed_range.take()
+    }
+
+    /// Whether the text was changed since the last `take_changed_range`
+    pub(crate) fn is_changed(&self) -> bool {
+        self.changed_range.is_some()
+    }
+
+    /// Deletes the selected text, returns `false` if nothing was selected
+    fn delete_selection(&mut self) -> bool {
+        match self.get_selected_text() {
+            Some(_) => self.insert_text(""),
+            None => false,
+        }
+    }
+
+    /// Returns the end of the selection that stays in place when the selection is extended
+    fn get_selection_anchor(&self) -> usize {
+        match &self.selection {
+            Some(selection) => {
+                let range = selection.get_range(&self.text);
+                if self.get_cursor() == range.start { range.end } else { range.start }
+            },
+            None => self.get_cursor(),
+        }
+    }
+
+    /// Moves the caret, either extending the selection (Shift) or clearing it
+    pub(crate) fn move_cursor(&mut self, new_cursor: usize, extend_selection: bool) {
+        let anchor = self.get_selection_anchor();
+        self.history.stop_merging();
+        self.cursor = new_cursor;
+        self.selection = if extend_selection && anchor != new_cursor {
+            Some(Selection::FromTo(anchor.min(new_cursor)..anchor.max(new_cursor)))
+        } else {
+            None
+        };
+    }
+
+    /// Returns the caret that is drawn by the label of the text input
+    pub(crate) fn get_caret(&self) -> TextCaret {
+        let selection = self.selection.as_ref()
+            .map(|selection| selection.get_range(&self.text))
+            .filter(|range| range.start < range.end)
+            .map(|range| (range.start, range.end));
+
+        TextCaret {
+            selection,
+            .. TextCaret::new(self.get_cursor())
+        }
+    }
 }
 
 impl TextInput {
 
     pub fn new() -> Self {
-        TextInput { on_text_input_callback: None }
+        TextInput { callbacks: None }
     }
 
     pub fn bind<T: Layout>(self, window: &mut FakeWindow<T>, field: &TextInputState, data: &T) -> Self {
         let ptr = StackCheckedPointer::new(data, field);
-        let on_text_input_callback = ptr.and_then(|ptr|{
-            Some((
-                window.add_callback(ptr, DefaultCallback(TextInputState::on_text_input_private)),
-                window.add_callback(ptr, DefaultCallback(TextInputState::on_virtual_key_down_private))
-            ))
+        let callbacks = ptr.and_then(|ptr|{
+            Some(TextInputCallbacks {
+                text_input: window.add_callback(ptr, DefaultCallback(TextInputState::on_text_input_private)),
+                virtual_key_down: window.add_callback(ptr, DefaultCallback(TextInputState::on_virtual_key_down_private)),
+                mouse_down: window.add_callback(ptr, DefaultCallback(TextInputState::on_mouse_down_private)),
+                mouse_over: window.add_callback(ptr, DefaultCallback(TextInputState::on_mouse_over_private)),
+            })
         });
 
         Self {
-            on_text_input_callback,
+            callbacks,
             .. self
         }
     }
 
     pub fn dom<T: Layout>(&self, field: &TextInputState) -> Dom<T> {
 
-        let mut parent_div = Dom::new(NodeType::Div).with_class("__azul-native-input-text");
+        // The input is focusable, the caret is only drawn while the input is focused
+        let mut parent_div = Dom::new(NodeType::Div)
+            .with_class("__azul-native-input-text")
+            .with_tab_index(TabIndex::Auto);
 
-        if let Some((text_input_callback, vk_callback)) = self.on_text_input_callback {
-            parent_div.add_default_callback_id(On::TextInput, text_input_callback);
-            parent_div.add_default_callback_id(On::VirtualKeyDown, vk_callback);
+        if let Some(callbacks) = self.callbacks {
+            parent_div.add_default_callback_id(On::TextInput, callbacks.text_input);
+            parent_div.add_default_callback_id(On::VirtualKeyDown, callbacks.virtual_key_down);
+            parent_div.add_default_callback_id(On::LeftMouseDown, callbacks.mouse_down);
+            parent_div.add_default_callback_id(On::MouseOver, callbacks.mouse_over);
         }
 
-        parent_div.with_child(Dom::new(NodeType::Label(field.text.clone())).with_class("__azul-native-input-text-label"))
+        let label = Dom::new(NodeType::Label(field.text.clone()))
+            .with_class("__azul-native-input-text-label")
+            .with_caret(field.get_caret());
+
+        parent_div.with_child(label)
     }
 }
 
@@ -98,83 +394,119 @@ impl TextInputState {
         unsafe { data.invoke_mut(Self::on_text_input, app_state_no_data, window_event) }
     }
 
+    fn on_mouse_down_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_mouse_down, app_state_no_data, window_event) }
+    }
+
+    fn on_mouse_over_private<T: Layout>(data: &StackCheckedPointer<T>, app_state_no_data: AppStateNoData<T>, window_event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { data.invoke_mut(Self::on_mouse_over, app_state_no_data, window_event) }
+    }
+
     pub fn on_virtual_key_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
 
-        let keyboard_state = app_state_no_data.windows[event.window].get_keyboard_state();
+        let (virtual_keycode, shift_down, ctrl_down) = {
+            let keyboard_state = app_state_no_data.windows[event.window].get_keyboard_state();
+            (keyboard_state.latest_virtual_keycode, keyboard_state.shift_down, keyboard_state.ctrl_down)
+        };
 
-        match keyboard_state.latest_virtual_keycode {
-            Some(VirtualKeyCode::Back) => {
-                // TODO: shift + back = delete last word
-                let selection = self.selection.clone();
-                match selection {
-                    None => {
-                        if self.cursor == self.text.len() {
-                            self.text.pop();
-                        } else {
-                            let mut a = self.text.chars().take(self.cursor).collect::<String>();
-                            let new = self.text.len().min(self.cursor.saturating_add(1));
-                            a.extend(self.text.chars().skip(new));
-                            self.text = a;
-                        }
-                        self.cursor = self.cursor.saturating_sub(1);
-                    },
-                    Some(Selection::All) => {
-                        self.text.clear();
-                        self.cursor = 0;
-                        self.selection = None;
-                    },
-                    Some(Selection::FromTo(range)) => {
-                        delete_selection(self, range, None);
-                    },
-                }
+        match virtual_keycode {
+            Some(key) => self.handle_virtual_key(key, shift_down, ctrl_down, app_state_no_data.resources),
+            None => UpdateScreen::DontRedraw,
+        }
+    }
 
+    /// Edits the text or moves the caret according to the pressed key, shared with the `TextArea`
+    pub(crate) fn handle_virtual_key(&mut self, key: VirtualKeyCode, shift_down: bool, ctrl_down: bool, resources: &mut AppResources) -> UpdateScreen {
+        match key {
+            VirtualKeyCode::Back => {
+                if !self.delete_selection() {
+                    // Ctrl + Backspace deletes the previous word, Backspace the previous grapheme cluster
+                    let start = if ctrl_down {
+                        previous_word_boundary(&self.text, self.get_cursor())
+                    } else {
+                        previous_grapheme_boundary(&self.text, self.get_cursor())
+                    };
+                    let end = self.get_cursor();
+                    self.replace_text(start..end, "", false);
+                }
                 UpdateScreen::Redraw
             },
-            Some(VirtualKeyCode::Return) => {
-                // TODO: selection!
-                self.text.push('\n');
-                self.cursor = self.cursor.saturating_add(1);
-                /*
-                match self.selection {
-                    None => {  },
+            VirtualKeyCode::Delete => {
+                if !self.delete_selection() {
+                    let end = if ctrl_down {
+                        next_word_boundary(&self.text, self.get_cursor())
+                    } else {
+                        next_grapheme_boundary(&self.text, self.get_cursor())
+                    };
+                    let start = self.get_cursor();
+                    self.replace_text(start..end, "", false);
                 }
-                */
                 UpdateScreen::Redraw
             },
-            Some(VirtualKeyCode::Home) => {
-                self.cursor = 0;
-                self.selection = None;
+            VirtualKeyCode::Home => {
+                self.move_cursor(0, shift_down);
                 UpdateScreen::Redraw
             },
-            Some(VirtualKeyCode::End) => {
-                self.cursor = self.text.len();
-                self.selection = None;
+            VirtualKeyCode::End => {
+                let end = self.text.len();
+                self.move_cursor(end, shift_down);
                 UpdateScreen::Redraw
             },
-            Some(VirtualKeyCode::A) if keyboard_state.ctrl_down => {
-                self.selection = Some(Selection::All);
+            VirtualKeyCode::Right => {
+                let new_cursor = match &self.selection {
+                    // Without Shift, the caret jumps to the end of the selection
+                    Some(selection) if !shift_down => selection.get_range(&self.text).end,
+                    _ if ctrl_down => next_word_boundary(&self.text, self.get_cursor()),
+                    _ => next_grapheme_boundary(&self.text, self.get_cursor()),
+                };
+                self.move_cursor(new_cursor, shift_down);
                 UpdateScreen::Redraw
             },
-            Some(VirtualKeyCode::Escape) => {
-                self.selection = None;
+            VirtualKeyCode::Left => {
+                let new_cursor = match &self.selection {
+                    Some(selection) if !shift_down => selection.get_range(&self.text).start,
+                    _ if ctrl_down => previous_word_boundary(&self.text, self.get_cursor()),
+                    _ => previous_grapheme_boundary(&self.text, self.get_cursor()),
+                };
+                self.move_cursor(new_cursor, shift_down);
                 UpdateScreen::Redraw
             },
-            Some(VirtualKeyCode::Right) => {
-                self.cursor = self.text.len().min(self.cursor.saturating_add(1));
+            VirtualKeyCode::A if ctrl_down => {
+                self.selection = Some(Selection::All);
+                self.cursor = self.text.len();
+                self.history.stop_merging();
                 UpdateScreen::Redraw
             },
-            Some(VirtualKeyCode::Left) => {
-                self.cursor = (0.max(self.cursor.saturating_sub(1))).min(self.cursor.saturating_add(1));
+            VirtualKeyCode::Escape => {
+                self.selection = None;
                 UpdateScreen::Redraw
             },
-            Some(VirtualKeyCode::C) => {
-                // TODO: copy
-                UpdateScreen::DontRedraw
+            VirtualKeyCode::Z if ctrl_down => {
+                let changed = if shift_down { self.redo() } else { self.undo() };
+                if changed { UpdateScreen::Redraw } else { UpdateScreen::DontRedraw }
+            },
+            VirtualKeyCode::Y if ctrl_down => {
+                if self.redo() { UpdateScreen::Redraw } else { UpdateScreen::DontRedraw }
             },
-            Some(VirtualKeyCode::V) => {
-                // TODO: paste
+            VirtualKeyCode::C if ctrl_down => {
+                if let Some(selected_text) = self.get_selected_text() {
+                    resources.set_clipboard_string(selected_text.to_string()).unwrap_or(());
+                }
                 UpdateScreen::DontRedraw
             },
+            VirtualKeyCode::X if ctrl_down => {
+                if let Some(selected_text) = self.get_selected_text().map(|text| text.to_string()) {
+                    resources.set_clipboard_string(selected_text).unwrap_or(());
+                    self.delete_selection();
+                }
+                UpdateScreen::Redraw
+            },
+            VirtualKeyCode::V if ctrl_down => {
+                if let Ok(clipboard_text) = resources.get_clipboard_string() {
+                    self.insert_text(&clipboard_text);
+                }
+                UpdateScreen::Redraw
+            },
             _ => UpdateScreen::DontRedraw,
         }
     }
@@ -182,52 +514,73 @@ impl TextInputState {
     pub fn on_text_input<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
         let keyboard_state = app_state_no_data.windows[event.window].get_keyboard_state();
         match keyboard_state.current_char {
-            Some(c) => {
-                let selection = self.selection.clone();
-                match selection {
-                    None => {
-                        if self.cursor == self.text.len() {
-                            self.text.push(c);
-                        } else {
-                            // TODO: insert character at the cursor location!
-                            self.text.push(c);
-                        }
-                        self.cursor = self.cursor.saturating_add(1);
-                    },
-                    Some(Selection::All) => {
-                        self.text = format!("{}", c);
-                        self.cursor = 1;
-                        self.selection = None;
-                    },
-                    Some(Selection::FromTo(range)) => {
-                        delete_selection(self, range, Some(c));
-                    },
-                }
-                UpdateScreen::Redraw
-            },
+            Some(c) => self.handle_text_input(c),
             None => UpdateScreen::DontRedraw,
         }
     }
-}
 
-fn delete_selection(state: &mut TextInputState, selection: Range<usize>, new_text: Option<char>) {
-    let Range { start, end } = selection;
-    let max = if end > state.text.len() { state.text.len() } else { end };
+    /// Inserts a typed character, shared with the `TextArea`
+    pub(crate) fn handle_text_input(&mut self, c: char) -> UpdateScreen {
+        // Control characters (ex. from Backspace or Ctrl + C) are handled in `on_virtual_key_down`
+        if c.is_control() {
+            return UpdateScreen::DontRedraw;
+        }
+        let mut buffer = [0; 4];
+        if self.insert_text_internal(c.encode_utf8(&mut buffer), true) {
+            UpdateScreen::Redraw
+        } else {
+            UpdateScreen::DontRedraw
+        }
+    }
 
-    let mut cur = start;
-    if max == state.text.len() {
-        state.text.truncate(start);
-    } else {
-        let mut a = state.text.chars().take(start).collect::<String>();
+    /// Places the caret at the clicked character, Shift + click extends the selection
+    pub fn on_mouse_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let shift_down = app_state_no_data.windows[event.window].get_keyboard_state().shift_down;
+        match self.get_offset_at_cursor(&event) {
+            Some(offset) => {
+                self.move_cursor(offset, shift_down);
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
 
-        if let Some(new) = new_text {
-            a.push(new);
-            cur += 1;
+    /// Selects the text while the mouse is dragged over the input
+    pub fn on_mouse_over<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        if !app_state_no_data.windows[event.window].get_mouse_state().left_down {
+            return UpdateScreen::DontRedraw;
+        }
+        match self.get_offset_at_cursor(&event) {
+            Some(offset) if offset != self.get_cursor() => {
+                self.move_cursor(offset, true);
+                UpdateScreen::Redraw
+            },
+            _ => UpdateScreen::DontRedraw,
         }
+    }
 
-        a.extend(state.text.chars().skip(end));
-        state.text = a;
+    /// Returns the position in the text under the mouse cursor, the label
+    /// is the first child of the node that the callbacks are attached to
+    fn get_offset_at_cursor<T: Layout>(&self, event: &WindowEvent<T>) -> Option<usize> {
+        let label = event.ui_state.dom.arena.borrow().node_layout[event.hit_dom_node].first_child?;
+        let (offset, _) = event.hit_test_text_at_cursor(label)?;
+        Some(floor_char_boundary(&self.text, offset))
     }
+}
+
+#[test]
+fn test_text_input_cursor_outside_of_the_text() {
+    let mut state = TextInputState::new("añb");
 
-    state.cursor = cur;
-}
\ No newline at end of file
+    // Inside of "ñ" (two bytes) and after the end of the text
+    state.cursor = 2;
+    assert_eq!(state.get_cursor(), 1);
+    state.cursor = 10;
+    assert_eq!(state.get_cursor(), 4);
+    assert!(state.insert_text("c"));
+    assert_eq!(state.text, "añbc");
+
+    state.selection = Some(Selection::FromTo(2..10));
+    assert_eq!(state.get_selected_text(), Some("ñbc"));
+    assert_eq!(state.get_caret().selection, Some((1, 5)));
+}
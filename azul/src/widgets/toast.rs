@@ -0,0 +1,370 @@
+//! Toast notifications: short messages in the corner of the window that disappear on their own
+//!
+//! `Toasts` wraps the content of a window and draws the notifications as an overlay in the
+//! bottom right corner. At most `MAX_VISIBLE_TOASTS` notifications are shown at the same time,
+//! the others wait in a queue until one of the shown notifications is dismissed. A notification
+//! is dismissed when its duration has passed (timed by a `Daemon`, see `FakeWindow::add_daemon`)
+//! or when its close button is clicked. Unlike the modal dialogs, the content can still be used
+//! while notifications are shown.
+//!
+//! ```rust,ignore
+//! app_state.data.modify(|data| data.toasts.push(Toast::new("File saved").with_kind(ToastKind::Success)));
+//! ```
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+use azul_css::{CssProperty, LayoutHeight};
+use {
+    traits::Layout,
+    app_resources::AppResources,
+    daemon::{Daemon, DaemonId, DaemonCallback, TerminateDaemon, new_daemon_id},
+    dom::{Dom, On, NodeType, UpdateScreen},
+    window::{FakeWindow, WindowEvent},
+    default_callbacks::{StackCheckedPointer, DefaultCallback},
+    app_state::AppStateNoData,
+};
+
+/// How long a notification is shown if no duration is given
+pub const DEFAULT_TOAST_DURATION: Duration = Duration::from_secs(4);
+/// How many notifications are shown at the same time, the others are queued
+pub const MAX_VISIBLE_TOASTS: usize = 3;
+
+/// ID of the dynamic CSS property of the area that the notifications are drawn in
+const AREA_HEIGHT_ID: &str = "__azul-native-toast-area-height";
+
+/// Height of a notification including the space below it, must match the native CSS.
+/// The notifications are drawn in an absolutely positioned overlay, which can't be
+/// sized by its content, so the height of the overlay is set from here.
+const TOAST_HEIGHT: f32 = 56.0;
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Toasts {
+
+}
+
+/// Changes the color of a notification
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Default for ToastKind {
+    fn default() -> Self {
+        ToastKind::Info
+    }
+}
+
+impl ToastKind {
+    fn get_class(&self) -> &'static str {
+        use self::ToastKind::*;
+        match self {
+            Info => "__azul-native-toast-info",
+            Success => "__azul-native-toast-success",
+            Warning => "__azul-native-toast-warning",
+            Error => "__azul-native-toast-error",
+        }
+    }
+}
+
+/// A notification in the queue of a `ToastState`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Toast {
+    pub text: String,
+    pub kind: ToastKind,
+    /// How long the notification is shown, counted from when it leaves the queue.
+    /// If `None`, the notification is shown until its close button is clicked.
+    pub duration: Option<Duration>,
+}
+
+impl Toast {
+
+    /// Creates an info notification that is shown for `DEFAULT_TOAST_DURATION`
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        Self {
+            text: text.into(),
+            kind: ToastKind::default(),
+            duration: Some(DEFAULT_TOAST_DURATION),
+        }
+    }
+
+    pub fn with_kind(self, kind: ToastKind) -> Self {
+        Self { kind, .. self }
+    }
+
+    pub fn with_duration(self, duration: Option<Duration>) -> Self {
+        Self { duration, .. self }
+    }
+}
+
+/// A notification in the queue and when it was shown
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueuedToast {
+    toast: Toast,
+    /// When the notification was shown. Only set when the queue is changed, until then the
+    /// time is calculated from the notifications before it, see `ToastState::get_times`.
+    shown_at: Option<Instant>,
+    /// Redraws the window when the duration of the notification has passed
+    timer: DaemonId,
+}
+
+/// The queue of notifications, the first notifications in the queue are shown
+///
+/// Rendering doesn't change the state: when the notifications are shown and hidden is calculated
+/// from the time of the last change of the queue. The queue is only updated when it is changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToastState {
+    toasts: VecDeque<QueuedToast>,
+    /// When the queue was last changed, the notifications that weren't shown yet can be shown from then on
+    changed_at: Instant,
+}
+
+impl Default for ToastState {
+    fn default() -> Self {
+        Self {
+            toasts: VecDeque::new(),
+            changed_at: Instant::now(),
+        }
+    }
+}
+
+impl ToastState {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the notification to the end of the queue
+    pub fn push(&mut self, toast: Toast) {
+        self.push_at(toast, Instant::now());
+    }
+
+    /// Removes all notifications, including the queued ones
+    pub fn clear(&mut self) {
+        self.toasts.clear();
+        self.changed_at = Instant::now();
+    }
+
+    /// Number of notifications that are shown or queued
+    pub fn len(&self) -> usize {
+        let now = Instant::now();
+        self.get_times().iter().filter(|times| !is_hidden(**times, now)).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Dismisses the `index`-th shown notification
+    pub fn dismiss(&mut self, index: usize) {
+        self.dismiss_at(index, Instant::now());
+    }
+
+    fn push_at(&mut self, toast: Toast, now: Instant) {
+        self.update(now);
+        self.toasts.push_back(QueuedToast { toast, shown_at: None, timer: new_daemon_id() });
+    }
+
+    fn dismiss_at(&mut self, index: usize, now: Instant) {
+        if let Some(toast_index) = self.get_visible_toasts(now).get(index).cloned() {
+            let timer = self.toasts[toast_index].timer;
+            self.update(now);
+            self.toasts.retain(|queued| queued.timer != timer);
+        }
+    }
+
+    /// Removes the notifications that have been hidden at `now` and stores when the shown notifications were shown
+    fn update(&mut self, now: Instant) {
+        let times = self.get_times();
+        let mut index = 0;
+        self.toasts.retain(|_| {
+            let keep = !is_hidden(times[index], now);
+            index += 1;
+            keep
+        });
+        let shown_times = times.into_iter().filter(|times| !is_hidden(*times, now));
+        for (toast, times) in self.toasts.iter_mut().zip(shown_times) {
+            toast.shown_at = times.map(|(shown_at, _)| shown_at).filter(|shown_at| *shown_at <= now);
+        }
+        self.changed_at = now;
+    }
+
+    /// Returns when each notification is shown and hidden (`None` if it is shown until it is dismissed).
+    /// A queued notification is shown when the first of the shown notifications is hidden, or never
+    /// (`None`) if all shown notifications are shown until they are dismissed.
+    fn get_times(&self) -> Vec<Option<(Instant, Option<Instant>)>> {
+
+        // When the places of the shown notifications become free, `None` if never
+        let mut places = Vec::<Option<Instant>>::new();
+
+        self.toasts.iter().map(|queued| {
+            let shown_at = match queued.shown_at {
+                Some(shown_at) => shown_at,
+                None if places.len() < MAX_VISIBLE_TOASTS => self.changed_at,
+                None => {
+                    let (place, free_at) = places.iter().enumerate()
+                        .filter_map(|(place, free_at)| free_at.map(|free_at| (place, free_at)))
+                        .min_by_key(|(_, free_at)| *free_at)?;
+                    places.remove(place);
+                    free_at.max(self.changed_at)
+                },
+            };
+            let hidden_at = queued.toast.duration.map(|duration| shown_at + duration);
+            places.push(hidden_at);
+            Some((shown_at, hidden_at))
+        }).collect()
+    }
+
+    /// Returns the indices of the notifications that are shown at `now`, at most `MAX_VISIBLE_TOASTS`
+    fn get_visible_toasts(&self, now: Instant) -> Vec<usize> {
+        self.get_times().into_iter().enumerate()
+            .filter(|(_, times)| is_visible(*times, now))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// Whether the notification is shown at `now`, see `ToastState::get_times`
+fn is_visible(times: Option<(Instant, Option<Instant>)>, now: Instant) -> bool {
+    times.map(|(shown_at, _)| shown_at <= now).unwrap_or(false) && !is_hidden(times, now)
+}
+
+/// Whether the notification has been hidden at `now`, see `ToastState::get_times`
+fn is_hidden(times: Option<(Instant, Option<Instant>)>, now: Instant) -> bool {
+    times.and_then(|(_, hidden_at)| hidden_at).map(|hidden_at| hidden_at <= now).unwrap_or(false)
+}
+
+impl Toasts {
+
+    pub fn new() -> Self {
+        Self {
+
+        }
+    }
+
+    /// Renders the `content` of the window and the shown notifications on top of it
+    pub fn dom<T: Layout>(&self, state: &ToastState, t: &T, window: &mut FakeWindow<T>, content: Dom<T>) -> Dom<T> {
+
+        let ptr = match StackCheckedPointer::new(t, state) {
+            Some(ptr) => ptr,
+            None => return Dom::new(NodeType::Label(
+                "Cannot create toasts from heap-allocated ToastState, \
+                 please store the ToastState in the application data".into())
+            ),
+        };
+
+        // div.__azul-native-toast-window
+        //     |-> the content
+        //     '-> div.__azul-native-toast-area (only if notifications are shown)
+        //         '-> div.__azul-native-toast (.__azul-native-toast-info, -success, -warning or -error)
+        //             |-> p.__azul-native-toast-text
+        //             '-> p.__azul-native-toast-close
+        //
+        // The children are keyed, so that showing a notification doesn't change
+        // the identity of the content (and its focus and text selection).
+
+        let toast_window = Dom::new(NodeType::Div)
+            .with_class("__azul-native-toast-window")
+            .with_child(content.with_key("content"));
+
+        let now = Instant::now();
+        let times = state.get_times();
+        let visible = state.get_visible_toasts(now);
+
+        // Redraw the window when a notification has to disappear (and the next one in the queue is shown)
+        for index in &visible {
+            if let Some((_, Some(hidden_at))) = times[*index] {
+                let timer = Daemon::with_id(DaemonCallback(redraw_after_timeout::<T>), state.toasts[*index].timer);
+                window.add_daemon(timer.with_delay(hidden_at - now));
+            }
+        }
+
+        if visible.is_empty() {
+            return toast_window;
+        }
+
+        let close_mouse_up = window.add_callback(ptr, DefaultCallback(Self::toast_on_close_mouse_up::<T>));
+
+        let mut area = Dom::new(NodeType::Div)
+            .with_class("__azul-native-toast-area")
+            .with_key("toasts")
+            .with_overlay(true)
+            .with_style_override(AREA_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(visible.len() as f32 * TOAST_HEIGHT)));
+
+        for index in visible {
+            let QueuedToast { toast, timer, .. } = &state.toasts[index];
+            let mut close = Dom::new(NodeType::Label("\u{00d7}".into())).with_class("__azul-native-toast-close");
+            close.add_default_callback_id(On::LeftMouseUp, close_mouse_up);
+            area.add_child(Dom::new(NodeType::Div)
+                .with_class("__azul-native-toast")
+                .with_class(toast.kind.get_class())
+                .with_key(*timer)
+                .with_child(Dom::new(NodeType::Label(toast.text.clone())).with_class("__azul-native-toast-text"))
+                .with_child(close));
+        }
+
+        toast_window.with_child(area)
+    }
+
+    fn toast_on_close_mouse_up<T: Layout>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        unsafe { ptr.invoke_mut(ToastState::on_close_mouse_up::<T>, data, event) }
+    }
+}
+
+impl ToastState {
+
+    /// Dismisses the notification whose close button was clicked
+    pub fn on_close_mouse_up<T: Layout>(&mut self, _app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let index = event.get_index_in_parent(event.hit_dom_node)
+            .and_then(|(_, toast)| event.get_index_in_parent(toast));
+        match index {
+            Some((index, _)) => {
+                self.dismiss(index);
+                UpdateScreen::Redraw
+            },
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+}
+
+/// The notifications are shown and hidden by `Toasts::dom`, so the daemon only has to redraw the window.
+/// A daemon with the same ID isn't started again while it is running.
+fn redraw_after_timeout<T: Layout>(_data: &mut T, _app_resources: &mut AppResources) -> (UpdateScreen, TerminateDaemon) {
+    (UpdateScreen::Redraw, TerminateDaemon::Terminate)
+}
+
+#[test]
+fn test_toast_queue_and_expiry() {
+    let start = Instant::now();
+    let after = |millis| start + Duration::from_millis(millis);
+
+    let mut toasts = ToastState::new();
+    toasts.push_at(Toast::new("a").with_duration(Some(Duration::from_secs(1))), start);
+    toasts.push_at(Toast::new("b").with_duration(Some(Duration::from_secs(5))), start);
+    toasts.push_at(Toast::new("c").with_kind(ToastKind::Error).with_duration(None), start);
+    toasts.push_at(Toast::new("d"), start);
+
+    // Only the first notifications are shown, rendering doesn't change the state
+    let state = toasts.clone();
+    assert_eq!(toasts.get_visible_toasts(start), vec![0, 1, 2]);
+    assert_eq!(toasts.get_visible_toasts(after(500)), vec![0, 1, 2]);
+    assert_eq!(toasts, state);
+
+    // When the first notification is hidden, the queued one is shown for its duration from then on
+    assert_eq!(toasts.get_visible_toasts(after(2000)), vec![1, 2, 3]);
+    assert_eq!(toasts.get_times()[3], Some((after(1000), Some(after(1000) + DEFAULT_TOAST_DURATION))));
+
+    // Dismissing removes the hidden notifications, the shown ones keep their times
+    toasts.dismiss_at(0, after(2000));
+    assert_eq!(toasts.toasts.iter().map(|queued| queued.toast.text.as_str()).collect::<Vec<_>>(), vec!["c", "d"]);
+    assert_eq!(toasts.get_times()[1], Some((after(1000), Some(after(1000) + DEFAULT_TOAST_DURATION))));
+
+    // Notifications without a duration stay until they are dismissed
+    assert_eq!(toasts.get_visible_toasts(after(60000)), vec![0]);
+    toasts.dismiss_at(0, after(60000));
+    assert!(toasts.toasts.is_empty());
+}
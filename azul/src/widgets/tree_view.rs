@@ -0,0 +1,754 @@
+//! Tree view for hierarchical data (ex. a project hierarchy or a JSON document)
+//!
+//! The tree is read from a `TreeModel`. Children are loaded when their parent is
+//! expanded for the first time, so that huge trees don't have to be loaded up front.
+//! The tree view renders into an `IFrame` and only creates DOM nodes for the rows
+//! that are visible, so the size of the tree doesn't affect the size of the DOM.
+
+use std::{
+    fmt::{self, Debug, Display},
+    hash::Hash,
+    ops::Range,
+    collections::BTreeSet,
+};
+use azul_css::{CssProperty, LayoutHeight, LayoutWidth};
+use {
+    dom::{Dom, On, NodeType, IFrameCallback, UpdateScreen, TabIndex},
+    app_state::AppStateNoData,
+    traits::Layout,
+    window::{FakeWindow, WindowEvent, WindowInfo, HidpiAdjustedBounds},
+    prelude::VirtualKeyCode,
+    default_callbacks::{StackCheckedPointer, DefaultCallback},
+    id_tree::{Arena, NodeId},
+    FastHashMap,
+};
+
+/// ID of the dynamic `height` property of the rows, see the native CSS
+const ROW_HEIGHT_ID: &str = "__azul-native-tree-view-row-height";
+/// ID of the dynamic `width` property of one level of indentation
+const INDENT_ID: &str = "__azul-native-tree-view-indent";
+/// ID of the dynamic `height` property of the guide that ends at the last child
+const GUIDE_END_HEIGHT_ID: &str = "__azul-native-tree-view-guide-end-height";
+
+/// Source of the nodes of a `TreeView`
+pub trait TreeModel {
+    /// Identifies a node, must stay the same while the node is in the tree
+    type Id: Debug + Clone + PartialEq + Eq + PartialOrd + Ord + Hash;
+
+    /// Returns the top-level nodes, in the order in which they are shown
+    fn get_roots(&self) -> Vec<Self::Id>;
+    /// Returns whether the node can be expanded. Called before the children are loaded,
+    /// so this should be cheap (ex. checking whether a directory entry is a directory)
+    fn has_children(&self, id: &Self::Id) -> bool;
+    /// Called when the node is expanded for the first time, before `get_children`.
+    /// Use this to load the children lazily (ex. by reading a directory). Default: does nothing.
+    fn load_children(&mut self, _id: &Self::Id) { }
+    /// Returns the (loaded) children of the node, in the order in which they are shown
+    fn get_children(&self, id: &Self::Id) -> Vec<Self::Id>;
+    /// Returns the text that is shown for the node
+    fn get_label(&self, id: &Self::Id) -> String;
+}
+
+/// Identifies a node of a `SimpleTreeModel`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TreeNodeId(NodeId);
+
+/// Tree that is built up front, for trees that don't have to be loaded lazily.
+/// Shows the data of the nodes as the labels.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SimpleTreeModel<T> {
+    arena: Arena<T>,
+    roots: Vec<TreeNodeId>,
+}
+
+impl<T> SimpleTreeModel<T> {
+
+    pub fn new() -> Self {
+        Self { arena: Arena::new(), roots: Vec::new() }
+    }
+
+    /// Adds a top-level node after the existing top-level nodes
+    pub fn add_root(&mut self, data: T) -> TreeNodeId {
+        let id = TreeNodeId(self.arena.new_node(data));
+        self.roots.push(id);
+        id
+    }
+
+    /// Adds a node after the existing children of the `parent`
+    pub fn add_child(&mut self, parent: TreeNodeId, data: T) -> TreeNodeId {
+        let child = self.arena.new_node(data);
+        self.arena.append_child(parent.0, child);
+        TreeNodeId(child)
+    }
+
+    pub fn get(&self, id: TreeNodeId) -> Option<&T> {
+        self.arena.node_data_ref(&id.0)
+    }
+}
+
+impl<T: Display> TreeModel for SimpleTreeModel<T> {
+    type Id = TreeNodeId;
+
+    fn get_roots(&self) -> Vec<TreeNodeId> {
+        self.roots.clone()
+    }
+
+    fn has_children(&self, id: &TreeNodeId) -> bool {
+        self.arena.node_info_ref(&id.0).map(|node| node.first_child.is_some()).unwrap_or(false)
+    }
+
+    fn get_children(&self, id: &TreeNodeId) -> Vec<TreeNodeId> {
+        id.0.children(&self.arena.node_layout).map(TreeNodeId).collect()
+    }
+
+    fn get_label(&self, id: &TreeNodeId) -> String {
+        self.get(*id).map(|data| data.to_string()).unwrap_or_default()
+    }
+}
+
+/// Whether one or multiple rows can be selected
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// Selecting a row deselects the previously selected row
+    Single,
+    /// Ctrl + click adds a row to the selection, Shift + click selects a range of rows
+    Multiple,
+}
+
+impl Default for SelectionMode {
+    fn default() -> Self {
+        SelectionMode::Single
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TreeView {
+
+}
+
+/// A visible row of the tree, i.e. a node whose parents are all expanded
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TreeRow<I> {
+    id: I,
+    /// Number of parents of the node
+    depth: usize,
+    /// Index of the row of the parent node
+    parent_row: Option<usize>,
+    is_last_child: bool,
+    has_children: bool,
+}
+
+pub struct TreeViewState<M: TreeModel> {
+    /// The nodes of the tree. Call `reload()` after changing the model.
+    pub model: M,
+    pub selection_mode: SelectionMode,
+    /// Height of one row in pixels. Default: 20.0
+    pub row_height: f32,
+    /// Width of one level of indentation in pixels, also the width of the expand / collapse arrow. Default: 16.0
+    pub indent: f32,
+    expanded: BTreeSet<M::Id>,
+    loaded: BTreeSet<M::Id>,
+    selected: BTreeSet<M::Id>,
+    /// The row that is moved by the arrow keys, may not be selected (Ctrl + arrow key in multi-selection mode)
+    cursor: Option<M::Id>,
+    /// Start of the range that is selected by Shift + click / Shift + arrow key
+    selection_anchor: Option<M::Id>,
+    /// Index of the first visible row
+    scroll_offset: usize,
+    /// How many rows fit into the tree view, updated every time the tree view is rendered
+    visible_row_count: usize,
+    /// All visible rows, in the order in which they are shown
+    rows: Vec<TreeRow<M::Id>>,
+    /// Index of the row of every visible node
+    row_indices: FastHashMap<M::Id, usize>,
+}
+
+impl<M: TreeModel + Debug> Debug for TreeViewState<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+            "TreeViewState {{ model: {:?}, selection_mode: {:?}, row_height: {:?}, indent: {:?}, \
+            expanded: {:?}, selected: {:?}, cursor: {:?}, scroll_offset: {:?}, row_count: {:?} }}",
+            self.model, self.selection_mode, self.row_height, self.indent,
+            self.expanded, self.selected, self.cursor, self.scroll_offset, self.rows.len())
+    }
+}
+
+impl<M: TreeModel> TreeViewState<M> {
+
+    /// Creates a tree view with all nodes collapsed and nothing selected
+    pub fn new(model: M) -> Self {
+        let mut state = Self {
+            model,
+            selection_mode: SelectionMode::default(),
+            row_height: 20.0,
+            indent: 16.0,
+            expanded: BTreeSet::new(),
+            loaded: BTreeSet::new(),
+            selected: BTreeSet::new(),
+            cursor: None,
+            selection_anchor: None,
+            scroll_offset: 0,
+            visible_row_count: 1,
+            rows: Vec::new(),
+            row_indices: FastHashMap::default(),
+        };
+        state.reload();
+        state
+    }
+
+    pub fn with_selection_mode(self, selection_mode: SelectionMode) -> Self {
+        Self { selection_mode, .. self }
+    }
+
+    pub fn with_row_height(self, row_height: f32) -> Self {
+        Self { row_height: row_height.max(1.0), .. self }
+    }
+
+    pub fn with_indent(self, indent: f32) -> Self {
+        Self { indent: indent.max(0.0), .. self }
+    }
+
+    /// Reads the visible rows from the model again, necessary after the model was changed.
+    /// The cursor is reset if its node isn't visible anymore.
+    pub fn reload(&mut self) {
+        let mut rows = Vec::new();
+        let roots = self.model.get_roots();
+        self.push_rows(&mut rows, 0, roots, 0, None);
+        let row_count = self.rows.len();
+        self.replace_rows(0..row_count, rows);
+
+        if self.cursor.as_ref().map(|cursor| self.get_row(cursor).is_none()).unwrap_or(false) {
+            self.cursor = None;
+        }
+    }
+
+    /// Pushes the rows of the nodes and of their expanded children, `first_row` is the index
+    /// that the first pushed row will have in `self.rows`
+    fn push_rows(&self, rows: &mut Vec<TreeRow<M::Id>>, first_row: usize, ids: Vec<M::Id>, depth: usize, parent_row: Option<usize>) {
+        let child_count = ids.len();
+        for (index, id) in ids.into_iter().enumerate() {
+            let has_children = self.model.has_children(&id);
+            let is_expanded = has_children && self.expanded.contains(&id);
+            let row = first_row + rows.len();
+            rows.push(TreeRow { id: id.clone(), depth, parent_row, is_last_child: index + 1 == child_count, has_children });
+            if is_expanded {
+                let children = self.model.get_children(&id);
+                self.push_rows(rows, first_row, children, depth + 1, Some(row));
+            }
+        }
+    }
+
+    /// Replaces the rows in the `range` (the rows of complete subtrees) with the `new_rows`,
+    /// without reading the other rows from the model again
+    fn replace_rows(&mut self, range: Range<usize>, new_rows: Vec<TreeRow<M::Id>>) {
+        let start = range.start;
+        let end = range.end;
+        let inserted_count = new_rows.len();
+
+        for removed in self.rows.splice(range, new_rows) {
+            self.row_indices.remove(&removed.id);
+        }
+
+        // The rows after the replaced rows moved, so did the parents that are after them
+        for row in (start + inserted_count)..self.rows.len() {
+            if let Some(parent_row) = self.rows[row].parent_row.as_mut() {
+                if *parent_row >= end {
+                    *parent_row = *parent_row + inserted_count - (end - start);
+                }
+            }
+        }
+
+        for row in start..self.rows.len() {
+            self.row_indices.insert(self.rows[row].id.clone(), row);
+        }
+
+        let max_scroll_offset = self.rows.len().saturating_sub(self.visible_row_count);
+        self.scroll_offset = self.scroll_offset.min(max_scroll_offset);
+    }
+
+    /// Returns the range of the rows of the visible children (and their children) of the row
+    fn get_descendant_rows(&self, row: usize) -> Range<usize> {
+        let depth = self.rows[row].depth;
+        let end = self.rows[(row + 1)..].iter().position(|next| next.depth <= depth).map(|count| row + 1 + count).unwrap_or(self.rows.len());
+        (row + 1)..end
+    }
+
+    /// Returns the index of the row of the node, `None` if the node isn't visible
+    fn get_row(&self, id: &M::Id) -> Option<usize> {
+        self.row_indices.get(id).cloned()
+    }
+
+    /// Returns how many rows are visible, i.e. nodes whose parents are all expanded
+    pub fn get_row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_expanded(&self, id: &M::Id) -> bool {
+        self.expanded.contains(id)
+    }
+
+    /// Shows the children of the node, loads them first if the node wasn't expanded yet
+    pub fn expand(&mut self, id: &M::Id) {
+        if !self.model.has_children(id) || self.expanded.contains(id) {
+            return;
+        }
+        if !self.loaded.contains(id) {
+            self.model.load_children(id);
+            self.loaded.insert(id.clone());
+        }
+        self.expanded.insert(id.clone());
+
+        // Only the children of the node are added, if the node is visible
+        if let Some(row) = self.get_row(id) {
+            let depth = self.rows[row].depth;
+            let mut children_rows = Vec::new();
+            let children = self.model.get_children(id);
+            self.push_rows(&mut children_rows, row + 1, children, depth + 1, Some(row));
+            self.replace_rows((row + 1)..(row + 1), children_rows);
+        }
+    }
+
+    /// Hides the children of the node. If the cursor was on one of the children,
+    /// it is moved to the node.
+    pub fn collapse(&mut self, id: &M::Id) {
+        if !self.expanded.remove(id) {
+            return;
+        }
+        let cursor_is_hidden = match (self.get_row(id), self.cursor.as_ref().and_then(|cursor| self.get_row(cursor))) {
+            (Some(row), Some(cursor_row)) => self.is_descendant_row(cursor_row, row),
+            _ => false,
+        };
+        if cursor_is_hidden {
+            self.cursor = Some(id.clone());
+        }
+        if let Some(row) = self.get_row(id) {
+            let descendant_rows = self.get_descendant_rows(row);
+            self.replace_rows(descendant_rows, Vec::new());
+        }
+    }
+
+    pub fn toggle_expanded(&mut self, id: &M::Id) {
+        if self.expanded.contains(id) {
+            self.collapse(id);
+        } else {
+            self.expand(id);
+        }
+    }
+
+    /// Whether the row is below the `parent_row` in the tree
+    fn is_descendant_row(&self, row: usize, parent_row: usize) -> bool {
+        let mut current = self.rows[row].parent_row;
+        while let Some(current_row) = current {
+            if current_row == parent_row {
+                return true;
+            }
+            current = self.rows[current_row].parent_row;
+        }
+        false
+    }
+
+    /// Returns the selected nodes, including selected nodes whose parent was collapsed
+    pub fn get_selected(&self) -> &BTreeSet<M::Id> {
+        &self.selected
+    }
+
+    pub fn is_selected(&self, id: &M::Id) -> bool {
+        self.selected.contains(id)
+    }
+
+    /// Selects only the node and moves the cursor to it
+    pub fn select(&mut self, id: &M::Id) {
+        self.selected.clear();
+        self.selected.insert(id.clone());
+        self.cursor = Some(id.clone());
+        self.selection_anchor = Some(id.clone());
+    }
+
+    /// Selects or deselects the node without deselecting the other nodes (only in `SelectionMode::Multiple`)
+    pub fn toggle_selected(&mut self, id: &M::Id) {
+        if self.selection_mode == SelectionMode::Single {
+            self.select(id);
+            return;
+        }
+        if !self.selected.remove(id) {
+            self.selected.insert(id.clone());
+        }
+        self.cursor = Some(id.clone());
+        self.selection_anchor = Some(id.clone());
+    }
+
+    /// Selects all visible rows between the selection anchor (the last clicked row) and the
+    /// node (only in `SelectionMode::Multiple`)
+    pub fn select_range_to(&mut self, id: &M::Id) {
+        let anchor_row = self.selection_anchor.as_ref().and_then(|anchor| self.get_row(anchor));
+        let (anchor_row, row) = match (self.selection_mode, anchor_row, self.get_row(id)) {
+            (SelectionMode::Multiple, Some(anchor_row), Some(row)) => (anchor_row, row),
+            _ => { self.select(id); return; },
+        };
+        self.selected = self.rows[anchor_row.min(row)..=anchor_row.max(row)].iter().map(|row| row.id.clone()).collect();
+        self.cursor = Some(id.clone());
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected.clear();
+        self.selection_anchor = None;
+    }
+
+    /// Scrolls the tree view so that the row of the node is visible
+    pub fn scroll_to(&mut self, id: &M::Id) {
+        if let Some(row) = self.get_row(id) {
+            self.scroll_to_row(row);
+        }
+    }
+
+    fn scroll_to_row(&mut self, row: usize) {
+        let visible_row_count = self.visible_row_count.max(1);
+        if row < self.scroll_offset {
+            self.scroll_offset = row;
+        } else if row >= self.scroll_offset + visible_row_count {
+            self.scroll_offset = row + 1 - visible_row_count;
+        }
+    }
+
+    /// Moves the cursor to the `row` and selects it: with Shift the range from the anchor
+    /// to the row is selected, with Ctrl the selection doesn't change (only in `SelectionMode::Multiple`)
+    fn move_cursor_to_row(&mut self, row: usize, shift_down: bool, ctrl_down: bool) {
+        let id = match self.rows.get(row) {
+            Some(row) => row.id.clone(),
+            None => return,
+        };
+        match self.selection_mode {
+            SelectionMode::Multiple if shift_down => self.select_range_to(&id),
+            SelectionMode::Multiple if ctrl_down => self.cursor = Some(id),
+            _ => self.select(&id),
+        }
+        self.scroll_to_row(row);
+    }
+
+    /// Handles a key that was pressed while the tree view is focused: Up / Down / PageUp / PageDown /
+    /// Home / End move the cursor, Left collapses the node (or moves to the parent), Right expands the
+    /// node (or moves to the first child), Return toggles the node, Space and Ctrl + A select
+    fn handle_virtual_key(&mut self, key: VirtualKeyCode, shift_down: bool, ctrl_down: bool) -> UpdateScreen {
+
+        if self.rows.is_empty() {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let cursor_row = self.cursor.as_ref().and_then(|cursor| self.get_row(cursor));
+        let last_row = self.rows.len() - 1;
+        let page = self.visible_row_count.max(1);
+
+        let new_row = match (key, cursor_row) {
+            (VirtualKeyCode::Up, Some(row)) => row.saturating_sub(1),
+            (VirtualKeyCode::Down, Some(row)) => (row + 1).min(last_row),
+            (VirtualKeyCode::PageUp, Some(row)) => row.saturating_sub(page),
+            (VirtualKeyCode::PageDown, Some(row)) => (row + page).min(last_row),
+            (VirtualKeyCode::Up, None) | (VirtualKeyCode::PageUp, None) => last_row,
+            (VirtualKeyCode::Down, None) | (VirtualKeyCode::PageDown, None) | (VirtualKeyCode::Home, _) => 0,
+            (VirtualKeyCode::End, _) => last_row,
+            (VirtualKeyCode::Left, Some(row)) => {
+                let id = self.rows[row].id.clone();
+                if self.is_expanded(&id) {
+                    self.collapse(&id);
+                    return UpdateScreen::Redraw;
+                }
+                match self.rows[row].parent_row {
+                    Some(parent_row) => parent_row,
+                    None => return UpdateScreen::DontRedraw,
+                }
+            },
+            (VirtualKeyCode::Right, Some(row)) => {
+                let id = self.rows[row].id.clone();
+                if !self.rows[row].has_children {
+                    return UpdateScreen::DontRedraw;
+                }
+                if !self.is_expanded(&id) {
+                    self.expand(&id);
+                    return UpdateScreen::Redraw;
+                }
+                // The first child is the next row, unless the node has no children after loading them
+                match self.rows.get(row + 1) {
+                    Some(next) if next.parent_row == Some(row) => row + 1,
+                    _ => return UpdateScreen::DontRedraw,
+                }
+            },
+            (VirtualKeyCode::Return, Some(row)) => {
+                let id = self.rows[row].id.clone();
+                self.toggle_expanded(&id);
+                return UpdateScreen::Redraw;
+            },
+            (VirtualKeyCode::Space, Some(row)) => {
+                let id = self.rows[row].id.clone();
+                if ctrl_down {
+                    self.toggle_selected(&id);
+                } else {
+                    self.select(&id);
+                }
+                return UpdateScreen::Redraw;
+            },
+            (VirtualKeyCode::A, _) if ctrl_down && self.selection_mode == SelectionMode::Multiple => {
+                self.selected = self.rows.iter().map(|row| row.id.clone()).collect();
+                return UpdateScreen::Redraw;
+            },
+            _ => return UpdateScreen::DontRedraw,
+        };
+
+        // Left / Right only move the cursor to the parent / child, without extending the selection
+        let can_extend_selection = key != VirtualKeyCode::Left && key != VirtualKeyCode::Right;
+        self.move_cursor_to_row(new_row, shift_down && can_extend_selection, ctrl_down);
+        UpdateScreen::Redraw
+    }
+
+    /// Returns the classes of the indentation guides of the row: a vertical line for
+    /// every parent that has more children below the row
+    fn get_guides(&self, row: usize) -> Vec<&'static str> {
+        let mut guides = vec!["__azul-native-tree-view-guide-line"; self.rows[row].depth];
+        let mut current = Some(row);
+        while let Some(current_row) = current {
+            let tree_row = &self.rows[current_row];
+            if tree_row.depth == 0 {
+                break;
+            }
+            let is_own_level = current_row == row;
+            guides[tree_row.depth - 1] = match (tree_row.is_last_child, is_own_level) {
+                (true, true) => "__azul-native-tree-view-guide-end",
+                (true, false) => "__azul-native-tree-view-guide-empty",
+                (false, _) => "__azul-native-tree-view-guide-line",
+            };
+            current = tree_row.parent_row;
+        }
+        guides
+    }
+
+    pub fn render<T: Layout>(state: &mut TreeViewState<M>, _info: WindowInfo<T>, dimensions: HidpiAdjustedBounds)
+    -> Dom<T>
+    {
+        let height = dimensions.logical_size.height as f32;
+        state.visible_row_count = ((height / state.row_height).floor() as usize).max(1);
+        let max_scroll_offset = state.rows.len().saturating_sub(state.visible_row_count);
+        state.scroll_offset = state.scroll_offset.min(max_scroll_offset);
+
+        // Render the row that is only partially visible at the bottom, too
+        let rendered_row_count = (height / state.row_height).ceil() as usize;
+
+        // div.__azul-native-tree-view-rows
+        //     '-> div.__azul-native-tree-view-row (.__azul-native-tree-view-row-selected, .__azul-native-tree-view-row-cursor)
+        //         |-> div.__azul-native-tree-view-guide (one per level of indentation)
+        //         |   '-> div.__azul-native-tree-view-guide-line / -guide-end / -guide-empty
+        //         |-> div.__azul-native-tree-view-arrow (empty if the node has no children)
+        //         '-> p.__azul-native-tree-view-label
+
+        let mut rows = Dom::new(NodeType::Div).with_class("__azul-native-tree-view-rows");
+
+        for row in state.scroll_offset..(state.scroll_offset + rendered_row_count).min(state.rows.len()) {
+
+            let tree_row = &state.rows[row];
+
+            let mut row_dom = Dom::new(NodeType::Div)
+                .with_class("__azul-native-tree-view-row")
+                .with_style_override(ROW_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(state.row_height)));
+
+            if state.selected.contains(&tree_row.id) {
+                row_dom.add_class("__azul-native-tree-view-row-selected");
+            }
+            if state.cursor.as_ref() == Some(&tree_row.id) {
+                row_dom.add_class("__azul-native-tree-view-row-cursor");
+            }
+
+            for guide in state.get_guides(row) {
+                row_dom.add_child(Dom::new(NodeType::Div)
+                    .with_class("__azul-native-tree-view-guide")
+                    .with_style_override(INDENT_ID, CssProperty::Width(LayoutWidth::px(state.indent)))
+                    .with_child(Dom::new(NodeType::Div)
+                        .with_class(guide)
+                        .with_style_override(GUIDE_END_HEIGHT_ID, CssProperty::Height(LayoutHeight::px(state.row_height / 2.0)))));
+            }
+
+            let mut arrow = Dom::new(NodeType::Div)
+                .with_class("__azul-native-tree-view-arrow")
+                .with_style_override(INDENT_ID, CssProperty::Width(LayoutWidth::px(state.indent)));
+
+            if tree_row.has_children {
+                let glyph = if state.expanded.contains(&tree_row.id) { "\u{25BE}" } else { "\u{25B8}" };
+                arrow.add_child(Dom::new(NodeType::Label(glyph.into())));
+            }
+
+            row_dom.add_child(arrow);
+            row_dom.add_child(Dom::new(NodeType::Label(state.model.get_label(&tree_row.id)))
+                .with_class("__azul-native-tree-view-label"));
+
+            rows.add_child(row_dom);
+        }
+
+        rows
+    }
+}
+
+impl TreeView {
+    pub fn new() -> Self {
+        Self {
+
+        }
+    }
+
+    pub fn dom<T: Layout, M: TreeModel>(&self, state: &TreeViewState<M>, t: &T, window: &mut FakeWindow<T>) -> Dom<T> {
+        if let Some(ptr) = StackCheckedPointer::new(t, state) {
+            let mut dom = Dom::new(NodeType::IFrame((IFrameCallback(render_tree_callback::<T, M>), ptr)))
+                .with_class("__azul-native-tree-view")
+                .with_tab_index(TabIndex::Auto);
+            let mouse_down = window.add_callback(ptr, DefaultCallback(Self::tree_view_on_mouse_down::<T, M>));
+            let scroll = window.add_callback(ptr, DefaultCallback(Self::tree_view_on_scroll::<T, M>));
+            let virtual_key_down = window.add_callback(ptr, DefaultCallback(Self::tree_view_on_virtual_key_down::<T, M>));
+            dom.add_default_callback_id(On::LeftMouseDown, mouse_down);
+            dom.add_default_callback_id(On::Scroll, scroll);
+            dom.add_default_callback_id(On::VirtualKeyDown, virtual_key_down);
+            dom
+        } else {
+            Dom::new(NodeType::Label(
+                "Cannot create tree from heap-allocated TreeViewState, \
+                 please call TreeViewState::render manually".into())
+            )
+        }
+    }
+
+    fn tree_view_on_mouse_down<T: Layout, M: TreeModel>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TreeViewState::<M>::on_mouse_down, data, event) }
+    }
+
+    fn tree_view_on_scroll<T: Layout, M: TreeModel>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TreeViewState::<M>::on_scroll, data, event) }
+    }
+
+    fn tree_view_on_virtual_key_down<T: Layout, M: TreeModel>(ptr: &StackCheckedPointer<T>, data: AppStateNoData<T>, event: WindowEvent<T>)
+    -> UpdateScreen
+    {
+        unsafe { ptr.invoke_mut(TreeViewState::<M>::on_virtual_key_down, data, event) }
+    }
+}
+
+fn render_tree_callback<T: Layout, M: TreeModel>(ptr: &StackCheckedPointer<T>, info: WindowInfo<T>, dimensions: HidpiAdjustedBounds)
+-> Dom<T>
+{
+    unsafe { ptr.invoke_mut_iframe(TreeViewState::<M>::render, info, dimensions) }
+}
+
+impl<M: TreeModel> TreeViewState<M> {
+
+    /// Clicking the arrow expands / collapses the node, clicking the rest of the row selects it
+    pub fn on_mouse_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let (x, y) = event.cursor_relative_to_item;
+        let row = self.scroll_offset + (y / self.row_height).max(0.0) as usize;
+
+        let (id, depth, has_children) = match self.rows.get(row) {
+            Some(tree_row) => (tree_row.id.clone(), tree_row.depth, tree_row.has_children),
+            None => return UpdateScreen::DontRedraw,
+        };
+
+        let arrow_start = depth as f32 * self.indent;
+        if has_children && x >= arrow_start && x < arrow_start + self.indent {
+            self.toggle_expanded(&id);
+            return UpdateScreen::Redraw;
+        }
+
+        let keyboard_state = app_state_no_data.windows[event.window].get_keyboard_state();
+        if keyboard_state.shift_down {
+            self.select_range_to(&id);
+        } else if keyboard_state.ctrl_down {
+            self.toggle_selected(&id);
+        } else {
+            self.select(&id);
+        }
+
+        UpdateScreen::Redraw
+    }
+
+    /// Scrolls by whole rows, at least one row per scroll event
+    pub fn on_scroll<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+
+        let scroll_y = app_state_no_data.windows[event.window].get_mouse_state().scroll_y as f32;
+        if scroll_y == 0.0 {
+            return UpdateScreen::DontRedraw;
+        }
+
+        let rows = (scroll_y.abs() / self.row_height).ceil() as usize;
+        let max_scroll_offset = self.rows.len().saturating_sub(self.visible_row_count);
+        let new_scroll_offset = if scroll_y > 0.0 {
+            (self.scroll_offset + rows).min(max_scroll_offset)
+        } else {
+            self.scroll_offset.saturating_sub(rows)
+        };
+
+        if new_scroll_offset == self.scroll_offset {
+            UpdateScreen::DontRedraw
+        } else {
+            self.scroll_offset = new_scroll_offset;
+            UpdateScreen::Redraw
+        }
+    }
+
+    pub fn on_virtual_key_down<T: Layout>(&mut self, app_state_no_data: AppStateNoData<T>, event: WindowEvent<T>) -> UpdateScreen {
+        let keyboard_state = app_state_no_data.windows[event.window].get_keyboard_state();
+        match keyboard_state.latest_virtual_keycode {
+            Some(key) => self.handle_virtual_key(key, keyboard_state.shift_down, keyboard_state.ctrl_down),
+            None => UpdateScreen::DontRedraw,
+        }
+    }
+}
+
+#[test]
+fn test_tree_view_keyboard_navigation() {
+
+    // root
+    //  |-> a
+    //  |   '-> a1
+    //  '-> b
+    let mut model = SimpleTreeModel::new();
+    let root = model.add_root("root");
+    let a = model.add_child(root, "a");
+    let a1 = model.add_child(a, "a1");
+    let b = model.add_child(root, "b");
+
+    let mut tree = TreeViewState::new(model).with_selection_mode(SelectionMode::Multiple);
+    assert_eq!(tree.get_row_count(), 1);
+
+    tree.handle_virtual_key(VirtualKeyCode::Down, false, false);
+    assert!(tree.is_selected(&root));
+
+    // Right expands the root, the second Right moves to the first child
+    tree.handle_virtual_key(VirtualKeyCode::Right, false, false);
+    tree.handle_virtual_key(VirtualKeyCode::Right, false, false);
+    tree.handle_virtual_key(VirtualKeyCode::Right, false, false);
+    assert_eq!(tree.get_row_count(), 4);
+    assert_eq!(tree.get_row(&a1), Some(2));
+    assert_eq!(tree.get_row(&b), Some(3));
+    assert_eq!(tree.get_selected().iter().cloned().collect::<Vec<_>>(), vec![a]);
+    assert_eq!(tree.get_guides(2), vec!["__azul-native-tree-view-guide-line", "__azul-native-tree-view-guide-end"]);
+    assert_eq!(tree.get_guides(3), vec!["__azul-native-tree-view-guide-end"]);
+
+    // Shift + Down extends the selection
+    tree.handle_virtual_key(VirtualKeyCode::Down, true, false);
+    tree.handle_virtual_key(VirtualKeyCode::Down, true, false);
+    assert_eq!(tree.get_selected().len(), 3);
+
+    // Collapsing "a" with Left on "a1" moves the cursor to the parent first
+    tree.handle_virtual_key(VirtualKeyCode::Up, false, false);
+    tree.handle_virtual_key(VirtualKeyCode::Left, false, false);
+    assert_eq!(tree.get_selected().iter().cloned().collect::<Vec<_>>(), vec![a]);
+    tree.handle_virtual_key(VirtualKeyCode::Left, false, false);
+    assert_eq!(tree.get_row_count(), 3);
+    assert_eq!(tree.get_row(&a1), None);
+    assert_eq!(tree.get_row(&b), Some(2));
+
+    // Expanding and collapsing only updates the rows of the children, which
+    // have to be the same as the rows that are read from the model again
+    let rows = tree.rows.clone();
+    tree.reload();
+    assert_eq!(tree.rows, rows);
+    tree.handle_virtual_key(VirtualKeyCode::Left, false, false);
+    assert_eq!(tree.get_selected().iter().cloned().collect::<Vec<_>>(), vec![root]);
+}
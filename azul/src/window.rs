@@ -4,12 +4,13 @@ use std::{
     time::Duration,
     fmt,
     rc::Rc,
+    collections::BTreeMap,
     marker::PhantomData,
     io::Error as IoError,
 };
 use webrender::{
     api::{
-        LayoutRect, PipelineId, Epoch, ColorF, BuiltDisplayList, DocumentId,
+        LayoutRect, LayoutPoint, PipelineId, Epoch, ColorF, BuiltDisplayList, DocumentId,
         RenderApi, ExternalScrollId, RenderNotifier, HitTestResult, DeviceIntSize,
     },
     Renderer, RendererOptions, RendererKind, ShaderPrecacheFlags,
@@ -26,7 +27,7 @@ use glium::{
     backend::{Context, Facade, glutin::DisplayCreationError},
 };
 use gleam::gl::{self, Gl};
-use azul_css::Css;
+use azul_css::{Css, LayoutRect as CssLayoutRect};
 #[cfg(debug_assertions)]
 use azul_css::HotReloadHandler;
 use {
@@ -39,10 +40,13 @@ use {
     style::sort_by_specificity,
     app::FrameEventInfo,
     app_resources::AppResources,
-    id_tree::NodeId,
+    id_tree::{NodeId, NodeDataContainer},
     default_callbacks::{DefaultCallbackSystem, StackCheckedPointer, DefaultCallback, DefaultCallbackId},
     ui_state::UiState,
     display_list::ScrolledNodes,
+    text_selection::{TextSelectionState, LaidOutText, CaretAffinity},
+    tooltip::TooltipState,
+    css::webrender_translate::wr_translate_layout_rect,
 };
 
 /// azul-internal ID for a window
@@ -68,6 +72,16 @@ pub struct FakeWindow<T: Layout> {
     /// but not change any window properties from underneath - this would
     /// lead to mismatch between the
     pub(crate) read_only_window: Rc<Display>,
+    /// The laid out text of the `Label` and `Text` nodes of the last frame
+    pub(crate) laid_out_texts: Rc<BTreeMap<NodeId, LaidOutText>>,
+    /// The laid out rectangles of the IFrames of the last frame, by the address of the
+    /// data that the IFrames were rendered with, see `get_iframe_rects`
+    pub(crate) iframe_rects: BTreeMap<usize, Vec<CssLayoutRect>>,
+    /// The laid out rectangles of the IFrames of the current frame, replace
+    /// `iframe_rects` once the frame is finished
+    pub(crate) next_iframe_rects: BTreeMap<usize, Vec<CssLayoutRect>>,
+    /// Daemons that were added during the `.layout()` phase, see `add_daemon`
+    pub(crate) daemons: Vec<Daemon<T>>,
 }
 
 impl<T: Layout> FakeWindow<T> {
@@ -100,6 +114,36 @@ impl<T: Layout> FakeWindow<T> {
         self.state.get_mouse_state()
     }
 
+    /// Returns the position in the text of the `Label` / `Text` node that is closest to the `point`
+    /// (relative to the top left of the node): the byte index of the character in the text and
+    /// whether the point is on the character before or after that index.
+    ///
+    /// Uses the layout of the last frame, returns `None` if the node doesn't display any text
+    /// or if its text can't be hit-tested (neither selectable nor a text editor, see `Dom::with_caret`).
+    pub fn hit_test_text(&self, node_id: NodeId, point: (f32, f32)) -> Option<(usize, CaretAffinity)> {
+        let text = self.laid_out_texts.get(&node_id)?;
+        let (offset, affinity) = text.get_position_at(LayoutPoint::new(point.0, point.1));
+        Some((text.to_original_offset(offset), affinity))
+    }
+
+    /// Returns the rectangle of the caret in front of the character at the byte index `char_index`
+    /// of the text of the `Label` / `Text` node, relative to the top left of the node. The `affinity`
+    /// decides whether the caret is at the end or the start of a line where the text is broken.
+    ///
+    /// Uses the layout of the last frame, returns `None` if the node doesn't display any text.
+    pub fn caret_rect(&self, node_id: NodeId, char_index: usize, affinity: CaretAffinity) -> Option<CssLayoutRect> {
+        let text = self.laid_out_texts.get(&node_id)?;
+        Some(wr_translate_layout_rect(text.get_caret_rect(text.from_original_offset(char_index), affinity)))
+    }
+
+    /// Returns the position and size (relative to the top left of the window) of the nodes of
+    /// the IFrame that was rendered with the `iframe_data` in the last frame, in the order in
+    /// which the nodes were added to the DOM of the IFrame. Useful for measuring the content
+    /// of an IFrame, for example the rows of a virtualized list with different row heights.
+    pub fn get_iframe_rects<U>(&self, iframe_data: &U) -> Option<&[CssLayoutRect]> {
+        self.iframe_rects.get(&(iframe_data as *const U as usize)).map(|rects| &rects[..])
+    }
+
     /// Adds a default callback to the window. The default callbacks are
     /// cleared after every frame, so two-way data binding widgets have to call this
     /// on every frame they want to insert a default callback.
@@ -118,6 +162,13 @@ impl<T: Layout> FakeWindow<T> {
         self.default_callbacks.add_callback(default_callback_id, callback_ptr, callback_fn);
         default_callback_id
     }
+
+    /// Starts a daemon during the `.layout()` phase, ex. a timer that redraws the window when
+    /// a widget has to change at a certain time. The daemon is added to the `AppState` after
+    /// the frame, see `AppState::add_daemon`.
+    pub fn add_daemon(&mut self, daemon: Daemon<T>) {
+        self.daemons.push(daemon);
+    }
 }
 
 /// Read-only window which can be used to create / draw
@@ -216,6 +267,12 @@ pub struct WindowEvent<'a, T: 'a + Layout> {
     pub cursor_relative_to_item: (f32, f32),
     /// The (x, y) position of the mouse cursor, **relative to top left of the window**.
     pub cursor_in_viewport: (f32, f32),
+    /// The laid out text of the last frame, see `FakeWindow::hit_test_text`
+    pub(crate) laid_out_texts: &'a BTreeMap<NodeId, LaidOutText>,
+    /// The laid out rectangles of the last frame, see `WindowEvent::get_node_rect`
+    pub(crate) laid_out_rects: &'a NodeDataContainer<LayoutRect>,
+    /// The node that currently captures the pointer, see `WindowEvent::has_pointer_capture`
+    pub(crate) pointer_capture: Option<NodeId>,
 }
 
 impl<'a, T: 'a + Layout> Clone for WindowEvent<'a, T> {
@@ -227,6 +284,9 @@ impl<'a, T: 'a + Layout> Clone for WindowEvent<'a, T> {
             hit_test_result: self.hit_test_result,
             cursor_relative_to_item: self.cursor_relative_to_item,
             cursor_in_viewport: self.cursor_in_viewport,
+            laid_out_texts: self.laid_out_texts,
+            laid_out_rects: self.laid_out_rects,
+            pointer_capture: self.pointer_capture,
         }
     }
 }
@@ -283,6 +343,48 @@ impl<'a, T: 'a + Layout> WindowEvent<'a, T> {
         let parent = node_layout[node_id].parent?;
         Some((node_id.preceding_siblings(&node_layout).count() - 1, parent))
     }
+
+    /// Returns the position and size of the node in the last frame (relative to the top left
+    /// of the window), for example to find out how many items of a list fit into it
+    pub fn get_node_rect(&self, node_id: NodeId) -> Option<CssLayoutRect> {
+        self.laid_out_rects.get(node_id).map(|rect| wr_translate_layout_rect(*rect))
+    }
+
+    /// Whether the hit node captured the pointer, i.e. the left mouse button was pressed on it
+    /// and hasn't been released yet (or is released by this event), see `Dom::with_pointer_capture`.
+    /// Used for dragging.
+    pub fn has_pointer_capture(&self) -> bool {
+        self.pointer_capture == Some(self.hit_dom_node)
+    }
+
+    /// Whether the mouse is over the hit node. The focused node and the node that captured the
+    /// pointer receive events even if the mouse isn't over them.
+    pub fn is_hovered(&self) -> bool {
+        match self.ui_state.node_ids_to_tag_ids.get(&self.hit_dom_node) {
+            Some(tag_id) => self.hit_test_result.items.iter().any(|item| item.tag.0 == *tag_id),
+            None => false,
+        }
+    }
+
+    /// Same as `FakeWindow::hit_test_text`, but at the position of the mouse cursor, which doesn't have
+    /// to be over the text node itself (ex. when clicking on the padding of a text input)
+    pub fn hit_test_text_at_cursor(&self, node_id: NodeId) -> Option<(usize, CaretAffinity)> {
+        let text = self.laid_out_texts.get(&node_id)?;
+        let tag_id = self.ui_state.node_ids_to_tag_ids.get(&node_id);
+
+        // If the text node was hit, the position relative to its hit-test rect is exact (even if the
+        // text is scrolled), otherwise use the position in the window
+        let point = self.hit_test_result.items.iter()
+            .find(|item| Some(&item.tag.0) == tag_id)
+            .map(|item| {
+                let hit_test_origin = text.hit_test_origins.get(item.tag.1 as usize).cloned().unwrap_or(text.origin);
+                hit_test_origin + item.point_relative_to_item.to_vector()
+            })
+            .unwrap_or(LayoutPoint::new(self.cursor_in_viewport.0, self.cursor_in_viewport.1));
+
+        let (offset, affinity) = text.get_position_at(point - text.origin.to_vector());
+        Some((text.to_original_offset(offset), affinity))
+    }
 }
 
 /// Options on how to initially create the window
@@ -308,7 +410,8 @@ pub struct WindowCreateOptions<T: Layout> {
     pub update_behaviour: UpdateBehaviour,
     /// Renderer type: Hardware-with-software-fallback, pure software or pure hardware renderer?
     pub renderer_type: RendererType,
-    /// Win32 menu callbacks
+    /// Win32 menu callbacks, never called
+    #[deprecated(note = "never called, use the `Menu` widget for the menus of a window")]
     pub menu_callbacks: FastHashMap<u16, Callback<T>>,
     /// Sets the window icon (Windows and Linux only). Usually 16x16 px or 32x32px
     pub window_icon: Option<Icon>,
@@ -319,6 +422,7 @@ pub struct WindowCreateOptions<T: Layout> {
 }
 
 impl<T: Layout> Default for WindowCreateOptions<T> {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             state: WindowState::default(),
@@ -552,6 +656,10 @@ pub struct Window<T: Layout> {
     pub(crate) animations: FastHashMap<DaemonId, Daemon<AnimationState>>,
     /// States of scrolling animations, updated every frame
     pub(crate) scroll_states: ScrollStates,
+    /// The selected text and the laid out texts of the last frame
+    pub(crate) text_selection: TextSelectionState,
+    /// The hovered node with a tooltip and whether its tooltip is visible
+    pub(crate) tooltip: TooltipState<T>,
     // The background thread that is running for this window.
     // pub(crate) background_thread: Option<JoinHandle<()>>,
     /// The style applied to the current window
@@ -610,7 +718,7 @@ impl ScrollStates {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct ScrollState {
     /// Amount in pixel that the current node is scrolled
     scroll_amount_x: f32,
@@ -622,7 +730,7 @@ pub struct ScrollState {
 }
 
 impl ScrollState {
-    fn new(overflow_x: f32, overflow_y: f32) -> Self {
+    pub(crate) fn new(overflow_x: f32, overflow_y: f32) -> Self {
         ScrollState {
             scroll_amount_x: 0.0,
             scroll_amount_y: 0.0,
@@ -641,6 +749,19 @@ impl ScrollState {
         self.scroll_amount_x = self.overflow_x.min(self.scroll_amount_x + x).max(0.0);
         self.scroll_amount_y = self.overflow_y.min(self.scroll_amount_y + y).max(0.0);
     }
+
+    /// Returns the scroll amount without marking the state as used, for widgets that
+    /// scroll their content themselves (ex. the `TextArea`)
+    pub(crate) fn get_scroll_amount(&self) -> (f32, f32) {
+        (self.scroll_amount_x, self.scroll_amount_y)
+    }
+
+    /// Changes how far the node can be scrolled, the scroll amount is clamped to the new overflow
+    pub(crate) fn set_overflow(&mut self, overflow_x: f32, overflow_y: f32) {
+        self.overflow_x = overflow_x.max(0.0);
+        self.overflow_y = overflow_y.max(0.0);
+        self.add(0.0, 0.0);
+    }
 }
 
 impl Default for ScrollState {
@@ -658,6 +779,8 @@ impl Default for ScrollState {
 pub(crate) struct WindowInternal {
     pub(crate) last_display_list_builder: BuiltDisplayList,
     pub(crate) last_scrolled_nodes: ScrolledNodes,
+    /// The laid out rectangles of the last frame, for `WindowEvent::get_node_rect`
+    pub(crate) last_laid_out_rects: NodeDataContainer<LayoutRect>,
     pub(crate) api: RenderApi,
     pub(crate) epoch: Epoch,
     pub(crate) pipeline_id: PipelineId,
@@ -862,6 +985,8 @@ impl<'a, T: Layout> Window<T> {
             style_loader: None,
             animations: FastHashMap::default(),
             scroll_states: ScrollStates::new(),
+            text_selection: TextSelectionState::default(),
+            tooltip: TooltipState::new(),
             internal: WindowInternal {
                 api: api,
                 epoch: epoch,
@@ -869,6 +994,7 @@ impl<'a, T: Layout> Window<T> {
                 document_id: document_id,
                 last_display_list_builder: BuiltDisplayList::default(),
                 last_scrolled_nodes: ScrolledNodes::default(),
+                last_laid_out_rects: NodeDataContainer::default(),
             },
             marker: PhantomData,
         };
@@ -977,6 +1103,15 @@ impl<'a, T: Layout> Window<T> {
         self.state.mouse_state.scroll_x = 0.0;
         self.state.mouse_state.scroll_y = 0.0;
     }
+
+    /// The focus, the pointer capture and the text selection refer to the nodes of the last frame.
+    /// When the DOM is rebuilt, they are moved to the nodes with the same identity in the new DOM
+    /// (see `NodeData::key`) or removed, so that they don't end up on an unrelated node.
+    pub(crate) fn update_node_ids(&mut self, previous_ui_state: &UiState<T>, ui_state: &UiState<T>) {
+        let get_node_id = ui_state.get_node_id_mapping(previous_ui_state);
+        self.state.update_node_ids(&get_node_id);
+        self.text_selection.update_node_ids(&get_node_id);
+    }
 }
 
 pub(crate) fn get_gl_context(display: &Display) -> Result<Rc<Gl>, WindowCreateError> {
@@ -2,19 +2,21 @@
 //! click was a mouseover, mouseout, and so on and calling the correct callbacks)
 
 use std::{
+    mem,
     collections::{HashSet, BTreeMap},
     path::PathBuf,
+    time::{Duration, Instant},
 };
 use glium::glutin::{
     Window, Event, WindowEvent, KeyboardInput, ScanCode, ElementState,
     MouseCursor, VirtualKeyCode, MouseScrollDelta,
     ModifiersState, dpi::{LogicalPosition, LogicalSize},
 };
-use webrender::api::{HitTestResult, HitTestItem};
+use webrender::api::{HitTestResult, HitTestItem, PipelineId, LayoutPoint, LayoutRect};
 use {
-    dom::{On, Callback, TabIndex},
+    dom::{On, Callback, TabIndex, TagId},
     default_callbacks::DefaultCallbackId,
-    id_tree::NodeId,
+    id_tree::{NodeId, NodeDataContainer},
     ui_state::UiState,
     traits::Layout,
 };
@@ -104,6 +106,50 @@ impl Default for MouseState {
     }
 }
 
+/// Maximum time between the clicks of a double- or triple-click
+pub(crate) const MULTI_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Detects double- and triple-clicks: counts the clicks on the same target (ex. a
+/// table cell or a splitter divider) that follow each other within `MULTI_CLICK_INTERVAL`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct ClickCounter<K> {
+    /// Time, target and number of the last click
+    last_click: Option<(Instant, K, usize)>,
+}
+
+impl<K> Default for ClickCounter<K> {
+    fn default() -> Self {
+        Self { last_click: None }
+    }
+}
+
+impl<K: PartialEq> ClickCounter<K> {
+
+    /// Registers a click on the `target`, returns the number of the click: 1 for a single click,
+    /// 2 for a double-click and so on, up to `max_count` after which the count starts at 1 again
+    pub(crate) fn click(&mut self, target: K, now: Instant, max_count: usize) -> usize {
+        self.click_near(target, now, max_count, |last_target, target| last_target == target)
+    }
+
+    /// Same as `click`, but `is_same_target` decides whether the click continues the last click
+    /// (ex. whether a click in a text is close enough to the last click)
+    pub(crate) fn click_near<F: Fn(&K, &K) -> bool>(&mut self, target: K, now: Instant, max_count: usize, is_same_target: F) -> usize {
+        let count = match &self.last_click {
+            Some((time, last_target, count)) if now.duration_since(*time) < MULTI_CLICK_INTERVAL && is_same_target(last_target, &target) => {
+                count % max_count.max(1) + 1
+            },
+            _ => 1,
+        };
+        self.last_click = Some((now, target, count));
+        count
+    }
+
+    /// Forgets the last click, so that the next click is a single click
+    pub(crate) fn reset(&mut self) {
+        self.last_click = None;
+    }
+}
+
 /// Toggles webrender debug flags (will make stuff appear on
 /// the screen that you might not want to - used for debugging purposes)
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -168,6 +214,16 @@ pub struct WindowState {
     /// What node is currently hovered over, default to None. Only necessary internal
     /// to the crate, for emitting `On::FocusReceived` and `On::FocusLost` events.
     pub(crate) focused_element: Option<NodeId>,
+    /// The node that captured the pointer when the left mouse button was pressed on it,
+    /// see `Dom::with_pointer_capture`. Reset with the first event after the left mouse button is released.
+    pub(crate) pointer_capture: Option<NodeId>,
+    /// Whether the propagation of the last key press was stopped (see `AppStateNoData::stop_propagation`),
+    /// so that the text of the key isn't sent either. Reset with the next key press.
+    pub(crate) text_input_stopped: bool,
+    /// The focus traps that took the focus (see `NodeData::traps_focus`) and the node that was
+    /// focused before, innermost trap last. When a trap is removed (ex. a modal dialog is closed),
+    /// the focus goes back to the node that was focused before.
+    pub(crate) focus_trap_history: Vec<(NodeId, Option<NodeId>)>,
     /// Previous window state, used for determining mouseout, etc. events
     pub(crate) previous_window_state: Option<Box<WindowState>>,
     /// Mostly used for debugging, shows WebRender-builtin graphs on the screen.
@@ -223,6 +279,9 @@ impl Default for WindowState {
             keyboard_state: KeyboardState::default(),
             mouse_state: MouseState::default(),
             focused_element: None,
+            pointer_capture: None,
+            text_input_stopped: false,
+            focus_trap_history: Vec::new(),
             hovered_file: None,
             previous_window_state: None,
             title: DEFAULT_TITLE.into(),
@@ -245,6 +304,97 @@ pub(crate) struct DetermineCallbackResult<T: Layout> {
     pub(crate) normal_callbacks: BTreeMap<On, Callback<T>>,
 }
 
+/// Returns whether the event is sent to the focused node instead of the hovered nodes
+fn is_keyboard_event(event: &On) -> bool {
+    match event {
+        On::VirtualKeyDown | On::VirtualKeyUp | On::TextInput => true,
+        _ => false,
+    }
+}
+
+/// Returns whether the event is sent only to the node that captured the pointer (if any)
+fn is_mouse_event(event: &On) -> bool {
+    match event {
+        On::MouseOver |
+        On::MouseDown | On::LeftMouseDown | On::RightMouseDown | On::MiddleMouseDown |
+        On::MouseUp | On::LeftMouseUp | On::RightMouseUp | On::MiddleMouseUp => true,
+        _ => false,
+    }
+}
+
+/// Fabricates a hit-test item for a node that receives an event without being hovered
+/// (the focused node or the node that captured the pointer), from the last laid out rectangle of the node
+fn get_unhovered_hit_test_item(tag_id: TagId, node_id: NodeId, pipeline_id: PipelineId, cursor_pos: LayoutPoint, laid_out_rects: &NodeDataContainer<LayoutRect>)
+-> HitTestItem
+{
+    let point_relative_to_item = match laid_out_rects.get(node_id) {
+        Some(rect) => LayoutPoint::new(cursor_pos.x - rect.origin.x, cursor_pos.y - rect.origin.y),
+        None => LayoutPoint::zero(),
+    };
+
+    HitTestItem {
+        pipeline: pipeline_id,
+        tag: (tag_id, 0),
+        point_in_viewport: cursor_pos,
+        point_relative_to_item,
+    }
+}
+
+/// If an overlay (see `NodeData::is_overlay`) is hit, returns only the hit-test items of the
+/// top-most overlay, its children and its parents, since all other nodes are covered by the overlay
+pub(crate) fn get_hit_test_items_above_overlay<T: Layout>(hit_test_result: &HitTestResult, ui_state: &UiState<T>) -> HitTestResult {
+
+    let arena = ui_state.dom.arena.borrow();
+    let node_layout = &arena.node_layout;
+    let node_count = node_layout.len();
+
+    // The hit-test items are sorted back-to-front
+    let overlay = hit_test_result.items.iter().rev()
+        .filter_map(|item| ui_state.overlay_tags.get(&item.tag.0).cloned())
+        .find(|node_id| node_id.index() < node_count);
+
+    let overlay = match overlay {
+        Some(overlay) => overlay,
+        None => return HitTestResult { items: hit_test_result.items.clone() },
+    };
+
+    let overlay_and_parents = overlay.ancestors(node_layout).collect::<HashSet<NodeId>>();
+
+    let items = hit_test_result.items.iter().filter(|item| {
+        match ui_state.tag_ids_to_node_ids.get(&item.tag.0) {
+            Some(node_id) if node_id.index() < node_count => {
+                overlay_and_parents.contains(node_id) || node_id.ancestors(node_layout).any(|parent| parent == overlay)
+            },
+            _ => false,
+        }
+    }).cloned().collect();
+
+    HitTestResult { items }
+}
+
+/// Returns the callbacks of the node with the `tag_id` that should be called for the `events`
+fn get_callbacks_for_tag<T: Layout>(ui_state: &UiState<T>, tag_id: TagId, events: &HashSet<On>, hit_test_item: HitTestItem)
+-> DetermineCallbackResult<T>
+{
+    let default_callbacks = ui_state.tag_ids_to_default_callbacks
+        .get(&tag_id)
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(on, _)| events.contains(&on))
+        .collect();
+
+    let normal_callbacks = ui_state.tag_ids_to_callbacks
+        .get(&tag_id)
+        .cloned()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(on, _)| events.contains(&on))
+        .collect();
+
+    DetermineCallbackResult { default_callbacks, normal_callbacks, hit_test_item }
+}
+
 impl WindowState
 {
     pub fn get_mouse_state(&self) -> &MouseState {
@@ -269,7 +419,7 @@ impl WindowState
     /// A BTreeMap where each item is already filtered by the proper hit-testing type,
     /// meaning in order to get the proper callbacks, you simply have to iterate through
     /// all node IDs
-    pub(crate) fn determine_callbacks<T: Layout>(&mut self, hit_test_result: &HitTestResult, event: &Event, ui_state: &UiState<T>)
+    pub(crate) fn determine_callbacks<T: Layout>(&mut self, hit_test_result: &HitTestResult, event: &Event, ui_state: &UiState<T>, pipeline_id: PipelineId, laid_out_rects: &NodeDataContainer<LayoutRect>)
     -> BTreeMap<NodeId, DetermineCallbackResult<T>>
     {
         use std::collections::HashSet;
@@ -280,6 +430,9 @@ impl WindowState
 
         let event = if let Event::WindowEvent { event, .. } = event { event } else { return BTreeMap::new(); };
 
+        // The nodes below an overlay can't be clicked or hovered
+        let hit_test_result = &get_hit_test_items_above_overlay(hit_test_result, ui_state);
+
         // store the current window state so we can set it in this.previous_window_state later on
         let mut previous_state = Box::new(self.clone());
         previous_state.previous_window_state = None;
@@ -343,9 +496,10 @@ impl WindowState
             },
             WindowEvent::KeyboardInput { input: KeyboardInput { state: ElementState::Pressed, virtual_keycode: Some(_), .. }, .. } => {
                 events_vec.insert(On::VirtualKeyDown);
+                self.text_input_stopped = false;
             },
             WindowEvent::ReceivedCharacter(c) => {
-                if !c.is_control() {
+                if !c.is_control() && !self.text_input_stopped {
                     events_vec.insert(On::TextInput);
                 }
             },
@@ -366,6 +520,8 @@ impl WindowState
 
         let event_was_mouse_down = if let WindowEvent::MouseInput { state: ElementState::Pressed, .. } = event { true } else { false };
         let event_was_mouse_release = if let WindowEvent::MouseInput { state: ElementState::Released, .. } = event { true } else { false };
+        let event_was_left_mouse_down = if let WindowEvent::MouseInput { state: ElementState::Pressed, button: Left, .. } = event { true } else { false };
+        let left_mouse_was_down = previous_state.mouse_state.left_down;
 
         // TODO: If the current mouse is down, but the event
         // wasn't a click, that means it was a drag
@@ -382,46 +538,161 @@ impl WindowState
             // Update the current window states focus element, regardless of
             // whether an On::FocusReceived or a On::FocusLost
             self.focused_element = Some(new_focused_element_node_id);
-            if previous_state.focused_element != Some(new_focused_element_node_id) {
-                if previous_state.focused_element.is_none() {
-                    events_vec.insert(On::FocusReceived);
-                } else {
-                    events_vec.insert(On::FocusLost);
-                }
-                // else, if the last element = current element,
-                // then the focus is still on the same field
-            }
         } else if event_was_mouse_release || event_was_mouse_down {
             self.focused_element = None;
-            events_vec.insert(On::FocusLost);
         }
 
+        // The focus can't leave a focus trap (ex. a modal dialog), the trap itself is focused instead
+        if let Some(focus_trap) = ui_state.focus_trap {
+            let arena = ui_state.dom.arena.borrow();
+            let node_count = arena.node_layout.len();
+            let is_inside_trap = self.focused_element
+                .filter(|focused_element| focused_element.index() < node_count)
+                .map(|focused_element| focused_element.ancestors(&arena.node_layout).any(|parent| parent == focus_trap))
+                .unwrap_or(false);
+            if !is_inside_trap && focus_trap.index() < node_count {
+                if !self.focus_trap_history.iter().any(|(trap, _)| *trap == focus_trap) {
+                    self.focus_trap_history.push((focus_trap, previous_state.focused_element));
+                }
+                self.focused_element = Some(focus_trap);
+            }
+        }
+
+        // On::FocusLost is sent to the node that lost the focus and On::FocusReceived to the node
+        // that received it, not to the hovered nodes (the node that lost the focus usually isn't
+        // hovered anymore). If the last focused element is the current element, then the focus
+        // is still on the same field and neither event is sent. The focused element was moved
+        // to the current DOM by `update_node_ids`, so the node that lost the focus is the same
+        // node in the current DOM (or `None` if it doesn't exist anymore).
+        let (focus_lost_node, focus_received_node) = if previous_state.focused_element != self.focused_element {
+            (previous_state.focused_element, self.focused_element)
+        } else {
+            (None, None)
+        };
+
         self.previous_window_state = Some(previous_state);
 
-        hit_test_result.items
-            .iter()
-            .filter_map(|item| {
-                let item_node_id = ui_state.tag_ids_to_node_ids.get(&item.tag.0)?;
-                let default_callbacks = ui_state.tag_ids_to_default_callbacks
-                    .get(&item.tag.0)
-                    .cloned()
-                    .unwrap_or_default()
-                    .into_iter()
-                    .filter(|(on, _)| events_vec.contains(&on))
-                    .collect();
-
-                let normal_callbacks = ui_state.tag_ids_to_callbacks
-                    .get(&item.tag.0)
-                    .cloned()
-                    .unwrap_or_default()
-                    .into_iter()
-                    .filter(|(on, _)| events_vec.contains(&on))
-                    .collect();
-                let hit_test_item = item.clone();
-
-                Some((*item_node_id, DetermineCallbackResult { default_callbacks, normal_callbacks, hit_test_item }))
-            })
-            .collect()
+        // Pressing the left mouse button on a node that captures the pointer routes all mouse
+        // events to that node until the button is released (ex. for dragging a slider thumb).
+        // The callbacks of the release still see the capture (so that a click can check that it
+        // was pressed on the same node), it is dropped with the next event.
+        if !left_mouse_was_down {
+            self.pointer_capture = None;
+        }
+
+        if event_was_left_mouse_down {
+            self.pointer_capture = hit_test_result.items.iter().rev().find_map(|item| ui_state.pointer_capture_tags.get(&item.tag.0)).cloned();
+        }
+
+        // Keyboard events go to the focused node and its parents (i.e. a text input receives the typed
+        // text even if the mouse isn't over it), or to the hovered nodes if nothing is focused
+        let (focused_nodes, pointer_capture, focus_lost_node, focus_received_node) = {
+            let arena = ui_state.dom.arena.borrow();
+            let node_count = arena.node_layout.len();
+            let focused_nodes = match self.focused_element {
+                Some(focused_element) if focused_element.index() < node_count => {
+                    focused_element.ancestors(&arena.node_layout).collect()
+                },
+                _ => HashSet::<NodeId>::new(),
+            };
+            let is_valid = |node_id: &NodeId| node_id.index() < node_count;
+            (focused_nodes, self.pointer_capture.filter(&is_valid), focus_lost_node.filter(&is_valid), focus_received_node.filter(&is_valid))
+        };
+
+        let mut callbacks = BTreeMap::new();
+
+        for item in hit_test_result.items.iter() {
+            let item_node_id = match ui_state.tag_ids_to_node_ids.get(&item.tag.0) {
+                Some(node_id) => *node_id,
+                None => continue,
+            };
+            let receives_keyboard_events = focused_nodes.is_empty() || focused_nodes.contains(&item_node_id);
+            let receives_mouse_events = pointer_capture.map(|captured| captured == item_node_id).unwrap_or(true);
+            let events = events_vec.iter()
+                .filter(|on| receives_keyboard_events || !is_keyboard_event(on))
+                .filter(|on| receives_mouse_events || !is_mouse_event(on))
+                .cloned()
+                .collect();
+            callbacks.insert(item_node_id, get_callbacks_for_tag(ui_state, item.tag.0, &events, item.clone()));
+        }
+
+        // The focused nodes, the node that captured the pointer and the node that lost the focus
+        // may not be hovered, so there is no hit-test item for them
+        let keyboard_events = events_vec.iter().filter(|on| is_keyboard_event(on)).cloned().collect::<HashSet<On>>();
+        let mouse_events = events_vec.iter().filter(|on| is_mouse_event(on)).cloned().collect::<HashSet<On>>();
+
+        let mut targeted_nodes = Vec::new();
+        if !keyboard_events.is_empty() {
+            targeted_nodes.extend(focused_nodes.into_iter().map(|node_id| (node_id, keyboard_events.clone())));
+        }
+        if let Some(captured) = pointer_capture {
+            if !mouse_events.is_empty() {
+                targeted_nodes.push((captured, mouse_events));
+            }
+        }
+        if let Some(focus_lost_node) = focus_lost_node {
+            targeted_nodes.push((focus_lost_node, [On::FocusLost].iter().cloned().collect()));
+        }
+        if let Some(focus_received_node) = focus_received_node {
+            targeted_nodes.push((focus_received_node, [On::FocusReceived].iter().cloned().collect()));
+        }
+
+        let cursor_pos = self.mouse_state.cursor_pos.unwrap_or(LogicalPosition::new(0.0, 0.0));
+        let cursor_pos = LayoutPoint::new(cursor_pos.x as f32, cursor_pos.y as f32);
+
+        for (node_id, events) in targeted_nodes {
+            let tag_id = match ui_state.node_ids_to_tag_ids.get(&node_id) {
+                Some(tag_id) => *tag_id,
+                None => continue,
+            };
+            match callbacks.get_mut(&node_id) {
+                Some(hovered_node) => {
+                    // Add the events to the callbacks of the hovered node
+                    let DetermineCallbackResult { default_callbacks, normal_callbacks, .. } =
+                        get_callbacks_for_tag(ui_state, tag_id, &events, hovered_node.hit_test_item.clone());
+                    hovered_node.default_callbacks.extend(default_callbacks);
+                    hovered_node.normal_callbacks.extend(normal_callbacks);
+                    continue;
+                },
+                None => { },
+            }
+            let hit_test_item = get_unhovered_hit_test_item(tag_id, node_id, pipeline_id, cursor_pos, laid_out_rects);
+            callbacks.insert(node_id, get_callbacks_for_tag(ui_state, tag_id, &events, hit_test_item));
+        }
+
+        callbacks
+    }
+
+    /// Moves the focus and the pointer capture (of this and of the previous state) to the nodes
+    /// with the same identity when the DOM is rebuilt, see `Window::update_node_ids`. If a focus
+    /// trap was removed, the node that was focused before the trap took the focus is focused again.
+    pub(crate) fn update_node_ids<F: Fn(NodeId) -> Option<NodeId>>(&mut self, get_node_id: &F) {
+        self.focused_element = self.focused_element.and_then(get_node_id);
+        self.pointer_capture = self.pointer_capture.and_then(get_node_id);
+
+        // The outermost removed trap took the focus first, so the focus goes back to where it was before that trap
+        let mut focus_before_removed_trap = None;
+        let focus_trap_history = mem::replace(&mut self.focus_trap_history, Vec::new());
+        self.focus_trap_history = focus_trap_history.into_iter().filter_map(|(trap, focus_before)| {
+            let focus_before = focus_before.and_then(get_node_id);
+            match get_node_id(trap) {
+                Some(trap) => Some((trap, focus_before)),
+                None => {
+                    focus_before_removed_trap.get_or_insert(focus_before);
+                    None
+                },
+            }
+        }).collect();
+
+        if self.focused_element.is_none() {
+            if let Some(focus_before) = focus_before_removed_trap {
+                self.focused_element = focus_before;
+            }
+        }
+
+        if let Some(previous_state) = self.previous_window_state.as_mut() {
+            previous_state.update_node_ids(get_node_id);
+        }
     }
 
     pub(crate) fn update_keyboard_modifiers(&mut self, event: &Event) {
@@ -550,3 +821,272 @@ fn update_mouse_cursor(window: &Window, old: &MouseCursor, new: &MouseCursor) {
         window.set_cursor(*new);
     }
 }
+
+#[cfg(test)]
+use test_support::Mock;
+
+#[cfg(test)]
+fn mock_callback(_: &mut ::app_state::AppState<Mock>, _: ::window::WindowEvent<Mock>) -> ::dom::UpdateScreen {
+    ::dom::UpdateScreen::DontRedraw
+}
+
+/// root
+///     |-> node 1: focusable, has keyboard and focus callbacks
+///     '-> node 2: focusable, has keyboard and focus callbacks
+#[cfg(test)]
+fn get_test_ui_state() -> UiState<Mock> {
+    use dom::{Dom, NodeType};
+    let focusable = || Dom::new(NodeType::Div)
+        .with_tab_index(TabIndex::Auto)
+        .with_callback(On::VirtualKeyDown, Callback(mock_callback))
+        .with_callback(On::FocusReceived, Callback(mock_callback))
+        .with_callback(On::FocusLost, Callback(mock_callback));
+    Dom::new(NodeType::Div)
+        .with_child(focusable())
+        .with_child(focusable())
+        .into_ui_state()
+}
+
+/// Runs `determine_callbacks` for the `event` while the mouse is over the `hovered` node
+#[cfg(test)]
+fn get_test_callbacks(window_state: &mut WindowState, ui_state: &UiState<Mock>, hovered: usize, event: WindowEvent)
+-> BTreeMap<NodeId, DetermineCallbackResult<Mock>>
+{
+    use glium::glutin::WindowId;
+    let hit_test_result = HitTestResult {
+        items: ui_state.node_ids_to_tag_ids.get(&NodeId::new(hovered)).map(|tag_id| HitTestItem {
+            pipeline: PipelineId(0, 0),
+            tag: (*tag_id, 0),
+            point_in_viewport: LayoutPoint::zero(),
+            point_relative_to_item: LayoutPoint::zero(),
+        }).into_iter().collect(),
+    };
+    let event = Event::WindowEvent { window_id: unsafe { WindowId::dummy() }, event };
+    window_state.determine_callbacks(&hit_test_result, &event, ui_state, PipelineId(0, 0), &NodeDataContainer::new(Vec::new()))
+}
+
+/// Returns the events of the callbacks that are called on the `node`
+#[cfg(test)]
+fn get_test_events(callbacks: &BTreeMap<NodeId, DetermineCallbackResult<Mock>>, node: usize) -> Vec<On> {
+    callbacks.get(&NodeId::new(node)).map(|result| result.normal_callbacks.keys().cloned().collect()).unwrap_or_default()
+}
+
+#[test]
+fn test_keyboard_events_go_to_the_focused_node() {
+    use glium::glutin::DeviceId;
+
+    let key_down = || WindowEvent::KeyboardInput {
+        device_id: unsafe { DeviceId::dummy() },
+        input: KeyboardInput {
+            scancode: 0,
+            state: ElementState::Pressed,
+            virtual_keycode: Some(VirtualKeyCode::A),
+            modifiers: ModifiersState::default(),
+        },
+    };
+
+    let ui_state = get_test_ui_state();
+    let mut window_state = WindowState::default();
+
+    // Nothing is focused, so the hovered node receives the keyboard events
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 2, key_down());
+    assert_eq!(get_test_events(&callbacks, 1), vec![]);
+    assert_eq!(get_test_events(&callbacks, 2), vec![On::VirtualKeyDown]);
+
+    // The focused node receives the keyboard events, even though the mouse is over another node
+    window_state.focused_element = Some(NodeId::new(1));
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 2, key_down());
+    assert_eq!(get_test_events(&callbacks, 1), vec![On::VirtualKeyDown]);
+    assert_eq!(get_test_events(&callbacks, 2), vec![]);
+
+    // ... or if the mouse isn't over any node
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 0, key_down());
+    assert_eq!(get_test_events(&callbacks, 1), vec![On::VirtualKeyDown]);
+}
+
+#[test]
+fn test_stopped_key_press_sends_no_text_input() {
+    use glium::glutin::DeviceId;
+    use dom::{Dom, NodeType};
+
+    let key_down = || WindowEvent::KeyboardInput {
+        device_id: unsafe { DeviceId::dummy() },
+        input: KeyboardInput {
+            scancode: 0,
+            state: ElementState::Pressed,
+            virtual_keycode: Some(VirtualKeyCode::F),
+            modifiers: ModifiersState::default(),
+        },
+    };
+
+    let ui_state = Dom::<Mock>::new(NodeType::Div)
+        .with_child(Dom::new(NodeType::Div).with_tab_index(TabIndex::Auto).with_callback(On::TextInput, Callback(mock_callback)))
+        .into_ui_state();
+    let mut window_state = WindowState::default();
+    window_state.focused_element = Some(NodeId::new(1));
+
+    get_test_callbacks(&mut window_state, &ui_state, 1, key_down());
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 1, WindowEvent::ReceivedCharacter('f'));
+    assert_eq!(get_test_events(&callbacks, 1), vec![On::TextInput]);
+
+    // A parent (ex. a menu) consumed the key press, so its text isn't inserted
+    get_test_callbacks(&mut window_state, &ui_state, 1, key_down());
+    window_state.text_input_stopped = true;
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 1, WindowEvent::ReceivedCharacter('f'));
+    assert_eq!(get_test_events(&callbacks, 1), vec![]);
+
+    // The next key press is sent again
+    get_test_callbacks(&mut window_state, &ui_state, 1, key_down());
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 1, WindowEvent::ReceivedCharacter('f'));
+    assert_eq!(get_test_events(&callbacks, 1), vec![On::TextInput]);
+}
+
+#[test]
+fn test_focus_events_go_to_the_nodes_that_lost_and_received_the_focus() {
+    use glium::glutin::{DeviceId, MouseButton};
+
+    let mouse_input = |state| WindowEvent::MouseInput {
+        device_id: unsafe { DeviceId::dummy() },
+        state,
+        button: MouseButton::Left,
+        modifiers: ModifiersState::default(),
+    };
+
+    let ui_state = get_test_ui_state();
+    let mut window_state = WindowState::default();
+
+    // Clicking node 1 focuses it
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 1, mouse_input(ElementState::Pressed));
+    assert_eq!(window_state.focused_element, Some(NodeId::new(1)));
+    assert_eq!(get_test_events(&callbacks, 1), vec![On::FocusReceived]);
+    get_test_callbacks(&mut window_state, &ui_state, 1, mouse_input(ElementState::Released));
+
+    // Clicking node 2 moves the focus: node 1 isn't hovered, but still receives On::FocusLost
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 2, mouse_input(ElementState::Pressed));
+    assert_eq!(window_state.focused_element, Some(NodeId::new(2)));
+    assert_eq!(get_test_events(&callbacks, 1), vec![On::FocusLost]);
+    assert_eq!(get_test_events(&callbacks, 2), vec![On::FocusReceived]);
+
+    // Releasing the mouse on the focused node doesn't send any focus events
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 2, mouse_input(ElementState::Released));
+    assert_eq!(get_test_events(&callbacks, 2), vec![]);
+
+    // Clicking outside of the focusable nodes clears the focus
+    let callbacks = get_test_callbacks(&mut window_state, &ui_state, 0, mouse_input(ElementState::Pressed));
+    assert_eq!(window_state.focused_element, None);
+    assert_eq!(get_test_events(&callbacks, 2), vec![On::FocusLost]);
+}
+
+#[test]
+fn test_focus_lost_goes_to_the_node_in_the_rebuilt_dom() {
+    use glium::glutin::{DeviceId, MouseButton};
+    use dom::{Dom, NodeType};
+
+    let mouse_input = |state| WindowEvent::MouseInput {
+        device_id: unsafe { DeviceId::dummy() },
+        state,
+        button: MouseButton::Left,
+        modifiers: ModifiersState::default(),
+    };
+
+    // root
+    //     |-> node 1..: focusable nodes with the keys
+    let get_ui_state = |keys: &[u64]| {
+        let mut dom = Dom::new(NodeType::Div);
+        for key in keys {
+            dom.add_child(Dom::new(NodeType::Div)
+                .with_key(*key)
+                .with_tab_index(TabIndex::Auto)
+                .with_callback(On::FocusLost, Callback(mock_callback)));
+        }
+        dom.into_ui_state()
+    };
+
+    let ui_state = get_ui_state(&[10, 20]);
+    let mut window_state = WindowState::default();
+    get_test_callbacks(&mut window_state, &ui_state, 2, mouse_input(ElementState::Pressed));
+    get_test_callbacks(&mut window_state, &ui_state, 2, mouse_input(ElementState::Released));
+    assert_eq!(window_state.focused_element, Some(NodeId::new(2)));
+
+    // A node is inserted in front of the focused node, which is now node 3
+    let rebuilt_ui_state = get_ui_state(&[5, 10, 20]);
+    window_state.update_node_ids(&rebuilt_ui_state.get_node_id_mapping(&ui_state));
+    assert_eq!(window_state.focused_element, Some(NodeId::new(3)));
+
+    // Clicking the new node sends On::FocusLost to the node that was focused, not to node 2
+    let callbacks = get_test_callbacks(&mut window_state, &rebuilt_ui_state, 1, mouse_input(ElementState::Pressed));
+    assert_eq!(window_state.focused_element, Some(NodeId::new(1)));
+    assert_eq!(get_test_events(&callbacks, 2), vec![]);
+    assert_eq!(get_test_events(&callbacks, 3), vec![On::FocusLost]);
+
+    // A node that doesn't exist anymore doesn't receive On::FocusLost
+    let rebuilt_again_ui_state = get_ui_state(&[10, 20]);
+    window_state.update_node_ids(&rebuilt_again_ui_state.get_node_id_mapping(&rebuilt_ui_state));
+    assert_eq!(window_state.focused_element, None);
+}
+
+#[test]
+fn test_focus_goes_back_when_a_focus_trap_is_removed() {
+    use glium::glutin::{DeviceId, MouseButton};
+    use dom::{Dom, NodeType};
+
+    let mouse_input = |state| WindowEvent::MouseInput {
+        device_id: unsafe { DeviceId::dummy() },
+        state,
+        button: MouseButton::Left,
+        modifiers: ModifiersState::default(),
+    };
+
+    // root
+    //     |-> node 1: focusable
+    //     '-> node 2: focus trap, ex. a modal dialog (if it is open)
+    let get_ui_state = |is_dialog_open: bool| {
+        let mut dom = Dom::new(NodeType::Div).with_child(Dom::new(NodeType::Div).with_key(10).with_tab_index(TabIndex::Auto));
+        if is_dialog_open {
+            dom.add_child(Dom::new(NodeType::Div).with_key(20).with_tab_index(TabIndex::Auto).with_focus_trap(true));
+        }
+        dom.into_ui_state()
+    };
+
+    let ui_state = get_ui_state(false);
+    let mut window_state = WindowState::default();
+    get_test_callbacks(&mut window_state, &ui_state, 1, mouse_input(ElementState::Pressed));
+    assert_eq!(window_state.focused_element, Some(NodeId::new(1)));
+
+    // The dialog is opened, it takes the focus with the next event
+    let dialog_ui_state = get_ui_state(true);
+    window_state.update_node_ids(&dialog_ui_state.get_node_id_mapping(&ui_state));
+    get_test_callbacks(&mut window_state, &dialog_ui_state, 1, mouse_input(ElementState::Released));
+    assert_eq!(window_state.focused_element, Some(NodeId::new(2)));
+
+    // The dialog is closed, the node that was focused before is focused again
+    let closed_ui_state = get_ui_state(false);
+    window_state.update_node_ids(&closed_ui_state.get_node_id_mapping(&dialog_ui_state));
+    assert_eq!(window_state.focused_element, Some(NodeId::new(1)));
+    assert!(window_state.focus_trap_history.is_empty());
+}
+
+#[test]
+fn test_click_counter() {
+    let start = Instant::now();
+    let after = |millis| start + Duration::from_millis(millis);
+
+    let mut clicks = ClickCounter::default();
+    assert_eq!(clicks.click(1, after(0), 2), 1);
+    assert_eq!(clicks.click(1, after(100), 2), 2);
+    // After a double-click, the next click is a single click again
+    assert_eq!(clicks.click(1, after(200), 2), 1);
+    // Clicks on another target or after too much time are single clicks
+    assert_eq!(clicks.click(2, after(300), 2), 1);
+    assert_eq!(clicks.click(2, after(300) + MULTI_CLICK_INTERVAL, 2), 1);
+
+    // Triple-clicks on points that are close to each other
+    let mut clicks = ClickCounter::default();
+    let is_near = |last: &f32, current: &f32| (current - last).abs() < 4.0;
+    assert_eq!(clicks.click_near(10.0, after(0), 3, is_near), 1);
+    assert_eq!(clicks.click_near(12.0, after(100), 3, is_near), 2);
+    assert_eq!(clicks.click_near(11.0, after(200), 3, is_near), 3);
+    assert_eq!(clicks.click_near(11.0, after(300), 3, is_near), 1);
+    clicks.reset();
+    assert_eq!(clicks.click_near(11.0, after(400), 3, is_near), 1);
+}